@@ -0,0 +1,86 @@
+//! Microbenchmarks of the representation of `Edges`, one byte per edge in the lanes of a 16 byte vector,
+//! against the alternative of packing the 12 edges into a single u64 with 5 bits per edge.
+//!
+//! Each iteration composes the edges of a random twist sequence, like table generation does.
+//!
+//! The byte lanes won: the packed word composes in 12 ns against 13 ns without the `simd` feature,
+//! but the lanes take 6 ns with it (and `-C target-feature=+ssse3`), while the packed word can't use the byte shuffle.
+//! Inverting takes about 12 ns either way.
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use rubikscube::*;
+use std::hint::black_box;
+use std::ops::Mul;
+
+const INPUTS: usize = 1024;
+
+const SLOT_BITS: usize = 5;
+const SLOT_MASK: u64 = 0x1F;
+const PRM_MASK: u64 = 0x0F;
+const ORI_BIT: u64 = 0x10;
+
+/// The edges packed into a single word: bits 0..4 of a slot hold the edge at the position and bit 4 its orientation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct PackedEdges {
+    state: u64,
+}
+
+impl PackedEdges {
+    fn from_edges(edges: &Edges) -> Self {
+        let state = (0..12).fold(0, |state, i| {
+            let (prm, ori) = edges.piece_at(i);
+            state | ((prm | ori << 4) as u64) << (i * SLOT_BITS)
+        });
+        Self { state }
+    }
+
+    #[inline(always)]
+    fn slot(&self, i: usize) -> u64 {
+        (self.state >> (i * SLOT_BITS)) & SLOT_MASK
+    }
+
+    fn inverse(&self) -> Self {
+        let mut state = 0;
+        for i in 0..12 {
+            let slot = self.slot(i);
+            state |= ((slot & ORI_BIT) | i as u64) << ((slot & PRM_MASK) as usize * SLOT_BITS);
+        }
+        Self { state }
+    }
+}
+
+impl Mul for PackedEdges {
+    type Output = PackedEdges;
+
+    fn mul(self, r: PackedEdges) -> PackedEdges {
+        let mut state = 0;
+        for i in 0..12 {
+            let slot = self.slot(i);
+            state |= (r.slot((slot & PRM_MASK) as usize) ^ (slot & ORI_BIT)) << (i * SLOT_BITS);
+        }
+        PackedEdges { state }
+    }
+}
+
+fn edges(c: &mut Criterion) {
+    let twists = RandomTwistGen::new(42, &ALL_TWISTS).gen_twists(INPUTS);
+    let edges: Vec<Edges> = twists.iter().map(|&t| Edges::twist(t)).collect();
+    let packed: Vec<PackedEdges> = edges.iter().map(PackedEdges::from_edges).collect();
+    let scrambled = Edges::twists(&twists);
+    assert_eq!(packed.iter().fold(PackedEdges::from_edges(&Edges::solved()), |e, &t| t * e), PackedEdges::from_edges(&scrambled));
+
+    let mut group = c.benchmark_group("edges");
+    group.throughput(Throughput::Elements(INPUTS as u64));
+    group.bench_function("byte lanes, twist", |b| {
+        b.iter(|| edges.iter().fold(Edges::solved(), |e, &t| t * black_box(e)))
+    });
+    group.bench_function("packed u64, twist", |b| {
+        b.iter(|| packed.iter().fold(PackedEdges::from_edges(&Edges::solved()), |e, &t| t * black_box(e)))
+    });
+    group.bench_function("byte lanes, inverse", |b| b.iter(|| edges.iter().map(|e| black_box(e).inverse()).collect::<Vec<_>>()));
+    group.bench_function("packed u64, inverse", |b| b.iter(|| packed.iter().map(|e| black_box(e).inverse()).collect::<Vec<_>>()));
+    group.finish();
+}
+
+criterion_group!(benches, edges);
+criterion_main!(benches);
@@ -0,0 +1,124 @@
+//! Microbenchmarks of the table lookups, which dominate the runtime of the solver.
+//!
+//! Each iteration does one lookup per input, at random indices, so that they hit the memory like the search does.
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use rand::{RngExt, SeedableRng, rngs::StdRng};
+use rubikscube::*;
+use std::hint::black_box;
+
+const INPUTS: usize = 1024;
+
+fn random_indices(rng: &mut StdRng, size: usize) -> Vec<usize> {
+    (0..INPUTS).map(|_| rng.random_range(0..size)).collect()
+}
+
+fn random_twists(seed: u64, twists: &[Twist]) -> Vec<Twist> {
+    RandomTwistGen::new(seed, twists).gen_twists(INPUTS)
+}
+
+fn twister(c: &mut Criterion) {
+    init_twister();
+    init_subset_twister();
+    let mut rng = StdRng::seed_from_u64(42);
+    let twists = random_twists(42, &ALL_TWISTS);
+    let h0_twists = random_twists(42, &H0_TWISTS);
+    let c_ori: Vec<COri> = random_indices(&mut rng, COri::SIZE).into_iter().map(COri::new).collect();
+    let c_prm: Vec<CPrm> = random_indices(&mut rng, CPrm::SIZE).into_iter().map(CPrm::new).collect();
+    let e_ori: Vec<EOri> = random_indices(&mut rng, EOri::SIZE).into_iter().map(EOri::new).collect();
+    let e_loc_prm: Vec<LocPrm> = random_indices(&mut rng, Edges::LOC_PRM_SIZE).into_iter().map(LocPrm::from_index).collect();
+    let xy_prm: Vec<ENonSlicePrm> = random_indices(&mut rng, ENonSlicePrm::SIZE).into_iter().map(ENonSlicePrm::new).collect();
+    let cubes: Vec<Cube> = random_indices(&mut rng, Cube::CORNER_INDEX_SIZE).into_iter().map(Cube::from_corner_index).collect();
+    let subset_cubes: Vec<SubsetCube> = random_indices(&mut rng, SubsetCube::INDEX_SIZE).into_iter().map(SubsetCube::from_index).collect();
+
+    let mut group = c.benchmark_group("twister");
+    group.throughput(Throughput::Elements(INPUTS as u64));
+    group.bench_function("c_ori", |b| {
+        b.iter(|| c_ori.iter().zip(&twists).map(|(&i, &t)| TWISTER.twisted_c_ori(black_box(i), t).index()).sum::<usize>())
+    });
+    group.bench_function("c_prm", |b| {
+        b.iter(|| c_prm.iter().zip(&twists).map(|(&i, &t)| TWISTER.twisted_c_prm(black_box(i), t).index()).sum::<usize>())
+    });
+    group.bench_function("e_ori", |b| {
+        b.iter(|| e_ori.iter().zip(&twists).map(|(&i, &t)| TWISTER.twisted_e_ori(black_box(i), t).index()).sum::<usize>())
+    });
+    group.bench_function("e_loc_prm", |b| {
+        b.iter(|| e_loc_prm.iter().zip(&twists).map(|(&i, &t)| TWISTER.twisted_e_loc_prm(black_box(i), t).index()).sum::<usize>())
+    });
+    group.bench_function("subset xy_prm", |b| {
+        b.iter(|| xy_prm.iter().zip(&h0_twists).map(|(&i, &t)| SUBSET_TWISTER.twisted_xy_prm(black_box(i), t).index()).sum::<usize>())
+    });
+    group.bench_function("Cube twisted", |b| {
+        b.iter(|| cubes.iter().zip(&twists).map(|(&c, &t)| black_box(c).twisted(t).coset_index()).sum::<usize>())
+    });
+    // The tail of phase 1 twists the cube before the last twist of a sequence, rather than looking up the whole sequence.
+    let tail = phase_1_tail(3);
+    let sequences = random_indices(&mut rng, tail.sequences().len());
+    group.bench_function("Cube twisted_by_sequence (tail 3)", |b| {
+        b.iter(|| cubes.iter().zip(&sequences).map(|(&c, &i)| black_box(c).twisted_by_sequence(&tail, i).coset_index()).sum::<usize>())
+    });
+    group.bench_function("SubsetCube twisted", |b| {
+        b.iter(|| subset_cubes.iter().zip(&h0_twists).map(|(&c, &t)| black_box(c).twisted(t).index()).sum::<usize>())
+    });
+    group.finish();
+}
+
+/// The layout of `Twister`, index-major, against a twist-major one, on the largest table, c_prm.
+/// The search expands all twists of a cube at once, and a single twist is looked up when applying a sequence.
+fn twister_layout(c: &mut Criterion) {
+    init_twister();
+    let mut rng = StdRng::seed_from_u64(42);
+    let twists = random_twists(42, &ALL_TWISTS);
+    let c_prm: Vec<CPrm> = random_indices(&mut rng, CPrm::SIZE).into_iter().map(CPrm::new).collect();
+    let twist_major: Vec<Vec<u16>> = ALL_TWISTS
+        .iter()
+        .map(|&t| (0..CPrm::SIZE).map(|i| TWISTER.twisted_c_prm(CPrm::new(i), t).index() as u16).collect())
+        .collect();
+
+    let mut group = c.benchmark_group("twister layout");
+    group.throughput(Throughput::Elements(INPUTS as u64));
+    group.bench_function("index-major, all twists", |b| {
+        b.iter(|| c_prm.iter().map(|&i| ALL_TWISTS.iter().map(|&t| TWISTER.twisted_c_prm(black_box(i), t).index()).sum::<usize>()).sum::<usize>())
+    });
+    group.bench_function("twist-major, all twists", |b| {
+        b.iter(|| c_prm.iter().map(|&i| twist_major.iter().map(|row| row[black_box(i).index()] as usize).sum::<usize>()).sum::<usize>())
+    });
+    group.bench_function("index-major, one twist", |b| {
+        b.iter(|| c_prm.iter().zip(&twists).map(|(&i, &t)| TWISTER.twisted_c_prm(black_box(i), t).index()).sum::<usize>())
+    });
+    group.bench_function("twist-major, one twist", |b| {
+        b.iter(|| c_prm.iter().zip(&twists).map(|(&i, &t)| twist_major[t as usize][black_box(i).index()] as usize).sum::<usize>())
+    });
+    group.finish();
+}
+
+fn distance(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(42);
+    let corners = create_corners_table();
+    let corner_indices = random_indices(&mut rng, corners.len());
+
+    let mut group = c.benchmark_group("distance");
+    group.throughput(Throughput::Elements(INPUTS as u64));
+    group.bench_function("corners", |b| {
+        b.iter(|| corner_indices.iter().map(|&i| corners.distance(black_box(i)) as usize).sum::<usize>())
+    });
+
+    // The subset and coset tables take too long to create here.
+    match TableSet::builder().config_file("config.txt").create_missing(false).build() {
+        Ok(tables) => {
+            let subset_indices = random_indices(&mut rng, tables.subset.len());
+            let coset_indices = random_indices(&mut rng, tables.coset.len());
+            group.bench_function("subset", |b| {
+                b.iter(|| subset_indices.iter().map(|&i| tables.subset.distance(black_box(i)) as usize).sum::<usize>())
+            });
+            group.bench_function("coset", |b| {
+                b.iter(|| coset_indices.iter().map(|&i| tables.coset.distance(black_box(i)) as usize).sum::<usize>())
+            });
+        }
+        Err(err) => eprintln!("Skipping the subset and coset tables: {err}"),
+    }
+    group.finish();
+}
+
+criterion_group!(benches, twister, twister_layout, distance);
+criterion_main!(benches);
@@ -0,0 +1,69 @@
+//! Microbenchmarks of the index functions, which table generation calls billions of times in `from_index`.
+//!
+//! Each compares the Vec-returning function with its allocation-free array variant.
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use rand::{RngExt, SeedableRng, rngs::StdRng};
+use rubikscube::*;
+use std::hint::black_box;
+
+const INPUTS: usize = 1024;
+
+fn random_indices(rng: &mut StdRng, size: usize) -> Vec<usize> {
+    (0..INPUTS).map(|_| rng.random_range(0..size)).collect()
+}
+
+fn nth_permutation_bench(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(42);
+    let indices_8 = random_indices(&mut rng, factorial(8));
+    let indices_12 = random_indices(&mut rng, factorial(12));
+
+    let mut group = c.benchmark_group("nth_permutation");
+    group.throughput(Throughput::Elements(INPUTS as u64));
+    group.bench_function("vec (len 8)", |b| {
+        b.iter(|| indices_8.iter().map(|&i| nth_permutation(black_box(i), 8)[7]).sum::<usize>())
+    });
+    group.bench_function("array (len 8)", |b| {
+        b.iter(|| indices_8.iter().map(|&i| nth_permutation_array::<8>(black_box(i))[7]).sum::<usize>())
+    });
+    group.bench_function("vec (len 12)", |b| {
+        b.iter(|| indices_12.iter().map(|&i| nth_permutation(black_box(i), 12)[11]).sum::<usize>())
+    });
+    group.bench_function("array (len 12)", |b| {
+        b.iter(|| indices_12.iter().map(|&i| nth_permutation_array::<12>(black_box(i))[11]).sum::<usize>())
+    });
+    group.finish();
+}
+
+fn nth_combination_bench(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(42);
+    let indices = random_indices(&mut rng, binomial(12, 4));
+
+    let mut group = c.benchmark_group("nth_combination");
+    group.throughput(Throughput::Elements(INPUTS as u64));
+    group.bench_function("vec (12, 4)", |b| {
+        b.iter(|| indices.iter().map(|&i| nth_combination(12, 4, black_box(i))[3]).sum::<usize>())
+    });
+    group.bench_function("array (12, 4)", |b| {
+        b.iter(|| indices.iter().map(|&i| nth_combination_array::<4>(12, black_box(i))[3]).sum::<usize>())
+    });
+    group.finish();
+}
+
+fn decode_bench(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(42);
+    let values = random_indices(&mut rng, Corners::ORI_SIZE);
+
+    let mut group = c.benchmark_group("decode");
+    group.throughput(Throughput::Elements(INPUTS as u64));
+    group.bench_function("vec (base 3)", |b| {
+        b.iter(|| values.iter().map(|&v| decode(black_box(v), 3, 7)[6]).sum::<usize>())
+    });
+    group.bench_function("array (base 3)", |b| {
+        b.iter(|| values.iter().map(|&v| decode_array::<7>(black_box(v), 3)[6]).sum::<usize>())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, nth_permutation_bench, nth_combination_bench, decode_bench);
+criterion_main!(benches);
@@ -0,0 +1,128 @@
+//! End-to-end benchmarks of the solver at fixed scramble distances.
+//!
+//! The two-phase solver needs the tables of `config.txt` and is skipped without them.
+//! The corners are always benchmarked, with a corners table created on the fly.
+//! Each iteration solves a batch of cubes, so the throughput is in search nodes per second.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use rubikscube::*;
+use std::hint::black_box;
+
+const CUBES_PER_DISTANCE: usize = 100;
+
+/// Cubes scrambled by `distance` twists, where no two consecutive twists cancel.
+fn scrambled_cubes(distance: usize) -> Vec<Cube> {
+    let mut scrambles = WcaScrambleGen::new(distance as u64);
+    (0..CUBES_PER_DISTANCE)
+        .map(|_| Cube::solved().twisted_by(&scrambles.gen_twists(distance)))
+        .collect()
+}
+
+fn two_phase(c: &mut Criterion) {
+    let tables = match TableSet::builder().config_file("config.txt").create_missing(false).build() {
+        Ok(tables) => tables,
+        Err(err) => {
+            eprintln!("Skipping the two-phase solver: {err}");
+            return;
+        }
+    };
+    tables.warm_up();
+    let mut solver = TwoPhaseSolver::with_tables(&tables);
+
+    let mut group = c.benchmark_group("two_phase");
+    group.sample_size(10);
+    for distance in [8, 12, 16, 20] {
+        let cubes = scrambled_cubes(distance);
+        let mut stats = SolveStats::default();
+        let mut solution_length = 0;
+        for &cube in &cubes {
+            let solution = solver.solve_with_stats(cube, 20).unwrap();
+            solution_length += solution.alg.len();
+            stats += &solution.stats;
+        }
+        println!(
+            "two_phase/{distance}: average solution length {:.2}",
+            solution_length as f64 / cubes.len() as f64
+        );
+
+        group.throughput(Throughput::Elements((stats.phase_1_probes + stats.phase_2_probes) as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(distance), &cubes, |b, cubes| {
+            b.iter(|| {
+                for &cube in cubes {
+                    black_box(solver.solve_twists(cube, 20).unwrap());
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Iterative deepening search of the corners, pruned by the corners table.
+struct CornersSolver<'a> {
+    table: &'a DistanceTable,
+    twists: Vec<Twist>,
+    nodes: u64,
+}
+
+impl CornersSolver<'_> {
+    fn solve(&mut self, cube: Cube) -> Vec<Twist> {
+        self.twists.clear();
+        let mut depth = 0;
+        while !self.search(cube, depth) {
+            depth += 1;
+        }
+        self.twists.clone()
+    }
+
+    fn search(&mut self, cube: Cube, depth: u8) -> bool {
+        self.nodes += 1;
+        let distance = self.table.distance(cube.corner_index());
+        if distance == 0 {
+            return true;
+        }
+        if distance > depth {
+            return false;
+        }
+        let twists = match self.twists.last() {
+            Some(&previous) => unique_twists_after(previous),
+            None => TwistSet::FULL,
+        };
+        for twist in twists.iter() {
+            self.twists.push(twist);
+            if self.search(cube.twisted(twist), depth - 1) {
+                return true;
+            }
+            self.twists.pop();
+        }
+        false
+    }
+}
+
+fn corners(c: &mut Criterion) {
+    let table = create_corners_table();
+    let mut solver = CornersSolver { table: &table, twists: Vec::new(), nodes: 0 };
+
+    let mut group = c.benchmark_group("corners");
+    for distance in [4, 8, 12] {
+        let cubes = scrambled_cubes(distance);
+        solver.nodes = 0;
+        let solution_length: usize = cubes.iter().map(|&cube| solver.solve(cube).len()).sum();
+        println!(
+            "corners/{distance}: average solution length {:.2}",
+            solution_length as f64 / cubes.len() as f64
+        );
+
+        group.throughput(Throughput::Elements(solver.nodes));
+        group.bench_with_input(BenchmarkId::from_parameter(distance), &cubes, |b, cubes| {
+            b.iter(|| {
+                for &cube in cubes {
+                    black_box(solver.solve(cube));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, two_phase, corners);
+criterion_main!(benches);
@@ -0,0 +1,32 @@
+// Generates the service code of the `grpc-server` binary.
+// The messages are defined with prost in `src/grpc_server.rs`, so no protoc is needed;
+// `proto/rubikscube.proto` describes the same service for clients.
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        use tonic_build::manual::{Builder, Method, Service};
+
+        let method = |name: &str, route_name: &str, input: &str, output: &str| {
+            Method::builder()
+                .name(name)
+                .route_name(route_name)
+                .input_type(format!("crate::{input}"))
+                .output_type(format!("crate::{output}"))
+                .codec_path("tonic_prost::ProstCodec")
+        };
+        let service = Service::builder()
+            .name("RubiksCube")
+            .package("rubikscube")
+            .method(method("solve", "Solve", "SolveRequest", "SolveReply").build())
+            .method(method("scramble", "Scramble", "ScrambleRequest", "ScrambleReply").build())
+            .method(method("validate", "Validate", "ValidateRequest", "ValidateReply").build())
+            .method(
+                method("solve_batch", "SolveBatch", "SolveRequest", "SolveReply")
+                    .client_streaming()
+                    .server_streaming()
+                    .build(),
+            )
+            .build();
+        Builder::new().build_client(false).compile(&[service]);
+    }
+}
@@ -0,0 +1,79 @@
+use crate::*;
+use std::collections::HashMap;
+
+/// Calls `f` with every canonical sequence of `length` twists of `allowed`, see `is_canonical_after`.
+fn for_each_sequence(length: usize, allowed: TwistSet, f: &mut impl FnMut(&[Twist])) {
+    fn extend(twists: &mut Vec<Twist>, length: usize, allowed: TwistSet, f: &mut impl FnMut(&[Twist])) {
+        if twists.len() == length {
+            f(twists);
+            return;
+        }
+        let next = twists.last().map_or(allowed, |&last| allowed & unique_twists_after(last));
+        for twist in next {
+            twists.push(twist);
+            extend(twists, length, allowed, f);
+            twists.pop();
+        }
+    }
+    extend(&mut Vec::with_capacity(length), length, allowed, f);
+}
+
+/// All algs of at most `max_length` twists of `allowed` that take the solved cube to `target_effect`,
+/// e.g. all PLL variants of up to 11 twists of ⟨R,U,F⟩ for a trainer.
+///
+/// Only canonical twist sequences are listed, see `is_canonical_after`, so "L R" but not "R L".
+/// The algs are sorted by length, then lexicographically by their twists.
+///
+/// The search meets in the middle: it stores the states of all first halves and looks up the second halves,
+/// which takes memory exponential in half of `max_length`.
+pub fn find_algs(target_effect: Cube, max_length: usize, allowed: TwistSet) -> Vec<Alg> {
+    // The first halves of the twists, by the state they reach and their length.
+    let mut first_halves: HashMap<(Cube, usize), Vec<Vec<Twist>>> = HashMap::new();
+    for length in 0..=max_length.div_ceil(2) {
+        for_each_sequence(length, allowed, &mut |twists| {
+            let state = Cube::solved().twisted_by(twists);
+            first_halves.entry((state, length)).or_default().push(twists.to_vec());
+        });
+    }
+
+    // An alg of `length` twists splits into a first half of `length.div_ceil(2)` twists and a second half of the rest.
+    let mut algs = Vec::new();
+    for length in 0..=max_length {
+        for_each_sequence(length / 2, allowed, &mut |second| {
+            let state = target_effect.twisted_by(&inverse(second));
+            for first in first_halves.get(&(state, length.div_ceil(2))).into_iter().flatten() {
+                if first.last().zip(second.first()).is_none_or(|(&a, &b)| is_canonical_after(a, b)) {
+                    algs.push(Alg::new([first.as_slice(), second].concat()));
+                }
+            }
+        });
+    }
+    algs.sort_by(|a, b| (a.len(), a.twists()).cmp(&(b.len(), b.twists())));
+    algs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn effect(alg: &str) -> Cube {
+        Cube::solved().twisted_by(&parse_twist_sequence(alg).unwrap())
+    }
+
+    #[test]
+    fn test_find_algs() {
+        assert_eq!(find_algs(Cube::solved(), 4, TwistSet::FULL), vec![Alg::new(vec![])]);
+        assert_eq!(find_algs(effect("R U"), 3, TwistSet::FULL), vec!["R U".parse().unwrap()]);
+        assert_eq!(find_algs(effect("R L"), 2, TwistSet::FULL), vec!["L R".parse().unwrap()]);
+        assert!(find_algs(effect("R U"), 3, TwistSet::TWO_GEN - TwistSet::from_faces(&[1])).is_empty());
+
+        let sune = effect("R U R' U R U2 R'");
+        let algs = find_algs(sune, 7, TwistSet::TWO_GEN);
+        assert!(algs.contains(&"R U R' U R U2 R'".parse().unwrap()));
+        assert!(algs.iter().all(|alg| alg.len() <= 7 && Cube::solved().twisted_by(alg) == sune));
+
+        // An Ua perm.
+        let u_perm = "R U' R U R U R U' R' U' R2";
+        assert!(find_algs(effect(u_perm), 11, TwistSet::TWO_GEN).contains(&u_perm.parse().unwrap()));
+    }
+}
@@ -0,0 +1,144 @@
+use crate::*;
+
+/// The twists that a cube allows in its current state, e.g. of a bandaged cube, where bonded pieces block the twists that would split them.
+/// The searches of `TwoPhaseSolver::with_legal_moves` and `MaskedSolver::with_legal_moves` only try these twists.
+pub trait LegalMoves: Send + Sync {
+    fn legal_twists(&self, cube: &Cube) -> TwistSet;
+}
+
+/// The same twists in every state, e.g. `TwistSet::TWO_GEN`.
+impl LegalMoves for TwistSet {
+    fn legal_twists(&self, _: &Cube) -> TwistSet {
+        *self
+    }
+}
+
+impl<F: Fn(&Cube) -> TwistSet + Send + Sync> LegalMoves for F {
+    fn legal_twists(&self, cube: &Cube) -> TwistSet {
+        self(cube)
+    }
+}
+
+/// The legal twists of the cube that may follow `previous`. Since the legal twists depend on the state, "L R" may be legal where "R L" isn't,
+/// so unlike `unique_twists_after`, only the twists of the same face are left out.
+pub(crate) fn legal_twists_after(legal_moves: &dyn LegalMoves, cube: &Cube, previous: Option<Twist>) -> TwistSet {
+    let legal = legal_moves.legal_twists(cube);
+    previous.map_or(legal, |previous| legal - TwistSet::from_faces(&[previous as usize / 3]))
+}
+
+/// A piece of the cube, numbered like in the README. The centers don't move, but can be bonded to the pieces around them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Piece {
+    Corner(usize),
+    Edge(usize),
+    Center(Face),
+}
+
+/// A bandaged cube on the 3x3x3: pieces bonded together, which only twist as a block.
+/// A twist is legal if it turns either both pieces of every bond or neither of them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bandaging {
+    bonds: Vec<(Piece, Piece)>,
+}
+
+impl Bandaging {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_bond(mut self, a: Piece, b: Piece) -> Self {
+        self.bonds.push((a, b));
+        self
+    }
+
+    /// Bonds all pieces of the block, e.g. a 1x2x2 block of a corner, two edges and a center.
+    pub fn with_block(self, pieces: &[Piece]) -> Self {
+        pieces.windows(2).fold(self, |bandaging, pair| bandaging.with_bond(pair[0], pair[1]))
+    }
+
+    pub fn bonds(&self) -> &[(Piece, Piece)] {
+        &self.bonds
+    }
+
+    /// Whether the twists of the face turn the piece of the cube.
+    fn in_layer(corners: &Corners, edges: &Edges, piece: Piece, face: Twist) -> bool {
+        match piece {
+            Piece::Corner(c) => {
+                let position = corners.position_of(c);
+                Corners::twist(face).piece_at(position).0 != position
+            }
+            Piece::Edge(e) => {
+                let position = edges.position_of(e);
+                Edges::twist(face).piece_at(position).0 != position
+            }
+            Piece::Center(center) => center == face.face(),
+        }
+    }
+}
+
+impl LegalMoves for Bandaging {
+    fn legal_twists(&self, cube: &Cube) -> TwistSet {
+        let (corners, edges) = (cube.corners(), cube.edges());
+        ALL_TWISTS
+            .into_iter()
+            .filter(|&twist| {
+                // All twists of a face turn the same pieces, so the quarter turn decides.
+                let face = twist.with_power(1);
+                self.bonds.iter().all(|&(a, b)| Self::in_layer(&corners, &edges, a, face) == Self::in_layer(&corners, &edges, b, face))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The corner UFR and the edge UF, which R splits.
+    fn ufr_block() -> Bandaging {
+        Bandaging::new().with_bond(Piece::Corner(1), Piece::Edge(0))
+    }
+
+    #[test]
+    fn test_legal_twists() {
+        assert_eq!(Bandaging::new().legal_twists(&Cube::solved()), TwistSet::FULL);
+        assert_eq!(ufr_block().legal_twists(&Cube::solved()), TwistSet::FULL - TwistSet::from_faces(&[1]));
+        // After U, the block is at UFL and UL, where F splits it.
+        let cube = Cube::solved().twisted(Twist::U1);
+        assert_eq!(ufr_block().legal_twists(&cube), TwistSet::FULL - TwistSet::from_faces(&[4]));
+
+        // A center bonded to an edge blocks the twists of the other face of the edge.
+        let bandaging = Bandaging::new().with_bond(Piece::Center(Face::U), Piece::Edge(0));
+        assert_eq!(bandaging.legal_twists(&Cube::solved()), TwistSet::FULL - TwistSet::from_faces(&[4]));
+        let bandaging = Bandaging::new().with_block(&[Piece::Center(Face::F), Piece::Edge(0), Piece::Corner(1)]);
+        assert_eq!(bandaging.bonds().len(), 2);
+        assert_eq!(bandaging.legal_twists(&Cube::solved()), TwistSet::from_faces(&[0, 3, 4, 5]));
+
+        assert_eq!(TwistSet::TWO_GEN.legal_twists(&cube), TwistSet::TWO_GEN);
+        let closure = |cube: &Cube| if *cube == Cube::solved() { TwistSet::H0 } else { TwistSet::FULL };
+        assert_eq!(closure.legal_twists(&Cube::solved()), TwistSet::H0);
+    }
+
+    #[test]
+    fn test_masked_solver() {
+        // Scrambled with the legal twists only, the block solves with them too.
+        let scramble = parse_twist_sequence("U L B2 U' L2").unwrap();
+        let mut cube = Cube::solved();
+        for &twist in &scramble {
+            assert!(ufr_block().legal_twists(&cube).contains(twist));
+            cube = cube.twisted(twist);
+        }
+        let solver = MaskedSolver::new(PieceMask::BLOCK_2X2X2).with_legal_moves(ufr_block());
+        let solution = solver.solve(cube, 8).unwrap();
+        let mut solved = cube;
+        for &twist in &solution {
+            assert!(ufr_block().legal_twists(&solved).contains(twist));
+            solved = solved.twisted(twist);
+        }
+        assert!(PieceMask::BLOCK_2X2X2.is_solved(&solved.corners(), &solved.edges()));
+
+        // Only L, D and F twist the DFL corner back into place.
+        let solver = MaskedSolver::new(PieceMask::BLOCK_2X2X2).with_legal_moves(TwistSet::from_faces(&[1, 2, 5]));
+        assert!(solver.solve(Cube::solved().twisted(Twist::F1), 4).is_err());
+    }
+}
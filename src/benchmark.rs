@@ -4,9 +4,7 @@ use std::hint::black_box;
 use std::time::Instant;
 
 struct Benchmarker {
-    corners_table: DistanceTable,
-    subset_table: DistanceTable,
-    coset_table: DirectionsTable,
+    tables: TableSet,
     iterations: usize,
     rnd: StdRng,
     rnd_twist: Vec<Twist>,
@@ -19,7 +17,13 @@ struct Benchmarker {
 
 impl Benchmarker {
     fn new(iterations: usize) -> Self {
-        let (corners_table, subset_table, coset_table) = get_tables();
+        let tables = TableSet::builder()
+            .config_file("config.txt")
+            .build()
+            .unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            });
         let mut rnd = StdRng::seed_from_u64(42);
         let mut rnd_twist_gen = RandomTwistGen::new(42, &ALL_TWISTS);
         let mut rnd_subset_twist_gen = RandomTwistGen::new(42, &H0_TWISTS);
@@ -31,9 +35,7 @@ impl Benchmarker {
         let rnd_cube = rnd_twists.iter().map(|t| Cube::solved().twisted_by(t)).collect();
         let rnd_subset_cube = rnd_subset_twists.iter().map(|t| SubsetCube::solved().twisted_by(t)).collect();
         Self {
-            corners_table,
-            subset_table,
-            coset_table,
+            tables,
             iterations,
             rnd,
             rnd_twist,
@@ -57,7 +59,7 @@ impl Benchmarker {
 
     fn test_vec_of_twists<R, F: Fn(&Vec<Twist>) -> R>(&mut self, function: F) -> Vec<R>
     {
-        self.rnd_twists.iter().map(|twists| function(twists)).collect()
+        self.rnd_twists.iter().map(function).collect()
     }
     
     fn bench<T, R, F: FnMut(&T) -> R>(&self, name: &str, items: &[T], mut function: F) {
@@ -93,8 +95,8 @@ impl Benchmarker {
     fn bench_encode(&mut self) {
         let rnd_base2: Vec<Vec<usize>> = (0..self.iterations).map(|_| (0..11).map(|_| self.rnd.random_range(0..2)).collect()).collect();
         let rnd_base3: Vec<Vec<usize>> = (0..self.iterations).map(|_| (0..8).map(|_| self.rnd.random_range(0..3)).collect()).collect();
-        self.bench("encode (base 2)", &rnd_base2, |v| { encode(&v, 2) });
-        self.bench("encode (base 3)", &rnd_base3, |v| { encode(&v, 3) });
+        self.bench("encode (base 2)", &rnd_base2, |v| { encode(v, 2) });
+        self.bench("encode (base 3)", &rnd_base3, |v| { encode(v, 3) });
     }
     
     fn bench_decode(&mut self) {
@@ -178,6 +180,7 @@ impl Benchmarker {
         let mut cube = Cube::solved();
     
         self.bench("Cube twisted", &self.rnd_twist, |&t| { cube = cube.twisted(t) });
+        self.bench("Cube twisted (all twists)", &self.rnd_cube, |c| { ALL_TWISTS.map(|t| c.twisted(t)) });
         self.bench("Cube from_corner_index", &cube_corner_index, |&i| { Cube::from_corner_index(i) });
         self.bench("Cube from_coset_index", &cube_coset_index, |&i| { Cube::from_coset_index(i) });
         self.bench("Cube corner_index", &self.rnd_cube, |c| { c.corner_index() });
@@ -187,19 +190,28 @@ impl Benchmarker {
     }
     
     fn bench_distances(&mut self) {
-        self.bench("Corners distance", &self.rnd_cube, |c| { self.corners_table.distance(c.corner_index()) });
-        self.bench("Coset distance", &self.rnd_cube, |c| { self.coset_table.distance(c.coset_index()) });
-        self.bench("Subset distance", &self.rnd_subset_cube, |c| { self.subset_table.distance(c.index()) });
+        self.bench("Corners distance", &self.rnd_cube, |c| { self.tables.corners.distance(c.corner_index()) });
+        self.bench("Coset distance", &self.rnd_cube, |c| { self.tables.coset.distance(c.coset_index()) });
+        self.bench("Subset distance", &self.rnd_subset_cube, |c| { self.tables.subset.subset_distance(c) });
     }
 
     fn bench_phase_2(&mut self) {
-        let mut solver = TwoPhaseSolver::new(
-            &self.coset_table,
-            &self.subset_table,
-            &self.corners_table
-        );
-        let foo = self.rnd_subset_cube.iter().map(|&c| (c, self.subset_table.distance(c.index()))).collect::<Vec<_>>();
-        self.bench("TwoPhaseSolver phase_2", &foo, |&c| { solver.search_phase_2(c.0, c.1) });
+        let mut solver = TwoPhaseSolver::with_tables(&self.tables);
+        let cubes_and_distances = self.rnd_subset_cube.iter().map(|&c| (c, self.tables.subset.subset_distance(&c))).collect::<Vec<_>>();
+        self.bench("TwoPhaseSolver phase_2", &cubes_and_distances, |&c| { solver.search_phase_2(c.0, c.1) });
+    }
+
+    fn bench_solve(&mut self) {
+        use rubikscube::solver::Solver;
+
+        let cubes = &self.rnd_cube[..1_000];
+        let options = SolveOptions::default();
+        let solver = TwoPhaseSolver::with_tables(&self.tables);
+        self.bench("TwoPhaseSolver solve", cubes, |&c| { solver.solve(c, &options) });
+
+        let tail = phase_1_tail(3);
+        let solver = TwoPhaseSolver::with_tables(&self.tables).with_tail(&tail);
+        self.bench("solve (phase 1 tail 3)", cubes, |&c| { solver.solve(c, &options) });
     }
 }
 
@@ -231,4 +243,5 @@ fn main() {
     benchmarker.bench_cube();
     benchmarker.bench_distances();
     benchmarker.bench_phase_2();
+    benchmarker.bench_solve();
 }
@@ -0,0 +1,97 @@
+use super::geometry::*;
+use super::LayerTwist;
+use crate::*;
+use std::sync::LazyLock;
+
+/// The maps of the layer twists on the centers of the 4x4x4, which are the X-centers of the 5x5x5 as well, by `map_index`.
+static X_CENTER_MAPS: LazyLock<Vec<[u8; 24]>> = LazyLock::new(|| layer_maps(&center_positions(4, (1, 1)), 4));
+
+/// The maps of the layer twists on the T-centers of the 5x5x5, by `map_index`.
+static T_CENTER_MAPS: LazyLock<Vec<[u8; 24]>> = LazyLock::new(|| layer_maps(&center_positions(5, (0, 2)), 5));
+
+macro_rules! centers {
+    ($(#[$doc:meta])* $name:ident, $maps:ident) => {
+        $(#[$doc])*
+        /// Centers of the same colour can't be told apart, so only their colours are kept.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub struct $name {
+            faces: [Face; 24],
+        }
+
+        impl $name {
+            pub const fn new(faces: [Face; 24]) -> Self {
+                Self { faces }
+            }
+
+            pub fn solved() -> Self {
+                Self { faces: core::array::from_fn(|slot| FACES[slot / 4]) }
+            }
+
+            /// The colours of the centers.
+            pub fn faces(&self) -> &[Face; 24] {
+                &self.faces
+            }
+
+            /// After the twist, slot i holds the center that was at slot `map[i]`.
+            pub(crate) fn map(twist: LayerTwist) -> &'static [u8; 24] {
+                &$maps[map_index(twist)]
+            }
+
+            pub fn twisted(&self, twist: LayerTwist) -> Self {
+                let map = Self::map(twist);
+                Self { faces: core::array::from_fn(|slot| self.faces[map[slot] as usize]) }
+            }
+
+            pub fn twisted_by(&self, twists: &[LayerTwist]) -> Self {
+                twists.iter().fold(*self, |centers, &twist| centers.twisted(twist))
+            }
+
+            /// Whether each face has the four centers of its colour.
+            pub fn is_solved(&self) -> bool {
+                *self == Self::solved()
+            }
+        }
+    };
+}
+
+centers!(
+    /// The 24 center pieces of the 4x4x4, four per face in the order URFDLB, each face read row by row like in `Facelets`.
+    /// The X-centers of the 5x5x5, the ones diagonal to the middle of a face, turn alike.
+    Centers4, X_CENTER_MAPS
+);
+centers!(
+    /// The 24 T-centers of the 5x5x5, the ones next to the middle of a face, four per face in the order URFDLB,
+    /// each face read row by row like in `Facelets`.
+    TCenters5, T_CENTER_MAPS
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_twisted() {
+        let solved = Centers4::solved();
+        // Outer layers only turn the centers of their face.
+        for twist in ALL_TWISTS {
+            assert!(solved.twisted(LayerTwist::outer(twist)).is_solved());
+            assert!(TCenters5::solved().twisted(LayerTwist::outer(twist)).is_solved());
+        }
+        // "2R" takes two centers of F up to U.
+        let centers = solved.twisted("2R".parse().unwrap());
+        assert_eq!(centers.faces()[..4], [Face::U, Face::F, Face::U, Face::F]);
+        assert_eq!(centers.faces()[4..8], [Face::R; 4]);
+        let twists = parse_layer_twists("2R U 2F' 2D2 L").unwrap();
+        assert!(solved.twisted_by(&twists).twisted_by(&inverse_layer_twists(&twists)).is_solved());
+
+        // On the 5x5x5, "2R" takes one T-center of F up to U.
+        let centers = TCenters5::solved().twisted("2R".parse().unwrap());
+        assert_eq!(centers.faces()[..4], [Face::U, Face::U, Face::F, Face::U]);
+    }
+
+    #[test]
+    fn test_x_centers() {
+        // The X-centers of the 5x5x5 turn like the centers of the 4x4x4.
+        assert_eq!(layer_maps(&center_positions(5, (2, 2)), 5), *X_CENTER_MAPS);
+    }
+}
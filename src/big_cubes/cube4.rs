@@ -0,0 +1,82 @@
+use super::*;
+use crate::*;
+
+/// The 4x4x4 by its pieces: the corners, which turn like those of the 3x3x3, the wings and the centers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cube4 {
+    corners: Corners,
+    wings: Wings4,
+    centers: Centers4,
+}
+
+impl Cube4 {
+    pub const fn new(corners: Corners, wings: Wings4, centers: Centers4) -> Self {
+        Self { corners, wings, centers }
+    }
+
+    pub fn solved() -> Self {
+        Self { corners: Corners::solved(), wings: Wings4::solved(), centers: Centers4::solved() }
+    }
+
+    pub fn corners(&self) -> Corners {
+        self.corners
+    }
+
+    pub fn wings(&self) -> Wings4 {
+        self.wings
+    }
+
+    pub fn centers(&self) -> Centers4 {
+        self.centers
+    }
+
+    pub fn twisted(&self, twist: LayerTwist) -> Self {
+        Self {
+            corners: if twist.layer == 0 { twist.twist * self.corners } else { self.corners },
+            wings: self.wings.twisted(twist),
+            centers: self.centers.twisted(twist),
+        }
+    }
+
+    pub fn twisted_by(&self, twists: &[LayerTwist]) -> Self {
+        twists.iter().fold(*self, |cube, &twist| cube.twisted(twist))
+    }
+
+    pub fn is_solved(&self) -> bool {
+        *self == Self::solved()
+    }
+
+    /// The 3x3x3 that the cube reduces to, if the centers are solved and the wings paired into edges.
+    /// None otherwise, and if the edges are flipped or permuted in a way that no 3x3x3 can be, the parities of the 4x4x4.
+    pub fn reduced(&self) -> Option<Cube> {
+        if !self.centers.is_solved() {
+            return None;
+        }
+        let edges = self.wings.edges()?;
+        let flips = edges.orientations().iter().sum::<usize>();
+        let parity = is_even_permutation(edges.prm_index()) == is_even_permutation(self.corners.prm_index());
+        (flips % 2 == 0 && parity).then(|| Cube::from_cubies(&self.corners, &edges))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reduced() {
+        let twists = parse_twist_sequence("R U2 F' L D B2 R'").unwrap();
+        let cube = Cube4::solved().twisted_by(&twists.iter().map(|&t| LayerTwist::outer(t)).collect::<Vec<_>>());
+        assert_eq!(cube.reduced(), Some(Cube::solved().twisted_by(&twists)));
+
+        let twists = parse_layer_twists("2R U 2R'").unwrap();
+        assert_eq!(Cube4::solved().twisted_by(&twists).reduced(), None);
+        let cube = Cube4::solved().twisted_by(&twists).twisted_by(&inverse_layer_twists(&twists));
+        assert!(cube.is_solved());
+
+        // "2R2 U2 2R2 Uw2 2R2 Uw2" swaps two edges, but no corners: the permutation parity.
+        let cube = Cube4::solved().twisted_by(&parse_layer_twists("2R2 U2 2R2 Uw2 2R2 Uw2").unwrap());
+        assert!(cube.centers().is_solved() && cube.wings().is_paired());
+        assert_eq!(cube.reduced(), None);
+    }
+}
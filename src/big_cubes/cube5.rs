@@ -0,0 +1,106 @@
+use super::*;
+use crate::*;
+
+/// The 5x5x5 by its pieces: the corners and the middle edges, which turn like those of the 3x3x3,
+/// the wings next to the middle edges, the X-centers and the T-centers.
+/// It turns the layers 0 and 1 of each face and keeps the middle layers in place, so its middle centers stay where they are.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cube5 {
+    corners: Corners,
+    midges: Edges,
+    wings: Wings4,
+    x_centers: Centers4,
+    t_centers: TCenters5,
+}
+
+impl Cube5 {
+    pub const fn new(corners: Corners, midges: Edges, wings: Wings4, x_centers: Centers4, t_centers: TCenters5) -> Self {
+        Self { corners, midges, wings, x_centers, t_centers }
+    }
+
+    pub fn solved() -> Self {
+        Self {
+            corners: Corners::solved(),
+            midges: Edges::solved(),
+            wings: Wings4::solved(),
+            x_centers: Centers4::solved(),
+            t_centers: TCenters5::solved(),
+        }
+    }
+
+    pub fn corners(&self) -> Corners {
+        self.corners
+    }
+
+    /// The middle edges.
+    pub fn midges(&self) -> Edges {
+        self.midges
+    }
+
+    pub fn wings(&self) -> Wings4 {
+        self.wings
+    }
+
+    pub fn x_centers(&self) -> Centers4 {
+        self.x_centers
+    }
+
+    pub fn t_centers(&self) -> TCenters5 {
+        self.t_centers
+    }
+
+    /// Panics for a twist of a middle layer.
+    pub fn twisted(&self, twist: LayerTwist) -> Self {
+        let outer = twist.layer == 0;
+        Self {
+            corners: if outer { twist.twist * self.corners } else { self.corners },
+            midges: if outer { twist.twist * self.midges } else { self.midges },
+            wings: self.wings.twisted(twist),
+            x_centers: self.x_centers.twisted(twist),
+            t_centers: self.t_centers.twisted(twist),
+        }
+    }
+
+    pub fn twisted_by(&self, twists: &[LayerTwist]) -> Self {
+        twists.iter().fold(*self, |cube, &twist| cube.twisted(twist))
+    }
+
+    pub fn is_solved(&self) -> bool {
+        *self == Self::solved()
+    }
+
+    /// The 3x3x3 that the cube reduces to, if the centers are solved and the wings paired with the middle edges.
+    /// The middle edges turn like the edges of the 3x3x3, so unlike the 4x4x4, the 5x5x5 has no parities.
+    pub fn reduced(&self) -> Option<Cube> {
+        let solved_centers = self.x_centers.is_solved() && self.t_centers.is_solved();
+        (solved_centers && self.wings.edges() == Some(self.midges)).then(|| Cube::from_cubies(&self.corners, &self.midges))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reduced() {
+        let twists = parse_twist_sequence("R U2 F' L D B2 R'").unwrap();
+        let cube = Cube5::solved().twisted_by(&twists.iter().map(|&t| LayerTwist::outer(t)).collect::<Vec<_>>());
+        assert_eq!(cube.reduced(), Some(Cube::solved().twisted_by(&twists)));
+
+        let twists = parse_layer_twists("Rw U 2F'").unwrap();
+        let cube = Cube5::solved().twisted_by(&twists);
+        assert_eq!(cube.reduced(), None);
+        assert!(cube.twisted_by(&inverse_layer_twists(&twists)).is_solved());
+
+        // The 4x4x4 permutation parity alg swaps wings of two edges, which the middle edges don't pair with.
+        let cube = Cube5::solved().twisted_by(&parse_layer_twists("2R2 U2 2R2 Uw2 2R2 Uw2").unwrap());
+        assert!(cube.x_centers().is_solved() && cube.wings().is_paired());
+        assert_eq!(cube.reduced(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_middle_layer() {
+        Cube5::solved().twisted("3R".parse().unwrap());
+    }
+}
@@ -0,0 +1,151 @@
+use super::*;
+use crate::*;
+use std::collections::HashMap;
+
+/// The pieces of an orbit of 24 after the twists, where `map` is the map of a twist on the orbit, like `Wings4::map`:
+/// slot i holds the piece that the solved orbit had at slot `pieces[i]`.
+pub(crate) fn twisted(twists: &[LayerTwist], map: fn(LayerTwist) -> &'static [u8; 24]) -> [u8; 24] {
+    twists.iter().fold(core::array::from_fn(|slot| slot as u8), |pieces, &twist| {
+        let map = map(twist);
+        core::array::from_fn(|slot| pieces[map[slot] as usize])
+    })
+}
+
+/// Whether the permutation of the pieces of an orbit of 24 is even, which their 3-cycles keep.
+pub(crate) fn is_even(permutation: &[usize; 24]) -> bool {
+    let mut seen = [false; 24];
+    let mut transpositions = 0;
+    for start in 0..24 {
+        if seen[start] {
+            continue;
+        }
+        // A cycle of length n takes n - 1 transpositions.
+        let mut slot = start;
+        let mut length = 0;
+        while !seen[slot] {
+            seen[slot] = true;
+            slot = permutation[slot];
+            length += 1;
+        }
+        transpositions += length - 1;
+    }
+    transpositions % 2 == 0
+}
+
+/// The three slots of a cycle, where the piece at the first slot goes to the second, starting with the smallest.
+fn cycle_key(cycle: [usize; 3]) -> [usize; 3] {
+    let start = (0..3).min_by_key(|&i| cycle[i]).unwrap();
+    core::array::from_fn(|i| cycle[(start + i) % 3])
+}
+
+/// The twists of each 3-cycle of the pieces of an orbit of 24, made of a 3-cycle that keeps all other pieces of the cube.
+pub(crate) struct ThreeCycles {
+    cycles: HashMap<[usize; 3], Vec<LayerTwist>>,
+}
+
+impl ThreeCycles {
+    /// The shortest conjugates of the cycle, rotated, mirrored and inverted, for each cycle of three slots of the orbit.
+    pub(crate) fn new(cycle: &str, map: fn(LayerTwist) -> &'static [u8; 24]) -> Self {
+        let cycle = parse_layer_twists(cycle).unwrap();
+        let mut bases = Vec::new();
+        for rotation in rotations() {
+            let rotated: Vec<LayerTwist> = cycle.iter().map(|t| rotation.iter().fold(*t, |t, &rot| t.conjugate_by(rot))).collect();
+            let mirrored: Vec<LayerTwist> = rotated.iter().map(|t| t.mirrored(Axis::X)).collect();
+            bases.extend([inverse_layer_twists(&rotated), rotated, inverse_layer_twists(&mirrored), mirrored]);
+        }
+        // The slots of each base cycle, where the piece at the first goes to the second.
+        let slots: Vec<[usize; 3]> = bases
+            .iter()
+            .map(|base| {
+                let pieces = twisted(base, map);
+                let position = |piece| (0..24).find(|&slot| pieces[slot] as usize == piece).unwrap();
+                let a = (0..24).find(|&slot| pieces[slot] as usize != slot).expect("the cycle moves pieces");
+                [a, position(a), position(position(a))]
+            })
+            .collect();
+
+        let mut cycles = HashMap::new();
+        let mut setups = vec![vec![]];
+        for depth in 0..=3 {
+            let mut next_setups = Vec::new();
+            for setup in &setups {
+                // After the setup, the slot s holds the piece of slot `pieces[s]`, which the base cycle moves on.
+                let pieces = twisted(setup, map);
+                for (base, slots) in bases.iter().zip(&slots) {
+                    cycles
+                        .entry(cycle_key(slots.map(|s| pieces[s] as usize)))
+                        .or_insert_with(|| [setup.as_slice(), base, &inverse_layer_twists(setup)].concat());
+                }
+                // The twists of the layers 0 and 1, see `map_index`.
+                for twist in LayerTwist::all(4).into_iter().filter(|_| depth < 3) {
+                    if setup.last().is_none_or(|last: &LayerTwist| last.twist.face() != twist.twist.face() || last.layer != twist.layer) {
+                        next_setups.push([setup.as_slice(), &[twist]].concat());
+                    }
+                }
+            }
+            setups = next_setups;
+        }
+
+        // The few cycles that need longer setups are two cycles that share two slots.
+        for key in (0..24).flat_map(|a| (a + 1..24).flat_map(move |b| (a + 1..24).map(move |c| [a, b, c]))) {
+            if key[1] == key[2] || cycles.contains_key(&key) {
+                continue;
+            }
+            let [a, b, c] = key;
+            let expected: [u8; 24] = core::array::from_fn(|slot| {
+                let piece = if slot == b { a } else if slot == c { b } else if slot == a { c } else { slot };
+                piece as u8
+            });
+            let twists = (0..24)
+                .filter(|d| !key.contains(d))
+                .flat_map(|d| {
+                    let parts = [[a, b, d], [a, d, b], [a, c, d], [a, d, c], [b, c, d], [b, d, c]].map(cycle_key);
+                    parts.into_iter().flat_map(move |first| parts.map(|second| (first, second)))
+                })
+                .filter_map(|(first, second)| Some([cycles.get(&first)?.as_slice(), cycles.get(&second)?].concat()))
+                .find(|twists| twisted(twists, map) == expected)
+                .expect("two cycles make up the cycle");
+            cycles.insert(key, twists);
+        }
+        Self { cycles }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.cycles.len()
+    }
+
+    /// The 3-cycles that take each piece to its destination, `destinations[slot]` for the piece at the slot,
+    /// which have to be an even permutation.
+    /// It cycles the piece at the first wrong slot to its destination, and the piece there on to its own.
+    pub(crate) fn solve(&self, mut destinations: [usize; 24]) -> Vec<LayerTwist> {
+        let mut twists = Vec::new();
+        while let Some(b) = (0..24).find(|&slot| destinations[slot] != slot) {
+            let x = destinations[b];
+            let y = if destinations[x] == b {
+                (0..24).find(|&s| s != b && s != x && destinations[s] != s).expect("the pieces are an even permutation")
+            } else {
+                destinations[x]
+            };
+            twists.extend(&self.cycles[&cycle_key([b, x, y])]);
+            (destinations[x], destinations[y], destinations[b]) = (destinations[b], destinations[x], destinations[y]);
+        }
+        twists
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        let cycles = ThreeCycles::new("2R U' L U 2R' U' L' U", Wings4::map);
+        let twists = parse_layer_twists("2R U 2F' L 2D2 R 2B 2U").unwrap();
+        let wings = twists.iter().fold(Wings4::solved(), |w, &t| w.twisted(t));
+        assert!(wings.is_even());
+        // The destination of the wing at each slot is the slot of its number.
+        let solution = cycles.solve(core::array::from_fn(|slot| wings.wing(slot)));
+        assert_eq!(solution.iter().fold(wings, |w, &t| w.twisted(t)), Wings4::solved());
+    }
+}
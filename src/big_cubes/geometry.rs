@@ -0,0 +1,124 @@
+use super::LayerTwist;
+use crate::*;
+
+// The pieces of an NxN cube sit at the integer positions (x, y, z) with coordinates in {-(N-1), -(N-3), .., N-1},
+// with R towards +x, U towards +y and F towards +z. The effect of a layer twist on the pieces follows from rotating them.
+
+pub(crate) type Vector = [i32; 3];
+
+pub(crate) const FACES: [Face; 6] = [Face::U, Face::R, Face::F, Face::D, Face::L, Face::B];
+
+/// The outward normal of the face.
+pub(crate) const fn normal(face: Face) -> Vector {
+    match face {
+        Face::U => [0, 1, 0],
+        Face::R => [1, 0, 0],
+        Face::F => [0, 0, 1],
+        Face::D => [0, -1, 0],
+        Face::L => [-1, 0, 0],
+        Face::B => [0, 0, -1],
+    }
+}
+
+/// The directions of the columns and of the rows of the face, as read in the facelet order of `Facelets`.
+pub(crate) const fn right_and_down(face: Face) -> (Vector, Vector) {
+    match face {
+        Face::U => ([1, 0, 0], [0, 0, 1]),
+        Face::R => ([0, 0, -1], [0, -1, 0]),
+        Face::F => ([1, 0, 0], [0, -1, 0]),
+        Face::D => ([1, 0, 0], [0, 0, -1]),
+        Face::L => ([0, 0, 1], [0, -1, 0]),
+        Face::B => ([-1, 0, 0], [0, -1, 0]),
+    }
+}
+
+pub(crate) const fn dot(a: Vector, b: Vector) -> i32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) const fn cross(a: Vector, b: Vector) -> Vector {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+pub(crate) fn combination(terms: &[(i32, Vector)]) -> Vector {
+    core::array::from_fn(|i| terms.iter().map(|(factor, v)| factor * v[i]).sum())
+}
+
+/// The vector rotated by the twist, i.e. by `power` clockwise quarter turns as seen when looking at the face.
+pub(crate) fn rotated(mut v: Vector, twist: Twist) -> Vector {
+    let n = normal(twist.face());
+    for _ in 0..twist.power() {
+        v = combination(&[(dot(n, v), n), (-1, cross(n, v))]);
+    }
+    v
+}
+
+/// Whether a piece at the position is in the layer of the twist, counted from its face, of a cube of the size.
+pub(crate) fn in_layer(position: Vector, twist: Twist, layer: usize, size: usize) -> bool {
+    dot(position, normal(twist.face())) == size as i32 - 1 - 2 * layer as i32
+}
+
+/// The primary and the secondary face of the edge positions, see `EDGE_FACELETS`.
+pub(crate) const EDGE_FACES: [(Face, Face); 12] = [
+    (Face::U, Face::F),
+    (Face::U, Face::B),
+    (Face::D, Face::B),
+    (Face::D, Face::F),
+    (Face::U, Face::L),
+    (Face::U, Face::R),
+    (Face::D, Face::R),
+    (Face::D, Face::L),
+    (Face::L, Face::F),
+    (Face::R, Face::F),
+    (Face::R, Face::B),
+    (Face::L, Face::B),
+];
+
+/// The map of a layer twist on the pieces at the positions, of a cube of the size:
+/// after the twist, the piece at `positions[i]` is the one that was at `positions[map[i]]`, like the twists of `Corners`.
+pub(crate) fn slot_map<const N: usize>(positions: &[Vector; N], twist: Twist, layer: usize, size: usize) -> [u8; N] {
+    let mut map = [0; N];
+    for (from, &position) in positions.iter().enumerate() {
+        let position = if in_layer(position, twist, layer, size) { rotated(position, twist) } else { position };
+        let to = positions.iter().position(|&p| p == position).expect("a piece moves to another piece's position");
+        map[to] = from as u8;
+    }
+    map
+}
+
+/// The positions of an orbit of 24 centers of a cube of the size, four per face in the order URFDLB:
+/// the one `offset` (row, column) away from the middle of the face and its quarter turns about it, read row by row like in `Facelets`.
+pub(crate) fn center_positions(size: usize, offset: (i32, i32)) -> [Vector; 24] {
+    let (r, c) = offset;
+    let mut offsets = [(r, c), (c, -r), (-r, -c), (-c, r)];
+    offsets.sort();
+    core::array::from_fn(|slot| {
+        let face = FACES[slot / 4];
+        let (right, down) = right_and_down(face);
+        let (row, col) = offsets[slot % 4];
+        combination(&[(size as i32 - 1, normal(face)), (col, right), (row, down)])
+    })
+}
+
+/// The positions of an orbit of 24 wings of a cube of the size, two per edge position of the 3x3x3, `offset` away from its middle.
+/// Slot 2p + k is half k of edge position p, where half 0 lies towards the negative cross product of the normals
+/// of the primary and the secondary face of the position. Both halves turn into each other's half when an edge flips.
+pub(crate) fn wing_positions(size: usize, offset: i32) -> [Vector; 24] {
+    core::array::from_fn(|slot| {
+        let (primary, secondary) = EDGE_FACES[slot / 2];
+        let (p, s) = (normal(primary), normal(secondary));
+        combination(&[(size as i32 - 1, p), (size as i32 - 1, s), ((2 * (slot % 2) as i32 - 1) * offset, cross(p, s))])
+    })
+}
+
+/// The maps of the twists of the layers 0 and 1 on an orbit of 24 pieces of a cube of the size, by `map_index`.
+pub(crate) fn layer_maps(positions: &[Vector; 24], size: usize) -> Vec<[u8; 24]> {
+    LayerTwist::all(size).iter().filter(|t| t.layer < 2).map(|t| slot_map(positions, t.twist, t.layer, size)).collect()
+}
+
+/// The index of the twist in the maps of `layer_maps`.
+/// The 4x4x4 and the 5x5x5 turn the layers 0 and 1, and the 5x5x5 keeps its middle layers in place.
+pub(crate) fn map_index(twist: LayerTwist) -> usize {
+    assert!(twist.layer < 2, "only the layers 0 and 1 of a 4x4x4 or 5x5x5 turn, not {}", twist.layer);
+    twist.twist as usize + 18 * twist.layer
+}
@@ -0,0 +1,149 @@
+use crate::*;
+use std::fmt;
+
+/// A twist of a single layer of a big cube, counted from the face of the twist:
+/// layer 0 is the outer layer, which turns like the twist of the 3x3x3, and layer 1 the one below it.
+/// In SiGN notation, "R" twists layer 0 and "2R" layer 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LayerTwist {
+    pub twist: Twist,
+    pub layer: usize,
+}
+
+impl LayerTwist {
+    pub const fn new(twist: Twist, layer: usize) -> Self {
+        Self { twist, layer }
+    }
+
+    /// The twist of the outer layer, which a 3x3x3 twist stands for on a reduced big cube.
+    pub const fn outer(twist: Twist) -> Self {
+        Self { twist, layer: 0 }
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self { twist: self.twist.inverse(), layer: self.layer }
+    }
+
+    /// The twist of the cube before a rotation of the whole cube as the twist of the cube after it, see `Twist::conjugate_by`.
+    pub fn conjugate_by(&self, rot: Axis) -> Self {
+        Self { twist: self.twist.conjugate_by(rot), layer: self.layer }
+    }
+
+    /// The twist as seen in a mirror perpendicular to the axis, see `Twist::mirrored`.
+    pub fn mirrored(&self, axis: Axis) -> Self {
+        Self { twist: self.twist.mirrored(axis), layer: self.layer }
+    }
+
+    /// The twists of all layers of a cube of the size that don't hold its middle, outer layers first.
+    pub fn all(size: usize) -> Vec<Self> {
+        (0..size / 2).flat_map(|layer| ALL_TWISTS.map(|twist| Self::new(twist, layer))).collect()
+    }
+}
+
+impl fmt::Display for LayerTwist {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.layer > 0 {
+            write!(f, "{}", self.layer + 1)?;
+        }
+        write!(f, "{}", self.twist.to_wca())
+    }
+}
+
+/// Splits a twist like "3Rw'" into its layer number, 1 if there is none, whether it is wide and the 3x3x3 twist.
+fn split(s: &str) -> Result<(usize, bool, Twist), String> {
+    let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let number = if digits == 0 { 1 } else { s[..digits].parse().map_err(|_| format!("Unknown twist: '{s}'"))? };
+    let rest = &s[digits..];
+    let (wide, twist) = match rest.get(1..2) {
+        Some("w") => (true, format!("{}{}", &rest[..1], &rest[2..])),
+        _ => (false, rest.to_string()),
+    };
+    if number == 0 {
+        return Err(format!("Unknown twist: '{s}'"));
+    }
+    Ok((number, wide, twist.parse().map_err(|_| format!("Unknown twist: '{s}'"))?))
+}
+
+impl core::str::FromStr for LayerTwist {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match split(s)? {
+            (number, false, twist) => Ok(Self::new(twist, number - 1)),
+            (_, true, _) => Err(format!("'{s}' twists several layers")),
+        }
+    }
+}
+
+/// Parses whitespace-separated layer twists in SiGN notation.
+/// A wide twist like "Rw" or "3Rw" stands for the twists of all layers up to its own, "Rw" for "R 2R".
+pub fn parse_layer_twists(input: &str) -> Result<Vec<LayerTwist>, String> {
+    let mut twists = Vec::new();
+    for s in input.split_whitespace() {
+        match split(s)? {
+            (number, false, twist) => twists.push(LayerTwist::new(twist, number - 1)),
+            (number, true, twist) => {
+                let layers = if s.starts_with(|c: char| c.is_ascii_digit()) { number } else { 2 };
+                twists.extend((0..layers).map(|layer| LayerTwist::new(twist, layer)));
+            }
+        }
+    }
+    Ok(twists)
+}
+
+/// Formats layer twists as space-separated SiGN notation.
+pub fn to_sign(twists: &[LayerTwist]) -> String {
+    twists.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+/// The twists that undo the twists.
+pub fn inverse_layer_twists(twists: &[LayerTwist]) -> Vec<LayerTwist> {
+    twists.iter().rev().map(|t| t.inverse()).collect()
+}
+
+/// Merges the twists of the same layer of the same face with only twists of the same axis between them,
+/// which commute with them, e.g. "2R 2R" into "2R2" and "R 2R R' 2R'" into nothing.
+pub fn cancelled_layer_twists(twists: &[LayerTwist]) -> Vec<LayerTwist> {
+    let mut result: Vec<LayerTwist> = Vec::with_capacity(twists.len());
+    for &twist in twists {
+        let same_axis = result.iter().rev().take_while(|t| t.twist.axis() == twist.twist.axis()).count();
+        let start = result.len() - same_axis;
+        match (start..result.len()).find(|&i| result[i].layer == twist.layer && result[i].twist.face() == twist.twist.face()) {
+            Some(i) => {
+                let power = (result[i].twist.power() + twist.twist.power()) % 4;
+                if power == 0 {
+                    result.remove(i);
+                } else {
+                    result[i].twist = result[i].twist.with_power(power);
+                }
+            }
+            None => result.push(twist),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let twists = parse_layer_twists("R 2U' Rw2 3Fw").unwrap();
+        assert_eq!(to_sign(&twists), "R 2U' R2 2R2 F 2F 3F");
+        assert_eq!("2L2".parse(), Ok(LayerTwist::new(Twist::L2, 1)));
+        assert!("Rw".parse::<LayerTwist>().is_err());
+        assert!(parse_layer_twists("R 0U").is_err());
+        assert!(parse_layer_twists("2X").is_err());
+        assert_eq!(LayerTwist::all(4).len(), 36);
+        assert_eq!(LayerTwist::all(5).len(), 36);
+    }
+
+    #[test]
+    fn test_cancelled() {
+        let twists = parse_layer_twists("R 2R 2R U U' 2R' F").unwrap();
+        assert_eq!(to_sign(&cancelled_layer_twists(&twists)), "R 2R F");
+        assert_eq!(cancelled_layer_twists(&parse_layer_twists("Rw Rw'").unwrap()), vec![]);
+        assert_eq!(to_sign(&inverse_layer_twists(&parse_layer_twists("R 2U'").unwrap())), "2U R'");
+    }
+}
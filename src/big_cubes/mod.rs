@@ -0,0 +1,21 @@
+//! Cubes bigger than the 3x3x3, solved by reducing them to one.
+
+mod geometry;
+mod cycles;
+pub mod layer_twist;
+pub mod centers;
+pub mod wings4;
+pub mod cube4;
+pub mod reduction4;
+pub mod cube5;
+pub mod reduction5;
+pub mod nxn_scramble;
+
+pub use layer_twist::*;
+pub use centers::*;
+pub use wings4::*;
+pub use cube4::*;
+pub use reduction4::*;
+pub use cube5::*;
+pub use reduction5::*;
+pub use nxn_scramble::*;
@@ -0,0 +1,136 @@
+use super::*;
+use crate::*;
+use rand::{rngs::StdRng, RngExt, SeedableRng};
+use std::fmt;
+
+/// A twist of the `width` outer layers of a face, as in WCA scrambles of big cubes:
+/// "R" twists one layer, "Rw" two and "3Rw" three.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WideTwist {
+    pub twist: Twist,
+    pub width: usize,
+}
+
+impl WideTwist {
+    pub const fn new(twist: Twist, width: usize) -> Self {
+        Self { twist, width }
+    }
+
+    /// The twists of the single layers, see `parse_layer_twists`.
+    pub fn layer_twists(&self) -> Vec<LayerTwist> {
+        (0..self.width).map(|layer| LayerTwist::new(self.twist, layer)).collect()
+    }
+}
+
+impl fmt::Display for WideTwist {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let wca = self.twist.to_wca();
+        match self.width {
+            1 => write!(f, "{wca}"),
+            2 => write!(f, "{}w{}", &wca[..1], &wca[1..]),
+            width => write!(f, "{width}{}w{}", &wca[..1], &wca[1..]),
+        }
+    }
+}
+
+/// Random twist generator for scrambles of the 4x4x4 to the 7x7x7, in the style of WCA random-move scrambles.
+/// It twists up to half of the layers of a face, but on an even cube the half of a face only for U, R and F,
+/// since the half of the opposite face is the same up to a rotation.
+/// Like `WcaScrambleGen`, it never twists the same layers twice with only twists of the same axis between them, like "R L R".
+pub struct NxNScrambleGen {
+    rng: StdRng,
+    size: usize,
+    twists: Vec<WideTwist>,
+}
+
+impl NxNScrambleGen {
+    /// Panics unless the size is 4 to 7.
+    pub fn new(size: usize, seed: u64) -> Self {
+        assert!((4..=7).contains(&size), "scrambles are for the sizes 4 to 7, not {size}");
+        let twists = (1..=size / 2)
+            .flat_map(|width| ALL_TWISTS.map(|twist| WideTwist::new(twist, width)))
+            .filter(|t| 2 * t.width < size || [Face::U, Face::R, Face::F].contains(&t.twist.face()))
+            .collect();
+        Self { rng: StdRng::seed_from_u64(seed), size, twists }
+    }
+
+    /// Seeded from the operating system's entropy.
+    pub fn from_entropy(size: usize) -> Self {
+        Self::new(size, rand::random())
+    }
+
+    /// The number of twists of a scramble of the size: 40, 60, 80 and 100 for the 4x4x4 to the 7x7x7.
+    pub const fn length(size: usize) -> usize {
+        20 * (size - 2)
+    }
+
+    pub fn gen_twists(&mut self) -> Vec<WideTwist> {
+        let count = Self::length(self.size);
+        let mut twists: Vec<WideTwist> = Vec::with_capacity(count);
+        for _ in 0..count {
+            // The twists since the last one of another axis, which commute with each other.
+            let run = match twists.last() {
+                Some(last) => twists.iter().rev().take_while(|t| same_axis(t.twist, last.twist)).count(),
+                None => 0,
+            };
+            let run = &twists[twists.len() - run..];
+            let candidates: Vec<WideTwist> = self
+                .twists
+                .iter()
+                .copied()
+                .filter(|t| !run.iter().any(|r| r.twist.face() == t.twist.face() && r.width == t.width))
+                .collect();
+            twists.push(candidates[self.rng.random_range(0..candidates.len())]);
+        }
+        twists
+    }
+
+    /// A scramble in WCA notation, e.g. "Rw U2 3Fw' ...".
+    pub fn gen_scramble(&mut self) -> String {
+        self.gen_twists().iter().map(|t| t.to_string()).collect::<Vec<_>>().join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(WideTwist::new(Twist::R1, 1).to_string(), "R");
+        assert_eq!(WideTwist::new(Twist::U3, 2).to_string(), "Uw'");
+        assert_eq!(WideTwist::new(Twist::F2, 3).to_string(), "3Fw2");
+        assert_eq!(to_sign(&WideTwist::new(Twist::F2, 3).layer_twists()), "F2 2F2 3F2");
+    }
+
+    #[test]
+    fn test_gen_scramble() {
+        for size in 4..=7 {
+            let twists = NxNScrambleGen::new(size, 42).gen_twists();
+            assert_eq!(twists.len(), 20 * (size - 2));
+            assert!(twists.iter().all(|t| t.width <= size / 2));
+            for (i, t) in twists.iter().enumerate() {
+                if 2 * t.width == size {
+                    assert!([Face::U, Face::R, Face::F].contains(&t.twist.face()));
+                }
+                // The same layers aren't twisted again with only twists of the same axis between.
+                let run = twists[..i].iter().rev().take_while(|s| same_axis(s.twist, t.twist));
+                assert!(run.clone().all(|s| s.twist.face() != t.twist.face() || s.width != t.width));
+            }
+            // The notation parses into the twists of the single layers.
+            let layer_twists: Vec<LayerTwist> = twists.iter().flat_map(|t| t.layer_twists()).collect();
+            let scramble = twists.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(" ");
+            assert_eq!(parse_layer_twists(&scramble), Ok(layer_twists));
+        }
+        // The 4x4x4 and the 5x5x5 can be scrambled with them.
+        let twists: Vec<LayerTwist> = NxNScrambleGen::new(5, 43).gen_twists().iter().flat_map(|t| t.layer_twists()).collect();
+        assert!(Cube5::solved().twisted_by(&twists).reduced().is_none());
+        assert_ne!(NxNScrambleGen::new(4, 1).gen_scramble(), NxNScrambleGen::new(4, 2).gen_scramble());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_size() {
+        NxNScrambleGen::new(3, 42);
+    }
+}
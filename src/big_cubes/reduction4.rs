@@ -0,0 +1,301 @@
+use super::cycles::ThreeCycles;
+use super::*;
+use crate::*;
+use std::collections::VecDeque;
+
+// The reduction solves the centers in three stages, each with the distance table of what it solves,
+// and with only the twists that keep what the stages before solved:
+// 1. the U and D centers onto U and D,
+// 2. the L and R centers onto L and R, and the wings into an even permutation, which wing 3-cycles can't change,
+// 3. all centers.
+// Then wing 3-cycles, which keep the centers and the corners, pair the wings, and a 3x3x3 solver solves the rest.
+
+// The center slots of faces as bit masks, see `Centers4`.
+const ALL: u32 = 0xFF_FFFF;
+const UD: u32 = 0x00_F00F;
+const RL: u32 = 0x0F_00F0;
+const FB: u32 = 0xF0_0F00;
+
+const BINOMIALS: [[usize; 25]; 25] = {
+    let mut binomials = [[0; 25]; 25];
+    let mut n = 0;
+    while n < 25 {
+        let mut k = 0;
+        while k < 25 {
+            binomials[n][k] = binomial(n, k);
+            k += 1;
+        }
+        n += 1;
+    }
+    binomials
+};
+
+/// The colexicographic index of the combination of the set bits of `mask` among those of `slots`, which hold them.
+fn combination_of(mask: u32, slots: u32) -> usize {
+    debug_assert_eq!(mask & !slots, 0);
+    let (mut index, mut count, mut rest) = (0, 0, mask);
+    while rest != 0 {
+        let below = (1 << rest.trailing_zeros()) - 1;
+        count += 1;
+        index += BINOMIALS[(slots & below).count_ones() as usize][count];
+        rest &= rest - 1;
+    }
+    index
+}
+
+/// The slots of the centers of each group of colours as bit masks.
+fn masks_of(centers: &Centers4, groups: &[&[Face]]) -> [u32; 3] {
+    core::array::from_fn(|g| {
+        groups.get(g).map_or(0, |group| (0..24).filter(|&slot| group.contains(&centers.faces()[slot])).fold(0, |mask, slot| mask | 1 << slot))
+    })
+}
+
+/// Twists masks of center slots a byte at a time: the mask of `twist` is the OR of `bytes[i][(mask >> 8 * i) & 0xFF]`.
+struct MaskTwister {
+    bytes: [[u32; 256]; 3],
+}
+
+impl MaskTwister {
+    fn new(twist: LayerTwist) -> Self {
+        let map = Centers4::map(twist);
+        let mut bytes = [[0; 256]; 3];
+        for (slot, &from) in map.iter().enumerate() {
+            let (i, bit) = (from as usize / 8, from % 8);
+            for (byte, twisted) in bytes[i].iter_mut().enumerate() {
+                if byte & 1 << bit != 0 {
+                    *twisted |= 1 << slot;
+                }
+            }
+        }
+        Self { bytes }
+    }
+
+    fn twisted(&self, mask: u32) -> u32 {
+        self.bytes[0][(mask & 0xFF) as usize] | self.bytes[1][(mask >> 8 & 0xFF) as usize] | self.bytes[2][(mask >> 16) as usize]
+    }
+}
+
+/// Whether the twist changes the parity of the wings, see `Wings4::is_even`.
+fn flips_wing_parity(twist: LayerTwist) -> bool {
+    twist.layer == 1 && twist.twist.power() != 2
+}
+
+/// A stage of the centers, which tracks the slots of up to three groups of colours and the parity of the wings,
+/// with the distance of each index of them.
+struct Stage {
+    twists: Vec<LayerTwist>,
+    groups: &'static [&'static [Face]],
+    index: fn([u32; 3], bool) -> usize,
+    distances: Vec<u8>,
+}
+
+impl Stage {
+    /// Creates the distance table with a breadth-first search from the solved centers and wings.
+    fn new(twists: Vec<LayerTwist>, groups: &'static [&'static [Face]], index: fn([u32; 3], bool) -> usize, size: usize) -> Self {
+        let mut distances = vec![u8::MAX; size];
+        let solved = masks_of(&Centers4::solved(), groups);
+        let mut queue = VecDeque::from([(solved, true, 0)]);
+        distances[index(solved, true)] = 0;
+        let twisters: Vec<(MaskTwister, bool)> = twists.iter().map(|&t| (MaskTwister::new(t), flips_wing_parity(t))).collect();
+        while let Some((masks, even, distance)) = queue.pop_front() {
+            for (twister, flips) in &twisters {
+                let (next, next_even) = (masks.map(|mask| twister.twisted(mask)), even != *flips);
+                let i = index(next, next_even);
+                if distances[i] == u8::MAX {
+                    distances[i] = distance + 1;
+                    queue.push_back((next, next_even, distance + 1));
+                }
+            }
+        }
+        Self { twists, groups, index, distances }
+    }
+
+    fn distance(&self, centers: &Centers4, even_wings: bool) -> u8 {
+        self.distances[(self.index)(masks_of(centers, self.groups), even_wings)]
+    }
+
+    /// The twists that solve the stage, one that gets closer at a time.
+    fn solve(&self, mut centers: Centers4, mut even_wings: bool) -> Vec<LayerTwist> {
+        let mut solution = Vec::new();
+        let mut distance = self.distance(&centers, even_wings);
+        assert_ne!(distance, u8::MAX, "the stages before leave the centers in a state that this stage solves");
+        while distance > 0 {
+            let closer = |&&t: &&LayerTwist| self.distance(&centers.twisted(t), even_wings != flips_wing_parity(t)) == distance - 1;
+            let twist = *self.twists.iter().find(closer).expect("a twist gets closer");
+            centers = centers.twisted(twist);
+            even_wings ^= flips_wing_parity(twist);
+            solution.push(twist);
+            distance -= 1;
+        }
+        solution
+    }
+}
+
+fn ud_index(masks: [u32; 3], _: bool) -> usize {
+    combination_of(masks[0], ALL)
+}
+
+fn rl_index(masks: [u32; 3], even: bool) -> usize {
+    combination_of(masks[0], ALL & !UD) * 2 + usize::from(!even)
+}
+
+fn centers_index(masks: [u32; 3], _: bool) -> usize {
+    (combination_of(masks[0], UD) * 70 + combination_of(masks[1], RL)) * 70 + combination_of(masks[2], FB)
+}
+
+/// The wing 3-cycle "2R U' L U 2R' U' L' U", which keeps all other pieces.
+pub(crate) const WING_CYCLE: &str = "2R U' L U 2R' U' L' U";
+
+/// Solves the 4x4x4 by reducing it to a 3x3x3.
+pub struct Reduction4Solver {
+    stages: [Stage; 3],
+    wing_cycles: ThreeCycles,
+}
+
+impl Default for Reduction4Solver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Reduction4Solver {
+    /// Creates the distance tables of the centers, which takes about a second, and the wing 3-cycles.
+    pub fn new() -> Self {
+        let outer: Vec<LayerTwist> = ALL_TWISTS.iter().map(|&t| LayerTwist::outer(t)).collect();
+        let inner = |keep: fn(Twist) -> bool| ALL_TWISTS.into_iter().filter(move |&t| keep(t)).map(|t| LayerTwist::new(t, 1));
+        let stage_2_twists = outer.iter().copied().chain(inner(|t| t.axis() == Axis::Z || t.power() == 2)).collect();
+        let stage_3_twists = outer.iter().copied().chain(inner(|t| t.power() == 2)).collect();
+        let stages = [
+            Stage::new(LayerTwist::all(4), &[&[Face::U, Face::D]], ud_index, binomial(24, 8)),
+            Stage::new(stage_2_twists, &[&[Face::R, Face::L]], rl_index, binomial(16, 8) * 2),
+            Stage::new(stage_3_twists, &[&[Face::U], &[Face::R], &[Face::F]], centers_index, 70 * 70 * 70),
+        ];
+        Self { stages, wing_cycles: ThreeCycles::new(WING_CYCLE, Wings4::map) }
+    }
+
+    /// The twists that solve the centers and pair the wings, such that the cube reduces to a 3x3x3, see `Cube4::reduced`.
+    pub fn reduce(&self, cube: &Cube4) -> Vec<LayerTwist> {
+        let mut twists = self.solve_centers(cube.centers(), cube.wings().is_even());
+        let reduced = cube.twisted_by(&twists);
+        let target = pairing_target(&reduced);
+        twists.extend(self.pair_wings(reduced.wings(), &target));
+        cancelled_layer_twists(&twists)
+    }
+
+    /// The twists that solve the centers and make the wings an even permutation, which the 4x4x4 and the 5x5x5 share.
+    pub(crate) fn solve_centers(&self, centers: Centers4, even_wings: bool) -> Vec<LayerTwist> {
+        let mut twists: Vec<LayerTwist> = Vec::new();
+        for stage in &self.stages {
+            let even = even_wings == twists.iter().filter(|&&t| flips_wing_parity(t)).count().is_multiple_of(2);
+            twists.extend(stage.solve(centers.twisted_by(&twists), even));
+        }
+        twists
+    }
+
+    /// The wing 3-cycles that take the wings to the target, the wing for each slot, see `pairing_target`.
+    pub(crate) fn pair_wings(&self, wings: Wings4, target: &[usize; 24]) -> Vec<LayerTwist> {
+        self.wing_cycles.solve(core::array::from_fn(|slot| (0..24).find(|&s| target[s] == wings.wing(slot)).unwrap()))
+    }
+
+    /// Solves the cube: reduces it, and solves the 3x3x3 with the twists of the outer layers, in at most `max_solution_length` of them.
+    pub fn solve(&self, cube: &Cube4, solver: &mut TwoPhaseSolver, max_solution_length: u8) -> Result<Vec<LayerTwist>, String> {
+        let mut twists = self.reduce(cube);
+        let reduced = cube.twisted_by(&twists).reduced().expect("the reduction reduces the cube");
+        twists.extend(solver.solve_twists(reduced, max_solution_length)?.into_iter().map(LayerTwist::outer));
+        Ok(cancelled_layer_twists(&twists))
+    }
+}
+
+/// The wing for each slot that pairs the wings into a 3x3x3 without parities, keeping the edges that are paired already.
+fn pairing_target(cube: &Cube4) -> [usize; 24] {
+    let wings = cube.wings();
+    let paired: Vec<Option<(usize, usize)>> = (0..12).map(|p| wings.edge(p)).collect();
+    let mut edges = paired.clone();
+    let used = |edges: &[Option<(usize, usize)>], e| edges.iter().flatten().any(|&(edge, _)| edge == e);
+    // The unpaired positions take the edge of their wing at half 0, or else any edge left.
+    for p in 0..12 {
+        let wing = wings.wing(2 * p);
+        if edges[p].is_none() && !used(&edges, wing / 2) {
+            edges[p] = Some((wing / 2, wing % 2));
+        }
+    }
+    for p in 0..12 {
+        if edges[p].is_none() {
+            edges[p] = Some(((0..12).find(|&e| !used(&edges, e)).unwrap(), 0));
+        }
+    }
+    let mut edges: Vec<(usize, usize)> = edges.into_iter().flatten().collect();
+    let unpaired: Vec<usize> = (0..12).filter(|&p| paired[p].is_none()).collect();
+    // The wings are an even permutation, so if all are paired, the flips are even as well.
+    if edges.iter().map(|&(_, o)| o).sum::<usize>() % 2 == 1 {
+        edges[unpaired[0]].1 ^= 1;
+    }
+    let edge_prm: [usize; 12] = core::array::from_fn(|p| edges[p].0);
+    if is_even_permutation(permutation_index(&edge_prm)) != is_even_permutation(cube.corners().prm_index()) {
+        let (a, b) = if unpaired.len() >= 2 { (unpaired[0], unpaired[1]) } else { (0, 1) };
+        let (ea, eb) = (edges[a].0, edges[b].0);
+        edges[a].0 = eb;
+        edges[b].0 = ea;
+    }
+    core::array::from_fn(|slot| {
+        let (e, o) = edges[slot / 2];
+        2 * e + (o ^ (slot % 2))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::two_phase::empty_table_solver;
+    use rand::{rngs::StdRng, RngExt, SeedableRng};
+    use std::sync::LazyLock;
+
+    static SOLVER: LazyLock<Reduction4Solver> = LazyLock::new(Reduction4Solver::new);
+
+    #[test]
+    fn test_wing_cycle() {
+        let cube = Cube4::solved().twisted_by(&parse_layer_twists(WING_CYCLE).unwrap());
+        assert_eq!(cube.corners(), Corners::solved());
+        assert!(cube.centers().is_solved());
+        assert_eq!((0..24).filter(|&slot| cube.wings().wing(slot) != slot).count(), 3);
+        // All cycles of three wings.
+        assert_eq!(SOLVER.wing_cycles.len(), 24 * 23 * 22 / 3);
+    }
+
+    #[test]
+    fn test_stages() {
+        for stage in &SOLVER.stages {
+            assert!(stage.distances.iter().all(|&d| d != u8::MAX));
+        }
+    }
+
+    #[test]
+    fn test_reduce() {
+        assert_eq!(SOLVER.reduce(&Cube4::solved()), vec![]);
+        let cube = Cube4::solved().twisted_by(&parse_layer_twists("R U F'").unwrap());
+        assert_eq!(SOLVER.reduce(&cube), vec![]);
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let all = LayerTwist::all(4);
+        for _ in 0..20 {
+            let scramble: Vec<LayerTwist> = (0..40).map(|_| all[rng.random_range(0..all.len())]).collect();
+            let cube = Cube4::solved().twisted_by(&scramble);
+            assert!(cube.twisted_by(&SOLVER.reduce(&cube)).reduced().is_some());
+        }
+    }
+
+    #[test]
+    fn test_solve() {
+        // Scrambles that the reduction undoes completely, since the tables of the solver only reach the solved 3x3x3.
+        let mut solver = empty_table_solver();
+        for scramble in ["2R U", "2U 2R", "2F2 L"] {
+            let cube = Cube4::solved().twisted_by(&parse_layer_twists(scramble).unwrap());
+            let solution = SOLVER.solve(&cube, &mut solver, 20).unwrap();
+            assert!(cube.twisted_by(&solution).is_solved(), "{scramble}");
+        }
+
+        let mut solver = empty_table_solver().with_options(SolveOptions { max_depth: 20, ..SolveOptions::default() });
+        let cube = Cube4::solved().twisted_by(&parse_layer_twists("2R U").unwrap());
+        assert_eq!(SOLVER.solve(&cube, &mut solver, 21), Err("The maximum solution length 21 exceeds the depth limit of 20".into()));
+    }
+}
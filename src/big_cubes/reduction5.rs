@@ -0,0 +1,128 @@
+use super::cycles::{is_even, ThreeCycles};
+use super::geometry::FACES;
+use super::*;
+use crate::*;
+
+// The reduction of the 5x5x5 reuses the one of the 4x4x4, since the X-centers and the wings turn alike:
+// its center stages solve the X-centers and make the wings an even permutation.
+// Then T-center 3-cycles, which keep all other pieces, solve the T-centers,
+// wing 3-cycles pair the wings with the middle edges, and a 3x3x3 solver solves the rest.
+
+/// The T-center 3-cycle "Rw Lw' B 2L B' Lw Rw' U 2L' U'", which keeps all other pieces.
+/// It stands for the commutator of the middle layer "3R" and "U 2L' U'" on a cube without twists of the middle layers.
+const T_CENTER_CYCLE: &str = "Rw Lw' B 2L B' Lw Rw' U 2L' U'";
+
+/// Solves the 5x5x5 by reducing it to a 3x3x3.
+pub struct Reduction5Solver {
+    reduction4: Reduction4Solver,
+    t_center_cycles: ThreeCycles,
+}
+
+impl Default for Reduction5Solver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Reduction5Solver {
+    /// Creates the tables of the 4x4x4 reduction, which takes about a second, and the T-center 3-cycles.
+    pub fn new() -> Self {
+        Self { reduction4: Reduction4Solver::new(), t_center_cycles: ThreeCycles::new(T_CENTER_CYCLE, TCenters5::map) }
+    }
+
+    /// The twists that solve the centers and pair the wings with the middle edges, such that the cube reduces to a 3x3x3, see `Cube5::reduced`.
+    pub fn reduce(&self, cube: &Cube5) -> Vec<LayerTwist> {
+        let mut twists = self.reduction4.solve_centers(cube.x_centers(), cube.wings().is_even());
+        twists.extend(self.t_center_cycles.solve(t_center_destinations(&cube.twisted_by(&twists).t_centers())));
+        let reduced = cube.twisted_by(&twists);
+        // The wings of each edge go to the slots of the middle edge they belong to.
+        let midges = reduced.midges();
+        let target = core::array::from_fn(|slot| {
+            let (e, o) = midges.piece_at(slot / 2);
+            2 * e + (o ^ (slot % 2))
+        });
+        twists.extend(self.reduction4.pair_wings(reduced.wings(), &target));
+        cancelled_layer_twists(&twists)
+    }
+
+    /// Solves the cube: reduces it, and solves the 3x3x3 with the twists of the outer layers, in at most `max_solution_length` of them.
+    pub fn solve(&self, cube: &Cube5, solver: &mut TwoPhaseSolver, max_solution_length: u8) -> Result<Vec<LayerTwist>, String> {
+        let mut twists = self.reduce(cube);
+        let reduced = cube.twisted_by(&twists).reduced().expect("the reduction reduces the cube");
+        twists.extend(solver.solve_twists(reduced, max_solution_length)?.into_iter().map(LayerTwist::outer));
+        Ok(cancelled_layer_twists(&twists))
+    }
+}
+
+/// A slot of its colour for the T-center at each slot, keeping the ones in place that are, as an even permutation,
+/// which the T-center 3-cycles can reach: T-centers of the same colour can be swapped to change the parity.
+fn t_center_destinations(centers: &TCenters5) -> [usize; 24] {
+    let faces = centers.faces();
+    let mut taken: [bool; 24] = core::array::from_fn(|slot| faces[slot] == FACES[slot / 4]);
+    let mut destinations: [usize; 24] = core::array::from_fn(|slot| {
+        if faces[slot] == FACES[slot / 4] {
+            return slot;
+        }
+        let face = FACES.iter().position(|&f| f == faces[slot]).unwrap();
+        let destination = (4 * face..4 * face + 4).find(|&d| !taken[d]).expect("each face has four T-centers");
+        taken[destination] = true;
+        destination
+    });
+    if !is_even(&destinations) {
+        let mut of_u = (0..24).filter(|&slot| destinations[slot] < 4);
+        let (a, b) = (of_u.next().unwrap(), of_u.next().unwrap());
+        destinations.swap(a, b);
+    }
+    destinations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::big_cubes::cycles::twisted;
+    use crate::two_phase::empty_table_solver;
+    use rand::{rngs::StdRng, RngExt, SeedableRng};
+    use std::sync::LazyLock;
+
+    static SOLVER: LazyLock<Reduction5Solver> = LazyLock::new(Reduction5Solver::new);
+
+    #[test]
+    fn test_t_center_cycle() {
+        let cube = Cube5::solved().twisted_by(&parse_layer_twists(T_CENTER_CYCLE).unwrap());
+        assert_eq!(Cube5::new(cube.corners(), cube.midges(), cube.wings(), cube.x_centers(), TCenters5::solved()), Cube5::solved());
+        let pieces = twisted(&parse_layer_twists(T_CENTER_CYCLE).unwrap(), TCenters5::map);
+        assert_eq!((0..24).filter(|&slot| pieces[slot] as usize != slot).count(), 3);
+        // All cycles of three T-centers.
+        assert_eq!(SOLVER.t_center_cycles.len(), 24 * 23 * 22 / 3);
+    }
+
+    #[test]
+    fn test_reduce() {
+        assert_eq!(SOLVER.reduce(&Cube5::solved()), vec![]);
+        let cube = Cube5::solved().twisted_by(&parse_layer_twists("R U F'").unwrap());
+        assert_eq!(SOLVER.reduce(&cube), vec![]);
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let all = LayerTwist::all(5);
+        for _ in 0..20 {
+            let scramble: Vec<LayerTwist> = (0..60).map(|_| all[rng.random_range(0..all.len())]).collect();
+            let cube = Cube5::solved().twisted_by(&scramble);
+            assert!(cube.twisted_by(&SOLVER.reduce(&cube)).reduced().is_some());
+        }
+    }
+
+    #[test]
+    fn test_solve() {
+        // Scrambles that the reduction undoes completely, since the tables of the solver only reach the solved 3x3x3.
+        let mut solver = empty_table_solver();
+        for scramble in ["2R U", "2U 2R", "2F2 L"] {
+            let cube = Cube5::solved().twisted_by(&parse_layer_twists(scramble).unwrap());
+            let solution = SOLVER.solve(&cube, &mut solver, 20).unwrap();
+            assert!(cube.twisted_by(&solution).is_solved(), "{scramble}");
+        }
+
+        let mut solver = empty_table_solver().with_options(SolveOptions { max_depth: 20, ..SolveOptions::default() });
+        let cube = Cube5::solved().twisted_by(&parse_layer_twists("2R U").unwrap());
+        assert_eq!(SOLVER.solve(&cube, &mut solver, 21), Err("The maximum solution length 21 exceeds the depth limit of 20".into()));
+    }
+}
@@ -0,0 +1,96 @@
+use super::cycles::is_even;
+use super::geometry::*;
+use super::LayerTwist;
+use crate::*;
+use std::sync::LazyLock;
+
+/// The maps of the layer twists on the wings of the 4x4x4, which are the outer wings of the 5x5x5 as well, by `map_index`.
+static WING_MAPS: LazyLock<Vec<[u8; 24]>> = LazyLock::new(|| layer_maps(&wing_positions(4, 1), 4));
+
+/// The 24 wings, the edge pieces of the 4x4x4, by the wing at each slot, see `wing_positions`.
+/// The wings of the 5x5x5 next to the middle edges turn alike.
+/// Wing 2e + h is the one at slot 2e + h of the solved cube. A wing at a slot of the other half is flipped,
+/// so the two wings at an edge position form an edge of the 3x3x3 if they are 2e + o and 2e + 1 - o: edge e with orientation o.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Wings4 {
+    wings: [u8; 24],
+}
+
+impl Wings4 {
+    /// Panics if the wings are not a permutation of 0 to 23.
+    pub fn new(wings: [u8; 24]) -> Self {
+        assert!((0..24).all(|w| wings.contains(&w)), "the wings are not a permutation");
+        Self { wings }
+    }
+
+    pub fn solved() -> Self {
+        Self { wings: core::array::from_fn(|slot| slot as u8) }
+    }
+
+    /// The wing at the slot.
+    pub fn wing(&self, slot: usize) -> usize {
+        self.wings[slot] as usize
+    }
+
+    /// After the twist, slot i holds the wing that was at slot `map[i]`.
+    pub(crate) fn map(twist: LayerTwist) -> &'static [u8; 24] {
+        &WING_MAPS[map_index(twist)]
+    }
+
+    pub fn twisted(&self, twist: LayerTwist) -> Self {
+        let map = Self::map(twist);
+        Self { wings: core::array::from_fn(|slot| self.wings[map[slot] as usize]) }
+    }
+
+    /// The edge and its orientation that the wings at the edge position form, if they are paired.
+    pub fn edge(&self, position: usize) -> Option<(usize, usize)> {
+        let wing = self.wing(2 * position);
+        (self.wing(2 * position + 1) == wing ^ 1).then_some((wing / 2, wing % 2))
+    }
+
+    /// Whether the wings at each edge position form an edge.
+    pub fn is_paired(&self) -> bool {
+        (0..12).all(|position| self.edge(position).is_some())
+    }
+
+    /// The edges of the 3x3x3 that the paired wings form.
+    pub fn edges(&self) -> Option<Edges> {
+        let edges: Option<Vec<(usize, usize)>> = (0..12).map(|position| self.edge(position)).collect();
+        let edges = edges?;
+        Some(Edges::new(core::array::from_fn(|i| edges[i].0), core::array::from_fn(|i| edges[i].1)))
+    }
+
+    /// Whether the wings are an even permutation of the solved ones.
+    /// A twist of an inner layer cycles four wings, so it changes the parity, while one of an outer layer doesn't.
+    pub fn is_even(&self) -> bool {
+        is_even(&core::array::from_fn(|slot| self.wing(slot)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edges() {
+        // The outer layers move the paired wings like the twists of the 3x3x3 move the edges.
+        let mut rnd = RandomTwistGen::new(42, &ALL_TWISTS);
+        let twists = rnd.gen_twists(30);
+        let wings = twists.iter().fold(Wings4::solved(), |w, &t| w.twisted(LayerTwist::outer(t)));
+        assert!(wings.is_paired());
+        assert_eq!(wings.edges(), Some(Edges::twists(&twists)));
+        assert!(wings.is_even());
+
+        let wings = Wings4::solved().twisted("2U".parse().unwrap());
+        assert!(!wings.is_paired());
+        assert_eq!(wings.edges(), None);
+        assert!(!wings.is_even());
+        assert!(wings.twisted("2U".parse().unwrap()).is_even());
+    }
+
+    #[test]
+    fn test_5x5x5_wings() {
+        // The wings of the 5x5x5 next to the middle edges turn like the wings of the 4x4x4.
+        assert_eq!(layer_maps(&wing_positions(5, 2), 5), *WING_MAPS);
+    }
+}
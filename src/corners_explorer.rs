@@ -0,0 +1,96 @@
+use crate::*;
+use std::collections::HashMap;
+
+/// God's algorithm for the corners: exact distances and optimal solutions of corner states,
+/// looked up in the corners table, e.g. from `create_corners_table` or `TableSet::corners`.
+pub struct CornersExplorer<'a> {
+    table: &'a DistanceTable,
+}
+
+impl<'a> CornersExplorer<'a> {
+    pub fn new(table: &'a DistanceTable) -> Self {
+        Self { table }
+    }
+
+    /// The number of twists of an optimal solution.
+    pub fn distance(&self, corners: &Corners) -> u8 {
+        self.table.distance(corners.index())
+    }
+
+    /// An optimal solution, found by descending the distances.
+    pub fn optimal_path(&self, corners: &Corners) -> Vec<Twist> {
+        let mut corners = *corners;
+        let mut path = Vec::new();
+        for d in (1..=self.distance(&corners)).rev() {
+            let twist = ALL_TWISTS.into_iter().find(|&t| self.distance(&(t * corners)) < d).unwrap();
+            corners = twist * corners;
+            path.push(twist);
+        }
+        path
+    }
+
+    /// The number of distinct optimal solutions, as sequences of twists.
+    pub fn count_optimal_paths(&self, corners: &Corners) -> u64 {
+        self.count_paths(*corners, &mut HashMap::new())
+    }
+
+    // Each state on the way is counted once, since many paths lead through the same states.
+    fn count_paths(&self, corners: Corners, counts: &mut HashMap<usize, u64>) -> u64 {
+        let d = self.distance(&corners);
+        if d == 0 {
+            return 1;
+        }
+        if let Some(&count) = counts.get(&corners.index()) {
+            return count;
+        }
+        let count = ALL_TWISTS
+            .into_iter()
+            .map(|t| t * corners)
+            .filter(|next| self.distance(next) < d)
+            .map(|next| self.count_paths(next, counts))
+            .sum();
+        counts.insert(corners.index(), count);
+        count
+    }
+
+    /// The states of the largest distance, 11 twists.
+    pub fn antipodes(&self) -> impl Iterator<Item = Corners> + '_ {
+        let max_distance = self.table.max_distance();
+        (0..self.table.len())
+            .filter(move |&i| self.table.distance(i) == max_distance)
+            .map(Corners::from_index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corners_explorer() {
+        let table = create_corners_table();
+        let explorer = CornersExplorer::new(&table);
+
+        let solved = Corners::solved();
+        assert_eq!(explorer.distance(&solved), 0);
+        assert_eq!(explorer.optimal_path(&solved), []);
+        assert_eq!(explorer.count_optimal_paths(&solved), 1);
+
+        let corners = Corners::twists(&[Twist::R1, Twist::U1]);
+        assert_eq!(explorer.distance(&corners), 2);
+        assert_eq!(explorer.optimal_path(&corners), [Twist::U3, Twist::R3]);
+        assert_eq!(explorer.count_optimal_paths(&corners), 1);
+        // The twists of opposite faces commute.
+        assert_eq!(explorer.count_optimal_paths(&Corners::twists(&[Twist::R1, Twist::L1])), 2);
+
+        let corners = Corners::twists(&RandomTwistGen::new(3, &ALL_TWISTS).gen_twists(20));
+        let path = explorer.optimal_path(&corners);
+        assert_eq!(path.len(), explorer.distance(&corners) as usize);
+        assert_eq!(Corners::twists(&path) * corners, solved);
+
+        let antipodes: Vec<Corners> = explorer.antipodes().collect();
+        // According to https://oeis.org/A080629
+        assert_eq!(antipodes.len(), 64736);
+        assert!(antipodes.iter().all(|c| explorer.distance(c) == 11));
+    }
+}
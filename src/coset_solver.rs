@@ -0,0 +1,256 @@
+use crate::*;
+use crate::parallel::*;
+use std::ops::Range;
+
+/// Determines which cubes of a coset are solved within a given length by a set of phase-1 solutions,
+/// each followed by an optimal phase 2. This is the coset-by-coset computation style used to prove God's number.
+///
+/// The coset of a cube are all cubes with the same coset index. They share their phase-1 solutions.
+/// A cube of the coset is identified with the subset cube it becomes when twisted by the first phase-1 solution,
+/// so the coset is covered by a bitmap over the subset indices.
+pub struct CosetCover<F> {
+    reference: Vec<Twist>,
+    lengths: Vec<usize>,
+    multi_twister: MultiTwister,
+    phase_2: F,
+}
+
+impl<F: Fn(&SubsetCube) -> u8 + Sync> CosetCover<F> {
+    /// `phase_1_solutions` take the cubes of the coset into the subset.
+    /// `phase_2` returns the distance of a subset cube, e.g. `DistanceTable::subset_distance` of the subset table,
+    /// which also looks up a table reduced by inversion.
+    pub fn new(phase_1_solutions: &[Vec<Twist>], phase_2: F) -> Self {
+        assert!(!phase_1_solutions.is_empty());
+        let reference = phase_1_solutions[0].clone();
+        let inverse_reference = inverse(&reference);
+
+        // A cube 'h' of the subset, identified with the cube 'h * inverse(reference)' of the coset,
+        // is taken into the subset by a phase-1 solution 'p' as 'h * inverse(reference) * p'.
+        let sequences = phase_1_solutions
+            .iter()
+            .map(|p| [inverse_reference.as_slice(), p].concat())
+            .collect();
+        Self {
+            reference,
+            lengths: phase_1_solutions.iter().map(|p| p.len()).collect(),
+            multi_twister: MultiTwister::new(sequences),
+            phase_2,
+        }
+    }
+
+    /// The subset cube that identifies a cube of the coset.
+    pub fn subset_cube(&self, cube: Cube) -> SubsetCube {
+        cube.twisted_by(&self.reference).subset_cube()
+    }
+
+    /// Length of the shortest solution that starts with one of the phase-1 solutions.
+    pub fn solution_length(&self, cube: SubsetCube) -> usize {
+        self.lengths
+            .iter()
+            .enumerate()
+            .map(|(i, &length)| length + (self.phase_2)(&cube.twisted_by_sequence(&self.multi_twister, i)) as usize)
+            .min()
+            .unwrap()
+    }
+
+    /// Whether the cube is solved within `max_length` twists.
+    pub fn is_covered(&self, cube: SubsetCube, max_length: usize) -> bool {
+        self.lengths.iter().enumerate().any(|(i, &length)| {
+            length <= max_length
+                && length + (self.phase_2)(&cube.twisted_by_sequence(&self.multi_twister, i)) as usize <= max_length
+        })
+    }
+
+    /// Marks the cubes of the coset that are solved within `max_length` twists.
+    /// Bit `i` of the bitmap corresponds to the subset index `range.start + i`.
+    pub fn cover_range(&self, max_length: usize, range: Range<usize>) -> Vec<u64> {
+        let mut bitmap = vec![0u64; range.len().div_ceil(64)];
+        bitmap.par_iter_mut().enumerate().for_each(|(word_index, word)| {
+            let start = range.start + word_index * 64;
+            for index in start..(start + 64).min(range.end) {
+                if self.is_covered(SubsetCube::from_index(index), max_length) {
+                    *word |= 1 << (index - start);
+                }
+            }
+        });
+        bitmap
+    }
+
+    /// Marks the cubes of the whole coset that are solved within `max_length` twists.
+    pub fn cover(&self, max_length: usize) -> Vec<u64> {
+        self.cover_range(max_length, 0..SubsetCube::INDEX_SIZE)
+    }
+}
+
+/// The representative of each coset, in the order of the coset index.
+/// There are `Cube::COSETS_INDEX_SIZE`, about 2.2 billion.
+pub fn cosets() -> impl Iterator<Item = Cube> {
+    (0..Cube::COSETS_INDEX_SIZE).map(Cube::from_coset_index)
+}
+
+/// Coset indices drawn uniformly at random, e.g. to analyze a sample of the cosets.
+pub fn sample_cosets(count: usize, seed: u64) -> Vec<usize> {
+    use rand::{RngExt, SeedableRng, rngs::StdRng};
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..count).map(|_| rng.random_range(0..Cube::COSETS_INDEX_SIZE)).collect()
+}
+
+/// All canonical twist sequences of at most `max_length` twists that take the cube into the subset,
+/// except the ones ending in an H0 twist, since phase 2 covers them.
+/// `phase_1` returns the distance of a coset index to the subset, e.g. from the coset table.
+pub fn phase_1_solutions(cube: Cube, phase_1: impl Fn(usize) -> u8, max_length: usize) -> Vec<Vec<Twist>> {
+    fn search(cube: Cube, phase_1: &impl Fn(usize) -> u8, depth: usize, twists: &mut Vec<Twist>, solutions: &mut Vec<Vec<Twist>>) {
+        let coset_index = cube.coset_index();
+        if coset_index == Cube::solved().coset_index() && twists.last().is_none_or(|&t| !TwistSet::H0.contains(t)) {
+            solutions.push(twists.clone());
+        }
+        if depth == 0 || phase_1(coset_index) as usize > depth {
+            return;
+        }
+        let allowed = twists.last().map_or(TwistSet::FULL, |&t| unique_twists_after(t));
+        for twist in allowed.iter() {
+            twists.push(twist);
+            search(cube.twisted(twist), phase_1, depth - 1, twists, solutions);
+            twists.pop();
+        }
+    }
+    let mut solutions = Vec::new();
+    search(cube, &phase_1, max_length, &mut Vec::new(), &mut solutions);
+    solutions
+}
+
+/// The optimal solution lengths of all cubes of a coset.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CosetAnalysis {
+    pub coset_index: usize,
+    /// Number of cubes of the coset at each distance.
+    pub histogram: Vec<u64>,
+}
+
+impl CosetAnalysis {
+    /// The largest distance of a cube of the coset.
+    pub fn diameter(&self) -> usize {
+        self.histogram.len() - 1
+    }
+}
+
+/// Solves every cube of the coset optimally, by covering it with all phase-1 solutions
+/// of increasing length until every cube is covered.
+/// `phase_1` returns the distance of a coset index to the subset, e.g. from the coset table,
+/// and `phase_2` the distance of a subset cube, e.g. `DistanceTable::subset_distance` of the subset table.
+/// This takes a bitmap of 2.4 GB and, for far cosets, hours.
+pub fn analyze_coset(coset_index: usize, phase_1: impl Fn(usize) -> u8, phase_2: impl Fn(&SubsetCube) -> u8 + Sync) -> CosetAnalysis {
+    analyze_coset_range(coset_index, phase_1, phase_2, 0..SubsetCube::INDEX_SIZE)
+}
+
+/// Like `analyze_coset`, but only for the cubes of the coset identified with the subset indices of `range`,
+/// see `CosetCover::cover_range`, e.g. to split a coset into parts.
+pub fn analyze_coset_range(
+    coset_index: usize,
+    phase_1: impl Fn(usize) -> u8,
+    phase_2: impl Fn(&SubsetCube) -> u8 + Sync,
+    range: Range<usize>,
+) -> CosetAnalysis {
+    let cube = Cube::from_coset_index(coset_index);
+    let mut histogram = Vec::new();
+    let mut covered = 0;
+    for max_length in 0.. {
+        let solutions = phase_1_solutions(cube, &phase_1, max_length);
+        let newly_covered = if solutions.is_empty() {
+            0
+        } else {
+            let bitmap = CosetCover::new(&solutions, &phase_2).cover_range(max_length, range.clone());
+            range.len() - count_uncovered(&bitmap, range.len()) - covered
+        };
+        histogram.push(newly_covered as u64);
+        covered += newly_covered;
+        if covered == range.len() {
+            break;
+        }
+    }
+    CosetAnalysis { coset_index, histogram }
+}
+
+/// Number of cubes that are not marked in a bitmap of `len` cubes.
+pub fn count_uncovered(bitmap: &[u64], len: usize) -> usize {
+    len - bitmap.iter().map(|word| word.count_ones() as usize).sum::<usize>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosets() {
+        assert_eq!(cosets().nth(5).map(|cube| cube.coset_index()), Some(5));
+        let sample = sample_cosets(100, 7);
+        assert_eq!(sample, sample_cosets(100, 7));
+        assert!(sample.iter().all(|&index| Cube::from_coset_index(index).coset_index() == index));
+    }
+
+    #[test]
+    fn test_phase_1_solutions() {
+        let cube = Cube::solved().twisted_by(&[Twist::R1, Twist::U1, Twist::F1]);
+        // Without a coset table, the search isn't pruned.
+        let solutions = phase_1_solutions(cube, |_| 0, 3);
+        assert!(solutions.contains(&vec![Twist::F3, Twist::U3, Twist::R3]));
+        assert!(solutions.iter().all(|s| cube.twisted_by(s).coset_index() == Cube::solved().coset_index()));
+        assert!(solutions.iter().all(|s| !TwistSet::H0.contains(*s.last().unwrap())));
+        assert!(phase_1_solutions(cube, |_| 0, 2).is_empty());
+        assert_eq!(phase_1_solutions(Cube::solved(), |_| 0, 0), vec![Vec::<Twist>::new()]);
+    }
+
+    #[test]
+    fn test_cover() {
+        let scramble = RandomTwistGen::new(42, &ALL_TWISTS).gen_twists(20);
+        let phase_1_solutions = vec![inverse(&scramble), [inverse(&scramble).as_slice(), &[Twist::U1]].concat()];
+        // Only the solved cube has a known phase-2 distance.
+        let cover = CosetCover::new(&phase_1_solutions, |cube: &SubsetCube| if *cube == SubsetCube::solved() { 0 } else { 20 });
+
+        let cube = cover.subset_cube(Cube::solved().twisted_by(&scramble));
+        assert_eq!(cover.solution_length(cube), 20);
+        assert!(cover.is_covered(cube, 20));
+
+        let cube = cover.subset_cube(Cube::solved().twisted_by(&[&[Twist::U3], scramble.as_slice()].concat()));
+        assert_eq!(cover.solution_length(cube), 21);
+        assert!(!cover.is_covered(cube, 20));
+        assert!(cover.is_covered(cube, 21));
+
+        let range = cube.index() - 10..cube.index() + 100;
+        let bitmap = cover.cover_range(21, range.clone());
+        assert_eq!(bitmap[0], 1 << 10);
+        assert_eq!(count_uncovered(&bitmap, range.len()), range.len() - 1);
+    }
+
+    #[test]
+    fn test_analyze_coset_reduced() {
+        // The subset cubes up to 4 twists from solved, stored by index and, like a subset table reduced by inversion, by reduced index.
+        // The farther cubes get a lower bound, which leaves the result the same for both.
+        let mut by_index = std::collections::HashMap::new();
+        let mut by_reduced_index = std::collections::HashMap::new();
+        let mut layer = vec![SubsetCube::solved()];
+        for distance in 0..=4u8 {
+            let mut next = Vec::new();
+            for cube in layer {
+                if by_index.contains_key(&cube.index()) {
+                    continue;
+                }
+                by_index.insert(cube.index(), distance);
+                by_reduced_index.entry(cube.reduced_index()).or_insert(distance);
+                next.extend(TwistSet::H0.iter().map(|twist| cube.twisted(twist)));
+            }
+            layer = next;
+        }
+        let full = |cube: &SubsetCube| by_index.get(&cube.index()).copied().unwrap_or(5);
+        let reduced = |cube: &SubsetCube| by_reduced_index.get(&cube.reduced_index()).copied().unwrap_or(5);
+
+        let coset_index = Cube::solved().coset_index();
+        let range = SubsetCube::solved().index()..SubsetCube::solved().index() + 256;
+        // Only the cubes of the coset are in the subset.
+        let phase_1 = |index: usize| u8::from(index != coset_index);
+        let analysis = analyze_coset_range(coset_index, phase_1, reduced, range.clone());
+        assert_eq!(analysis, analyze_coset_range(coset_index, phase_1, full, range.clone()));
+        assert_eq!(analysis.histogram[0], 1);
+        assert_eq!(analysis.histogram.iter().sum::<u64>(), range.len() as u64);
+    }
+}
@@ -13,7 +13,13 @@ fn main() {
     let twist_sequences: usize = args[1].parse().expect("Failed to parse twist sequences");
     let file: &str = &args[2];
 
-    let (corners_table, subset_table, coset_table) = get_tables();
+    let tables = TableSet::builder()
+        .config_file("config.txt")
+        .build()
+        .unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        });
 
     let mut rnd_twist = RandomTwistGen::new(42, &ALL_TWISTS);
     let cubes = Vec::from_iter((0..twist_sequences)
@@ -28,12 +34,8 @@ fn main() {
     let out = Mutex::new(out);
 
     cubes.par_iter().for_each(|&cube| {
-        let mut solver = TwoPhaseSolver::new(
-            &coset_table,
-            &subset_table,
-            &corners_table,
-        );
-        let solution = solver.solve(cube, 20).unwrap();
+        let mut solver = TwoPhaseSolver::with_tables(&tables);
+        let solution = solver.solve_twists(cube, 20).unwrap();
         assert!(cube.twisted_by(&solution) == Cube::solved(), "Incorrect solution found! Solution: {:?}", solution);
         let line = solution.iter().map(|t| format!("{:?}", t)).collect::<Vec<_>>().join(" ");
         let mut out = out.lock().unwrap();
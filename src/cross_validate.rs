@@ -0,0 +1,95 @@
+use clap::Parser;
+use rubikscube::*;
+use std::process::Command;
+
+/// Cross-validates the solver against a reference implementation, e.g. the `kociemba` Python package or min2phase.
+///
+/// Random states are passed to the reference as facelet strings in URFDLB order.
+/// Both solutions must solve the state when applied by this crate,
+/// which catches disagreements about the facelet format and the twists as well as wrong solutions.
+#[derive(Parser)]
+struct Cli {
+    /// The reference solver, which is called with the facelet string as its last argument
+    /// and prints a solution in WCA notation. Anything in parentheses, like a move count, is ignored.
+    #[arg(long)]
+    command: String,
+    /// Arguments of the reference solver before the facelet string.
+    #[arg(long, allow_hyphen_values = true)]
+    args: Vec<String>,
+    /// Number of random states.
+    #[arg(long, default_value_t = 100)]
+    count: usize,
+    #[arg(long, default_value_t = 42)]
+    seed: u64,
+    /// Maximum solution length of this crate's solver.
+    #[arg(long, default_value_t = 20)]
+    max_length: u8,
+    /// Config file with the table paths.
+    #[arg(long, default_value = "config.txt")]
+    config: String,
+}
+
+fn reference_solution(cli: &Cli, facelets: &Facelets) -> Result<Vec<Twist>, String> {
+    let output = Command::new(&cli.command)
+        .args(&cli.args)
+        .arg(facelets.to_string())
+        .output()
+        .map_err(|err| format!("Failed to run {}: {err}", cli.command))?;
+    if !output.status.success() {
+        return Err(format!("{} failed: {}", cli.command, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let without_comments: String = stdout.split('(').map(|part| part.split_once(')').map_or(part, |(_, rest)| rest)).collect();
+    parse_twist_sequence(without_comments.trim())
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let tables = TableSet::builder().config_file(&cli.config).build().unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(1);
+    });
+    let mut solver = TwoPhaseSolver::with_tables(&tables);
+    let mut states = RandomStateGen::new(cli.seed);
+
+    let mut failures = 0;
+    let (mut own_total, mut reference_total, mut shorter, mut longer) = (0, 0, 0, 0);
+    for i in 0..cli.count {
+        let (corners, edges) = states.gen_cubies();
+        let cube = Cube::from_cubies(&corners, &edges);
+        let facelets = cube.facelets();
+
+        let own = solver.solve_twists(cube, cli.max_length);
+        let reference = reference_solution(&cli, &facelets);
+        match (own, reference) {
+            (Ok(own), Ok(reference)) => {
+                if cube.twisted_by(&own) != Cube::solved() {
+                    eprintln!("State {i} {facelets}: the solution {} doesn't solve it", to_wca(&own));
+                    failures += 1;
+                } else if cube.twisted_by(&reference) != Cube::solved() {
+                    eprintln!("State {i} {facelets}: the reference solution {} doesn't solve it", to_wca(&reference));
+                    failures += 1;
+                } else {
+                    own_total += own.len();
+                    reference_total += reference.len();
+                    shorter += usize::from(own.len() < reference.len());
+                    longer += usize::from(own.len() > reference.len());
+                }
+            }
+            (Err(err), _) | (_, Err(err)) => {
+                eprintln!("State {i} {facelets}: {err}");
+                failures += 1;
+            }
+        }
+    }
+
+    let solved = cli.count - failures;
+    if solved > 0 {
+        println!("Average length: {:.2}, reference: {:.2}", own_total as f64 / solved as f64, reference_total as f64 / solved as f64);
+        println!("Shorter than the reference: {shorter}, longer: {longer}");
+    }
+    println!("Failures: {failures} of {}", cli.count);
+    if failures > 0 {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,277 @@
+use super::twist::*;
+use super::{Corners, CycleStructure, Edges};
+use alloc::{string::String, vec::Vec};
+use core::ops::Deref;
+
+/// A sequence of twists, written in WCA notation, e.g. "R U R' U'".
+///
+/// With NISS (normal-inverse scramble switch), twists in parentheses are done on the inverse scramble,
+/// e.g. "R U (F' D)". They are written after the normal twists.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Alg {
+    twists: Vec<Twist>,
+    inverse_twists: Vec<Twist>,
+}
+
+impl Alg {
+    pub fn new(twists: Vec<Twist>) -> Self {
+        Self { twists, inverse_twists: Vec::new() }
+    }
+
+    /// An alg with twists on the normal and on the inverse scramble.
+    pub fn with_niss(twists: Vec<Twist>, inverse_twists: Vec<Twist>) -> Self {
+        Self { twists, inverse_twists }
+    }
+
+    /// The twists on the normal scramble.
+    pub fn twists(&self) -> &[Twist] {
+        &self.twists
+    }
+
+    /// The twists on the inverse scramble.
+    pub fn inverse_twists(&self) -> &[Twist] {
+        &self.inverse_twists
+    }
+
+    pub fn is_linear(&self) -> bool {
+        self.inverse_twists.is_empty()
+    }
+
+    /// The plain sequence with the same effect: the normal twists, followed by the inverse of the inverse twists.
+    pub fn to_linear(&self) -> Self {
+        Self::new([self.twists.as_slice(), &inverse(&self.inverse_twists)].concat())
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self::new(inverse(&self.to_linear().twists))
+    }
+
+    /// The inverse with the NISS notation kept: the inverse twists become the normal ones and vice versa.
+    pub fn inverted(&self) -> Self {
+        Self::with_niss(self.inverse_twists.clone(), self.twists.clone())
+    }
+
+    /// The alg as seen in a mirror perpendicular to the axis, e.g. the left-handed version of a right-handed alg for `Axis::X`.
+    pub fn mirrored(&self, axis: Axis) -> Self {
+        let mirror = |twists: &[Twist]| twists.iter().map(|t| t.mirrored(axis)).collect();
+        Self::with_niss(mirror(&self.twists), mirror(&self.inverse_twists))
+    }
+
+    /// The alg done on the cube rotated by `rotation`, i.e. the alg whose effect is the effect of `self` rotated, see `Cube::rotated`.
+    pub fn rotated(&self, rotation: &[Axis]) -> Self {
+        Self::with_niss(rotated(&self.twists, rotation), rotated(&self.inverse_twists, rotation))
+    }
+
+    /// The cycles of the pieces the alg moves, see `CycleStructure`.
+    pub fn cycles(&self) -> CycleStructure {
+        let twists = self.to_linear().twists;
+        CycleStructure::new(&Corners::twists(&twists), &Edges::twists(&twists))
+    }
+
+    /// How often the alg has to be applied to get back to solved.
+    pub fn order(&self) -> usize {
+        self.cycles().order()
+    }
+
+    /// The alg with twists merged across commuting opposite faces, e.g. "R L R2" becomes "L R'", see `compressed`.
+    /// The normal and the inverse twists are compressed separately.
+    pub fn compressed(&self) -> Self {
+        Self::with_niss(compressed(&self.twists), compressed(&self.inverse_twists))
+    }
+
+    /// The alg applied `n` times in a row, as a plain sequence. Twists at the seams are not cancelled, see `cancelled`.
+    pub fn repeat(&self, n: usize) -> Self {
+        Self::new(self.to_linear().twists.repeat(n))
+    }
+}
+
+/// The twists on the normal scramble, which are all twists of a linear alg.
+impl Deref for Alg {
+    type Target = [Twist];
+
+    fn deref(&self) -> &[Twist] {
+        &self.twists
+    }
+}
+
+impl From<Vec<Twist>> for Alg {
+    fn from(twists: Vec<Twist>) -> Self {
+        Self::new(twists)
+    }
+}
+
+impl From<Alg> for Vec<Twist> {
+    fn from(alg: Alg) -> Self {
+        alg.to_linear().twists
+    }
+}
+
+impl core::str::FromStr for Alg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (mut normal, mut inverse) = (String::new(), String::new());
+        let mut in_parentheses = false;
+        for c in s.chars() {
+            match (c, in_parentheses) {
+                ('(', false) | (')', true) => {
+                    in_parentheses = !in_parentheses;
+                    normal.push(' ');
+                    inverse.push(' ');
+                }
+                ('(', true) => return Err("Nested parentheses".into()),
+                (')', false) => return Err("Unmatched ')'".into()),
+                (c, true) => inverse.push(c),
+                (c, false) => normal.push(c),
+            }
+        }
+        if in_parentheses {
+            return Err("Unmatched '('".into());
+        }
+        Ok(Self::with_niss(parse_twist_sequence(&normal)?, parse_twist_sequence(&inverse)?))
+    }
+}
+
+impl core::fmt::Display for Alg {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", to_wca(&self.twists))?;
+        if !self.is_linear() {
+            let separator = if self.twists.is_empty() { "" } else { " " };
+            write!(f, "{separator}({})", to_wca(&self.inverse_twists))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Alg {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Alg {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Facelets, twist_generator::*};
+
+    #[test]
+    fn test_parse() {
+        let alg: Alg = "R U R' U'".parse().unwrap();
+        assert_eq!(alg.twists(), [Twist::R1, Twist::U1, Twist::R3, Twist::U3]);
+        assert_eq!(alg.to_string(), "R U R' U'");
+        assert_eq!(alg.inverse().to_string(), "U R U' R'");
+        assert_eq!(alg.order(), 6);
+        assert_eq!("".parse::<Alg>().unwrap(), Alg::default());
+        assert!("R X".parse::<Alg>().is_err());
+    }
+
+    #[test]
+    fn test_niss() {
+        let alg: Alg = "R U (F' D) L (B2)".parse().unwrap();
+        assert_eq!(alg.twists(), [Twist::R1, Twist::U1, Twist::L1]);
+        assert_eq!(alg.inverse_twists(), [Twist::F3, Twist::D1, Twist::B2]);
+        assert_eq!(alg.to_string(), "R U L (F' D B2)");
+        assert_eq!(alg.to_linear().to_string(), "R U L B2 D' F");
+        assert!(!alg.is_linear() && alg.to_linear().is_linear());
+        assert_eq!(alg.inverse(), alg.to_linear().inverse());
+        assert_eq!("(R U)".parse::<Alg>().unwrap().to_string(), "(R U)");
+        assert!("R (U (F))".parse::<Alg>().is_err());
+        assert!("R (U".parse::<Alg>().is_err());
+        assert!("R U)".parse::<Alg>().is_err());
+    }
+
+    #[test]
+    fn test_order_and_repeat() {
+        let sexy: Alg = "R U R' U'".parse().unwrap();
+        assert_eq!(sexy.order(), 6);
+        assert_eq!(sexy.repeat(2).to_string(), "R U R' U' R U R' U'");
+        assert_eq!(sexy.repeat(0), Alg::default());
+        assert_eq!(effect(&sexy.repeat(6)), (Corners::solved(), Edges::solved()));
+        assert_ne!(effect(&sexy.repeat(3)), (Corners::solved(), Edges::solved()));
+        assert!(!sexy.cycles().is_solved() && sexy.repeat(6).cycles().is_solved());
+
+        let niss: Alg = "R (U')".parse().unwrap();
+        assert_eq!(niss.repeat(2).to_string(), "R U R U");
+        assert_eq!(niss.order(), 105);
+        assert_eq!(Alg::default().order(), 1);
+    }
+
+    fn effect(alg: &Alg) -> (Corners, Edges) {
+        let twists = alg.to_linear();
+        (Corners::twists(&twists), Edges::twists(&twists))
+    }
+
+    #[test]
+    fn test_compressed() {
+        let alg: Alg = "R L R2 U (F B F)".parse().unwrap();
+        assert_eq!(alg.compressed().to_string(), "L R' U (F2 B)");
+        assert_eq!(effect(&alg.compressed()), effect(&alg));
+        assert_eq!(Alg::default().compressed(), Alg::default());
+    }
+
+    #[test]
+    fn test_inverted() {
+        let alg: Alg = "R U (F' D) L".parse().unwrap();
+        assert_eq!(alg.inverted().to_string(), "F' D (R U L)");
+        assert_eq!(alg.inverted().to_linear(), alg.inverse());
+        assert_eq!(alg.inverted().inverted(), alg);
+    }
+
+    #[test]
+    fn test_mirrored() {
+        let sune: Alg = "R U R' U R U2 R'".parse().unwrap();
+        assert_eq!(sune.mirrored(Axis::X).to_string(), "L' U' L U' L' U2 L");
+        assert_eq!(sune.mirrored(Axis::Z).to_string(), "R' D' R D' R' D2 R");
+        assert_eq!(sune.mirrored(Axis::Y).to_string(), "R' U' R U' R' U2 R");
+
+        let mut rnd = RandomTwistGen::new(5, &ALL_TWISTS);
+        for _ in 0..20 {
+            let alg = Alg::with_niss(rnd.gen_twists(10), rnd.gen_twists(3));
+            let (corners, edges) = effect(&alg.mirrored(Axis::X));
+            assert_eq!(Facelets::from_cubies(&corners, &edges), {
+                let (corners, edges) = effect(&alg);
+                Facelets::from_cubies(&corners, &edges).mirrored()
+            });
+            // Mirrors in the other axes are the mirror between L and R of the cube rotated onto it.
+            assert_eq!(alg.mirrored(Axis::Y), alg.rotated(&[Axis::Z]).mirrored(Axis::X).rotated(&[Axis::Z; 3]));
+            assert_eq!(alg.mirrored(Axis::Z), alg.rotated(&[Axis::Y]).mirrored(Axis::X).rotated(&[Axis::Y; 3]));
+            for axis in [Axis::X, Axis::Y, Axis::Z] {
+                assert_eq!(alg.mirrored(axis).mirrored(axis), alg);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotated() {
+        let mut rnd = RandomTwistGen::new(6, &ALL_TWISTS);
+        for _ in 0..20 {
+            let alg = Alg::with_niss(rnd.gen_twists(10), rnd.gen_twists(3));
+            for rotation in [[Axis::X, Axis::Y], [Axis::Z, Axis::Z], [Axis::Y, Axis::X]] {
+                let (corners, edges) = effect(&alg);
+                let expected = rotation.iter().fold((corners, edges), |(c, e), &rot| (c.conjugated_by(rot), e.conjugated_by(rot)));
+                assert_eq!(effect(&alg.rotated(&rotation)), expected);
+                assert_eq!(unrotated(alg.rotated(&rotation).twists(), &rotation), alg.twists());
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let alg: Alg = "F2 B' L".parse().unwrap();
+        let json = serde_json::to_string(&alg).unwrap();
+        assert_eq!(json, "\"F2 B' L\"");
+        assert_eq!(serde_json::from_str::<Alg>(&json).unwrap(), alg);
+        assert_eq!(serde_json::to_string(&Twist::U3).unwrap(), "\"U'\"");
+        assert_eq!(serde_json::from_str::<Twist>("\"D2\"").unwrap(), Twist::D2);
+        assert!(serde_json::from_str::<Alg>("\"R X\"").is_err());
+    }
+}
@@ -1,8 +1,8 @@
+use super::cubie_error::*;
 use super::math::*;
 use super::permutation::*;
-use super::modvec::*;
 use super::twist::*;
-use std::ops::Mul;
+use core::ops::Mul;
 
 /// Represents the corner pieces of a Rubik's cube.
 ///
@@ -21,46 +21,121 @@ use std::ops::Mul;
 /// Each corner has orientation 0 or 1 or 2.
 /// They represent how much it is twisted relative to its solved state.
 /// The sum of all 8 corner orientations is always 0 mod 3 (parity constraint).
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+///
+/// Each corner takes one byte: bits 0..4 hold the corner at the position, bits 4..6 its orientation.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Corners {
-    prm: Permutation<8>,
-    ori: ModVec<8, 3>,
+    lanes: [u8; 8],
 }
 
+const PRM_MASK: u8 = 0x0F;
+
 impl Corners {
     pub const PRM_SIZE: usize = factorial(8); // 40'320
     pub const ORI_SIZE: usize = 3_usize.pow(7); // 2'187
     pub const INDEX_SIZE: usize = Self::PRM_SIZE * Self::ORI_SIZE; // 88'179'840
 
-    const fn new(prm: [usize; 8], ori: [usize; 8]) -> Self {
-        Self { prm: Permutation::new(prm), ori: ModVec::new(ori) }
+    pub(crate) const fn new(prm: [usize; 8], ori: [usize; 8]) -> Self {
+        let mut lanes = [0; 8];
+        let mut i = 0;
+        while i < 8 {
+            lanes[i] = (prm[i] | (ori[i] << 4)) as u8;
+            i += 1;
+        }
+        Self { lanes }
+    }
+
+    /// The corners `cubies[i]` with orientation `orientations[i]` at the positions `i`, if they are valid.
+    /// Their permutation parity is not checked, since it is only constrained together with the edges.
+    pub fn try_new(cubies: [usize; 8], orientations: [usize; 8]) -> Result<Self, CubieError> {
+        check_cubies(&cubies, &orientations, 3)?;
+        Ok(Self::new(cubies, orientations))
     }
 
     pub const fn solved() -> Self {
-        Self { prm: Permutation::identity(), ori: ModVec::identity() }
+        Self::new([0, 1, 2, 3, 4, 5, 6, 7], [0; 8])
     }
 
-    pub fn twist(twist: Twist) -> Self {
-        match twist {
-            Twist::L1 => Self::new([2, 1, 6, 3, 0, 5, 4, 7], [1, 0, 2, 0, 2, 0, 1, 0]),
-            Twist::R1 => Self::new([0, 5, 2, 1, 4, 7, 6, 3], [0, 2, 0, 1, 0, 1, 0, 2]),
-            Twist::U1 => Self::new([1, 3, 0, 2, 4, 5, 6, 7], [0; 8]),
-            Twist::D1 => Self::new([0, 1, 2, 3, 6, 4, 7, 5], [0; 8]),
-            Twist::F1 => Self::new([4, 0, 2, 3, 5, 1, 6, 7], [2, 1, 0, 0, 1, 2, 0, 0]),
-            Twist::B1 => Self::new([0, 1, 3, 7, 4, 5, 2, 6], [0, 0, 1, 2, 0, 0, 2, 1]),
-            Twist::L2 => Self::twist(Twist::L1) * Self::twist(Twist::L1),
-            Twist::L3 => Self::twist(Twist::L1) * Self::twist(Twist::L2),
-            Twist::R2 => Self::twist(Twist::R1) * Self::twist(Twist::R1),
-            Twist::R3 => Self::twist(Twist::R1) * Self::twist(Twist::R2),
-            Twist::U2 => Self::twist(Twist::U1) * Self::twist(Twist::U1),
-            Twist::U3 => Self::twist(Twist::U1) * Self::twist(Twist::U2),
-            Twist::D2 => Self::twist(Twist::D1) * Self::twist(Twist::D1),
-            Twist::D3 => Self::twist(Twist::D1) * Self::twist(Twist::D2),
-            Twist::F2 => Self::twist(Twist::F1) * Self::twist(Twist::F1),
-            Twist::F3 => Self::twist(Twist::F1) * Self::twist(Twist::F2),
-            Twist::B2 => Self::twist(Twist::B1) * Self::twist(Twist::B1),
-            Twist::B3 => Self::twist(Twist::B1) * Self::twist(Twist::B2),
+    /// The corner at the given position.
+    #[inline(always)]
+    pub(crate) fn prm(&self, i: usize) -> usize {
+        (self.lanes[i] & PRM_MASK) as usize
+    }
+
+    /// The orientation of the corner at the given position.
+    #[inline(always)]
+    pub(crate) fn ori(&self, i: usize) -> usize {
+        (self.lanes[i] >> 4) as usize
+    }
+
+    /// The corner at the given position and its orientation, with the numbering of the README.
+    pub fn piece_at(&self, position: usize) -> (usize, usize) {
+        assert!(position < 8);
+        (self.prm(position), self.ori(position))
+    }
+
+    /// The position of the given corner.
+    pub fn position_of(&self, cubie: usize) -> usize {
+        assert!(cubie < 8);
+        (0..8).find(|&i| self.prm(i) == cubie).unwrap()
+    }
+
+    /// The orientations of the corners at the positions.
+    pub fn orientations(&self) -> [usize; 8] {
+        core::array::from_fn(|i| self.ori(i))
+    }
+
+    /// The corners with the corner at the position twisted in place by `amount` clockwise thirds of a turn.
+    /// A single twisted corner can't be reached by twists, e.g. for teaching orientation parity or testing invalid states.
+    pub fn twist_corner(&self, position: usize, amount: usize) -> Self {
+        assert!(position < 8);
+        let mut lanes = self.lanes;
+        let ori = (self.ori(position) + amount) % 3;
+        lanes[position] = (lanes[position] & PRM_MASK) | (ori << 4) as u8;
+        Self { lanes }
+    }
+
+    /// Scalar implementation of `Corners * Corners`.
+    const fn compose(&self, r: &Self) -> Self {
+        let mut lanes = [0; 8];
+        let mut i = 0;
+        while i < 8 {
+            let lane = self.lanes[i];
+            let gathered = r.lanes[(lane & PRM_MASK) as usize];
+            let mut ori = (gathered >> 4) + (lane >> 4);
+            if ori >= 3 {
+                ori -= 3;
+            }
+            lanes[i] = (gathered & PRM_MASK) | (ori << 4);
+            i += 1;
         }
+        Self { lanes }
+    }
+
+    const TWISTS: [Self; 18] = {
+        let quarter_twists = [
+            Self::new([2, 1, 6, 3, 0, 5, 4, 7], [1, 0, 2, 0, 2, 0, 1, 0]), // L1
+            Self::new([0, 5, 2, 1, 4, 7, 6, 3], [0, 2, 0, 1, 0, 1, 0, 2]), // R1
+            Self::new([1, 3, 0, 2, 4, 5, 6, 7], [0; 8]), // U1
+            Self::new([0, 1, 2, 3, 6, 4, 7, 5], [0; 8]), // D1
+            Self::new([4, 0, 2, 3, 5, 1, 6, 7], [2, 1, 0, 0, 1, 2, 0, 0]), // F1
+            Self::new([0, 1, 3, 7, 4, 5, 2, 6], [0, 0, 1, 2, 0, 0, 2, 1]), // B1
+        ];
+        let mut twists = [Self::solved(); 18];
+        let mut face = 0;
+        while face < 6 {
+            let quarter = quarter_twists[face];
+            let half = quarter.compose(&quarter);
+            twists[3 * face] = quarter;
+            twists[3 * face + 1] = half;
+            twists[3 * face + 2] = quarter.compose(&half);
+            face += 1;
+        }
+        twists
+    };
+
+    pub fn twist(twist: Twist) -> Self {
+        Self::TWISTS[twist as usize]
     }
 
     pub fn twists(twists: &[Twist]) -> Self {
@@ -68,10 +143,11 @@ impl Corners {
     }
 
     pub fn inverse(&self) -> Self {
-        Self {
-            prm: self.prm.inverse(),
-            ori: self.prm.inverse() * self.ori.inverse(),
+        let mut lanes = [0; 8];
+        for i in 0..8 {
+            lanes[self.prm(i)] = (i | ((3 - self.ori(i)) % 3) << 4) as u8;
         }
+        Self { lanes }
     }
 
     pub fn conjugated_by(&self, rot: Axis) -> Self {
@@ -84,20 +160,47 @@ impl Corners {
     }
 
     pub fn from_indices(prm: usize, ori: usize) -> Self {
-        let mut o = decode(ori, 3, 7);
-        o.push((7 * 3 - o.iter().sum::<usize>()) % 3); // Parity constraint
-        Self {
-            prm: Permutation::from_index(prm),
-            ori: ModVec::new(o.try_into().unwrap()),
-        }
+        let o = decode_array::<7>(ori, 3);
+        let last = (7 * 3 - o.iter().sum::<usize>()) % 3; // Parity constraint
+        Self::new(nth_permutation_array(prm), core::array::from_fn(|i| if i < 7 { o[i] } else { last }))
     }
 
     pub fn prm_index(&self) -> usize {
-        self.prm.index()
+        permutation_index(&core::array::from_fn::<usize, 8, _>(|i| self.prm(i)))
     }
 
     pub fn ori_index(&self) -> usize {
-        encode(&self.ori[..7], 3)
+        (0..7).fold(0, |index, i| index * 3 + self.ori(i))
+    }
+
+    /// The index of a permutation and an orientation index together, in [0, INDEX_SIZE).
+    pub const fn combine_indices(prm: usize, ori: usize) -> usize {
+        prm * Self::ORI_SIZE + ori
+    }
+
+    /// The permutation and orientation index of a combined index, the inverse of `combine_indices`.
+    pub const fn split_index(index: usize) -> (usize, usize) {
+        (index / Self::ORI_SIZE, index % Self::ORI_SIZE)
+    }
+
+    /// The index of the whole state of the corners, see `combine_indices`.
+    pub fn index(&self) -> usize {
+        Self::combine_indices(self.prm_index(), self.ori_index())
+    }
+
+    pub fn from_index(index: usize) -> Self {
+        assert!(index < Self::INDEX_SIZE);
+        let (prm, ori) = Self::split_index(index);
+        Self::from_indices(prm, ori)
+    }
+}
+
+impl core::fmt::Debug for Corners {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Corners")
+            .field("prm", &core::array::from_fn::<usize, 8, _>(|i| self.prm(i)))
+            .field("ori", &core::array::from_fn::<usize, 8, _>(|i| self.ori(i)))
+            .finish()
     }
 }
 
@@ -106,10 +209,11 @@ impl Mul for Corners {
     type Output = Corners;
 
     fn mul(self, r: Corners) -> Corners {
-        Corners {
-            prm: self.prm * r.prm,
-            ori: self.prm * r.ori + self.ori,
-        }
+        #[cfg(all(feature = "simd", target_arch = "x86_64", target_feature = "ssse3"))]
+        return Corners { lanes: super::simd::compose_corners(self.lanes, r.lanes) };
+
+        #[cfg(not(all(feature = "simd", target_arch = "x86_64", target_feature = "ssse3")))]
+        return self.compose(&r);
     }
 }
 
@@ -135,16 +239,76 @@ impl Mul<Twist> for Corners {
 mod tests {
     use super::*;
     use crate::twist_generator::*;
+    use crate::{CycleStructure, Edges, FaceletError, Facelets};
+
+    #[test]
+    fn test_try_new() {
+        let prm = [0, 1, 2, 3, 4, 5, 6, 7];
+        assert_eq!(Corners::try_new(prm, [1, 2, 0, 0, 0, 0, 0, 0]), Ok(Corners::new(prm, [1, 2, 0, 0, 0, 0, 0, 0])));
+        assert_eq!(Corners::try_new([0, 1, 2, 3, 4, 5, 6, 8], [0; 8]), Err(CubieError::InvalidCubie(8)));
+        assert_eq!(Corners::try_new([0, 1, 2, 3, 4, 5, 6, 0], [0; 8]), Err(CubieError::DuplicateCubie(0)));
+        assert_eq!(
+            Corners::try_new(prm, [0, 0, 3, 0, 0, 0, 0, 0]),
+            Err(CubieError::InvalidOrientation { position: 2, orientation: 3 })
+        );
+        assert_eq!(Corners::try_new(prm, [1, 0, 0, 0, 0, 0, 0, 0]), Err(CubieError::OrientationSum(3)));
+    }
+
+    #[test]
+    fn test_piece_at() {
+        // U moves the corner of UFR to UFL.
+        let c = Corners::twist(Twist::U1);
+        assert_eq!(c.piece_at(0), (1, 0));
+        assert_eq!(c.position_of(1), 0);
+        // R twists the corner of DFR into UFR.
+        assert_eq!(Corners::twist(Twist::R1).piece_at(1), (5, 2));
+
+        let c = Corners::twists(&RandomTwistGen::new(42, &ALL_TWISTS).gen_twists(20));
+        for cubie in 0..8 {
+            assert_eq!(c.piece_at(c.position_of(cubie)).0, cubie);
+        }
+    }
+
+    #[test]
+    fn test_twist_corner() {
+        let c = Corners::solved().twist_corner(0, 1);
+        assert_eq!(c.orientations(), [1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(c.twist_corner(0, 2), Corners::solved());
+        assert_eq!(Corners::solved().twist_corner(3, 3), Corners::solved());
+        assert_eq!(CycleStructure::new(&c, &Edges::solved()).to_string(), "UFL+");
+
+        let r = Corners::twist(Twist::R1);
+        assert_eq!(r.orientations(), [0, 2, 0, 1, 0, 1, 0, 2]);
+        assert_eq!(r.twist_corner(1, 1).piece_at(1), (r.prm(1), 0));
+        assert_eq!(
+            Facelets::from_cubies(&Corners::solved().twist_corner(5, 2), &Edges::solved()).to_cubies(),
+            Err(FaceletError::CornerTwist)
+        );
+    }
+
+    #[test]
+    fn test_index() {
+        assert_eq!(Corners::combine_indices(0, 0), 0);
+        assert_eq!(Corners::combine_indices(Corners::PRM_SIZE - 1, Corners::ORI_SIZE - 1), Corners::INDEX_SIZE - 1);
+        assert_eq!(Corners::split_index(Corners::combine_indices(123, 456)), (123, 456));
+        assert_eq!(Corners::solved().index(), 0);
+
+        let mut rnd = RandomTwistGen::new(42, &ALL_TWISTS);
+        for _ in 0..1_000 {
+            let c = Corners::twists(&rnd.gen_twists(20));
+            assert_eq!(Corners::split_index(c.index()), (c.prm_index(), c.ori_index()));
+            assert_eq!(Corners::from_index(c.index()), c);
+        }
+    }
 
     #[test]
-    fn test_indexing() {
-        let mut rnd = RandomTwistGen::new(181086, &ALL_TWISTS);
-        let mut c = Corners::solved();
-        for _ in 0..100_000 {
-            c = rnd.gen_twist() * c;
-            let prm = c.prm_index();
-            let ori = c.ori_index();
-            assert_eq!(c, Corners::from_indices(prm, ori));
+    fn test_mul() {
+        let mut rnd = RandomTwistGen::new(42, &ALL_TWISTS);
+        for _ in 0..10_000 {
+            let a = Corners::twists(&rnd.gen_twists(20));
+            let b = Corners::twists(&rnd.gen_twists(20));
+            assert_eq!(a * b, a.compose(&b));
+            assert_eq!(a * a.inverse(), Corners::solved());
         }
     }
 }
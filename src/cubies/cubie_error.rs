@@ -0,0 +1,37 @@
+use thiserror::Error;
+
+/// Reasons why cubies and orientations don't describe corners or edges of a cube, or the pieces of another puzzle.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CubieError {
+    #[error("Cubie {0} does not exist")]
+    InvalidCubie(usize),
+    #[error("Cubie {0} occurs more than once")]
+    DuplicateCubie(usize),
+    #[error("Invalid orientation {orientation} at position {position}")]
+    InvalidOrientation { position: usize, orientation: usize },
+    #[error("The orientations don't add up to a multiple of {0}")]
+    OrientationSum(usize),
+    #[error("The cubies are an odd permutation, which twists don't reach")]
+    OddPermutation,
+}
+
+/// Checks that the cubies are a permutation and the orientations are in range and add up to a multiple of `orientations`.
+pub(crate) fn check_cubies(cubies: &[usize], ori: &[usize], orientations: usize) -> Result<(), CubieError> {
+    let mut seen = 0u32;
+    for &cubie in cubies {
+        if cubie >= cubies.len() {
+            return Err(CubieError::InvalidCubie(cubie));
+        }
+        if seen & (1 << cubie) != 0 {
+            return Err(CubieError::DuplicateCubie(cubie));
+        }
+        seen |= 1 << cubie;
+    }
+    if let Some(position) = ori.iter().position(|&o| o >= orientations) {
+        return Err(CubieError::InvalidOrientation { position, orientation: ori[position] });
+    }
+    if ori.iter().sum::<usize>() % orientations != 0 {
+        return Err(CubieError::OrientationSum(orientations));
+    }
+    Ok(())
+}
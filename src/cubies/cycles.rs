@@ -0,0 +1,184 @@
+use super::corners::*;
+use super::edges::*;
+use super::facelets::{CORNER_NAMES, EDGE_NAMES};
+use super::math::*;
+use alloc::{format, string::String, vec, vec::Vec};
+
+/// Pieces that move through positions in a cycle.
+/// The piece at `positions[0]` moves to `positions[1]`, and so on, the last one to `positions[0]`.
+/// A cycle of a single position is a piece that is twisted or flipped in place.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    pub positions: Vec<usize>,
+    /// How much the pieces are twisted or flipped after going once around the cycle.
+    pub orientation: usize,
+}
+
+impl Cycle {
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// How often the cycle has to be applied to solve its pieces, for pieces with the given number of orientations.
+    pub fn order(&self, orientations: usize) -> usize {
+        self.len() * (orientations / gcd(self.orientation, orientations))
+    }
+}
+
+/// The cycles of the pieces that are not solved.
+/// `piece_at(i)` returns the piece at position i and its orientation.
+fn cycles(size: usize, orientations: usize, piece_at: impl Fn(usize) -> (usize, usize)) -> Vec<Cycle> {
+    // The position each piece moved to.
+    let mut destination = vec![0; size];
+    for i in 0..size {
+        destination[piece_at(i).0] = i;
+    }
+    let mut visited = vec![false; size];
+    let mut cycles = Vec::new();
+    for start in 0..size {
+        if visited[start] || piece_at(start) == (start, 0) {
+            continue;
+        }
+        let mut positions = Vec::new();
+        let mut orientation = 0;
+        let mut i = start;
+        while !visited[i] {
+            visited[i] = true;
+            positions.push(i);
+            orientation += piece_at(i).1;
+            i = destination[i];
+        }
+        cycles.push(Cycle { positions, orientation: orientation % orientations });
+    }
+    cycles
+}
+
+impl Corners {
+    /// The cycles of the corners that are not solved, see `Cycle`.
+    pub fn cycles(&self) -> Vec<Cycle> {
+        cycles(8, 3, |i| self.piece_at(i))
+    }
+}
+
+impl Edges {
+    /// The cycles of the edges that are not solved, see `Cycle`.
+    pub fn cycles(&self) -> Vec<Cycle> {
+        cycles(12, 2, |i| self.piece_at(i))
+    }
+}
+
+/// The cycles of the corners and edges of a cube, as used by blindfold solving methods.
+///
+/// Displays like "(UFL UBL UBR) UFR+ (UF UB)+ UR+", where a suffix "+" means
+/// a clockwise twist or a flip and "-" a counterclockwise twist.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CycleStructure {
+    pub corners: Vec<Cycle>,
+    pub edges: Vec<Cycle>,
+}
+
+impl CycleStructure {
+    pub fn new(corners: &Corners, edges: &Edges) -> Self {
+        Self { corners: corners.cycles(), edges: edges.cycles() }
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.corners.is_empty() && self.edges.is_empty()
+    }
+
+    /// How often the state has to be applied to get back to solved.
+    pub fn order(&self) -> usize {
+        let corners = self.corners.iter().map(|c| c.order(3));
+        let edges = self.edges.iter().map(|c| c.order(2));
+        corners.chain(edges).fold(1, lcm)
+    }
+
+    /// The positions of the corners that are twisted in place and their orientations.
+    pub fn twisted_corners(&self) -> Vec<(usize, usize)> {
+        in_place(&self.corners).map(|c| (c.positions[0], c.orientation)).collect()
+    }
+
+    /// The positions of the edges that are flipped in place.
+    pub fn flipped_edges(&self) -> Vec<usize> {
+        in_place(&self.edges).map(|c| c.positions[0]).collect()
+    }
+}
+
+fn in_place(cycles: &[Cycle]) -> impl Iterator<Item = &Cycle> {
+    cycles.iter().filter(|c| c.len() == 1)
+}
+
+fn format_cycle(cycle: &Cycle, names: &[&str]) -> String {
+    let names: Vec<&str> = cycle.positions.iter().map(|&p| names[p]).collect();
+    let suffix = ["", "+", "-"][cycle.orientation];
+    if names.len() == 1 {
+        format!("{}{suffix}", names[0])
+    } else {
+        format!("({}){suffix}", names.join(" "))
+    }
+}
+
+impl core::fmt::Display for CycleStructure {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.is_solved() {
+            return write!(f, "solved");
+        }
+        let corners = self.corners.iter().map(|c| format_cycle(c, &CORNER_NAMES));
+        let edges = self.edges.iter().map(|c| format_cycle(c, &EDGE_NAMES));
+        write!(f, "{}", corners.chain(edges).collect::<Vec<_>>().join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::twist::*;
+
+    fn cycle_structure(twists: &str) -> CycleStructure {
+        let twists = parse_twist_sequence(twists).unwrap();
+        CycleStructure::new(&Corners::twists(&twists), &Edges::twists(&twists))
+    }
+
+    #[test]
+    fn test_single_twists() {
+        assert_eq!(cycle_structure("").to_string(), "solved");
+        assert_eq!(cycle_structure("U").to_string(), "(UFL UBL UBR UFR) (UF UL UB UR)");
+        assert_eq!(cycle_structure("U2").to_string(), "(UFL UBR) (UFR UBL) (UF UB) (UL UR)");
+
+        let r = cycle_structure("R");
+        assert_eq!(r.corners.len(), 1);
+        assert_eq!(r.corners[0].len(), 4);
+        assert_eq!(r.corners[0].orientation, 0);
+        assert_eq!(r.edges[0].orientation, 0);
+        assert!(r.twisted_corners().is_empty());
+        assert_eq!(r.order(), 4);
+    }
+
+    #[test]
+    fn test_in_place() {
+        let corners = Corners::try_new([0, 1, 2, 3, 4, 5, 6, 7], [1, 2, 0, 0, 0, 0, 0, 0]).unwrap();
+        let cycles = CycleStructure::new(&corners, &Edges::solved());
+        assert_eq!(cycles.twisted_corners(), [(0, 1), (1, 2)]);
+        assert_eq!(cycles.to_string(), "UFL+ UFR-");
+
+        let superflip = cycle_structure("U R2 F B R B2 R U2 L B2 R U' D' R2 F R' L B2 U2 F2");
+        assert!(superflip.corners.is_empty());
+        assert_eq!(superflip.flipped_edges(), (0..12).collect::<Vec<_>>());
+        assert_eq!(superflip.to_string(), "UF+ UB+ DB+ DF+ UL+ UR+ DR+ DL+ FL+ FR+ BR+ BL+");
+    }
+
+    #[test]
+    fn test_orientation_of_cycle() {
+        // Sune swaps two pairs of corners, which come back twisted, and cycles three edges.
+        let sune = cycle_structure("R U R' U R U2 R'");
+        assert_eq!(sune.to_string(), "(UFL UBR)+ (UFR UBL)- (UB UL UR)");
+        assert_eq!(sune.corners[0], Cycle { positions: vec![0, 3], orientation: 1 });
+        assert!(sune.twisted_corners().is_empty());
+        assert_eq!(sune.corners[0].order(3), 6);
+        assert_eq!(sune.order(), 6);
+    }
+}
@@ -1,10 +1,10 @@
+use super::cubie_error::*;
 use super::math::*;
 use super::permutation::*;
-use super::modvec::*;
 use super::twist::*;
-use std::ops::Mul;
+use core::ops::Mul;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct LocPrm {
     value: u16,
 }
@@ -51,45 +51,120 @@ impl LocPrm {
 ///  8 /       9 /
 ///  |7        |6
 ///  +----3----+
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+///
+/// Each edge takes one byte: bits 0..4 hold the edge at the position and bit 4 its orientation.
+/// The 12 edges fill the lanes of a 16 byte vector, the remaining lanes are zero.
+/// This makes composing two `Edges` a byte shuffle plus a xor of the orientation bits.
+/// A single u64 with 5 bits per edge is benchmarked as the alternative in benches/edges_bench.rs.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(align(16))]
 pub struct Edges {
-    prm: Permutation<12>,
-    ori: ModVec<12, 2>,
+    lanes: [u8; 16],
 }
 
+const PRM_MASK: u8 = 0x0F;
+const ORI_BIT: u8 = 0x10;
+
 impl Edges {
     pub const LOC_PRM_SIZE: usize = LocPrm::INDEX_SIZE; // 11'880
     pub const ORI_SIZE: usize = 2_usize.pow(11); // 2'048
+    pub const PRM_SIZE: usize = factorial(12); // 479'001'600
+
+    pub(crate) const fn new(prm: [usize; 12], ori: [usize; 12]) -> Self {
+        let mut lanes = [0; 16];
+        let mut i = 0;
+        while i < 12 {
+            lanes[i] = (prm[i] | (ori[i] << 4)) as u8;
+            i += 1;
+        }
+        Self { lanes }
+    }
 
-    const fn new(prm: [usize; 12], ori: [usize; 12]) -> Self {
-        Self { prm: Permutation::new(prm), ori: ModVec::new(ori) }
+    /// The edges `cubies[i]` with orientation `orientations[i]` at the positions `i`, if they are valid.
+    /// Their permutation parity is not checked, since it is only constrained together with the corners.
+    pub fn try_new(cubies: [usize; 12], orientations: [usize; 12]) -> Result<Self, CubieError> {
+        check_cubies(&cubies, &orientations, 2)?;
+        Ok(Self::new(cubies, orientations))
     }
 
     pub const fn solved() -> Self {
-        Self { prm: Permutation::identity(), ori: ModVec::identity() }
+        Self::new([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11], [0; 12])
     }
 
-    pub fn twist(twist: Twist) -> Self {
-        match twist {
-            Twist::L1 => Self::new([0, 1, 2, 3, 11, 5, 6, 8, 4, 9, 10, 7], [0, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0, 1]),
-            Twist::R1 => Self::new([0, 1, 2, 3, 4, 9, 10, 7, 8, 6, 5, 11], [0, 0, 0, 0, 0, 1, 1, 0, 0, 1, 1, 0]),
-            Twist::U1 => Self::new([5, 4, 2, 3, 0, 1, 6, 7, 8, 9, 10, 11], [0; 12]),
-            Twist::D1 => Self::new([0, 1, 6, 7, 4, 5, 3, 2, 8, 9, 10, 11], [0; 12]),
-            Twist::F1 => Self::new([8, 1, 2, 9, 4, 5, 6, 7, 3, 0, 10, 11], [0; 12]),
-            Twist::B1 => Self::new([0, 10, 11, 3, 4, 5, 6, 7, 8, 9, 2, 1], [0; 12]),
-            Twist::L2 => Self::twist(Twist::L1) * Self::twist(Twist::L1),
-            Twist::L3 => Self::twist(Twist::L1) * Self::twist(Twist::L2),
-            Twist::R2 => Self::twist(Twist::R1) * Self::twist(Twist::R1),
-            Twist::R3 => Self::twist(Twist::R1) * Self::twist(Twist::R2),
-            Twist::U2 => Self::twist(Twist::U1) * Self::twist(Twist::U1),
-            Twist::U3 => Self::twist(Twist::U1) * Self::twist(Twist::U2),
-            Twist::D2 => Self::twist(Twist::D1) * Self::twist(Twist::D1),
-            Twist::D3 => Self::twist(Twist::D1) * Self::twist(Twist::D2),
-            Twist::F2 => Self::twist(Twist::F1) * Self::twist(Twist::F1),
-            Twist::F3 => Self::twist(Twist::F1) * Self::twist(Twist::F2),
-            Twist::B2 => Self::twist(Twist::B1) * Self::twist(Twist::B1),
-            Twist::B3 => Self::twist(Twist::B1) * Self::twist(Twist::B2),
+    /// The edge at the given position.
+    #[inline(always)]
+    pub(crate) fn prm(&self, i: usize) -> usize {
+        (self.lanes[i] & PRM_MASK) as usize
+    }
+
+    /// The orientation of the edge at the given position.
+    #[inline(always)]
+    pub(crate) fn ori(&self, i: usize) -> usize {
+        (self.lanes[i] >> 4) as usize
+    }
+
+    /// The edge at the given position and its orientation, with the numbering of the README.
+    pub fn piece_at(&self, position: usize) -> (usize, usize) {
+        assert!(position < 12);
+        (self.prm(position), self.ori(position))
+    }
+
+    /// The position of the given edge.
+    pub fn position_of(&self, cubie: usize) -> usize {
+        assert!(cubie < 12);
+        (0..12).find(|&i| self.prm(i) == cubie).unwrap()
+    }
+
+    /// The orientations of the edges at the positions.
+    pub fn orientations(&self) -> [usize; 12] {
+        core::array::from_fn(|i| self.ori(i))
+    }
+
+    /// The edges with the edge at the position flipped in place.
+    /// A single flip can't be reached by twists, e.g. for teaching orientation parity or testing invalid states.
+    pub fn flip_edge(&self, position: usize) -> Self {
+        assert!(position < 12);
+        let mut lanes = self.lanes;
+        lanes[position] ^= ORI_BIT;
+        Self { lanes }
+    }
+
+    /// Scalar implementation of `Edges * Edges`.
+    const fn compose(&self, r: &Self) -> Self {
+        let mut lanes = [0; 16];
+        let mut i = 0;
+        while i < 12 {
+            let lane = self.lanes[i];
+            lanes[i] = r.lanes[(lane & PRM_MASK) as usize] ^ (lane & ORI_BIT);
+            i += 1;
+        }
+        Self { lanes }
+    }
+
+    const TWISTS: [Self; 18] = {
+        let quarter_twists = [
+            Self::new([0, 1, 2, 3, 11, 5, 6, 8, 4, 9, 10, 7], [0, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0, 1]), // L1
+            Self::new([0, 1, 2, 3, 4, 9, 10, 7, 8, 6, 5, 11], [0, 0, 0, 0, 0, 1, 1, 0, 0, 1, 1, 0]), // R1
+            Self::new([5, 4, 2, 3, 0, 1, 6, 7, 8, 9, 10, 11], [0; 12]), // U1
+            Self::new([0, 1, 6, 7, 4, 5, 3, 2, 8, 9, 10, 11], [0; 12]), // D1
+            Self::new([8, 1, 2, 9, 4, 5, 6, 7, 3, 0, 10, 11], [0; 12]), // F1
+            Self::new([0, 10, 11, 3, 4, 5, 6, 7, 8, 9, 2, 1], [0; 12]), // B1
+        ];
+        let mut twists = [Self::solved(); 18];
+        let mut face = 0;
+        while face < 6 {
+            let quarter = quarter_twists[face];
+            let half = quarter.compose(&quarter);
+            twists[3 * face] = quarter;
+            twists[3 * face + 1] = half;
+            twists[3 * face + 2] = quarter.compose(&half);
+            face += 1;
         }
+        twists
+    };
+
+    pub fn twist(twist: Twist) -> Self {
+        Self::TWISTS[twist as usize]
     }
 
     pub fn twists(twists: &[Twist]) -> Self {
@@ -97,10 +172,11 @@ impl Edges {
     }
 
     pub fn inverse(&self) -> Self {
-        Self {
-            prm: self.prm.inverse(),
-            ori: self.prm.inverse() * self.ori.inverse(),
+        let mut lanes = [0; 16];
+        for i in 0..12 {
+            lanes[self.prm(i)] = i as u8 | (self.lanes[i] & ORI_BIT);
         }
+        Self { lanes }
     }
 
     pub fn conjugated_by(&self, rot: Axis) -> Self {
@@ -114,9 +190,9 @@ impl Edges {
 
     pub fn from_indices(x: LocPrm, y: LocPrm, z: LocPrm, ori_index: usize) -> Self {
         assert!(ori_index < Self::ORI_SIZE);
-        let x_loc = nth_combination(12, 4, x.loc());
-        let y_loc = nth_combination(12, 4, y.loc());
-        let z_loc = nth_combination(12, 4, z.loc());
+        let x_loc = nth_combination_array::<4>(12, x.loc());
+        let y_loc = nth_combination_array::<4>(12, y.loc());
+        let z_loc = nth_combination_array::<4>(12, z.loc());
         let x_prm = Permutation::<4>::from_index(x.prm());
         let y_prm = Permutation::<4>::from_index(y.prm());
         let z_prm = Permutation::<4>::from_index(z.prm());
@@ -132,10 +208,19 @@ impl Edges {
             prm[z_loc[i]] = z_prm[i] + 8;
         }
 
-        let mut ori = decode(ori_index, 2, 11);
-        ori.push((ori_index.count_ones() % 2) as usize); // Ensure orientation parity is even
+        Self::new(prm, Self::oris_from_index(ori_index))
+    }
+
+    pub fn from_prm_index(prm_index: usize, ori_index: usize) -> Self {
+        assert!(prm_index < Self::PRM_SIZE);
+        assert!(ori_index < Self::ORI_SIZE);
+        Self::new(nth_permutation_array(prm_index), Self::oris_from_index(ori_index))
+    }
 
-        Self::new(prm, ori.try_into().unwrap())
+    fn oris_from_index(ori_index: usize) -> [usize; 12] {
+        let ori = decode_array::<11>(ori_index, 2);
+        let last = (ori_index.count_ones() % 2) as usize; // Ensure orientation parity is even
+        core::array::from_fn(|i| if i < 11 { ori[i] } else { last })
     }
 
     pub fn from_subset_indices(xy_prm_index: usize, z_prm_index: usize) -> Self {
@@ -161,7 +246,8 @@ impl Edges {
         let mut loc = [0; 4];
         let mut prm = [0; 4];
         let mut j = 0;
-        for (i, &p) in self.prm.iter().enumerate() {
+        for i in 0..12 {
+            let p = self.prm(i);
             if p >= min_val && p < max_val {
                 loc[j] = i;
                 prm[j] = p - min_val;
@@ -174,7 +260,8 @@ impl Edges {
     pub fn xy_prm_index(&self) -> usize {
         let mut prm = [0; 8];
         let mut j = 0;
-        for &p in self.prm.iter() {
+        for i in 0..12 {
+            let p = self.prm(i);
             if p < 8 {
                 prm[j] = p;
                 j += 1;
@@ -183,8 +270,21 @@ impl Edges {
         permutation_index(&prm)
     }
 
+    pub fn prm_index(&self) -> usize {
+        permutation_index(&core::array::from_fn::<usize, 12, _>(|i| self.prm(i)))
+    }
+
     pub fn ori_index(&self) -> usize {
-        encode(&self.ori[..11], 2)
+        (0..11).fold(0, |index, i| index * 2 + self.ori(i))
+    }
+}
+
+impl core::fmt::Debug for Edges {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Edges")
+            .field("prm", &core::array::from_fn::<usize, 12, _>(|i| self.prm(i)))
+            .field("ori", &core::array::from_fn::<usize, 12, _>(|i| self.ori(i)))
+            .finish()
     }
 }
 
@@ -193,10 +293,11 @@ impl Mul for Edges {
     type Output = Edges;
 
     fn mul(self, r: Edges) -> Edges {
-        Edges {
-            prm: self.prm * r.prm,
-            ori: self.prm * r.ori + self.ori,
-        }
+        #[cfg(all(feature = "simd", target_arch = "x86_64", target_feature = "ssse3"))]
+        return Edges { lanes: super::simd::compose_edges(self.lanes, r.lanes) };
+
+        #[cfg(not(all(feature = "simd", target_arch = "x86_64", target_feature = "ssse3")))]
+        return self.compose(&r);
     }
 }
 
@@ -222,30 +323,57 @@ impl Mul<Twist> for Edges {
 mod tests {
     use super::*;
     use crate::twist_generator::*;
+    use crate::{Corners, CycleStructure, FaceletError, Facelets};
+
+    #[test]
+    fn test_try_new() {
+        let prm = [1, 0, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+        let ori = [1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(Edges::try_new(prm, ori), Ok(Edges::new(prm, ori)));
+        assert_eq!(Edges::try_new([12, 0, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11], ori), Err(CubieError::InvalidCubie(12)));
+        assert_eq!(Edges::try_new([1, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11], ori), Err(CubieError::DuplicateCubie(1)));
+        assert_eq!(
+            Edges::try_new(prm, [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2]),
+            Err(CubieError::InvalidOrientation { position: 11, orientation: 2 })
+        );
+        assert_eq!(Edges::try_new(prm, [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]), Err(CubieError::OrientationSum(2)));
+    }
 
     #[test]
-    fn test_indexing() {
-        let mut rnd = RandomTwistGen::new(181086, &ALL_TWISTS);
-        let mut e = Edges::solved();
-        for _ in 0..100_000 {
-            e = Edges::twist(rnd.gen_twist()) * e;
-            let x_locprm = e.loc_prm(Axis::X);
-            let y_locprm = e.loc_prm(Axis::Y);
-            let z_locprm = e.loc_prm(Axis::Z);
-            let ori = e.ori_index();
-            assert_eq!(e, Edges::from_indices(x_locprm, y_locprm, z_locprm, ori));
+    fn test_flip_edge() {
+        let e = Edges::solved().flip_edge(0);
+        assert_eq!(e.orientations(), [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(e.flip_edge(0), Edges::solved());
+        assert_eq!(CycleStructure::new(&Corners::solved(), &e).to_string(), "UF+");
+
+        let f = Edges::twist(Twist::F1);
+        assert_eq!(f.flip_edge(8).piece_at(8), (f.prm(8), 1 - f.ori(8)));
+        assert_eq!(Facelets::from_cubies(&Corners::solved(), &Edges::solved().flip_edge(11)).to_cubies(), Err(FaceletError::EdgeFlip));
+    }
+
+    #[test]
+    fn test_piece_at() {
+        // F moves the edge of UF to FR, with its U sticker on R.
+        let e = Edges::twist(Twist::F1);
+        assert_eq!(e.piece_at(9), (0, 0));
+        assert_eq!(e.position_of(0), 9);
+        // R moves the edge of UR to BR, with its U sticker on B.
+        assert_eq!(Edges::twist(Twist::R1).piece_at(10), (5, 1));
+
+        let e = Edges::twists(&RandomTwistGen::new(42, &ALL_TWISTS).gen_twists(20));
+        for cubie in 0..12 {
+            assert_eq!(e.piece_at(e.position_of(cubie)).0, cubie);
         }
     }
 
     #[test]
-    fn test_subset_indexing() {
-        let mut rnd = RandomTwistGen::new(181086, &H0_TWISTS);
-        let mut e = Edges::solved();
-        for _ in 0..100_000 {
-            e = Edges::twist(rnd.gen_twist()) * e;
-            let xy_prm = e.xy_prm_index();
-            let z_prm = e.loc_prm(Axis::Z).prm();
-            assert_eq!(e, Edges::from_subset_indices(xy_prm, z_prm));
+    fn test_mul() {
+        let mut rnd = RandomTwistGen::new(42, &ALL_TWISTS);
+        for _ in 0..10_000 {
+            let a = Edges::twists(&rnd.gen_twists(20));
+            let b = Edges::twists(&rnd.gen_twists(20));
+            assert_eq!(a * b, a.compose(&b));
+            assert_eq!(a * a.inverse(), Edges::solved());
         }
     }
 }
@@ -0,0 +1,202 @@
+use super::facelets::Face;
+use super::orientation::*;
+use super::twist::*;
+use alloc::{string::String, string::ToString, vec, vec::Vec};
+use core::fmt;
+
+/// A move of the held cube, as a speedcuber does it. Its twist is of the face at a side of the cube as held.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeldMove {
+    /// A twist of the face at a side, e.g. "R".
+    Face(Twist),
+    /// A twist of the face at a side and the middle layer next to it, e.g. "Rw",
+    /// which turns the whole cube like the face and the opposite face back.
+    Wide(Twist),
+    /// A rotation of the whole cube like a twist of R, U or F: "x", "y" or "z".
+    Rotation(Twist),
+}
+
+impl fmt::Display for HeldMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::Face(twist) => write!(f, "{}", twist.to_wca()),
+            Self::Wide(twist) => {
+                let wca = twist.to_wca();
+                write!(f, "{}w{}", &wca[..1], &wca[1..])
+            }
+            Self::Rotation(twist) => {
+                let axis = match twist.face() {
+                    Face::R => 'x',
+                    Face::U => 'y',
+                    _ => 'z',
+                };
+                write!(f, "{axis}{}", &twist.to_wca()[1..])
+            }
+        }
+    }
+}
+
+/// The moves in WCA notation, which `normalize_moves` reads back.
+pub fn held_moves_to_wca(moves: &[HeldMove]) -> String {
+    moves.iter().map(|m| m.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+/// The rotations x, y and z, as the twists of R, U and F that they turn the cube like.
+const ROTATIONS: [Twist; 9] = [
+    Twist::R1,
+    Twist::R2,
+    Twist::R3,
+    Twist::U1,
+    Twist::U2,
+    Twist::U3,
+    Twist::F1,
+    Twist::F2,
+    Twist::F3,
+];
+
+/// Penalties of the moves of a held cube, in any unit, e.g. tenths of a second, whose total `ergonomic_moves` minimizes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErgonomicCosts {
+    /// Of the twists of the face at each side, numbered like the twists: L, R, U, D, F, B,
+    /// and by the clockwise quarter turns: 1, 2, 3.
+    pub twists: [[u32; 3]; 6],
+    /// Added to the penalty of the twist of the face for a wide twist. `None` doesn't use wide twists.
+    pub wide: Option<u32>,
+    /// Of the rotations x, y and z by the clockwise quarter turns: 1, 2, 3. `None` doesn't rotate the cube.
+    pub rotations: Option<[[u32; 3]; 3]>,
+}
+
+/// For a right-handed speedcuber: R and U are the fastest, then L, then D and F, and B is the slowest. z rotations are slower than x and y.
+impl Default for ErgonomicCosts {
+    fn default() -> Self {
+        Self {
+            twists: [[2, 3, 2], [1, 2, 1], [1, 2, 1], [3, 4, 3], [3, 4, 3], [5, 6, 5]],
+            wide: Some(1),
+            rotations: Some([[3, 4, 3], [2, 3, 2], [5, 6, 5]]),
+        }
+    }
+}
+
+impl ErgonomicCosts {
+    /// The penalty of the move, or `None` for wide twists and rotations if they aren't used.
+    pub fn penalty(&self, held_move: HeldMove) -> Option<u32> {
+        let of_twist = |twist: Twist| self.twists[twist as usize / 3][twist as usize % 3];
+        match held_move {
+            HeldMove::Face(twist) => Some(of_twist(twist)),
+            HeldMove::Wide(twist) => self.wide.map(|wide| of_twist(twist) + wide),
+            HeldMove::Rotation(twist) => {
+                let index = ROTATIONS.iter().position(|&r| r == twist)?;
+                self.rotations.map(|rotations| rotations[index / 3][index % 3])
+            }
+        }
+    }
+
+    /// The total penalty of the moves, or `None` if they use moves that aren't used.
+    pub fn total(&self, moves: &[HeldMove]) -> Option<u32> {
+        moves.iter().map(|&m| self.penalty(m)).sum()
+    }
+}
+
+/// Rewrites twists, e.g. of a solution, into moves of the held cube with the least total penalty, e.g. "y R U R'" for "B U B'".
+/// Each twist is done as a twist of the face at its side, or as a wide twist of the opposite face, which rotates the cube,
+/// and may follow a rotation. The moves do the same twists as before, see `normalize_moves`.
+pub fn ergonomic_moves(twists: &[Twist], costs: &ErgonomicCosts) -> Vec<HeldMove> {
+    let orientations = Orientation::all();
+    let index_of = |orientation: &Orientation| orientations.iter().position(|o| o == orientation).unwrap();
+    let rotations: Vec<Option<Twist>> = match costs.rotations {
+        Some(_) => core::iter::once(None).chain(ROTATIONS.map(Some)).collect(),
+        None => vec![None],
+    };
+
+    // The least penalty of the moves that end in each orientation, and the moves.
+    let mut best: Vec<Option<(u32, Vec<HeldMove>)>> = vec![None; orientations.len()];
+    best[index_of(&Orientation::IDENTITY)] = Some((0, Vec::new()));
+    for &twist in twists {
+        let mut next: Vec<Option<(u32, Vec<HeldMove>)>> = vec![None; orientations.len()];
+        for (orientation, entry) in orientations.iter().zip(&best) {
+            let Some((penalty, moves)) = entry else {
+                continue;
+            };
+            for &rotation in &rotations {
+                let (held, rotation_penalty) = match rotation {
+                    Some(r) => (
+                        orientation.rotated(r as usize / 3, r.power() as usize),
+                        costs.penalty(HeldMove::Rotation(r)).unwrap(),
+                    ),
+                    None => (*orientation, 0),
+                };
+                let face_twist = held.to_held(&[twist])[0];
+                // A twist of a face is a wide twist of the opposite face, which turns the cube like the wide twist.
+                let wide_twist = Twist::from(((face_twist as u32 / 3) ^ 1) * 3 + face_twist as u32 % 3);
+                let candidates = [
+                    (HeldMove::Face(face_twist), held),
+                    (HeldMove::Wide(wide_twist), held.rotated(wide_twist as usize / 3, wide_twist.power() as usize)),
+                ];
+                for (held_move, after) in candidates {
+                    let Some(move_penalty) = costs.penalty(held_move) else {
+                        continue;
+                    };
+                    let total = penalty + rotation_penalty + move_penalty;
+                    let slot = &mut next[index_of(&after)];
+                    if slot.as_ref().is_none_or(|(least, _)| total < *least) {
+                        let mut moves = moves.clone();
+                        moves.extend(rotation.map(HeldMove::Rotation));
+                        moves.push(held_move);
+                        *slot = Some((total, moves));
+                    }
+                }
+            }
+        }
+        best = next;
+    }
+    best.into_iter().flatten().min_by_key(|(penalty, moves)| (*penalty, moves.len())).unwrap().1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let moves = [
+            HeldMove::Rotation(Twist::U3),
+            HeldMove::Wide(Twist::R1),
+            HeldMove::Face(Twist::U2),
+            HeldMove::Rotation(Twist::F2),
+        ];
+        assert_eq!(held_moves_to_wca(&moves), "y' Rw U2 z2");
+        assert_eq!(ErgonomicCosts::default().total(&moves), Some(2 + 2 + 2 + 6));
+        assert_eq!(
+            ErgonomicCosts {
+                wide: None,
+                ..ErgonomicCosts::default()
+            }
+            .total(&moves),
+            None
+        );
+    }
+
+    #[test]
+    fn test_ergonomic_moves() {
+        let costs = ErgonomicCosts::default();
+        let twists = parse_twist_sequence("B U B'").unwrap();
+        assert_eq!(held_moves_to_wca(&ergonomic_moves(&twists, &costs)), "y R U R'");
+        assert!(ergonomic_moves(&[], &costs).is_empty());
+
+        // Without wide twists and rotations, the twists stay as they are.
+        let plain = ErgonomicCosts {
+            wide: None,
+            rotations: None,
+            ..ErgonomicCosts::default()
+        };
+        assert_eq!(ergonomic_moves(&twists, &plain), twists.iter().map(|&t| HeldMove::Face(t)).collect::<Vec<_>>());
+
+        for solution in ["R U F' D2 L B2 R' U2 F", "F B' D L2 B U' R2 D' B2 F", "D' B D B' L2 F' D F"] {
+            let twists = parse_twist_sequence(solution).unwrap();
+            let moves = ergonomic_moves(&twists, &costs);
+            assert_eq!(normalize_moves(&held_moves_to_wca(&moves)).unwrap().0, twists);
+            let as_they_are: Vec<HeldMove> = twists.iter().map(|&t| HeldMove::Face(t)).collect();
+            assert!(costs.total(&moves).unwrap() <= costs.total(&as_they_are).unwrap());
+        }
+    }
+}
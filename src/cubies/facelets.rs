@@ -0,0 +1,381 @@
+use crate::corners::*;
+use crate::cubie_error::*;
+use crate::edges::*;
+use crate::permutation::*;
+use alloc::{format, string::String, vec::Vec};
+use thiserror::Error;
+
+/// The face a sticker belongs to in the solved state, i.e. its colour.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Face {
+    U, R, F, D, L, B,
+}
+
+const FACES: [Face; 6] = [Face::U, Face::R, Face::F, Face::D, Face::L, Face::B];
+
+impl Face {
+    pub(crate) fn to_char(self) -> char {
+        b"URFDLB"[self as usize] as char
+    }
+
+    pub(crate) fn from_char(c: char) -> Option<Self> {
+        FACES.into_iter().find(|face| face.to_char() == c)
+    }
+}
+
+/// The 54 stickers of a cube, in the order of the facelet strings of Kociemba's solver:
+/// U1..U9, R1..R9, F1..F9, D1..D9, L1..L9, B1..B9.
+/// Each face is read row by row, as seen when looking at it, with U and D seen with F below respectively above them.
+///
+/// Facelet numbering:
+///              +------------+
+///              |  0   1   2 |
+///              |  3   4   5 |
+///              |  6   7   8 |
+/// +------------+------------+------------+------------+
+/// | 36  37  38 | 18  19  20 |  9  10  11 | 45  46  47 |
+/// | 39  40  41 | 21  22  23 | 12  13  14 | 48  49  50 |
+/// | 42  43  44 | 24  25  26 | 15  16  17 | 51  52  53 |
+/// +------------+------------+------------+------------+
+///              | 27  28  29 |
+///              | 30  31  32 |
+///              | 33  34  35 |
+///              +------------+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Facelets {
+    faces: [Face; 54],
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FaceletError {
+    #[error("Expected 54 facelets, found {0}")]
+    Length(usize),
+    #[error("Invalid facelet '{0}', expected one of URFDLB")]
+    InvalidFacelet(char),
+    #[error("The centers are not URFDLB")]
+    Centers,
+    #[error("The centers are not those of the cube held in any orientation")]
+    HeldCenters,
+    #[error("No corner matches the stickers at {}", CORNER_NAMES[*.0])]
+    InvalidCorner(usize),
+    #[error("No edge matches the stickers at {}", EDGE_NAMES[*.0])]
+    InvalidEdge(usize),
+    #[error("A corner or edge occurs twice")]
+    DuplicatePiece,
+    #[error("The corners are twisted")]
+    CornerTwist,
+    #[error("An edge is flipped")]
+    EdgeFlip,
+    #[error("The permutations of corners and edges have different parities")]
+    Parity,
+}
+
+pub(crate) const CORNER_NAMES: [&str; 8] = ["UFL", "UFR", "UBL", "UBR", "DFL", "DFR", "DBL", "DBR"];
+pub(crate) const EDGE_NAMES: [&str; 12] = ["UF", "UB", "DB", "DF", "UL", "UR", "DR", "DL", "FL", "FR", "BR", "BL"];
+
+// Stickers of the corner positions, clockwise starting with the U or D sticker.
+pub(crate) const CORNER_FACELETS: [[usize; 3]; 8] = [
+    [6, 18, 38],  // UFL
+    [8, 9, 20],   // UFR
+    [0, 36, 47],  // UBL
+    [2, 45, 11],  // UBR
+    [27, 44, 24], // DFL
+    [29, 26, 15], // DFR
+    [33, 53, 42], // DBL
+    [35, 17, 51], // DBR
+];
+
+// Stickers of the edge positions, starting with the primary sticker.
+pub(crate) const EDGE_FACELETS: [[usize; 2]; 12] = [
+    [7, 19],  // UF
+    [1, 46],  // UB
+    [34, 52], // DB
+    [28, 25], // DF
+    [3, 37],  // UL
+    [5, 10],  // UR
+    [32, 16], // DR
+    [30, 43], // DL
+    [41, 21], // FL
+    [12, 23], // FR
+    [14, 48], // BR
+    [39, 50], // BL
+];
+
+fn solved_face(facelet: usize) -> Face {
+    FACES[facelet / 9]
+}
+
+impl Facelets {
+    /// The stickers in facelet order, which may not form a valid cube.
+    pub fn new(faces: [Face; 54]) -> Self {
+        Self { faces }
+    }
+
+    pub fn solved() -> Self {
+        Self { faces: core::array::from_fn(solved_face) }
+    }
+
+    pub fn faces(&self) -> &[Face; 54] {
+        &self.faces
+    }
+
+    pub fn from_cubies(corners: &Corners, edges: &Edges) -> Self {
+        let mut faces = core::array::from_fn(solved_face);
+        for (i, facelets) in CORNER_FACELETS.iter().enumerate() {
+            let (prm, ori) = (corners.prm(i), corners.ori(i));
+            for (k, &facelet) in CORNER_FACELETS[prm].iter().enumerate() {
+                faces[facelets[(k + ori) % 3]] = solved_face(facelet);
+            }
+        }
+        for (i, facelets) in EDGE_FACELETS.iter().enumerate() {
+            let (prm, ori) = (edges.prm(i), edges.ori(i));
+            for (k, &facelet) in EDGE_FACELETS[prm].iter().enumerate() {
+                faces[facelets[(k + ori) % 2]] = solved_face(facelet);
+            }
+        }
+        Self { faces }
+    }
+
+    /// The sticker of a corner facelet, as in `from_cubies`, from only the corner at its position,
+    /// given by `piece_at` like `Corners::piece_at`.
+    pub fn corner_sticker(facelet: usize, piece_at: impl FnOnce(usize) -> (usize, usize)) -> Face {
+        let (i, j) = CORNER_FACELETS
+            .iter()
+            .enumerate()
+            .find_map(|(i, facelets)| Some((i, facelets.iter().position(|&f| f == facelet)?)))
+            .expect("not a corner facelet");
+        let (prm, ori) = piece_at(i);
+        solved_face(CORNER_FACELETS[prm][(j + 3 - ori) % 3])
+    }
+
+    /// The sticker of an edge facelet, as in `from_cubies`, from only the edge at its position,
+    /// given by `piece_at` like `Edges::piece_at`.
+    pub fn edge_sticker(facelet: usize, piece_at: impl FnOnce(usize) -> (usize, usize)) -> Face {
+        let (i, j) = EDGE_FACELETS
+            .iter()
+            .enumerate()
+            .find_map(|(i, facelets)| Some((i, facelets.iter().position(|&f| f == facelet)?)))
+            .expect("not an edge facelet");
+        let (prm, ori) = piece_at(i);
+        solved_face(EDGE_FACELETS[prm][(j + ori) % 2])
+    }
+
+    /// The cubies of the stickers, if they form a reachable cube.
+    pub fn to_cubies(&self) -> Result<(Corners, Edges), FaceletError> {
+        if (0..6).any(|face| self.faces[face * 9 + 4] != FACES[face]) {
+            return Err(FaceletError::Centers);
+        }
+
+        let mut c_prm = [0; 8];
+        let mut c_ori = [0; 8];
+        for (i, facelets) in CORNER_FACELETS.iter().enumerate() {
+            let ori = (0..3)
+                .find(|&k| matches!(self.faces[facelets[k]], Face::U | Face::D))
+                .ok_or(FaceletError::InvalidCorner(i))?;
+            let stickers: [Face; 3] = core::array::from_fn(|k| self.faces[facelets[(k + ori) % 3]]);
+            c_prm[i] = (0..8)
+                .find(|&p| CORNER_FACELETS[p].map(solved_face) == stickers)
+                .ok_or(FaceletError::InvalidCorner(i))?;
+            c_ori[i] = ori;
+        }
+
+        let mut e_prm = [0; 12];
+        let mut e_ori = [0; 12];
+        for (i, facelets) in EDGE_FACELETS.iter().enumerate() {
+            let stickers = [self.faces[facelets[0]], self.faces[facelets[1]]];
+            (e_prm[i], e_ori[i]) = (0..12)
+                .flat_map(|p| [(p, 0), (p, 1)])
+                .find(|&(p, ori)| {
+                    let solved = EDGE_FACELETS[p].map(solved_face);
+                    stickers == [solved[ori], solved[1 - ori]]
+                })
+                .ok_or(FaceletError::InvalidEdge(i))?;
+        }
+
+        let corners = Corners::try_new(c_prm, c_ori).map_err(|err| match err {
+            CubieError::OrientationSum(_) => FaceletError::CornerTwist,
+            _ => FaceletError::DuplicatePiece,
+        })?;
+        let edges = Edges::try_new(e_prm, e_ori).map_err(|err| match err {
+            CubieError::OrientationSum(_) => FaceletError::EdgeFlip,
+            _ => FaceletError::DuplicatePiece,
+        })?;
+        if is_even_permutation(permutation_index(&c_prm)) != is_even_permutation(permutation_index(&e_prm)) {
+            return Err(FaceletError::Parity);
+        }
+        Ok((corners, edges))
+    }
+
+    /// The colours of the centers, in the order U R F D L B.
+    pub fn centers(&self) -> [Face; 6] {
+        core::array::from_fn(|face| self.faces[face * 9 + 4])
+    }
+
+    /// The stickers with each colour replaced, by `colour(face)`.
+    pub fn recoloured(&self, colour: impl Fn(Face) -> Face) -> Self {
+        Self { faces: self.faces.map(colour) }
+    }
+
+    /// The cube as seen in a mirror between L and R, i.e. conjugated by that reflection.
+    pub fn mirrored(&self) -> Self {
+        let mirror = |face: Face| match face {
+            Face::L => Face::R,
+            Face::R => Face::L,
+            _ => face,
+        };
+        let mut faces = self.faces;
+        for (i, &face) in self.faces.iter().enumerate() {
+            let (row, col) = (i % 9 / 3, i % 9 % 3);
+            faces[mirror(FACES[i / 9]) as usize * 9 + row * 3 + 2 - col] = mirror(face);
+        }
+        Self { faces }
+    }
+}
+
+impl Face {
+    /// ANSI escape sequence for the background colour of the face, in the colours of the README.
+    fn ansi_background(self) -> &'static str {
+        match self {
+            Face::U => "\x1b[48;5;15m",  // White
+            Face::R => "\x1b[48;5;21m",  // Blue
+            Face::F => "\x1b[48;5;196m", // Red
+            Face::D => "\x1b[48;5;226m", // Yellow
+            Face::L => "\x1b[48;5;34m",  // Green
+            Face::B => "\x1b[48;5;208m", // Orange
+        }
+    }
+}
+
+impl Facelets {
+    /// The unfolded cube as 9 lines of coloured stickers for terminals with ANSI colours,
+    /// laid out like the facelet numbering above.
+    pub fn to_net_string(&self) -> String {
+        const RESET: &str = "\x1b[0m";
+        let row = |face: Face, r: usize| -> String {
+            (0..3)
+                .map(|c| format!("{}  ", self.faces[face as usize * 9 + r * 3 + c].ansi_background()))
+                .collect::<String>()
+                + RESET
+        };
+        let mut lines = Vec::new();
+        for r in 0..3 {
+            lines.push(format!("      {}", row(Face::U, r)));
+        }
+        for r in 0..3 {
+            lines.push([Face::L, Face::F, Face::R, Face::B].map(|face| row(face, r)).concat());
+        }
+        for r in 0..3 {
+            lines.push(format!("      {}", row(Face::D, r)));
+        }
+        lines.join("\n")
+    }
+}
+
+impl core::str::FromStr for Facelets {
+    type Err = FaceletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.trim().chars().collect();
+        if chars.len() != 54 {
+            return Err(FaceletError::Length(chars.len()));
+        }
+        let mut faces = [Face::U; 54];
+        for (face, &c) in faces.iter_mut().zip(&chars) {
+            *face = Face::from_char(c).ok_or(FaceletError::InvalidFacelet(c))?;
+        }
+        Ok(Self { faces })
+    }
+}
+
+impl core::fmt::Display for Facelets {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.faces.iter().try_for_each(|face| write!(f, "{}", face.to_char()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::twist::*;
+    use crate::twist_generator::*;
+
+    fn facelets(twists: &[Twist]) -> Facelets {
+        Facelets::from_cubies(&Corners::twists(twists), &Edges::twists(twists))
+    }
+
+    #[test]
+    fn test_single_twists() {
+        let expected = [
+            (Twist::U1, "UUUUUUUUUBBBRRRRRRRRRFFFFFFDDDDDDDDDFFFLLLLLLLLLBBBBBB"),
+            (Twist::R1, "UUFUUFUUFRRRRRRRRRFFDFFDFFDDDBDDBDDBLLLLLLLLLUBBUBBUBB"),
+            (Twist::F1, "UUUUUULLLURRURRURRFFFFFFFFFRRRDDDDDDLLDLLDLLDBBBBBBBBB"),
+            (Twist::D1, "UUUUUUUUURRRRRRFFFFFFFFFLLLDDDDDDDDDLLLLLLBBBBBBBBBRRR"),
+            (Twist::L1, "BUUBUUBUURRRRRRRRRUFFUFFUFFFDDFDDFDDLLLLLLLLLBBDBBDBBD"),
+            (Twist::B1, "RRRUUUUUURRDRRDRRDFFFFFFFFFDDDDDDLLLULLULLULLBBBBBBBBB"),
+        ];
+        for (twist, string) in expected {
+            assert_eq!(facelets(&[twist]).to_string(), string, "Failed for twist {:?}", twist);
+        }
+    }
+
+    #[test]
+    fn test_mirrored() {
+        assert_eq!(Facelets::solved().mirrored(), Facelets::solved());
+        assert_eq!(facelets(&[Twist::R1]).mirrored(), facelets(&[Twist::L3]));
+        assert_eq!(facelets(&[Twist::U1]).mirrored(), facelets(&[Twist::U3]));
+        assert_eq!(facelets(&[Twist::F2]).mirrored(), facelets(&[Twist::F2]));
+
+        let mut rnd = RandomTwistGen::new(7, &ALL_TWISTS);
+        for _ in 0..100 {
+            let facelets = facelets(&rnd.gen_twists(20));
+            assert_eq!(facelets.mirrored().mirrored(), facelets);
+            assert!(facelets.mirrored().to_cubies().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_net_string() {
+        let net = Facelets::solved().to_net_string();
+        let lines: Vec<&str> = net.lines().collect();
+        assert_eq!(lines.len(), 9);
+        assert_eq!(lines[0].matches(Face::U.ansi_background()).count(), 3);
+        assert_eq!(lines[4].matches(Face::F.ansi_background()).count(), 3);
+        assert_eq!(lines[4].matches(Face::B.ansi_background()).count(), 3);
+        assert!(lines[8].ends_with("\x1b[0m"));
+
+        let net = facelets(&[Twist::U1]).to_net_string();
+        // The top row of L shows the stickers of F after a U twist.
+        assert!(net.lines().nth(3).unwrap().starts_with(Face::F.ansi_background()));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut rnd = RandomTwistGen::new(42, &ALL_TWISTS);
+        for _ in 0..1_000 {
+            let twists = rnd.gen_twists(30);
+            let string = facelets(&twists).to_string();
+            let (corners, edges) = string.parse::<Facelets>().unwrap().to_cubies().unwrap();
+            assert_eq!(corners, Corners::twists(&twists));
+            assert_eq!(edges, Edges::twists(&twists));
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        let solved = Facelets::solved().to_string();
+        assert_eq!(solved.parse::<Facelets>().unwrap().to_cubies().unwrap(), (Corners::solved(), Edges::solved()));
+        assert_eq!(solved[1..].parse::<Facelets>(), Err(FaceletError::Length(53)));
+        assert_eq!(solved.replacen('U', "X", 1).parse::<Facelets>(), Err(FaceletError::InvalidFacelet('X')));
+
+        let to_cubies = |s: &str| s.parse::<Facelets>().unwrap().to_cubies();
+        // Swapped stickers of the UF edge.
+        let flipped = format!("{}F{}U{}", &solved[..7], &solved[8..19], &solved[20..]);
+        assert_eq!(to_cubies(&flipped), Err(FaceletError::EdgeFlip));
+        // Rotated stickers of the UFR corner.
+        let twisted = format!("{}FU{}R{}", &solved[..8], &solved[10..20], &solved[21..]);
+        assert_eq!(to_cubies(&twisted), Err(FaceletError::CornerTwist));
+        // Swapped UF and UB edges.
+        let swapped = format!("{}U{}U{}B{}F{}", &solved[..1], &solved[2..7], &solved[8..19], &solved[20..46], &solved[47..]);
+        assert_eq!(to_cubies(&swapped), Err(FaceletError::Parity));
+    }
+}
@@ -1,3 +1,5 @@
+use alloc::{vec, vec::Vec};
+
 /// Encodes a slice of usize values into a single usize value using the specified base.
 pub fn encode(data: &[usize], base: usize) -> usize {
     let mut result = 0;
@@ -17,6 +19,18 @@ pub fn decode(mut value: usize, base: usize, length: usize) -> Vec<usize> {
     result
 }
 
+/// Like `decode`, but returns an array instead of allocating a Vec.
+pub const fn decode_array<const L: usize>(mut value: usize, base: usize) -> [usize; L] {
+    let mut result = [0; L];
+    let mut i = L;
+    while i > 0 {
+        i -= 1;
+        result[i] = value % base;
+        value /= base;
+    }
+    result
+}
+
 /// Returns the factorial of n (n!).
 /// Valid for n in the range [0, 20]. For n > 20, the result will overflow usize.
 pub const fn factorial(n: usize) -> usize {
@@ -47,6 +61,36 @@ pub const fn factorial(n: usize) -> usize {
     PRECOMPUTED[n]
 }
 
+/// Returns n!, or None if it doesn't fit into u128, i.e. for n > 34.
+pub const fn checked_factorial(n: usize) -> Option<u128> {
+    let mut result = 1u128;
+    let mut i = 2;
+    while i <= n {
+        result = match result.checked_mul(i as u128) {
+            Some(result) => result,
+            None => return None,
+        };
+        i += 1;
+    }
+    Some(result)
+}
+
+/// Returns the greatest common divisor of a and b.
+pub const fn gcd(mut a: usize, mut b: usize) -> usize {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Returns the least common multiple of a and b.
+pub const fn lcm(a: usize, b: usize) -> usize {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    a / gcd(a, b) * b
+}
+
 /// Returns the binomial coefficient "n choose k", which is the number of ways to choose k elements from a set of n elements.
 pub const fn binomial(n: usize, k: usize) -> usize {
     if k > n {
@@ -100,6 +144,62 @@ pub const fn combination_index(n: usize, combination: &[usize]) -> usize {
     index
 }
 
+/// Returns "n choose k", or None if it doesn't fit into u128.
+pub fn checked_binomial(n: usize, k: usize) -> Option<u128> {
+    if k > n {
+        return Some(0);
+    }
+    let k = k.min(n - k);
+    let mut result = 1u128;
+    for i in 0..k as u128 {
+        // result * (n - i) is divisible by i + 1, but might overflow while the quotient doesn't.
+        let g = gcd_u128(result, i + 1);
+        result = (result / g).checked_mul((n as u128 - i) / ((i + 1) / g))?;
+    }
+    Some(result)
+}
+
+fn gcd_u128(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Like `combination_index`, but for index spaces beyond usize and elements of any unsigned width.
+/// Returns None if the index doesn't fit into `I`.
+pub fn checked_combination_index<T: Copy + Into<usize>, I: TryFrom<u128>>(n: usize, combination: &[T]) -> Option<I> {
+    let k = combination.len();
+    let mut index = 0u128;
+    let mut j = 0;
+    for (i, &c) in combination.iter().enumerate() {
+        j += 1;
+        while j < c.into() + 1 {
+            index = index.checked_add(checked_binomial(n - j, k - i - 1)?)?;
+            j += 1;
+        }
+    }
+    I::try_from(index).ok()
+}
+
+/// Like `nth_combination`, but for index spaces beyond usize and elements of any unsigned width.
+/// Returns None if k > n, the index is not below binomial(n, k) or an element doesn't fit into `T`.
+pub fn checked_nth_combination<T: TryFrom<usize>>(n: usize, k: usize, index: impl TryInto<u128>) -> Option<Vec<T>> {
+    let mut index: u128 = index.try_into().ok()?;
+    let mut combination = Vec::with_capacity(k);
+    for i in 0..n {
+        if combination.len() == k {
+            break;
+        }
+        // A binomial beyond u128 is bigger than any index.
+        match checked_binomial(n - 1 - i, k - combination.len() - 1) {
+            Some(count) if count <= index => index -= count,
+            _ => combination.push(T::try_from(i).ok()?),
+        }
+    }
+    (combination.len() == k && index == 0).then_some(combination)
+}
+
 /// Returns the index-th combination of n elements taken k at a time
 /// in lexicographically sorted order.
 pub fn nth_combination(n: usize, k: usize, mut index: usize) -> Vec<usize> {
@@ -108,7 +208,7 @@ pub fn nth_combination(n: usize, k: usize, mut index: usize) -> Vec<usize> {
     }
 
     let mut size = 0;
-    let mut combination = vec![0_usize; k as usize];
+    let mut combination = vec![0_usize; k];
 
     for i in 0..n {
         let count = binomial(n - 1 - i, k - size - 1);
@@ -125,6 +225,143 @@ pub fn nth_combination(n: usize, k: usize, mut index: usize) -> Vec<usize> {
     combination
 }
 
+/// Like `nth_combination`, but returns an array instead of allocating a Vec, for the hot paths of the tables.
+pub const fn nth_combination_array<const K: usize>(n: usize, mut index: usize) -> [usize; K] {
+    let mut combination = [0usize; K];
+    let mut size = 0;
+    let mut i = 0;
+    while i < n && size < K {
+        let count = binomial(n - 1 - i, K - size - 1);
+        if count > index {
+            combination[size] = i;
+            size += 1;
+        } else {
+            index -= count;
+        }
+        i += 1;
+    }
+    combination
+}
+
+/// Returns the number of distinct arrangements of a multiset, which holds `counts[v]` elements of value v.
+pub fn multinomial(counts: &[usize]) -> usize {
+    let mut total = 0;
+    let mut result = 1;
+    for &count in counts {
+        total += count;
+        result *= binomial(total, count);
+    }
+    result
+}
+
+/// Returns the index of the arrangement of a multiset, with values in [0, kinds),
+/// in the lexicographically sorted list of all distinct arrangements of the same multiset.
+/// E.g. the 24 center pieces of a 4x4 in their 6 colours.
+pub fn multiset_permutation_index(sequence: &[usize], kinds: usize) -> usize {
+    let mut counts = vec![0; kinds];
+    for &value in sequence {
+        counts[value] += 1;
+    }
+    let mut index = 0;
+    for &value in sequence {
+        for smaller in 0..value {
+            if counts[smaller] > 0 {
+                counts[smaller] -= 1;
+                index += multinomial(&counts);
+                counts[smaller] += 1;
+            }
+        }
+        counts[value] -= 1;
+    }
+    index
+}
+
+/// Returns the index-th arrangement of the multiset, which holds `counts[v]` elements of value v,
+/// in lexicographically sorted order.
+pub fn nth_multiset_permutation(counts: &[usize], mut index: usize) -> Vec<usize> {
+    let mut counts = counts.to_vec();
+    let length = counts.iter().sum();
+    let mut sequence = Vec::with_capacity(length);
+    for _ in 0..length {
+        for value in 0..counts.len() {
+            if counts[value] == 0 {
+                continue;
+            }
+            counts[value] -= 1;
+            let count = multinomial(&counts);
+            if count > index {
+                sequence.push(value);
+                break;
+            }
+            index -= count;
+            counts[value] += 1;
+        }
+    }
+    sequence
+}
+
+/// Returns the number of ordered selections of k out of n elements, n! / (n-k)!.
+pub const fn falling_factorial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    let mut result = 1;
+    let mut i = 0;
+    while i < k {
+        result *= n - i;
+        i += 1;
+    }
+    result
+}
+
+/// Returns the Lehmer code of an ordered selection of distinct elements of [0, n):
+/// the i-th digit counts the unselected elements smaller than the i-th element, in [0, n-i).
+pub fn lehmer_code(selection: &[usize]) -> Vec<usize> {
+    selection
+        .iter()
+        .enumerate()
+        .map(|(i, &element)| element - selection[..i].iter().filter(|&&e| e < element).count())
+        .collect()
+}
+
+/// Returns the index of the ordered selection of distinct elements of [0, n)
+/// in the lexicographically sorted list of all ordered selections of k out of n elements.
+/// E.g. the positions of the 4 E slice edges in the order of the edges.
+pub fn partial_permutation_index(n: usize, selection: &[usize]) -> usize {
+    let k = selection.len();
+    lehmer_code(selection)
+        .iter()
+        .enumerate()
+        .map(|(i, &digit)| digit * falling_factorial(n - i - 1, k - i - 1))
+        .sum()
+}
+
+/// Returns the index-th ordered selection of k out of n elements in lexicographically sorted order.
+pub fn nth_partial_permutation(n: usize, k: usize, mut index: usize) -> Vec<usize> {
+    let mut unused: Vec<usize> = (0..n).collect();
+    let mut selection = Vec::with_capacity(k);
+    for i in 0..k {
+        let f = falling_factorial(n - i - 1, k - i - 1);
+        selection.push(unused.remove(index / f));
+        index %= f;
+    }
+    selection
+}
+
+/// Returns the index of k pieces at the sorted positions `combination` out of n, with `orientations[i]` in [0, base)
+/// the orientation of the piece at `combination[i]`, in [0, binomial(n, k) * base^k).
+/// E.g. the positions and flips of the 4 E slice edges.
+pub fn oriented_combination_index(n: usize, combination: &[usize], orientations: &[usize], base: usize) -> usize {
+    combination_index(n, combination) * base.pow(combination.len() as u32) + encode(orientations, base)
+}
+
+/// Returns the positions and orientations of the index-th oriented combination of k out of n pieces,
+/// the inverse of `oriented_combination_index`.
+pub fn nth_oriented_combination(n: usize, k: usize, base: usize, index: usize) -> (Vec<usize>, Vec<usize>) {
+    let orientations_size = base.pow(k as u32);
+    (nth_combination(n, k, index / orientations_size), decode(index % orientations_size, base, k))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -136,6 +373,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(7, 0), 7);
+        assert_eq!(gcd(0, 0), 0);
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(lcm(3, 5), 15);
+        assert_eq!(lcm(0, 5), 0);
+    }
+
     #[test]
     fn test_binomial() {
         assert_eq!(binomial(5, 2), 10);
@@ -196,4 +443,97 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_array_variants() {
+        for value in 0..3_usize.pow(7) {
+            assert_eq!(decode_array::<7>(value, 3).to_vec(), decode(value, 3, 7));
+        }
+        for index in 0..binomial(12, 4) {
+            assert_eq!(nth_combination_array::<4>(12, index).to_vec(), nth_combination(12, 4, index));
+        }
+        assert_eq!(nth_combination_array::<0>(5, 0), [0usize; 0]);
+    }
+
+    #[test]
+    fn test_multiset_permutation_index() {
+        assert_eq!(multinomial(&[2, 1]), 3);
+        assert_eq!(multinomial(&[4; 6]), 3_246_670_537_110_000);
+        assert_eq!(multiset_permutation_index(&[0, 0, 1], 2), 0);
+        assert_eq!(multiset_permutation_index(&[0, 1, 0], 2), 1);
+        assert_eq!(multiset_permutation_index(&[1, 0, 0], 2), 2);
+
+        for counts in [vec![1, 1, 1, 1], vec![2, 2], vec![3, 0, 2, 1], vec![2, 2, 2]] {
+            for index in 0..multinomial(&counts) {
+                let sequence = nth_multiset_permutation(&counts, index);
+                assert_eq!(multiset_permutation_index(&sequence, counts.len()), index);
+            }
+        }
+        // Without repetitions, it's the permutation index.
+        assert_eq!(multiset_permutation_index(&[2, 0, 3, 1], 4), crate::permutation_index(&[2usize, 0, 3, 1]));
+    }
+
+    #[test]
+    fn test_partial_permutation_index() {
+        assert_eq!(falling_factorial(12, 4), 11_880);
+        assert_eq!(falling_factorial(4, 4), factorial(4));
+        assert_eq!(lehmer_code(&[3, 0, 4]), vec![3, 0, 2]);
+        assert_eq!(partial_permutation_index(5, &[0, 1]), 0);
+        assert_eq!(partial_permutation_index(5, &[0, 2]), 1);
+        assert_eq!(partial_permutation_index(5, &[1, 0]), 4);
+        assert_eq!(partial_permutation_index(5, &[4, 3]), 19);
+
+        for n in 1..=7 {
+            for k in 0..=n {
+                for index in 0..falling_factorial(n, k) {
+                    let selection = nth_partial_permutation(n, k, index);
+                    assert_eq!(partial_permutation_index(n, &selection), index);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_oriented_combination_index() {
+        assert_eq!(oriented_combination_index(12, &[0, 1, 2, 3], &[0, 0, 0, 0], 2), 0);
+        assert_eq!(oriented_combination_index(12, &[0, 1, 2, 3], &[0, 0, 0, 1], 2), 1);
+        assert_eq!(oriented_combination_index(12, &[0, 1, 2, 4], &[0, 0, 0, 0], 2), 16);
+
+        for index in 0..binomial(8, 3) * 27 {
+            let (combination, orientations) = nth_oriented_combination(8, 3, 3, index);
+            assert_eq!(oriented_combination_index(8, &combination, &orientations, 3), index);
+        }
+    }
+
+    #[test]
+    fn test_checked() {
+        for n in 0..=20 {
+            assert_eq!(checked_factorial(n), Some(factorial(n) as u128));
+        }
+        assert!(checked_factorial(34).is_some());
+        assert_eq!(checked_factorial(35), None);
+        for n in 0..30 {
+            for k in 0..=n + 1 {
+                assert_eq!(checked_binomial(n, k), Some(binomial(n, k) as u128));
+            }
+        }
+        assert_eq!(checked_binomial(100, 50), Some(100_891_344_545_564_193_334_812_497_256));
+        assert_eq!(checked_binomial(200, 100), None);
+
+        for index in 0..binomial(12, 4) {
+            let combination = nth_combination(12, 4, index);
+            let narrow: Vec<u8> = combination.iter().map(|&c| c as u8).collect();
+            assert_eq!(checked_combination_index::<_, u16>(12, &narrow), Some(index as u16));
+            assert_eq!(checked_nth_combination::<u8>(12, 4, index), Some(narrow));
+        }
+        assert_eq!(checked_nth_combination::<u8>(12, 4, binomial(12, 4)), None);
+        assert_eq!(checked_nth_combination::<u8>(3, 4, 0u64), None);
+        assert_eq!(checked_nth_combination::<u8>(3, 0, 0u64), Some(vec![]));
+        assert_eq!(checked_combination_index::<_, u8>(12, &[8u8, 9, 10, 11]), None); // 494 doesn't fit into u8
+
+        let combination: Vec<u8> = (0..50).map(|i| 2 * i + 1).collect();
+        let index = checked_combination_index::<_, u128>(100, &combination).unwrap();
+        assert_eq!(checked_nth_combination::<u8>(100, 50, index), Some(combination));
+        assert_eq!(checked_combination_index::<_, u64>(100, &[99u8; 1]), Some(99));
+    }
 }
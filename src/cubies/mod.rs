@@ -1,18 +1,36 @@
+pub mod alg;
 pub mod corners;
+pub mod cubie_error;
+pub mod cycles;
 pub mod edges;
+pub mod ergonomics;
+pub mod facelets;
 pub mod math;
+pub mod orientation;
 pub mod twist;
+pub mod twist_costs;
 pub mod twist_generator;
 pub mod twist_set;
 pub mod test;
 pub mod permutation;
+pub mod reid;
 pub mod modvec;
+#[cfg(all(feature = "simd", target_arch = "x86_64", target_feature = "ssse3"))]
+mod simd;
 
+pub use alg::*;
 pub use corners::*;
+pub use cubie_error::*;
+pub use cycles::*;
 pub use edges::*;
+pub use ergonomics::*;
+pub use facelets::*;
 pub use math::*;
+pub use orientation::*;
 pub use twist::*;
+pub use twist_costs::*;
 pub use twist_generator::*;
 pub use twist_set::*;
 pub use permutation::*;
+pub use reid::*;
 pub use modvec::*;
@@ -1,5 +1,5 @@
 use super::permutation::*;
-use std::ops::{Add, Mul, RangeTo, Index};
+use core::ops::{Add, Mul, RangeTo, Index};
 
 /// A vector (in the mathematical sense) of integers modulo a divisor.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -35,7 +35,7 @@ impl<const LEN: usize, const DIVISOR: usize> Add for ModVec<LEN, DIVISOR> {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self {
-        Self { values: std::array::from_fn(|i| (self.values[i] + rhs.values[i]) % DIVISOR) }
+        Self { values: core::array::from_fn(|i| (self.values[i] + rhs.values[i]) % DIVISOR) }
     }
 }
 
@@ -0,0 +1,260 @@
+use super::facelets::*;
+use super::twist::*;
+use alloc::{format, string::String, vec::Vec};
+
+// Faces are numbered like the twists: L, R, U, D, F, B.
+const FACE_NAMES: [char; 6] = ['L', 'R', 'U', 'D', 'F', 'B'];
+const FACES: [Face; 6] = [Face::L, Face::R, Face::U, Face::D, Face::F, Face::B];
+
+fn face_number(face: Face) -> usize {
+    FACES.iter().position(|&f| f == face).unwrap()
+}
+
+// The faces that x, y and z turn the cube like.
+const ROTATION_FACES: [usize; 3] = [1, 2, 4];
+
+const fn opposite(face: usize) -> usize {
+    face ^ 1
+}
+
+/// How the cube is held: which face of the fixed orientation, the one of the twists, is at each side.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Orientation {
+    faces: [usize; 6],
+}
+
+impl Orientation {
+    pub const IDENTITY: Self = Self { faces: [0, 1, 2, 3, 4, 5] };
+
+    /// The face of the fixed orientation that is held at the side of `face`.
+    pub const fn face_at(&self, face: usize) -> usize {
+        self.faces[face]
+    }
+
+    /// The orientation after turning the whole cube like the twist of `face` by quarter turns,
+    /// e.g. `x` turns it like R.
+    pub fn rotated(&self, face: usize, quarter_turns: usize) -> Self {
+        // The sides that the face at a side moves to, for a quarter turn like x, y and z.
+        const CYCLES: [[usize; 4]; 3] = [[4, 2, 5, 3], [4, 0, 5, 1], [2, 1, 3, 0]];
+        let mut cycle = CYCLES[face / 2];
+        if face != ROTATION_FACES[face / 2] {
+            cycle.reverse();
+        }
+        let mut faces = self.faces;
+        for _ in 0..quarter_turns % 4 {
+            let previous = faces;
+            for i in 0..4 {
+                faces[cycle[(i + 1) % 4]] = previous[cycle[i]];
+            }
+        }
+        Self { faces }
+    }
+
+    /// The 24 orientations the cube can be held in.
+    pub fn all() -> Vec<Self> {
+        let mut all = Vec::from([Self::IDENTITY]);
+        let mut i = 0;
+        while i < all.len() {
+            for face in ROTATION_FACES {
+                let next = all[i].rotated(face, 1);
+                if !all.contains(&next) {
+                    all.push(next);
+                }
+            }
+            i += 1;
+        }
+        all
+    }
+
+    /// The orientation a cube is held in, read from the colours of its centers,
+    /// if they are those of one of the 24 orientations and not, e.g., of a mirrored cube.
+    pub fn from_centers(facelets: &Facelets) -> Option<Self> {
+        let centers = facelets.centers();
+        let faces = FACES.map(|side| face_number(centers[side as usize]));
+        let orientation = Self { faces };
+        Self::all().contains(&orientation).then_some(orientation)
+    }
+
+    /// The stickers of a cube held in this orientation, recoloured as if it were held in the identity orientation.
+    /// This way a robot that holds the cube differently can solve it without converting twists:
+    /// the twists that solve the recoloured cube are the twists of the sides as held, see `Cube::from_held_facelets`.
+    pub fn to_identity_colours(&self, facelets: &Facelets) -> Facelets {
+        facelets.recoloured(|colour| FACES[self.faces.iter().position(|&f| f == face_number(colour)).unwrap()])
+    }
+
+    /// The stickers of a cube in the identity orientation recoloured as if held in this orientation,
+    /// e.g. what a robot that holds the cube this way sees. The inverse of `to_identity_colours`.
+    pub fn from_identity_colours(&self, facelets: &Facelets) -> Facelets {
+        facelets.recoloured(|side| FACES[self.faces[face_number(side)]])
+    }
+
+    /// Twists of the held cube as twists of the fixed orientation.
+    pub fn to_fixed(&self, twists: &[Twist]) -> Vec<Twist> {
+        twists.iter().map(|&t| with_face(t, self.faces[t as usize / 3])).collect()
+    }
+
+    /// Twists of the fixed orientation as twists of the held cube.
+    pub fn to_held(&self, twists: &[Twist]) -> Vec<Twist> {
+        twists
+            .iter()
+            .map(|&t| with_face(t, self.faces.iter().position(|&f| f == t as usize / 3).unwrap()))
+            .collect()
+    }
+
+    /// Twists of the fixed orientation written for the held cube, after the rotations that lead to it,
+    /// e.g. "y R U R'" for "B U B'" held after a y rotation. `normalize_moves` reverses this.
+    pub fn display_twists(&self, twists: &[Twist]) -> String {
+        let parts = [self.rotation(), to_wca(&self.to_held(twists))];
+        parts.into_iter().filter(|part| !part.is_empty()).collect::<Vec<_>>().join(" ")
+    }
+
+    /// A shortest sequence of x, y and z rotations that leads to this orientation, e.g. "x y'".
+    pub fn rotation(&self) -> String {
+        // Every orientation is at most two rotations away.
+        let rotations = (0..3).flat_map(|axis| (1..4).map(move |turns| (axis, turns)));
+        let singles = rotations.clone().map(|r| Vec::from([r]));
+        let pairs = rotations.clone().flat_map(|a| rotations.clone().map(move |b| Vec::from([a, b])));
+        core::iter::once(Vec::new())
+            .chain(singles)
+            .chain(pairs)
+            .find(|sequence| {
+                let orientation = sequence.iter().fold(Self::IDENTITY, |o, &(axis, turns)| o.rotated(ROTATION_FACES[axis], turns));
+                orientation == *self
+            })
+            .unwrap()
+            .iter()
+            .map(|&(axis, turns)| format!("{}{}", ['x', 'y', 'z'][axis], ["", "2", "'"][turns - 1]))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl Default for Orientation {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+fn with_face(twist: Twist, face: usize) -> Twist {
+    Twist::from((face * 3 + twist as usize % 3) as u32)
+}
+
+fn face_twist(face: usize, quarter_turns: usize) -> Twist {
+    Twist::from((face * 3 + quarter_turns - 1) as u32)
+}
+
+/// Rewrites moves with rotations (x, y, z), wide moves (Rw or r) and slice moves (M, E, S)
+/// into face twists of the fixed orientation, for the solver.
+/// Also returns the orientation the cube is held in at the end.
+pub fn normalize_moves(input: &str) -> Result<(Vec<Twist>, Orientation), String> {
+    let mut orientation = Orientation::IDENTITY;
+    let mut twists = Vec::new();
+    for token in input.split_whitespace() {
+        // WCA notation, or as in "L1 R2 U3".
+        let (name, quarter_turns) = match token.char_indices().last() {
+            Some((i, '\'' | '3')) => (&token[..i], 3),
+            Some((i, '2')) => (&token[..i], 2),
+            Some((i, '1')) => (&token[..i], 1),
+            _ => (token, 1),
+        };
+        let face_of = |c: char| FACE_NAMES.iter().position(|&f| f == c);
+        let mut chars = name.chars();
+        let (first, rest) = (chars.next(), chars.as_str());
+        // The held twists and how the whole cube turns with them, as the face it turns like.
+        let (held, rotation): (Vec<Twist>, Option<usize>) = match (first, rest) {
+            (Some(c), "") if face_of(c).is_some() => (Vec::from([face_twist(face_of(c).unwrap(), quarter_turns)]), None),
+            (Some(c), "w") if face_of(c).is_some() => {
+                let face = face_of(c).unwrap();
+                (Vec::from([face_twist(opposite(face), quarter_turns)]), Some(face))
+            }
+            (Some(c), "") if face_of(c.to_ascii_uppercase()).is_some() => {
+                let face = face_of(c.to_ascii_uppercase()).unwrap();
+                (Vec::from([face_twist(opposite(face), quarter_turns)]), Some(face))
+            }
+            (Some(c @ ('M' | 'E' | 'S')), "") => {
+                // The slices turn like L, D and F.
+                let face = match c {
+                    'M' => 0,
+                    'E' => 3,
+                    _ => 4,
+                };
+                let twists = [face_twist(face, 4 - quarter_turns), face_twist(opposite(face), quarter_turns)];
+                (Vec::from(twists), Some(face))
+            }
+            (Some(c @ ('x' | 'y' | 'z')), "") => (Vec::new(), Some(ROTATION_FACES[c as usize - 'x' as usize])),
+            _ => return Err(format!("Unknown move: '{}'", token)),
+        };
+        twists.extend(orientation.to_fixed(&held));
+        if let Some(face) = rotation {
+            orientation = orientation.rotated(face, quarter_turns);
+        }
+    }
+    Ok((twists, orientation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cubies::{Corners, Edges};
+
+    fn normalized(input: &str) -> Vec<Twist> {
+        normalize_moves(input).unwrap().0
+    }
+
+    #[test]
+    fn test_normalize_moves() {
+        assert_eq!(normalized("R U R' U'"), parse_twist_sequence("R U R' U'").unwrap());
+        assert_eq!(normalized("R1 U2 F3"), parse_twist_sequence("R U2 F'").unwrap());
+        assert_eq!(normalized("x U y R"), parse_twist_sequence("F U").unwrap());
+        assert_eq!(normalized("y R U R' U R U2 R'"), parse_twist_sequence("B U B' U B U2 B'").unwrap());
+        assert_eq!(normalize_moves("Rw").unwrap(), (Vec::from([Twist::L1]), Orientation::IDENTITY.rotated(1, 1)));
+        assert_eq!(normalized("r U"), normalized("Rw U"));
+        assert_eq!(normalize_moves("x x'").unwrap(), (Vec::new(), Orientation::IDENTITY));
+        assert!(normalize_moves("R Q").is_err());
+
+        // The H-perm with slices only permutes edges, and M2 turns the cube by x2.
+        let (twists, orientation) = normalize_moves("M2 U M2 U2 M2 U M2").unwrap();
+        assert_eq!(Corners::twists(&twists), Corners::solved());
+        assert_ne!(Edges::twists(&twists), Edges::solved());
+        assert_eq!(orientation, Orientation::IDENTITY);
+        assert_eq!(normalize_moves("M E S").unwrap().0.len(), 6);
+    }
+
+    #[test]
+    fn test_centers() {
+        let all = Orientation::all();
+        assert_eq!(all.len(), 24);
+        let x = normalize_moves("x").unwrap().1;
+        assert!(all.contains(&x));
+
+        // Held after an x rotation, the front is up.
+        let held = x.from_identity_colours(&Facelets::solved());
+        assert_eq!(held.centers(), [Face::F, Face::R, Face::D, Face::B, Face::L, Face::U]);
+        for orientation in all {
+            let twists = parse_twist_sequence("R U F' D2 L B").unwrap();
+            let facelets = Facelets::from_cubies(&Corners::twists(&twists), &Edges::twists(&twists));
+            let seen = orientation.from_identity_colours(&facelets);
+            assert_eq!(Orientation::from_centers(&seen), Some(orientation));
+            assert_eq!(orientation.to_identity_colours(&seen), facelets);
+        }
+        let mirrored_centers = Facelets::solved().recoloured(|f| match f {
+            Face::L => Face::R,
+            Face::R => Face::L,
+            _ => f,
+        });
+        assert_eq!(Orientation::from_centers(&mirrored_centers), None);
+    }
+
+    #[test]
+    fn test_display_twists() {
+        let twists = parse_twist_sequence("B U B' D2 L").unwrap();
+        assert_eq!(Orientation::IDENTITY.display_twists(&twists), "B U B' D2 L");
+        for rotation in ["x", "y'", "z2", "x y", "x' z"] {
+            let orientation = normalize_moves(rotation).unwrap().1;
+            assert_eq!(orientation.rotation(), rotation);
+            let displayed = orientation.display_twists(&twists);
+            assert_eq!(normalize_moves(&displayed).unwrap(), (twists.clone(), orientation));
+        }
+        assert_eq!(normalize_moves("y").unwrap().1.display_twists(&twists[..3]), "y R U R'");
+    }
+}
@@ -1,18 +1,20 @@
 use crate::math::*;
-use std::ops::{Mul, Index};
+use alloc::{vec, vec::Vec};
+use core::ops::{Mul, Index};
 
-/// Lexicographic index of the permutation (0 to N!-1).
-pub fn permutation_index(permutation: &[usize]) -> usize {
+/// Lexicographic index of the permutation (0 to N!-1), of elements of any unsigned width up to usize.
+pub fn permutation_index<T: Copy + Into<usize>>(permutation: &[T]) -> usize {
     assert!(permutation.len() <= 64, "Permutation too long to encode in usize");
     let size = permutation.len();
     let mut index = 0;
     let mut bitboard = 0;
 
-    for i in 0..size {
-        let mask: usize = 1usize << permutation[i];
+    for (i, &p) in permutation.iter().enumerate() {
+        let p: usize = p.into();
+        let mask: usize = 1usize << p;
 
         // Number of remaining elements smaller than the current element
-        let smaller = permutation[i] - (bitboard & (mask - 1)).count_ones() as usize;
+        let smaller = p - (bitboard & (mask - 1)).count_ones() as usize;
 
         // Total number of elements bigger than the current element
         let bigger = size - i - 1;
@@ -47,6 +49,99 @@ pub fn nth_permutation(mut n: usize, size: usize) -> Vec<usize> {
     permutation
 }
 
+/// Like `nth_permutation`, but returns an array instead of allocating a Vec, for the hot paths of the tables.
+pub const fn nth_permutation_array<const N: usize>(mut n: usize) -> [usize; N] {
+    assert!(N <= 64, "Permutation size too large to encode in usize");
+    let mut unused = usize::MAX;
+    let mut permutation = [0usize; N];
+    let mut i = 0;
+    while i < N {
+        let f = factorial(N - 1 - i);
+        let pos = n / f;
+        n %= f;
+
+        // Find the pos-th set bit in unused
+        let mut mask = unused;
+        let mut j = 0;
+        while j < pos {
+            mask &= mask - 1; // Clear lowest set bit
+            j += 1;
+        }
+        let selected_bit = mask & (!mask + 1); // Get lowest set bit
+
+        permutation[i] = selected_bit.trailing_zeros() as usize;
+        unused ^= selected_bit;
+        i += 1;
+    }
+    permutation
+}
+
+/// The element at `position` of `nth_permutation(n, size)`, without decoding the positions after it.
+pub const fn nth_permutation_element(mut n: usize, size: usize, position: usize) -> usize {
+    assert!(position < size && size <= 64);
+    let mut unused = usize::MAX;
+    let mut i = 0;
+    loop {
+        let f = factorial(size - 1 - i);
+        let mut mask = unused;
+        let mut j = 0;
+        while j < n / f {
+            mask &= mask - 1; // Clear lowest set bit
+            j += 1;
+        }
+        let selected_bit = mask & (!mask + 1); // Get lowest set bit
+        if i == position {
+            return selected_bit.trailing_zeros() as usize;
+        }
+        n %= f;
+        unused ^= selected_bit;
+        i += 1;
+    }
+}
+
+/// Like `permutation_index`, but for index spaces beyond usize, e.g. u128 for up to 34 elements.
+/// Returns None if the index doesn't fit into `I`.
+pub fn checked_permutation_index<T: Copy + Into<usize>, I: TryFrom<u128>>(permutation: &[T]) -> Option<I> {
+    let mut index = 0u128;
+    for (i, &p) in permutation.iter().enumerate() {
+        let p: usize = p.into();
+        // Number of remaining elements smaller than the current element
+        let smaller = permutation[i + 1..].iter().filter(|&&q| q.into() < p).count();
+        if smaller > 0 {
+            let term = checked_factorial(permutation.len() - i - 1)?.checked_mul(smaller as u128)?;
+            index = index.checked_add(term)?;
+        }
+    }
+    I::try_from(index).ok()
+}
+
+/// Like `nth_permutation`, but for index spaces beyond usize and elements of any unsigned width.
+/// Returns None if the index is not below size! or an element doesn't fit into `T`.
+pub fn checked_nth_permutation<T: TryFrom<usize>>(index: impl TryInto<u128>, size: usize) -> Option<Vec<T>> {
+    let mut index: u128 = index.try_into().ok()?;
+    let mut unused: Vec<usize> = (0..size).collect();
+    let mut permutation = Vec::with_capacity(size);
+    for i in (0..size).rev() {
+        // A factorial beyond u128 is bigger than any index.
+        let pos = match checked_factorial(i) {
+            Some(f) => {
+                let pos = index / f;
+                index %= f;
+                pos
+            }
+            None => 0,
+        };
+        if pos >= unused.len() as u128 {
+            return None;
+        }
+        permutation.push(T::try_from(unused.remove(pos as usize)).ok()?);
+    }
+    if index != 0 {
+        return None;
+    }
+    Some(permutation)
+}
+
 /// Returns true if the permutation represented by the lexicographical index is an even permutation.
 pub fn is_even_permutation(lexicographical_index: usize) -> bool {
     // Convert the index to its factoradic representation and sum the digits.
@@ -61,6 +156,19 @@ pub fn is_even_permutation(lexicographical_index: usize) -> bool {
     sum % 2 == 0
 }
 
+/// The index of a permutation among the permutations of the same parity, in [0, N!/2) for N > 1.
+/// Lexicographical indices 2k and 2k+1 only differ in the order of the last two elements, thus in their parity.
+pub fn even_permutation_index(lexicographical_index: usize) -> usize {
+    lexicographical_index / 2
+}
+
+/// The lexicographical index of the n-th even permutation, or of the n-th odd one if `even` is false.
+/// The inverse of `even_permutation_index` for a known parity.
+pub fn nth_even_permutation(n: usize, even: bool) -> usize {
+    let index = 2 * n;
+    if is_even_permutation(index) == even { index } else { index + 1 }
+}
+
 /// Represents a permutation of a fixed length.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Permutation<const LEN: usize> {
@@ -87,7 +195,7 @@ impl<const LEN: usize> Permutation<LEN> {
     }
 
     pub fn inverse(&self) -> Self {
-        let inv = std::array::from_fn(|i| self.map.iter().position(|&x| x == i).unwrap());
+        let inv = core::array::from_fn(|i| self.map.iter().position(|&x| x == i).unwrap());
         Self { map: inv }
     }
 
@@ -97,7 +205,7 @@ impl<const LEN: usize> Permutation<LEN> {
 
     pub fn from_index(index: usize) -> Self {
         assert!(index < factorial(LEN));
-        Self { map: nth_permutation(index, LEN).try_into().unwrap() }
+        Self { map: nth_permutation_array(index) }
     }
 }
 
@@ -115,7 +223,7 @@ impl<const LEN: usize> Mul for Permutation<LEN> {
     type Output = Self;
 
     fn mul(self, rhs: Self) -> Self {
-        Self { map: std::array::from_fn(|i| rhs.map[self.map[i]]) }
+        Self { map: core::array::from_fn(|i| rhs.map[self.map[i]]) }
     }
 }
 
@@ -124,7 +232,7 @@ impl<T: Copy, const N: usize> Mul<[T; N]> for Permutation<N> {
     type Output = [T; N];
 
     fn mul(self, rhs: [T; N]) -> [T; N] {
-        std::array::from_fn(|i| rhs[self.map[i]])
+        core::array::from_fn(|i| rhs[self.map[i]])
     }
 }
 
@@ -147,6 +255,47 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_nth_permutation_array() {
+        for index in 0..factorial(8) {
+            assert_eq!(nth_permutation_array::<8>(index).to_vec(), nth_permutation(index, 8));
+        }
+        assert_eq!(nth_permutation_array::<12>(factorial(12) - 1), [11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0]);
+        assert_eq!(nth_permutation_array::<0>(0), [0usize; 0]);
+    }
+
+    #[test]
+    fn test_nth_permutation_element() {
+        for index in 0..factorial(8) {
+            let permutation = nth_permutation_array::<8>(index);
+            assert!((0..8).all(|i| nth_permutation_element(index, 8, i) == permutation[i]));
+        }
+    }
+
+    #[test]
+    fn test_checked_permutation_index() {
+        let permutation: [u8; 5] = [3, 0, 4, 1, 2];
+        let index = permutation_index(&[3usize, 0, 4, 1, 2]);
+        assert_eq!(permutation_index(&permutation), index);
+        assert_eq!(permutation_index(&permutation.map(u16::from)), index);
+        assert_eq!(checked_permutation_index::<_, u64>(&permutation), Some(index as u64));
+        assert_eq!(checked_nth_permutation::<u8>(index, 5), Some(permutation.to_vec()));
+        assert_eq!(checked_nth_permutation::<u8>(factorial(5), 5), None);
+        assert_eq!(checked_nth_permutation::<u8>(0u64, 300), None); // 299 doesn't fit into u8
+
+        // 21! doesn't fit into u64, but into u128.
+        let reversed: Vec<u8> = (0..21).rev().collect();
+        assert_eq!(checked_permutation_index::<_, u64>(&reversed), None);
+        let index = checked_permutation_index::<_, u128>(&reversed).unwrap();
+        assert_eq!(index, checked_factorial(21).unwrap() - 1);
+        assert_eq!(checked_nth_permutation::<u8>(index, 21), Some(reversed));
+
+        let permutation: Vec<u16> = (0..30).map(|i| (i * 7) % 30).collect();
+        let index = checked_permutation_index::<_, u128>(&permutation).unwrap();
+        assert_eq!(checked_nth_permutation::<u16>(index, 30), Some(permutation));
+        assert_eq!(checked_permutation_index::<_, u128>(&(0..40u8).rev().collect::<Vec<_>>()), None);
+    }
+
     #[test]
     fn test_identity() {
         let prm = Permutation::new([1, 0, 2]); // Arbitrary
@@ -202,7 +351,7 @@ mod tests {
                 }
             }
         }
-        (perm.len() - cycles) % 2 == 0
+        (perm.len() - cycles).is_multiple_of(2)
     }
 
     #[test]
@@ -225,6 +374,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_even_permutation_index() {
+        for size in 2..=6 {
+            for index in 0..factorial(size) {
+                let even = is_even_permutation(index);
+                let n = even_permutation_index(index);
+                assert!(n < factorial(size) / 2);
+                assert_eq!(nth_even_permutation(n, even), index);
+            }
+        }
+        assert_eq!(nth_even_permutation(0, true), 0);
+        assert_eq!(nth_even_permutation(0, false), 1);
+        assert_eq!(nth_even_permutation(1, true), 3); // [0, 2, 3, 1]
+    }
+
     #[test]
     fn test_permutation_index_half_is_bijection() {
         // Tests that permutation_index/2 is a bijection,
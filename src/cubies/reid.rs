@@ -0,0 +1,118 @@
+use crate::facelets::*;
+use alloc::{string::{String, ToString}, vec::Vec};
+use thiserror::Error;
+
+/// Positions of Reid's cube strings, 12 edges followed by 8 corners.
+/// Each position lists its stickers starting with the U or D sticker, or the F or B sticker of the middle layer.
+/// Corners list their stickers counterclockwise.
+const REID_POSITIONS: [&str; 20] = [
+    "UF", "UR", "UB", "UL", "DF", "DR", "DB", "DL", "FR", "FL", "BR", "BL",
+    "UFR", "URB", "UBL", "ULF", "DRF", "DFL", "DLB", "DBR",
+];
+
+// Facelets of the stickers of each position, in the order of the position's name.
+const REID_FACELETS: [&[usize]; 20] = [
+    &[7, 19], &[5, 10], &[1, 46], &[3, 37], &[28, 25], &[32, 16], &[34, 52], &[30, 43],
+    &[23, 12], &[21, 41], &[48, 14], &[50, 39],
+    &[8, 20, 9], &[2, 11, 45], &[0, 47, 36], &[6, 38, 18],
+    &[29, 15, 26], &[27, 24, 44], &[33, 42, 53], &[35, 51, 17],
+];
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ReidError {
+    #[error("Expected 20 pieces, found {0}")]
+    PieceCount(usize),
+    #[error("Invalid piece '{piece}' at position {position}")]
+    InvalidPiece { position: &'static str, piece: String },
+    #[error(transparent)]
+    Facelets(#[from] FaceletError),
+}
+
+impl Facelets {
+    /// Parses a cube string in Reid's notation, like "UF UR UB UL DF DR DB DL FR FL BR BL UFR URB UBL ULF DRF DFL DLB DBR" for the solved cube.
+    /// Each piece names the faces of its stickers at the position of the same index in the solved string.
+    pub fn from_reid(s: &str) -> Result<Self, ReidError> {
+        let pieces: Vec<&str> = s.split_whitespace().collect();
+        if pieces.len() != REID_POSITIONS.len() {
+            return Err(ReidError::PieceCount(pieces.len()));
+        }
+        let mut faces = *Facelets::solved().faces();
+        for ((piece, position), facelets) in pieces.iter().zip(REID_POSITIONS).zip(REID_FACELETS) {
+            let invalid = || ReidError::InvalidPiece { position, piece: piece.to_string() };
+            if piece.chars().count() != facelets.len() {
+                return Err(invalid());
+            }
+            for (c, &facelet) in piece.chars().zip(facelets) {
+                faces[facelet] = Face::from_char(c).ok_or_else(invalid)?;
+            }
+        }
+        Ok(Facelets::new(faces))
+    }
+
+    pub fn to_reid(&self) -> String {
+        REID_FACELETS
+            .iter()
+            .map(|facelets| facelets.iter().map(|&f| self.faces()[f].to_char()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corners::*;
+    use crate::edges::*;
+    use crate::twist::*;
+    use crate::twist_generator::*;
+
+    const SOLVED: &str = "UF UR UB UL DF DR DB DL FR FL BR BL UFR URB UBL ULF DRF DFL DLB DBR";
+
+    fn facelets(twists: &[Twist]) -> Facelets {
+        Facelets::from_cubies(&Corners::twists(twists), &Edges::twists(twists))
+    }
+
+    #[test]
+    fn test_solved() {
+        assert_eq!(Facelets::solved().to_reid(), SOLVED);
+        assert_eq!(Facelets::from_reid(SOLVED).unwrap(), Facelets::solved());
+    }
+
+    #[test]
+    fn test_superflip() {
+        let superflip = parse_twist_sequence("U R2 F B R B2 R U2 L B2 R U' D' R2 F R' L B2 U2 F2").unwrap();
+        assert_eq!(
+            facelets(&superflip).to_reid(),
+            "FU RU BU LU FD RD BD LD RF LF RB LB UFR URB UBL ULF DRF DFL DLB DBR"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut rnd = RandomTwistGen::new(42, &ALL_TWISTS);
+        for _ in 0..1_000 {
+            let facelets = facelets(&rnd.gen_twists(30));
+            assert_eq!(Facelets::from_reid(&facelets.to_reid()).unwrap(), facelets);
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(Facelets::from_reid("UF UR"), Err(ReidError::PieceCount(2)));
+
+        let wrong_length = SOLVED.replace("UFR", "UF");
+        assert_eq!(
+            Facelets::from_reid(&wrong_length),
+            Err(ReidError::InvalidPiece { position: "UFR", piece: "UF".to_string() })
+        );
+        let wrong_face = SOLVED.replace("BL", "BX");
+        assert_eq!(
+            Facelets::from_reid(&wrong_face),
+            Err(ReidError::InvalidPiece { position: "BL", piece: "BX".to_string() })
+        );
+
+        // Parses, but describes a twisted corner.
+        let twisted = Facelets::from_reid(&SOLVED.replace("UFR", "FRU")).unwrap();
+        assert_eq!(twisted.to_cubies(), Err(FaceletError::CornerTwist));
+    }
+}
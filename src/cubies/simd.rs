@@ -0,0 +1,29 @@
+use core::arch::x86_64::*;
+
+/// Computes `Corners * Corners` on the byte lanes of the corners.
+#[inline(always)]
+pub fn compose_corners(l: [u8; 8], r: [u8; 8]) -> [u8; 8] {
+    unsafe {
+        let l = _mm_cvtsi64_si128(i64::from_le_bytes(l));
+        let r = _mm_cvtsi64_si128(i64::from_le_bytes(r));
+        let gathered = _mm_shuffle_epi8(r, _mm_and_si128(l, _mm_set1_epi8(0x0F)));
+        let sum = _mm_add_epi8(gathered, _mm_and_si128(l, _mm_set1_epi8(0x30)));
+        // Orientations are in 0..=4 now, reduce them modulo 3.
+        let overflow = _mm_cmpgt_epi8(sum, _mm_set1_epi8(0x2F));
+        let result = _mm_sub_epi8(sum, _mm_and_si128(overflow, _mm_set1_epi8(0x30)));
+        _mm_cvtsi128_si64(result).to_le_bytes()
+    }
+}
+
+/// Computes `Edges * Edges` on the byte lanes of the edges.
+#[inline(always)]
+pub fn compose_edges(l: [u8; 16], r: [u8; 16]) -> [u8; 16] {
+    unsafe {
+        let l: __m128i = core::mem::transmute(l);
+        let r: __m128i = core::mem::transmute(r);
+        let gathered = _mm_shuffle_epi8(r, _mm_and_si128(l, _mm_set1_epi8(0x0F)));
+        let result = _mm_xor_si128(gathered, _mm_and_si128(l, _mm_set1_epi8(0x10)));
+        // Clear the 4 unused lanes.
+        core::mem::transmute(_mm_and_si128(result, _mm_set_epi32(0, -1, -1, -1)))
+    }
+}
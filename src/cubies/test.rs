@@ -4,7 +4,7 @@ mod tests {
     use crate::edges::*;
     use crate::twist_generator::*;
     use crate::twist::*;
-    use std::ops::Mul;
+    use core::ops::Mul;
 
     /// Cubies of a Rubik's Cube.
     #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -17,7 +17,6 @@ mod tests {
         pub fn solved() -> Self { Self { corners: Corners::solved(), edges: Edges::solved() } }
         pub fn twist(twist: Twist) -> Self { Self { corners: Corners::twist(twist), edges: Edges::twist(twist) } }
         pub fn twists(twists: &[Twist]) -> Self { Self { corners: Corners::twists(twists), edges: Edges::twists(twists) } }
-        pub fn inverse(&self) -> Self { Self { corners: self.corners.inverse(), edges: self.edges.inverse() } }
         pub fn conjugated_by(&self, rot: Axis) -> Self { Self { corners: self.corners.conjugated_by(rot), edges: self.edges.conjugated_by(rot) } }
     }
 
@@ -83,17 +82,6 @@ mod tests {
         assert_eq!(cycle_length(&[Twist::R1, Twist::U2, Twist::D3, Twist::B1, Twist::D3]), 1260);
     }
 
-    #[test]
-    fn test_inverse() {
-        // Fuzzing
-        let mut rnd = RandomTwistGen::new(12345678, &ALL_TWISTS);
-        for _ in 0..100_000 {
-            let rnd_cube = Cubies::twists(&rnd.gen_twists(100));
-            assert_eq!(rnd_cube * rnd_cube.inverse(), Cubies::solved(), "A cube multiplied by its inverse should yield the solved state, failed for cube {:?}", rnd_cube);
-            assert_eq!(rnd_cube.inverse() * rnd_cube, Cubies::solved(), "The inverse of a cube multiplied by the cube should yield the solved state, failed for cube {:?}", rnd_cube);
-        }
-    }
-
     #[test]
     fn test_conjugation() {
         // Trivial cases (conjugating the solved state should yield the solved state)
@@ -1,3 +1,13 @@
+use super::corners::*;
+use super::cycles::*;
+use super::edges::*;
+use super::facelets::Face;
+#[cfg(feature = "std")]
+use super::twist_set::*;
+use alloc::{format, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+
 /// Face twist, where the number indicates how many quarter turns to perform.
 ///      +---------+
 ///     /    ←B   /|
@@ -7,7 +17,7 @@
 ///   | ↓     ↑ |  +
 ///   |   D→  R | /
 ///   +---------+
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Twist {
     L1, L2, L3, // Left face
     R1, R2, R3, // Right face
@@ -18,6 +28,7 @@ pub enum Twist {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Axis {
     X, Y, Z,
 }
@@ -61,12 +72,35 @@ impl From<u32> for Twist {
             15 => Twist::B1,
             16 => Twist::B2,
             17 => Twist::B3,
-            _ => unsafe { std::hint::unreachable_unchecked() },
+            _ => unsafe { core::hint::unreachable_unchecked() },
         }
     }
 }
 
 impl Twist {
+    /// The face the twist turns.
+    pub const fn face(&self) -> Face {
+        const FACES: [Face; 6] = [Face::L, Face::R, Face::U, Face::D, Face::F, Face::B];
+        FACES[*self as usize / 3]
+    }
+
+    /// The axis of the face the twist turns.
+    pub const fn axis(&self) -> Axis {
+        const AXES: [Axis; 3] = [Axis::X, Axis::Z, Axis::Y];
+        AXES[*self as usize / 6]
+    }
+
+    /// The number of clockwise quarter turns, 1 to 3.
+    pub const fn power(&self) -> u8 {
+        *self as u8 % 3 + 1
+    }
+
+    /// The twist of the same face with `power` clockwise quarter turns, 1 to 3.
+    pub fn with_power(&self, power: u8) -> Self {
+        assert!((1..=3).contains(&power), "the power of a twist is 1 to 3, not {power}");
+        Twist::from((*self as u32 / 3) * 3 + power as u32 - 1)
+    }
+
     pub fn inverse(&self) -> Self {
         match self {
             Twist::L2 | Twist::R2 | Twist::U2 | Twist::D2 | Twist::F2 | Twist::B2 => *self,
@@ -85,6 +119,18 @@ impl Twist {
         }
     }
 
+    /// The twist as seen in a mirror perpendicular to the axis, e.g. R becomes L' and U becomes U' in a mirror between L and R.
+    pub fn mirrored(&self, axis: Axis) -> Self {
+        let face = *self as u32 / 3;
+        let face = if self.axis() == axis { face ^ 1 } else { face };
+        Twist::from(face * 3 + 3 - self.power() as u32)
+    }
+
+    /// The twist of the cube before a rotation of the whole cube as the twist of the cube after it, the inverse of `conjugate_by_inv`.
+    pub fn conjugate_by(&self, rot: Axis) -> Self {
+        self.conjugate_by_inv(rot).conjugate_by_inv(rot).conjugate_by_inv(rot)
+    }
+
     pub fn conjugate_by_inv(&self, rot: Axis) -> Self {
         match rot {
             Axis::X => match self {
@@ -136,6 +182,79 @@ impl Twist {
     }
 }
 
+/// Whether the twists turn faces of the same axis, e.g. R and L, or R and R2.
+pub const fn same_axis(a: Twist, b: Twist) -> bool {
+    a as usize / 6 == b as usize / 6
+}
+
+/// Whether `next` may follow `twist` in a sequence without redundancy:
+/// not of the same face, since they'd merge into one twist,
+/// and of opposite faces, which commute, only in the order L before R, U before D and F before B.
+pub const fn is_canonical_after(twist: Twist, next: Twist) -> bool {
+    let same_face = twist as usize / 3 == next as usize / 3;
+    let out_of_order = same_axis(twist, next) && (next as usize) < (twist as usize);
+    !same_face && !out_of_order
+}
+
+/// Iterates over the sequences of a length whose twists each follow the one before canonically, see `is_canonical_after`,
+/// in lexicographic order, e.g. for brute-force searches.
+pub struct CanonicalSequences {
+    twists: Vec<Twist>,
+    done: bool,
+}
+
+/// The canonical sequences of `length` twists, see `CanonicalSequences`.
+pub fn canonical_sequences(length: usize) -> CanonicalSequences {
+    let mut twists = Vec::with_capacity(length);
+    if length > 0 {
+        twists.push(Twist::L1);
+        CanonicalSequences::fill(&mut twists, length);
+    }
+    CanonicalSequences { twists, done: false }
+}
+
+impl CanonicalSequences {
+    // Appends the smallest twists that follow canonically.
+    fn fill(twists: &mut Vec<Twist>, length: usize) {
+        while twists.len() < length {
+            let last = *twists.last().unwrap();
+            twists.push(ALL_TWISTS.into_iter().find(|&t| is_canonical_after(last, t)).unwrap());
+        }
+    }
+
+    // The smallest twist after `twist` that follows `previous` canonically.
+    fn successor(previous: Option<Twist>, twist: Twist) -> Option<Twist> {
+        ALL_TWISTS[twist as usize + 1..]
+            .iter()
+            .copied()
+            .find(|&t| previous.is_none_or(|previous| is_canonical_after(previous, t)))
+    }
+}
+
+impl Iterator for CanonicalSequences {
+    type Item = Vec<Twist>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let current = self.twists.clone();
+        // Advances the last twist that has a successor, like an odometer.
+        let length = self.twists.len();
+        self.done = true;
+        while let Some(twist) = self.twists.pop() {
+            let previous = self.twists.last().copied();
+            if let Some(next) = Self::successor(previous, twist) {
+                self.twists.push(next);
+                Self::fill(&mut self.twists, length);
+                self.done = false;
+                break;
+            }
+        }
+        Some(current)
+    }
+}
+
 pub fn inverse(twists: &[Twist]) -> Vec<Twist> {
     twists.iter().rev().map(|t| t.inverse()).collect()
 }
@@ -144,13 +263,91 @@ pub fn conjugate_by_inv(twists: &[Twist], rot: Axis) -> Vec<Twist> {
     twists.iter().map(|t| t.conjugate_by_inv(rot)).collect()
 }
 
-impl std::str::FromStr for Twist {
+/// The sequence with adjacent twists of the same face merged, and removed where they cancel out, e.g. "R U U2 U R'" becomes nothing.
+pub fn cancelled(twists: &[Twist]) -> Vec<Twist> {
+    let mut result: Vec<Twist> = Vec::with_capacity(twists.len());
+    for &twist in twists {
+        match result.last() {
+            Some(&last) if last.face() == twist.face() => {
+                result.pop();
+                let power = (last.power() + twist.power()) % 4;
+                if power != 0 {
+                    result.push(twist.with_power(power));
+                }
+            }
+            _ => result.push(twist),
+        }
+    }
+    result
+}
+
+/// Like `cancelled`, but also merges twists of the same face that are separated only by twists of the opposite face,
+/// since those commute, e.g. "R L R2" becomes "L R'". Opposite faces are ordered canonically, see `is_canonical_after`.
+pub fn compressed(twists: &[Twist]) -> Vec<Twist> {
+    let mut result: Vec<Twist> = Vec::with_capacity(twists.len());
+    for &twist in twists {
+        // The result is compressed, so the twists of the axis at its end are at most one per face.
+        let run = result.iter().rev().take_while(|&&t| same_axis(t, twist)).count();
+        let start = result.len() - run;
+        match result[start..].iter().position(|t| t.face() == twist.face()) {
+            Some(i) => {
+                let last = result.remove(start + i);
+                let power = (last.power() + twist.power()) % 4;
+                if power != 0 {
+                    result.insert(start + i, twist.with_power(power));
+                }
+            }
+            None => {
+                let i = result[start..].iter().position(|&t| (twist as usize) < (t as usize)).unwrap_or(run);
+                result.insert(start + i, twist);
+            }
+        }
+    }
+    result
+}
+
+/// Twists of the cube after a rotation of the whole cube, as twists of the cube before it.
+pub fn unrotated(twists: &[Twist], rotation: &[Axis]) -> Vec<Twist> {
+    rotation.iter().rev().fold(twists.to_vec(), |twists, &rot| conjugate_by_inv(&twists, rot))
+}
+
+/// Twists of the cube before a rotation of the whole cube, as twists of the cube after it, the inverse of `unrotated`.
+pub fn rotated(twists: &[Twist], rotation: &[Axis]) -> Vec<Twist> {
+    rotation.iter().fold(twists.to_vec(), |twists, &rot| twists.iter().map(|t| t.conjugate_by(rot)).collect())
+}
+
+impl Twist {
+    /// The twist in WCA notation, e.g. "R", "R2" and "R'".
+    pub fn to_wca(&self) -> &'static str {
+        const WCA: [&str; 18] = [
+            "L", "L2", "L'",
+            "R", "R2", "R'",
+            "U", "U2", "U'",
+            "D", "D2", "D'",
+            "F", "F2", "F'",
+            "B", "B2", "B'",
+        ];
+        WCA[*self as usize]
+    }
+}
+
+/// Formats twists as space-separated WCA notation.
+pub fn to_wca(twists: &[Twist]) -> String {
+    twists.iter().map(|t| t.to_wca()).collect::<Vec<_>>().join(" ")
+}
+
+/// Parses whitespace-separated twists, in WCA notation or as in "L1 R2 U3".
+pub fn parse_twist_sequence(input: &str) -> Result<Vec<Twist>, String> {
+    input.split_whitespace().map(|s| s.parse()).collect()
+}
+
+impl core::str::FromStr for Twist {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // Use Debug representation to match against all variants
+        // Use Debug representation and WCA notation to match against all variants
         for twist in ALL_TWISTS {
-            if format!("{:?}", twist) == s {
+            if format!("{:?}", twist) == s || twist.to_wca() == s {
                 return Ok(twist);
             }
         }
@@ -158,8 +355,23 @@ impl std::str::FromStr for Twist {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for Twist {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.to_wca())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Twist {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 /// Parse a string of space-separated twists into a Vec<Twist>.
 /// Anything onwards from '#' is ignored.
+#[cfg(feature = "std")]
 fn parse_twists(input: &str) -> Vec<Twist> {
     input
         .split('#') // Split off comments
@@ -170,14 +382,46 @@ fn parse_twists(input: &str) -> Vec<Twist> {
         .collect()
 }
 
+#[cfg(feature = "std")]
 pub fn read_twist_file(path: &str) -> Vec<Vec<Twist>> {
     let content = std::fs::read_to_string(path).unwrap();
-    content.lines().map(|line| parse_twists(line)).collect()
+    content.lines().map(parse_twists).collect()
+}
+
+/// How often the twists have to be applied to get back to solved.
+pub fn order(twists: &[Twist]) -> usize {
+    CycleStructure::new(&Corners::twists(twists), &Edges::twists(twists)).order()
+}
+
+/// The number of states reachable with the twists, if it is at most `limit`.
+/// Enumerates them breadth-first, so it's only feasible for small subgroups.
+#[cfg(feature = "std")]
+pub fn subgroup_size(twists: TwistSet, limit: usize) -> Option<usize> {
+    let solved = (Corners::solved(), Edges::solved());
+    let mut visited = HashSet::from([solved]);
+    let mut layer = vec![solved];
+    while !layer.is_empty() {
+        let mut next = Vec::new();
+        for (corners, edges) in layer {
+            for twist in twists.iter() {
+                let state = (twist * corners, twist * edges);
+                if visited.insert(state) {
+                    if visited.len() > limit {
+                        return None;
+                    }
+                    next.push(state);
+                }
+            }
+        }
+        layer = next;
+    }
+    Some(visited.len())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Corners, Edges, twist_generator::*};
 
     #[test]
     fn test_parse() {
@@ -186,6 +430,41 @@ mod tests {
     }
 
     #[test]
+    fn test_wca() {
+        for twist in ALL_TWISTS {
+            assert_eq!(twist.to_wca().parse::<Twist>().unwrap(), twist);
+        }
+        let sequence = parse_twist_sequence("R U' F2 L1").unwrap();
+        assert_eq!(sequence, vec![Twist::R1, Twist::U3, Twist::F2, Twist::L1]);
+        assert_eq!(to_wca(&sequence), "R U' F2 L");
+        assert!(parse_twist_sequence("R X").is_err());
+    }
+
+    #[test]
+    fn test_order() {
+        assert_eq!(order(&[]), 1);
+        assert_eq!(order(&[Twist::R1]), 4);
+        assert_eq!(order(&[Twist::R2]), 2);
+        assert_eq!(order(&parse_twist_sequence("R U").unwrap()), 105);
+        assert_eq!(order(&parse_twist_sequence("R U R' U'").unwrap()), 6);
+        assert_eq!(order(&parse_twist_sequence("R U2 D' B D'").unwrap()), 1260);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_subgroup_size() {
+        assert_eq!(subgroup_size(TwistSet::EMPTY, 10), Some(1));
+        assert_eq!(subgroup_size(TwistSet::from_twists(&[Twist::U1]), 10), Some(4));
+        assert_eq!(subgroup_size(TwistSet::from_twists(&[Twist::U1, Twist::D1]), 100), Some(16));
+        assert_eq!(subgroup_size(TwistSet::from_twists(&[Twist::R2, Twist::U2]), 100), Some(12));
+        assert_eq!(subgroup_size(TwistSet::from_twists(&[Twist::R1, Twist::U1]), 1_000), None);
+        // The square group.
+        let half_twists = [Twist::L2, Twist::R2, Twist::U2, Twist::D2, Twist::F2, Twist::B2];
+        assert_eq!(subgroup_size(TwistSet::from_twists(&half_twists), 1_000_000), Some(663_552));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
     fn test_parse_twists() {
         let input = "L1 R2 U3 # Comment";
         let expected = vec![Twist::L1, Twist::R2, Twist::U3];
@@ -202,6 +481,70 @@ mod tests {
         assert_eq!(inverse(&inverse(&sequence)), sequence);
     }
 
+    #[test]
+    fn test_cancelled() {
+        assert_eq!(cancelled(&parse_twist_sequence("R U U2 U R'").unwrap()), []);
+        assert_eq!(cancelled(&parse_twist_sequence("R U U L2 L' F").unwrap()), parse_twist_sequence("R U2 L F").unwrap());
+        assert_eq!(cancelled(&parse_twist_sequence("R L R").unwrap()), parse_twist_sequence("R L R").unwrap());
+    }
+
+    #[test]
+    fn test_compressed() {
+        let compress = |s: &str| to_wca(&compressed(&parse_twist_sequence(s).unwrap()));
+        assert_eq!(compress("R L R2"), "L R'");
+        assert_eq!(compress("R U U2 U R'"), "");
+        assert_eq!(compress("R U U' L R'"), "L");
+        assert_eq!(compress("D U F B' F' B U2 D"), "U' D2");
+        assert_eq!(compress("D U F B' F' U2 D"), "U D B' U2 D");
+        assert_eq!(compress("R U L U' R"), "R U L U' R");
+
+        let mut rnd = RandomTwistGen::new(3, &ALL_TWISTS);
+        for _ in 0..100 {
+            let twists = rnd.gen_twists(20);
+            let result = compressed(&twists);
+            assert_eq!((Corners::twists(&result), Edges::twists(&result)), (Corners::twists(&twists), Edges::twists(&twists)));
+            assert!(result.windows(2).all(|pair| is_canonical_after(pair[0], pair[1])));
+            assert_eq!(compressed(&result), result);
+            assert!(result.len() <= cancelled(&twists).len());
+        }
+    }
+
+    #[test]
+    fn test_face_axis_power() {
+        assert_eq!((Twist::R3.face(), Twist::R3.axis(), Twist::R3.power()), (Face::R, Axis::X, 3));
+        assert_eq!((Twist::D1.face(), Twist::D1.axis(), Twist::D1.power()), (Face::D, Axis::Z, 1));
+        assert_eq!((Twist::F2.face(), Twist::F2.axis(), Twist::F2.power()), (Face::F, Axis::Y, 2));
+        for twist in ALL_TWISTS {
+            assert_eq!(twist.with_power(twist.power()), twist);
+            assert_eq!(twist.with_power(4 - twist.power()), twist.inverse());
+            // The axis is the one the twist is unchanged by conjugating with.
+            assert_eq!(twist.conjugate_by_inv(twist.axis()), twist);
+        }
+        assert!(same_axis(Twist::R1, Twist::L3));
+        assert!(same_axis(Twist::U1, Twist::U2));
+        assert!(!same_axis(Twist::U1, Twist::F1));
+    }
+
+    #[test]
+    fn test_is_canonical_after() {
+        assert!(!is_canonical_after(Twist::R1, Twist::R2));
+        assert!(is_canonical_after(Twist::L1, Twist::R2));
+        assert!(!is_canonical_after(Twist::R1, Twist::L2));
+        assert!(is_canonical_after(Twist::R1, Twist::U1));
+        assert!(is_canonical_after(Twist::B1, Twist::L1));
+    }
+
+    #[test]
+    fn test_canonical_sequences() {
+        assert_eq!(canonical_sequences(0).collect::<Vec<_>>(), [Vec::<Twist>::new()]);
+        assert_eq!(canonical_sequences(1).collect::<Vec<_>>(), ALL_TWISTS.map(|t| vec![t]));
+        let sequences: Vec<Vec<Twist>> = canonical_sequences(3).collect();
+        assert_eq!(sequences.len(), 3240);
+        assert_eq!(sequences[0], [Twist::L1, Twist::R1, Twist::U1]);
+        assert!(sequences.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(sequences.iter().all(|s| s.windows(2).all(|pair| is_canonical_after(pair[0], pair[1]))));
+    }
+
     #[test]
     fn test_conjugation() {
         for twist in ALL_TWISTS {
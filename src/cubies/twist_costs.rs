@@ -0,0 +1,94 @@
+use super::facelets::Face;
+use super::twist::*;
+
+/// The cost of each twist, whose total the searches minimize instead of the number of twists,
+/// e.g. in the quarter turn metric, where half turns cost 2, or the time a robot takes for the twists of each face.
+/// Costs are positive, and tables created with costs store distances as total costs, see `DistanceTable::create_with_costs`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TwistCosts([u8; 18]);
+
+impl Default for TwistCosts {
+    fn default() -> Self {
+        Self::HTM
+    }
+}
+
+impl TwistCosts {
+    /// Every twist costs 1, the half turn metric.
+    pub const HTM: Self = Self([1; 18]);
+
+    /// Half turns cost 2, the quarter turn metric.
+    pub const QTM: Self = {
+        let mut costs = [1; 18];
+        let mut i = 1;
+        while i < 18 {
+            costs[i] = 2;
+            i += 3;
+        }
+        Self(costs)
+    };
+
+    /// The costs of the twists in the order of `ALL_TWISTS`. Panics if a cost is 0.
+    pub const fn new(costs: [u8; 18]) -> Self {
+        let mut i = 0;
+        while i < 18 {
+            assert!(costs[i] > 0, "twists cost at least 1");
+            i += 1;
+        }
+        Self(costs)
+    }
+
+    /// With the costs of the quarter and the half turns of the face, e.g. for a robot whose arms turn at different speeds.
+    pub fn with_face(mut self, face: Face, quarter: u8, half: u8) -> Self {
+        assert!(quarter > 0 && half > 0, "twists cost at least 1");
+        for twist in ALL_TWISTS.into_iter().filter(|twist| twist.face() == face) {
+            self.0[twist as usize] = if twist.power() == 2 { half } else { quarter };
+        }
+        self
+    }
+
+    pub const fn cost(&self, twist: Twist) -> u8 {
+        self.0[twist as usize]
+    }
+
+    /// The total cost of the twists.
+    pub fn total(&self, twists: &[Twist]) -> usize {
+        twists.iter().map(|&twist| self.cost(twist) as usize).sum()
+    }
+
+    /// The cost of the cheapest twist.
+    pub fn min_cost(&self) -> u8 {
+        self.0.into_iter().min().unwrap()
+    }
+
+    /// Whether every twist costs 1, where the total cost is the number of twists.
+    pub fn is_unit(&self) -> bool {
+        *self == Self::HTM
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_costs() {
+        let twists = [Twist::R1, Twist::U2, Twist::F3];
+        assert_eq!(TwistCosts::HTM.total(&twists), 3);
+        assert_eq!(TwistCosts::QTM.total(&twists), 4);
+        assert!(ALL_TWISTS.iter().all(|t| TwistCosts::QTM.cost(*t) == if t.power() == 2 { 2 } else { 1 }));
+        assert!(TwistCosts::default().is_unit() && !TwistCosts::QTM.is_unit());
+
+        let robot = TwistCosts::QTM.with_face(Face::F, 3, 5);
+        assert_eq!(robot.total(&twists), 1 + 2 + 3);
+        assert_eq!(robot.cost(Twist::F2), 5);
+        assert_eq!(robot.min_cost(), 1);
+        assert_eq!(TwistCosts::new([4; 18]).min_cost(), 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_cost() {
+        TwistCosts::HTM.with_face(Face::U, 0, 1);
+    }
+}
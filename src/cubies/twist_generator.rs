@@ -1,4 +1,9 @@
+use super::corners::*;
+use super::edges::*;
+use super::math::*;
+use super::permutation::*;
 use super::twist::*;
+use alloc::vec::Vec;
 use rand::{rngs::StdRng, RngExt, SeedableRng};
 
 /// Random twist generator
@@ -25,9 +30,69 @@ impl RandomTwistGen {
     }
 }
 
+/// Random twist generator for scrambles that don't trivially cancel, as in WCA scrambles.
+/// It never twists the same face twice in a row, and never twists the same axis three times in a row, like R L R.
+pub struct WcaScrambleGen {
+    rng: StdRng,
+}
+
+impl WcaScrambleGen {
+    pub fn new(seed: u64) -> Self {
+        Self { rng: StdRng::seed_from_u64(seed) }
+    }
+
+    /// Seeded from the operating system's entropy.
+    #[cfg(feature = "std")]
+    pub fn from_entropy() -> Self {
+        Self::new(rand::random())
+    }
+
+    pub fn gen_twists(&mut self, count: usize) -> Vec<Twist> {
+        let mut twists: Vec<Twist> = Vec::with_capacity(count);
+        for _ in 0..count {
+            let candidates: Vec<Twist> = ALL_TWISTS
+                .into_iter()
+                .filter(|&t| match twists.as_slice() {
+                    [.., a, b] if same_axis(*a, *b) => !same_axis(t, *b),
+                    [.., b] => t.face() != b.face(),
+                    [] => true,
+                })
+                .collect();
+            twists.push(candidates[self.rng.random_range(0..candidates.len())]);
+        }
+        twists
+    }
+}
+
+/// Random cube state generator, where every reachable state is equally likely.
+pub struct RandomStateGen {
+    rng: StdRng,
+}
+
+impl RandomStateGen {
+    pub fn new(seed: u64) -> Self {
+        Self { rng: StdRng::seed_from_u64(seed) }
+    }
+
+    pub fn gen_cubies(&mut self) -> (Corners, Edges) {
+        let c_prm = self.rng.random_range(0..Corners::PRM_SIZE);
+        let corners = Corners::from_indices(c_prm, self.rng.random_range(0..Corners::ORI_SIZE));
+
+        let mut e_prm: [usize; 12] = nth_permutation(self.rng.random_range(0..factorial(12)), 12).try_into().unwrap();
+        if is_even_permutation(permutation_index(&e_prm)) != is_even_permutation(c_prm) {
+            e_prm.swap(0, 1); // Ensure the permutation parities match
+        }
+        let ori_index = self.rng.random_range(0..Edges::ORI_SIZE);
+        let mut e_ori = decode(ori_index, 2, 11);
+        e_ori.push((ori_index.count_ones() % 2) as usize); // Ensure orientation parity is even
+        (corners, Edges::new(e_prm, e_ori.try_into().unwrap()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::facelets::*;
 
     #[test]
     fn test_gen_twist() {
@@ -47,4 +112,33 @@ mod tests {
             assert!(H0_TWISTS.contains(&twist));
         }
     }
+
+    #[test]
+    fn test_wca_scramble_gen() {
+        let twists = WcaScrambleGen::new(45).gen_twists(10_000);
+        assert_eq!(twists.len(), 10_000);
+        for pair in twists.windows(2) {
+            assert_ne!(pair[0] as usize / 3, pair[1] as usize / 3);
+        }
+        for triple in twists.windows(3) {
+            assert!(triple.iter().any(|&t| t as usize / 6 != triple[0] as usize / 6));
+        }
+        // Both faces of an axis occur in a row, e.g. "R L".
+        assert!(twists.windows(2).any(|pair| pair[0] as usize / 6 == pair[1] as usize / 6));
+
+        assert_eq!(WcaScrambleGen::new(46).gen_twists(25), WcaScrambleGen::new(46).gen_twists(25));
+        #[cfg(feature = "std")]
+        assert_eq!(WcaScrambleGen::from_entropy().gen_twists(25).len(), 25);
+    }
+
+    #[test]
+    fn test_gen_cubies() {
+        let mut rng = RandomStateGen::new(44);
+        for _ in 0..1_000 {
+            let (corners, edges) = rng.gen_cubies();
+            // Only reachable states can be converted back from their stickers.
+            let facelets = Facelets::from_cubies(&corners, &edges);
+            assert_eq!(facelets.to_cubies(), Ok((corners, edges)));
+        }
+    }
 }
@@ -1,4 +1,7 @@
 use crate::twist::*;
+use alloc::{string::String, vec::Vec};
+
+const FACE_NAMES: [char; 6] = ['L', 'R', 'U', 'D', 'F', 'B'];
 
 pub struct TwistBitsIter {
     bits: u32,
@@ -27,6 +30,21 @@ impl TwistSet {
     pub const EMPTY: Self = Self{ bits: 0 };
     pub const FULL: Self = Self::from_twists(&ALL_TWISTS);
     pub const H0: Self = Self::from_twists(&H0_TWISTS);
+    /// ⟨R,U⟩
+    pub const TWO_GEN: Self = Self::from_faces(&[1, 2]);
+    /// ⟨R,U,F⟩
+    pub const THREE_GEN: Self = Self::from_faces(&[1, 2, 4]);
+    /// The half turns, which generate the squares group.
+    pub const HALF_TURNS: Self = Self::from_twists(&[Twist::L2, Twist::R2, Twist::U2, Twist::D2, Twist::F2, Twist::B2]);
+
+    /// The named sets that `from_str` accepts, e.g. in config files.
+    pub const NAMED: [(&'static str, Self); 5] = [
+        ("full", Self::FULL),
+        ("h0", Self::H0),
+        ("two-gen", Self::TWO_GEN),
+        ("three-gen", Self::THREE_GEN),
+        ("half-turns", Self::HALF_TURNS),
+    ];
 
     pub const fn new(bits: u32) -> Self {
         Self { bits }
@@ -42,6 +60,17 @@ impl TwistSet {
         ret
     }
 
+    /// All twists of the faces, numbered like the twists: L, R, U, D, F, B.
+    pub const fn from_faces(faces: &[usize]) -> Self {
+        let mut ret = Self::EMPTY;
+        let mut i = 0;
+        while i < faces.len() {
+            ret.bits |= 0b111 << (faces[i] * 3);
+            i += 1;
+        }
+        ret
+    }
+
     pub fn bits(&self) -> u32 {
         self.bits
     }
@@ -50,14 +79,6 @@ impl TwistSet {
         self.bits |= 1 << (t as u32);
     }
 
-    pub fn remove(&mut self, t: TwistSet) {
-        self.bits &= !t.bits;
-    }
-
-    pub fn keep_only(&mut self, t: TwistSet) {
-        self.bits &= t.bits;
-    }
-
     pub fn contains(&self, t: Twist) -> bool {
         self.bits & (1 << t as u32) != 0
     }
@@ -75,17 +96,123 @@ impl TwistSet {
     }
 }
 
-pub fn unique_twists_after(twist: Twist) -> TwistSet {
-    match twist {
-        Twist::L1 | Twist::L2 | Twist::L3 => TwistSet::new(0b111_111_111_111_111_000),
-        Twist::R1 | Twist::R2 | Twist::R3 => TwistSet::new(0b111_111_111_111_000_000),
-        Twist::U1 | Twist::U2 | Twist::U3 => TwistSet::new(0b111_111_111_000_111_111),
-        Twist::D1 | Twist::D2 | Twist::D3 => TwistSet::new(0b111_111_000_000_111_111),
-        Twist::F1 | Twist::F2 | Twist::F3 => TwistSet::new(0b111_000_111_111_111_111),
-        Twist::B1 | Twist::B2 | Twist::B3 => TwistSet::new(0b000_000_111_111_111_111),
+impl core::ops::BitOr for TwistSet {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self { bits: self.bits | rhs.bits }
+    }
+}
+
+impl core::ops::BitAnd for TwistSet {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self { bits: self.bits & rhs.bits }
+    }
+}
+
+impl core::ops::Sub for TwistSet {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self { bits: self.bits & !rhs.bits }
+    }
+}
+
+/// The complement within the 18 twists.
+impl core::ops::Not for TwistSet {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self::FULL - self
     }
 }
 
+impl FromIterator<Twist> for TwistSet {
+    fn from_iter<I: IntoIterator<Item = Twist>>(twists: I) -> Self {
+        let mut ret = Self::EMPTY;
+        for twist in twists {
+            ret.add(twist);
+        }
+        ret
+    }
+}
+
+impl IntoIterator for TwistSet {
+    type Item = Twist;
+    type IntoIter = TwistBitsIter;
+
+    fn into_iter(self) -> TwistBitsIter {
+        self.iter()
+    }
+}
+
+impl core::str::FromStr for TwistSet {
+    type Err = String;
+
+    /// Parses a name of `NAMED`, twists like "R U2 F'", or generators like "⟨R,U⟩" or "<R, U>",
+    /// where each generator adds all twists of its face, since they are in the generated group.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(&(_, set)) = Self::NAMED.iter().find(|(name, _)| *name == s) {
+            return Ok(set);
+        }
+        let generators = s
+            .strip_prefix('⟨')
+            .and_then(|s| s.strip_suffix('⟩'))
+            .or_else(|| s.strip_prefix('<').and_then(|s| s.strip_suffix('>')));
+        let Some(generators) = generators else {
+            return Ok(Self::from_twists(&parse_twist_sequence(s)?));
+        };
+        let faces = generators
+            .split(',')
+            .map(str::trim)
+            .filter(|generator| !generator.is_empty())
+            .map(|generator| {
+                let twist: Twist = generator.parse()?;
+                Ok(twist as usize / 3)
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Self::from_faces(&faces))
+    }
+}
+
+impl core::fmt::Display for TwistSet {
+    /// Generators like "⟨R,U⟩" if the set has all or none of the twists of each face, twists like "R U2 F'" otherwise.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let faces: Vec<usize> = (0..6).filter(|&face| self.bits >> (face * 3) & 0b111 == 0b111).collect();
+        if Self::from_faces(&faces) == *self {
+            let generators: Vec<String> = faces.iter().map(|&face| String::from(FACE_NAMES[face])).collect();
+            write!(f, "⟨{}⟩", generators.join(","))
+        } else {
+            write!(f, "{}", to_wca(&self.iter().collect::<Vec<_>>()))
+        }
+    }
+}
+
+// The twists that may follow a twist of each face, by `is_canonical_after`.
+const UNIQUE_TWISTS_AFTER: [TwistSet; 6] = {
+    let mut sets = [TwistSet::EMPTY; 6];
+    let mut face = 0;
+    while face < 6 {
+        let mut i = 0;
+        while i < 18 {
+            if is_canonical_after(ALL_TWISTS[face * 3], ALL_TWISTS[i]) {
+                sets[face].bits |= 1 << i;
+            }
+            i += 1;
+        }
+        face += 1;
+    }
+    sets
+};
+
+/// The twists that may follow the twist without redundancy, see `is_canonical_after`.
+pub fn unique_twists_after(twist: Twist) -> TwistSet {
+    UNIQUE_TWISTS_AFTER[twist as usize / 3]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -117,4 +244,69 @@ mod tests {
         let set = TwistSet::from_twists(&[Twist::D1, Twist::B2]);
         assert_eq!(set.iter().collect::<Vec<_>>(), vec![Twist::D1, Twist::B2]);
     }
+
+    #[test]
+    fn test_unique_twists_after() {
+        assert_eq!(unique_twists_after(Twist::L2), TwistSet::new(0b111_111_111_111_111_000));
+        assert_eq!(unique_twists_after(Twist::R1), TwistSet::new(0b111_111_111_111_000_000));
+        assert_eq!(unique_twists_after(Twist::D3), TwistSet::new(0b111_111_000_000_111_111));
+        assert_eq!(unique_twists_after(Twist::B1), TwistSet::new(0b000_000_111_111_111_111));
+
+        // Since only L may precede R, and vice versa for the other axes, no three twists of an axis follow each other,
+        // e.g. "L R L", which is "L2 R".
+        let mut sequences: Vec<Vec<Twist>> = ALL_TWISTS.iter().map(|&t| vec![t]).collect();
+        let mut counts = vec![sequences.len()];
+        for _ in 0..3 {
+            sequences = sequences
+                .iter()
+                .flat_map(|s| unique_twists_after(*s.last().unwrap()).into_iter().map(move |t| [s.as_slice(), &[t]].concat()))
+                .collect();
+            counts.push(sequences.len());
+        }
+        assert!(sequences.iter().all(|s| s.windows(3).all(|w| !(same_axis(w[0], w[1]) && same_axis(w[1], w[2])))));
+        assert_eq!(sequences, canonical_sequences(4).collect::<Vec<_>>());
+        // Up to 3 twists, each sequence reaches another cube, see https://oeis.org/A080601.
+        // Of 4 twists, 43'239 cubes are reached by 43'254 sequences.
+        assert_eq!(counts, [18, 243, 3240, 43254]);
+    }
+
+    #[test]
+    fn test_operators() {
+        let ru = TwistSet::TWO_GEN;
+        let half_turns = TwistSet::HALF_TURNS;
+        assert_eq!(ru | half_turns, TwistSet::from_twists(&[Twist::L2, Twist::D2, Twist::F2, Twist::B2]) | ru);
+        assert_eq!((ru | half_turns).count(), 10);
+        assert_eq!(ru & half_turns, TwistSet::from_twists(&[Twist::R2, Twist::U2]));
+        assert_eq!(ru - half_turns, TwistSet::from_twists(&[Twist::R1, Twist::R3, Twist::U1, Twist::U3]));
+        assert_eq!(!TwistSet::EMPTY, TwistSet::FULL);
+        assert_eq!(!!ru, ru);
+        assert_eq!((!ru).count(), 12);
+
+        let set: TwistSet = [Twist::D1, Twist::B2, Twist::D1].into_iter().collect();
+        assert_eq!(set, TwistSet::from_twists(&[Twist::D1, Twist::B2]));
+        assert_eq!(set.into_iter().collect::<TwistSet>(), set);
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("R U2 F'".parse(), Ok(TwistSet::from_twists(&[Twist::R1, Twist::U2, Twist::F3])));
+        assert_eq!("⟨R,U⟩".parse(), Ok(TwistSet::TWO_GEN));
+        assert_eq!("<R, U', F2>".parse(), Ok(TwistSet::THREE_GEN));
+        assert_eq!("half-turns".parse(), Ok(TwistSet::HALF_TURNS));
+        assert_eq!("".parse(), Ok(TwistSet::EMPTY));
+        assert!("⟨R,X⟩".parse::<TwistSet>().is_err());
+        assert!("R X".parse::<TwistSet>().is_err());
+        assert_eq!(TwistSet::HALF_TURNS.count(), 6);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(TwistSet::TWO_GEN.to_string(), "⟨R,U⟩");
+        assert_eq!(TwistSet::FULL.to_string(), "⟨L,R,U,D,F,B⟩");
+        assert_eq!(TwistSet::EMPTY.to_string(), "⟨⟩");
+        assert_eq!(TwistSet::H0.to_string(), "L2 R2 U U2 U' D D2 D' F2 B2");
+        for (_, set) in TwistSet::NAMED {
+            assert_eq!(set.to_string().parse(), Ok(set));
+        }
+    }
 }
@@ -0,0 +1,162 @@
+use crate::solver::Solver;
+use crate::*;
+use rand::{rngs::StdRng, RngExt, SeedableRng};
+
+/// Generates cubes at a known distance from the solved cube.
+///
+/// Walks away from the solved cube such that a lower bound of the distance increases with every twist.
+/// A walk of d twists then ends in a cube whose lower bound is d and that is solved by d twists, so its distance is exactly d.
+/// This only reaches distances up to the largest lower bound, 11 for the corners table and 12 for the coset table.
+pub struct DistanceScrambleGen<'a> {
+    rng: StdRng,
+    lower_bound: Box<dyn Fn(&Cube) -> u8 + 'a>,
+    max_distance: u8,
+}
+
+impl<'a> DistanceScrambleGen<'a> {
+    /// The number of walks that `gen_scramble` tries, since a lower bound with local maxima can end every walk in a dead end.
+    pub const MAX_WALKS: usize = 1000;
+
+    /// Uses the corners and coset tables of the two-phase solver as lower bounds.
+    pub fn new(seed: u64, tables: &'a TableSet) -> Self {
+        let max_distance = tables.corners.max_distance().max(tables.coset.max_distance());
+        Self::with_lower_bound(seed, max_distance, |cube: &Cube| {
+            tables.corners.distance(cube.corner_index()).max(tables.coset.distance(cube.coset_index()))
+        })
+    }
+
+    /// Uses a lower bound of the distance that changes by at most one per twist and is at most `max_distance`.
+    pub fn with_lower_bound(seed: u64, max_distance: u8, lower_bound: impl Fn(&Cube) -> u8 + 'a) -> Self {
+        Self { rng: StdRng::seed_from_u64(seed), lower_bound: Box::new(lower_bound), max_distance }
+    }
+
+    /// The largest distance the generator can produce.
+    pub fn max_distance(&self) -> u8 {
+        self.max_distance
+    }
+
+    /// A scramble of `distance` twists whose cube can't be solved in fewer twists, if `distance` is reachable
+    /// and one of `MAX_WALKS` walks gets there.
+    pub fn gen_scramble(&mut self, distance: u8) -> Option<Alg> {
+        if distance > self.max_distance {
+            return None;
+        }
+        'walk: for _ in 0..Self::MAX_WALKS {
+            let mut cube = Cube::solved();
+            let mut twists = Vec::with_capacity(distance as usize);
+            for d in 1..=distance {
+                let candidates: Vec<Twist> = ALL_TWISTS
+                    .into_iter()
+                    .filter(|&t| (self.lower_bound)(&cube.twisted(t)) == d)
+                    .collect();
+                if candidates.is_empty() {
+                    continue 'walk; // Dead end, the lower bound has a local maximum here.
+                }
+                let twist = candidates[self.rng.random_range(0..candidates.len())];
+                cube = cube.twisted(twist);
+                twists.push(twist);
+            }
+            return Some(Alg::new(twists));
+        }
+        None
+    }
+
+    /// A cube at exactly the given distance, see `gen_scramble`.
+    pub fn gen_cube(&mut self, distance: u8) -> Option<Cube> {
+        self.gen_scramble(distance).map(|alg| Cube::solved().twisted_by(&alg))
+    }
+
+    /// A uniformly random cube among those whose lower bound is at least `distance`.
+    /// Rejects random cubes until one is found, so this gets slow for distances with few cubes.
+    pub fn gen_cube_at_least(&mut self, distance: u8) -> Option<Cube> {
+        if distance > self.max_distance {
+            return None;
+        }
+        let mut states = RandomStateGen::new(self.rng.random());
+        loop {
+            let (corners, edges) = states.gen_cubies();
+            let cube = Cube::from_cubies(&corners, &edges);
+            if (self.lower_bound)(&cube) >= distance {
+                return Some(cube);
+            }
+        }
+    }
+}
+
+/// A scramble of a uniformly random cube state, which is not solvable in less than 2 twists, as WCA regulations require.
+pub fn random_state_scramble(solver: &impl Solver, states: &mut RandomStateGen) -> Result<Alg, String> {
+    loop {
+        let (corners, edges) = states.gen_cubies();
+        let cube = Cube::from_cubies(&corners, &edges);
+        if cube == Cube::solved() || ALL_TWISTS.iter().any(|&twist| cube.twisted(twist) == Cube::solved()) {
+            continue;
+        }
+        return Ok(solver.solve(cube, &SolveOptions::default())?.alg.inverse());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    /// The exact distances of the corner orientations.
+    fn corner_orientation_table() -> DistanceTable {
+        DistanceTable::create(
+            &ALL_TWISTS,
+            Cube::solved(),
+            |c: Cube| c.corner_index() % Corners::ORI_SIZE,
+            |i: usize| Cube::from_corner_index(i),
+            Corners::ORI_SIZE,
+        )
+    }
+
+    /// The cubes that are solved by at most `distance` twists.
+    fn ball(distance: u8) -> HashSet<Cube> {
+        let mut cubes = HashSet::from([Cube::solved()]);
+        for _ in 0..distance {
+            let next: Vec<Cube> = cubes.iter().flat_map(|c| ALL_TWISTS.map(|t| c.twisted(t))).collect();
+            cubes.extend(next);
+        }
+        cubes
+    }
+
+    #[test]
+    fn test_gen_scramble() {
+        let table = corner_orientation_table();
+        let mut scrambles = DistanceScrambleGen::with_lower_bound(42, table.max_distance(), |c: &Cube| {
+            table.distance(c.corner_index() % Corners::ORI_SIZE)
+        });
+        let closer = ball(2);
+        for distance in 0..=scrambles.max_distance() {
+            let scramble = scrambles.gen_scramble(distance).unwrap();
+            assert_eq!(scramble.len(), distance as usize);
+            let cube = Cube::solved().twisted_by(&scramble);
+            assert_eq!(table.distance(cube.corner_index() % Corners::ORI_SIZE), distance);
+            if distance == 3 {
+                assert!(!closer.contains(&cube));
+            }
+        }
+        assert_eq!(scrambles.gen_scramble(scrambles.max_distance() + 1), None);
+    }
+
+    #[test]
+    fn test_gen_scramble_dead_end() {
+        // A lower bound that never increases ends every walk after the first twist.
+        let mut scrambles = DistanceScrambleGen::with_lower_bound(42, 3, |_: &Cube| 0);
+        assert_eq!(scrambles.gen_scramble(0), Some(Alg::new(vec![])));
+        assert_eq!(scrambles.gen_scramble(1), None);
+    }
+
+    #[test]
+    fn test_gen_cube_at_least() {
+        let table = corner_orientation_table();
+        let lower_bound = |c: &Cube| table.distance(c.corner_index() % Corners::ORI_SIZE);
+        let mut scrambles = DistanceScrambleGen::with_lower_bound(43, table.max_distance(), lower_bound);
+        for _ in 0..10 {
+            let cube = scrambles.gen_cube_at_least(5).unwrap();
+            assert!(lower_bound(&cube) >= 5);
+        }
+        assert_eq!(scrambles.gen_cube_at_least(scrambles.max_distance() + 1), None);
+    }
+}
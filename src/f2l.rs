@@ -0,0 +1,164 @@
+use crate::*;
+
+/// A slot of the first two layers, named by its edge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Slot {
+    FL,
+    FR,
+    BR,
+    BL,
+}
+
+impl Slot {
+    pub const ALL: [Slot; 4] = [Slot::FL, Slot::FR, Slot::BR, Slot::BL];
+
+    /// The corner of the slot, numbered like in the README.
+    pub const fn corner(self) -> usize {
+        match self {
+            Slot::FL => 4,
+            Slot::FR => 5,
+            Slot::BR => 7,
+            Slot::BL => 6,
+        }
+    }
+
+    /// The edge of the slot, numbered like in the README.
+    pub const fn edge(self) -> usize {
+        match self {
+            Slot::FL => 8,
+            Slot::FR => 9,
+            Slot::BR => 10,
+            Slot::BL => 11,
+        }
+    }
+
+    /// The corner and the edge of the slot.
+    pub const fn mask(self) -> PieceMask {
+        PieceMask::NONE.with_corner(self.corner()).with_edge(self.edge())
+    }
+
+    pub fn state(self, cube: &Cube) -> SlotState {
+        let (corners, edges) = (cube.corners(), cube.edges());
+        if self.mask().is_solved(&corners, &edges) {
+            return SlotState::Solved;
+        }
+        let corner_location = corners.position_of(self.corner());
+        let edge_location = edges.position_of(self.edge());
+        let faces = cube.facelets();
+        let faces = faces.faces();
+        // The edge is next to the corner if all its stickers are on faces of the corner location,
+        // and they form a pair if the stickers on these faces match.
+        let paired = EDGE_FACELETS[edge_location].iter().all(|&edge_facelet| {
+            CORNER_FACELETS[corner_location]
+                .iter()
+                .find(|&&corner_facelet| corner_facelet / 9 == edge_facelet / 9)
+                .is_some_and(|&corner_facelet| faces[corner_facelet] == faces[edge_facelet])
+        });
+        if paired { SlotState::Paired } else { SlotState::Unpaired }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SlotState {
+    /// The corner and the edge are in the slot and oriented.
+    Solved,
+    /// The corner and the edge are next to each other and match, but aren't solved.
+    Paired,
+    Unpaired,
+}
+
+/// The state of every slot.
+pub fn analyze_f2l(cube: &Cube) -> [(Slot, SlotState); 4] {
+    Slot::ALL.map(|slot| (slot, slot.state(cube)))
+}
+
+/// Solves the corner-edge pairs of the first two layers, keeping the cross and the solved slots.
+///
+/// The cross and each pair have a distance table, and the search is pruned by the ones that have to be kept or solved.
+pub struct F2LSolver {
+    solver: MaskedSolver,
+}
+
+impl F2LSolver {
+    pub fn new() -> Self {
+        let mut groups = vec![PieceMask::CROSS];
+        groups.extend(Slot::ALL.map(Slot::mask));
+        Self { solver: MaskedSolver::with_groups(&groups) }
+    }
+
+    /// A shortest sequence of twists that solves the slot, keeping the cross and the already solved slots.
+    pub fn solve_pair(&self, cube: Cube, slot: Slot, max_solution_length: u8) -> Result<Vec<Twist>, String> {
+        if !PieceMask::CROSS.is_solved(&cube.corners(), &cube.edges()) {
+            return Err("The cross isn't solved".into());
+        }
+        let goal = Slot::ALL
+            .into_iter()
+            .filter(|&s| s == slot || s.state(&cube) == SlotState::Solved)
+            .fold(PieceMask::CROSS, |goal, s| goal.union(s.mask()));
+        self.solver.solve_part(cube, goal, max_solution_length)
+    }
+
+    /// Solves all slots in sequence, each time taking the slot with the shortest solution.
+    /// Returns the solved slots in order with their solutions.
+    pub fn solve_all(&self, cube: Cube, max_pair_length: u8) -> Result<Vec<(Slot, Vec<Twist>)>, String> {
+        let mut cube = cube;
+        let mut solutions = Vec::new();
+        loop {
+            let unsolved = Slot::ALL.into_iter().filter(|slot| slot.state(&cube) != SlotState::Solved);
+            let mut best: Option<(Slot, Vec<Twist>)> = None;
+            for slot in unsolved {
+                // Only shorter solutions than the best so far are of interest.
+                let max = best.as_ref().map_or(max_pair_length, |(_, twists)| (twists.len() as u8).saturating_sub(1));
+                if let Ok(twists) = self.solve_pair(cube, slot, max) {
+                    best = Some((slot, twists));
+                }
+            }
+            match best {
+                Some((slot, twists)) => {
+                    cube = cube.twisted_by(&twists);
+                    solutions.push((slot, twists));
+                }
+                None if Slot::ALL.iter().all(|slot| slot.state(&cube) == SlotState::Solved) => return Ok(solutions),
+                None => return Err("No solution found".into()),
+            }
+        }
+    }
+}
+
+impl Default for F2LSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze() {
+        assert!(analyze_f2l(&Cube::solved()).iter().all(|&(_, state)| state == SlotState::Solved));
+
+        // R U R' takes the FR pair out of its slot as a pair.
+        let cube = Cube::solved().twisted_by(&[Twist::R1, Twist::U1, Twist::R3]);
+        assert_eq!(analyze_f2l(&cube).map(|(_, state)| state), [SlotState::Solved, SlotState::Paired, SlotState::Solved, SlotState::Solved]);
+
+        // L then moves the FR corner away from its edge, and the FL and BL pairs as pairs.
+        let cube = cube.twisted(Twist::L1);
+        assert_eq!(analyze_f2l(&cube).map(|(_, state)| state), [SlotState::Paired, SlotState::Unpaired, SlotState::Solved, SlotState::Paired]);
+    }
+
+    #[test]
+    fn test_solve_all() {
+        let solver = F2LSolver::new();
+        let cube = Cube::solved().twisted_by(&[Twist::R1, Twist::U1, Twist::R3, Twist::U3, Twist::L3, Twist::U2, Twist::L1]);
+        assert_eq!(solver.solve_pair(cube, Slot::FL, 10), Ok(vec![Twist::L3, Twist::U2, Twist::L1]));
+        assert_eq!(solver.solve_pair(cube, Slot::FR, 2), Err("No solution found".into()));
+
+        let solutions = solver.solve_all(cube, 10).unwrap();
+        let solved = solutions.iter().fold(cube, |cube, (_, twists)| cube.twisted_by(twists));
+        assert!(PieceMask::CROSS.is_solved(&solved.corners(), &solved.edges()));
+        assert!(analyze_f2l(&solved).iter().all(|&(_, state)| state == SlotState::Solved));
+        assert!(solutions.iter().map(|(_, twists)| twists.len()).sum::<usize>() <= 7);
+    }
+}
@@ -0,0 +1,338 @@
+use crate::*;
+use std::sync::LazyLock;
+
+// The steps of fewest-moves solving: edge orientation (EO), domino reduction (DR) and half turn reduction (HTR).
+//
+// Axis::X is the L/R axis, Axis::Y the F/B axis and Axis::Z the U/D axis.
+// Natively, the edges are oriented relative to L/R and the domino reduction is on U/D.
+// Other axes are rotated onto these, and their solutions are rotated back.
+
+/// The rotation that takes the edge orientation of the axis onto L/R.
+fn eo_rotation(axis: Axis) -> Option<Axis> {
+    match axis {
+        Axis::X => None,
+        Axis::Y => Some(Axis::Z),
+        Axis::Z => Some(Axis::Y),
+    }
+}
+
+/// The rotation that takes the domino reduction of the axis onto U/D.
+fn dr_rotation(axis: Axis) -> Option<Axis> {
+    match axis {
+        Axis::X => Some(Axis::Y),
+        Axis::Y => Some(Axis::X),
+        Axis::Z => None,
+    }
+}
+
+fn rotated(cube: &Cube, rotation: Option<Axis>) -> Cube {
+    rotation.map_or(*cube, |rot| cube.conjugated_by(rot))
+}
+
+fn rotated_back(twists: &[Twist], rotation: Option<Axis>) -> Vec<Twist> {
+    rotation.map_or(twists.to_vec(), |rot| conjugate_by_inv(twists, rot))
+}
+
+const Z_LOC_SIZE: usize = ESliceLoc::SIZE;
+
+fn from_coset_coordinates(c_ori: usize, e_ori: usize, z_loc: usize) -> Cube {
+    Cube::from_coset_index(coset_index(COri::new(c_ori), EOri::new(e_ori), ESliceLoc::new(z_loc)))
+}
+
+/// Corner permutations reachable with half twists.
+static HALF_TWIST_CORNERS: LazyLock<Vec<bool>> = LazyLock::new(|| {
+    let half_twists: Vec<Twist> = ALL_TWISTS.into_iter().filter(|&t| is_half_twist(t)).collect();
+    let mut reachable = vec![false; Corners::PRM_SIZE];
+    let solved = Cube::solved().c_prm();
+    reachable[solved.index()] = true;
+    let mut frontier = vec![solved];
+    while let Some(c_prm) = frontier.pop() {
+        for &twist in &half_twists {
+            let next = TWISTER.twisted_c_prm(c_prm, twist);
+            if !reachable[next.index()] {
+                reachable[next.index()] = true;
+                frontier.push(next);
+            }
+        }
+    }
+    reachable
+});
+
+fn is_half_twist(twist: Twist) -> bool {
+    twist as usize % 3 == 1
+}
+
+impl Cube {
+    /// Whether the edges are oriented relative to the axis, i.e. solvable without quarter twists of its faces.
+    pub fn is_eo(&self, axis: Axis) -> bool {
+        rotated(self, eo_rotation(axis)).e_ori().index() == 0
+    }
+
+    /// Whether the cube is in domino reduction on the axis, i.e. solvable with quarter twists only of its faces.
+    pub fn is_dr(&self, axis: Axis) -> bool {
+        rotated(self, dr_rotation(axis)).in_subset()
+    }
+
+    /// Whether the cube is solvable with half twists only.
+    pub fn is_htr(&self) -> bool {
+        // Half twists keep the edges in their slices and the corners in 96 permutations,
+        // and all even edge permutations within the slices occur with them.
+        let edges = self.edges();
+        self.is_dr(Axis::Z)
+            && edges.loc_prm(Axis::X).loc() == Edges::solved().loc_prm(Axis::X).loc()
+            && HALF_TWIST_CORNERS[self.c_prm().index()]
+    }
+}
+
+/// Solves the edge orientation relative to an axis, optimally.
+pub struct EoSolver {
+    table: DistanceTable,
+}
+
+impl EoSolver {
+    pub fn new() -> Self {
+        let table = DistanceTable::create(
+            &ALL_TWISTS,
+            Cube::solved(),
+            |cube| cube.e_ori().index(),
+            |e_ori| from_coset_coordinates(0, e_ori, 0),
+            Edges::ORI_SIZE,
+        );
+        Self { table }
+    }
+
+    pub fn solve(&self, cube: Cube, axis: Axis) -> Vec<Twist> {
+        let rotation = eo_rotation(axis);
+        let mut cube = rotated(&cube, rotation);
+        let mut twists = Vec::new();
+        let mut distance = self.table.distance(cube.e_ori().index());
+        while distance > 0 {
+            let twist = ALL_TWISTS
+                .into_iter()
+                .find(|&t| self.table.distance(cube.twisted(t).e_ori().index()) < distance)
+                .unwrap();
+            cube = cube.twisted(twist);
+            twists.push(twist);
+            distance -= 1;
+        }
+        rotated_back(&twists, rotation)
+    }
+}
+
+impl Default for EoSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Solves into domino reduction on an axis, optimally.
+///
+/// An iterative deepening search is pruned by tables of the corner orientation and of the edge orientation,
+/// each together with the location of the slice edges.
+pub struct DrSolver {
+    corners: DistanceTable,
+    edges: DistanceTable,
+}
+
+impl DrSolver {
+    pub fn new() -> Self {
+        let corners = DistanceTable::create(
+            &ALL_TWISTS,
+            Cube::solved(),
+            |cube| cube.c_ori().index() * Z_LOC_SIZE + cube.e_slice_loc().index(),
+            |i| from_coset_coordinates(i / Z_LOC_SIZE, 0, i % Z_LOC_SIZE),
+            Corners::ORI_SIZE * Z_LOC_SIZE,
+        );
+        let edges = DistanceTable::create(
+            &ALL_TWISTS,
+            Cube::solved(),
+            |cube| cube.e_ori().index() * Z_LOC_SIZE + cube.e_slice_loc().index(),
+            |i| from_coset_coordinates(0, i / Z_LOC_SIZE, i % Z_LOC_SIZE),
+            Edges::ORI_SIZE * Z_LOC_SIZE,
+        );
+        Self { corners, edges }
+    }
+
+    fn lower_bound(&self, cube: &Cube) -> u8 {
+        let z_loc = cube.e_slice_loc().index();
+        let corners = self.corners.distance(cube.c_ori().index() * Z_LOC_SIZE + z_loc);
+        corners.max(self.edges.distance(cube.e_ori().index() * Z_LOC_SIZE + z_loc))
+    }
+
+    pub fn solve(&self, cube: Cube, axis: Axis, max_solution_length: u8) -> Result<Vec<Twist>, String> {
+        let rotation = dr_rotation(axis);
+        let cube = rotated(&cube, rotation);
+        let mut twists = Vec::new();
+        for depth in self.lower_bound(&cube)..=max_solution_length {
+            if self.search(cube, depth, &mut twists) {
+                return Ok(rotated_back(&twists, rotation));
+            }
+        }
+        Err("No solution found".into())
+    }
+
+    fn search(&self, cube: Cube, depth: u8, twists: &mut Vec<Twist>) -> bool {
+        if depth == 0 {
+            return cube.coset_index() == Cube::solved().coset_index();
+        }
+        if self.lower_bound(&cube) > depth {
+            return false;
+        }
+        let candidates = twists.last().map_or(TwistSet::FULL, |&last| unique_twists_after(last));
+        for twist in candidates.iter() {
+            twists.push(twist);
+            if self.search(cube.twisted(twist), depth - 1, twists) {
+                return true;
+            }
+            twists.pop();
+        }
+        false
+    }
+}
+
+impl Default for DrSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Solves from domino reduction into half turn reduction, optimally and keeping the domino reduction.
+///
+/// In domino reduction, the corner permutation and the location of the M slice edges,
+/// which are among the 8 edge locations of U and D, determine the distance. A table holds all of them.
+pub struct HtrSolver {
+    table: Vec<u8>,
+    /// Indices of the M slice locations that are within U and D.
+    ud_locs: Vec<usize>,
+}
+
+impl HtrSolver {
+    const UD_LOC_SIZE: usize = binomial(8, 4);
+
+    pub fn new() -> Self {
+        let mut ud_locs = vec![usize::MAX; Z_LOC_SIZE];
+        for (i, loc) in (0..Z_LOC_SIZE).filter(|&loc| nth_combination(12, 4, loc).iter().all(|&l| l < 8)).enumerate() {
+            ud_locs[loc] = i;
+        }
+        let index = |c_prm: CPrm, x_loc: usize| c_prm.index() * Self::UD_LOC_SIZE + ud_locs[x_loc];
+
+        let mut table = vec![u8::MAX; Corners::PRM_SIZE * Self::UD_LOC_SIZE];
+        let solved_x_loc = Edges::solved().loc_prm(Axis::X).loc();
+        let mut frontier: Vec<(CPrm, usize)> = (0..CPrm::SIZE)
+            .filter(|&c_prm| HALF_TWIST_CORNERS[c_prm])
+            .map(|c_prm| (CPrm::new(c_prm), solved_x_loc))
+            .collect();
+        for &(c_prm, x_loc) in &frontier {
+            table[index(c_prm, x_loc)] = 0;
+        }
+        let mut distance = 0;
+        while !frontier.is_empty() {
+            distance += 1;
+            let mut next = Vec::new();
+            for (c_prm, x_loc) in frontier {
+                for twist in H0_TWISTS {
+                    let c_prm = TWISTER.twisted_c_prm(c_prm, twist);
+                    let x_loc = TWISTER.twisted_e_loc_prm(LocPrm::new(x_loc, 0), twist).loc();
+                    if table[index(c_prm, x_loc)] == u8::MAX {
+                        table[index(c_prm, x_loc)] = distance;
+                        next.push((c_prm, x_loc));
+                    }
+                }
+            }
+            frontier = next;
+        }
+        Self { table, ud_locs }
+    }
+
+    fn distance(&self, c_prm: CPrm, x_loc: usize) -> u8 {
+        self.table[c_prm.index() * Self::UD_LOC_SIZE + self.ud_locs[x_loc]]
+    }
+
+    /// A shortest sequence of twists that keep the domino reduction on the axis and reach half turn reduction.
+    pub fn solve(&self, cube: Cube, axis: Axis) -> Result<Vec<Twist>, String> {
+        if !cube.is_dr(axis) {
+            return Err("The cube isn't in domino reduction".into());
+        }
+        let rotation = dr_rotation(axis);
+        let cube = rotated(&cube, rotation);
+        let mut c_prm = cube.c_prm();
+        let mut x_loc = cube.edges().loc_prm(Axis::X).loc();
+        let mut twists = Vec::new();
+        let mut distance = self.distance(c_prm, x_loc);
+        while distance > 0 {
+            let twist = H0_TWISTS
+                .into_iter()
+                .find(|&t| {
+                    self.distance(TWISTER.twisted_c_prm(c_prm, t), TWISTER.twisted_e_loc_prm(LocPrm::new(x_loc, 0), t).loc()) < distance
+                })
+                .unwrap();
+            c_prm = TWISTER.twisted_c_prm(c_prm, twist);
+            x_loc = TWISTER.twisted_e_loc_prm(LocPrm::new(x_loc, 0), twist).loc();
+            twists.push(twist);
+            distance -= 1;
+        }
+        Ok(rotated_back(&twists, rotation))
+    }
+}
+
+impl Default for HtrSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AXES: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+
+    #[test]
+    fn test_predicates() {
+        let solved = Cube::solved();
+        assert!(AXES.iter().all(|&axis| solved.is_eo(axis) && solved.is_dr(axis)));
+        assert!(solved.is_htr());
+
+        let f = solved.twisted(Twist::F1);
+        assert!(!f.is_eo(Axis::Y) && f.is_eo(Axis::X) && f.is_eo(Axis::Z));
+        assert!(f.is_dr(Axis::Y) && !f.is_dr(Axis::X) && !f.is_dr(Axis::Z));
+        assert!(!f.is_htr());
+
+        let half_twists = solved.twisted_by(&[Twist::R2, Twist::U2, Twist::F2, Twist::L2, Twist::D2]);
+        assert!(half_twists.is_htr());
+        assert!(AXES.iter().all(|&axis| half_twists.is_dr(axis)));
+
+        // R U R' U' keeps the domino reduction on U/D only with half twists of R.
+        let sexy = solved.twisted_by(&[Twist::R1, Twist::U1, Twist::R3, Twist::U3]);
+        assert!(sexy.is_eo(Axis::Y) && !sexy.is_dr(Axis::Z));
+        let in_dr = solved.twisted_by(&[Twist::R2, Twist::U1, Twist::R2, Twist::U3]);
+        assert!(in_dr.is_dr(Axis::Z) && !in_dr.is_htr());
+    }
+
+    #[test]
+    fn test_solvers() {
+        let scramble = parse_twist_sequence("R' U' F D2 L2 F R2 U2 R2 B D2 L B2 U' L' F' B2 R' U F2").unwrap();
+        let cube = Cube::solved().twisted_by(&scramble);
+
+        let eo = EoSolver::new();
+        for axis in AXES {
+            let solution = eo.solve(cube, axis);
+            assert!(cube.twisted_by(&solution).is_eo(axis));
+            assert!(solution.len() <= 7);
+        }
+
+        let dr = DrSolver::new();
+        let htr = HtrSolver::new();
+        for axis in AXES {
+            let to_dr = dr.solve(cube, axis, 12).unwrap();
+            let in_dr = cube.twisted_by(&to_dr);
+            assert!(in_dr.is_dr(axis));
+            assert!(htr.solve(cube, axis).is_err());
+
+            let to_htr = htr.solve(in_dr, axis).unwrap();
+            let in_htr = in_dr.twisted_by(&to_htr);
+            assert!(in_htr.is_htr());
+            assert!((0..=to_htr.len()).all(|n| in_dr.twisted_by(&to_htr[..n]).is_dr(axis)));
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use clap::Parser;
+use rubikscube::*;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status, Streaming};
+
+// The service code generated by `build.rs`.
+include!(concat!(env!("OUT_DIR"), "/rubikscube.RubiksCube.rs"));
+use rubiks_cube_server::{RubiksCube, RubiksCubeServer};
+
+/// Serves the solver over gRPC, with the service of `proto/rubikscube.proto`.
+///
+/// The tables are loaded once and shared by all workers.
+#[derive(Parser)]
+struct Cli {
+    /// Address to listen on.
+    #[arg(long, default_value = "127.0.0.1:50051")]
+    address: std::net::SocketAddr,
+    /// Number of cubes solved at the same time [default: number of CPUs]
+    #[arg(long)]
+    threads: Option<usize>,
+    /// Give up on a cube after this many seconds.
+    #[arg(long, default_value_t = 10.0)]
+    max_time: f64,
+    /// Give up on a cube after this many phase-1 nodes [default: no limit]
+    #[arg(long)]
+    max_nodes: Option<usize>,
+    /// Config file with the table paths.
+    #[arg(long, default_value = "config.txt")]
+    config: String,
+}
+
+// The messages of `proto/rubikscube.proto`.
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+pub enum CubeSpec {
+    /// In WCA notation, with rotations, wide and slice moves.
+    #[prost(string, tag = "1")]
+    Scramble(String),
+    /// 54 facelets in URFDLB order.
+    #[prost(string, tag = "2")]
+    Facelets(String),
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SolveRequest {
+    #[prost(oneof = "CubeSpec", tags = "1, 2")]
+    pub cube: Option<CubeSpec>,
+    /// 0 means 20.
+    #[prost(uint32, tag = "3")]
+    pub max_length: u32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SolveReply {
+    #[prost(string, tag = "1")]
+    pub solution: String,
+    #[prost(uint32, tag = "2")]
+    pub length: u32,
+    #[prost(double, tag = "3")]
+    pub seconds: f64,
+    /// Set by SolveBatch instead of the solution if the cube could not be solved.
+    #[prost(string, tag = "4")]
+    pub error: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ScrambleRequest {
+    #[prost(uint32, tag = "1")]
+    pub count: u32,
+    /// A random seed if not set.
+    #[prost(uint64, optional, tag = "2")]
+    pub seed: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ScrambleReply {
+    #[prost(uint64, tag = "1")]
+    pub seed: u64,
+    #[prost(string, repeated, tag = "2")]
+    pub scrambles: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ValidateRequest {
+    #[prost(oneof = "CubeSpec", tags = "1, 2")]
+    pub cube: Option<CubeSpec>,
+    #[prost(string, tag = "3")]
+    pub solution: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ValidateReply {
+    #[prost(bool, tag = "1")]
+    pub valid: bool,
+    /// Why the request is invalid, if it is.
+    #[prost(string, tag = "2")]
+    pub error: String,
+}
+
+/// The most scrambles of one request, since each one takes a solve.
+const MAX_SCRAMBLES: u32 = 1000;
+
+fn parse_cube(cube: &Option<CubeSpec>) -> Result<Cube, String> {
+    match cube {
+        Some(CubeSpec::Scramble(scramble)) => Ok(Cube::solved().twisted_by(&normalize_moves(scramble)?.0)),
+        Some(CubeSpec::Facelets(facelets)) => facelets
+            .parse()
+            .and_then(|facelets| Cube::from_facelets(&facelets))
+            .map_err(|err| err.to_string()),
+        None => Err("Expected either a scramble or facelets".to_string()),
+    }
+}
+
+/// Blocks until a worker of the pool is idle, so it must run on a blocking thread.
+fn solve_blocking(pool: &SolverPool, request: &SolveRequest) -> Result<SolveReply, Status> {
+    let cube = parse_cube(&request.cube).map_err(Status::invalid_argument)?;
+    let max_length = match request.max_length {
+        0 => 20,
+        length => u8::try_from(length).map_err(|_| Status::invalid_argument("The maximum length is too large"))?,
+    };
+    let start = Instant::now();
+    let solution = pool.solve(cube, max_length).map_err(Status::failed_precondition)?;
+    Ok(SolveReply {
+        solution: solution.alg.to_string(),
+        length: solution.alg.twists().len() as u32,
+        seconds: start.elapsed().as_secs_f64(),
+        error: String::new(),
+    })
+}
+
+struct Service {
+    pool: &'static SolverPool<'static>,
+}
+
+#[tonic::async_trait]
+impl RubiksCube for Service {
+    async fn solve(&self, request: Request<SolveRequest>) -> Result<Response<SolveReply>, Status> {
+        let pool = self.pool;
+        let request = request.into_inner();
+        let reply = tokio::task::spawn_blocking(move || solve_blocking(pool, &request))
+            .await
+            .map_err(|err| Status::internal(err.to_string()))??;
+        Ok(Response::new(reply))
+    }
+
+    async fn scramble(&self, request: Request<ScrambleRequest>) -> Result<Response<ScrambleReply>, Status> {
+        let ScrambleRequest { count, seed } = request.into_inner();
+        if count > MAX_SCRAMBLES {
+            return Err(Status::invalid_argument(format!("At most {MAX_SCRAMBLES} scrambles per request")));
+        }
+        let pool = self.pool;
+        let seed = seed.unwrap_or_else(rand::random);
+        let scrambles = tokio::task::spawn_blocking(move || {
+            let mut states = RandomStateGen::new(seed);
+            (0..count).map(|_| random_state_scramble(pool, &mut states).map(|alg| alg.to_string())).collect::<Result<Vec<_>, _>>()
+        })
+        .await
+        .map_err(|err| Status::internal(err.to_string()))?
+        .map_err(Status::failed_precondition)?;
+        Ok(Response::new(ScrambleReply { seed, scrambles }))
+    }
+
+    async fn validate(&self, request: Request<ValidateRequest>) -> Result<Response<ValidateReply>, Status> {
+        let request = request.into_inner();
+        let result = parse_cube(&request.cube).and_then(|cube| {
+            let (twists, _) = normalize_moves(&request.solution)?;
+            match cube.twisted_by(&twists) == Cube::solved() {
+                true => Ok(()),
+                false => Err("The solution does not solve the cube".to_string()),
+            }
+        });
+        let reply = match result {
+            Ok(()) => ValidateReply { valid: true, error: String::new() },
+            Err(error) => ValidateReply { valid: false, error },
+        };
+        Ok(Response::new(reply))
+    }
+
+    type SolveBatchStream = ReceiverStream<Result<SolveReply, Status>>;
+
+    /// Solves up to one cube per worker at the same time and replies in the order of the requests.
+    async fn solve_batch(&self, request: Request<Streaming<SolveRequest>>) -> Result<Response<Self::SolveBatchStream>, Status> {
+        let pool = self.pool;
+        let mut requests = request.into_inner();
+        // The bounded channels stop reading requests while the replies aren't read.
+        let (solves, mut pending) = mpsc::channel(pool.workers());
+        let (replies, stream) = mpsc::channel(pool.workers());
+        tokio::spawn(async move {
+            while let Ok(Some(request)) = requests.message().await {
+                let solve = tokio::task::spawn_blocking(move || solve_blocking(pool, &request));
+                if solves.send(solve).await.is_err() {
+                    break;
+                }
+            }
+        });
+        tokio::spawn(async move {
+            while let Some(solve) = pending.recv().await {
+                // A cube that can't be solved doesn't end the batch.
+                let reply = match solve.await {
+                    Ok(Ok(reply)) => Ok(reply),
+                    Ok(Err(status)) => Ok(SolveReply { error: status.message().to_string(), ..SolveReply::default() }),
+                    Err(err) => Err(Status::internal(err.to_string())),
+                };
+                if replies.send(reply).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(stream)))
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+
+    init_twister();
+    init_subset_twister();
+    init_subset_index();
+    let tables = TableSet::builder().config_file(&cli.config).build().unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(1);
+    });
+    // The tables are needed until the server stops.
+    let tables: &'static TableSet = Box::leak(Box::new(tables));
+
+    let threads = cli.threads.unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+    let options = SolveOptions {
+        max_nodes: cli.max_nodes,
+        max_time: Some(Duration::from_secs_f64(cli.max_time)),
+        ..SolveOptions::default()
+    };
+    let pool: &'static SolverPool = Box::leak(Box::new(SolverPool::new(TwoPhaseSolver::with_tables(tables).with_options(options), threads)));
+    println!("Listening on {} with {threads} threads", cli.address);
+
+    let result = tonic::transport::Server::builder()
+        .add_service(RubiksCubeServer::new(Service { pool }))
+        .serve(cli.address)
+        .await;
+    if let Err(err) = result {
+        eprintln!("Failed to serve on {}: {err}", cli.address);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,94 @@
+use crate::corners::*;
+use crate::edges::*;
+use crate::math::*;
+
+// The coordinates of `Cube` and `SubsetCube`, as types of their own, so they can't be mixed up.
+// All fit into a u16, like the entries of the move tables.
+
+macro_rules! coordinate {
+    ($(#[$doc:meta])* $name:ident, $size:expr) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(u16);
+
+        impl $name {
+            pub const SIZE: usize = $size;
+
+            /// Panics if the index is not below `SIZE`.
+            pub const fn new(index: usize) -> Self {
+                assert!(index < Self::SIZE, concat!(stringify!($name), " out of range"));
+                Self(index as u16)
+            }
+
+            /// The coordinate, if the index is below `SIZE`.
+            pub const fn try_new(index: usize) -> Option<Self> {
+                if index < Self::SIZE { Some(Self(index as u16)) } else { None }
+            }
+
+            /// An entry of a move table, which is in range by construction.
+            #[allow(dead_code)] // Not every coordinate has a move table.
+            #[inline(always)]
+            pub(crate) const fn from_table(index: u16) -> Self {
+                debug_assert!((index as usize) < Self::SIZE);
+                Self(index)
+            }
+
+            #[inline(always)]
+            pub const fn index(self) -> usize {
+                self.0 as usize
+            }
+        }
+
+        impl From<$name> for usize {
+            fn from(coordinate: $name) -> usize {
+                coordinate.index()
+            }
+        }
+    };
+}
+
+coordinate!(
+    /// The orientation of the corners, see `Corners::ori_index`.
+    COri, Corners::ORI_SIZE
+);
+coordinate!(
+    /// The permutation of the corners, see `Corners::prm_index`.
+    CPrm, Corners::PRM_SIZE
+);
+coordinate!(
+    /// The orientation of the edges, see `Edges::ori_index`.
+    EOri, Edges::ORI_SIZE
+);
+coordinate!(
+    /// The positions of the four E slice edges, the ones between U and D, see `LocPrm::loc`.
+    ESliceLoc, binomial(12, 4)
+);
+coordinate!(
+    /// The permutation of the E slice edges among themselves, see `LocPrm::prm`.
+    ESlicePrm, factorial(4)
+);
+coordinate!(
+    /// The permutation of the eight U and D edges of a cube in the subset, see `Edges::xy_prm_index`.
+    ENonSlicePrm, factorial(8)
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range() {
+        assert_eq!(CPrm::new(40_319).index(), 40_319);
+        assert_eq!(usize::from(ESliceLoc::new(494)), 494);
+        assert_eq!(ESlicePrm::try_new(24), None);
+        assert_eq!(EOri::try_new(2047), Some(EOri::new(2047)));
+        assert_eq!(COri::default().index(), 0);
+        assert_eq!(ENonSlicePrm::SIZE, 40_320);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_out_of_range() {
+        COri::new(Corners::ORI_SIZE);
+    }
+}
@@ -1,130 +1,351 @@
-use super::{TWISTER, SUBSET_INDEX, Twistable, SubsetCube};
+use super::{COri, CPrm, EOri, ESliceLoc, ESlicePrm, coset_coordinates, coset_index, in_subset, Twistable};
+#[cfg(feature = "std")]
+use super::{TWISTER, SUBSET_INDEX, Twister, TwistContext, MultiTwister, SubsetCube};
 use crate::{LocPrm, cubies::*};
+use alloc::{string::String, vec::Vec};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+// Rotations that bring each face up, to be followed by the rotations around the U-D axis.
+const UP: [&[Axis]; 6] = [&[], &[Axis::X], &[Axis::X, Axis::X], &[Axis::X, Axis::X, Axis::X], &[Axis::Y], &[Axis::Y, Axis::Y, Axis::Y]];
+
+/// The 24 rotations of the whole cube, each as quarter rotations about the axes, to conjugate by in order.
+pub fn rotations() -> Vec<Vec<Axis>> {
+    UP.iter()
+        .flat_map(|up| (0..4).map(move |z| [*up, &[Axis::Z; 3][..z]].concat()))
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Cube {
-    c_ori: usize, // 3^7 = 2'187 (defines coset index)
-    c_prm: usize, // 8! = 40'320 (defines subset index)
-    e_ori: usize, // 2^11 = 2'048 (defines coset index)
+    c_ori: COri, // 3^7 = 2'187 (defines coset index)
+    c_prm: CPrm, // 8! = 40'320 (defines subset index)
+    e_ori: EOri, // 2^11 = 2'048 (defines coset index)
     x_loc_prm: LocPrm, // (12 choose 4) * 4! = 11'880 (defines subset index)
     y_loc_prm: LocPrm, // (12 choose 4) * 4! = 11'880 (defines subset index)
     z_loc_prm: LocPrm, // (12 choose 4) * 4! == 11'880 (loc defines coset index, prm defines subset index)
 }
 
 impl Cube {
-    pub const CORNER_INDEX_SIZE: usize = Corners::ORI_SIZE * Corners::PRM_SIZE; // 88'179'840
+    pub const CORNER_INDEX_SIZE: usize = Corners::INDEX_SIZE; // 88'179'840
     pub const SUBSET_INDEX_SIZE: usize = Corners::PRM_SIZE * factorial(8) * factorial(4) / 2;  // 19'508'428'800
     pub const COSETS_INDEX_SIZE: usize = Corners::ORI_SIZE * Edges::ORI_SIZE * binomial(12, 4); // 2'217'093'120
+    pub const FULL_INDEX_SIZE: u128 = (Self::CORNER_INDEX_SIZE * (Edges::PRM_SIZE / 2)) as u128 * Edges::ORI_SIZE as u128; // 43'252'003'274'489'856'000
 
     pub fn solved() -> Self {
         const C: Corners = Corners::solved();
         const E: Edges = Edges::solved();
         Self {
-            c_ori: C.ori_index(),
-            c_prm: C.prm_index(),
-            e_ori: E.ori_index(),
+            c_ori: COri::new(C.ori_index()),
+            c_prm: CPrm::new(C.prm_index()),
+            e_ori: EOri::new(E.ori_index()),
             x_loc_prm: E.loc_prm(Axis::X),
             y_loc_prm: E.loc_prm(Axis::Y),
             z_loc_prm: E.loc_prm(Axis::Z),
         }
     }
 
+    pub fn c_ori(&self) -> COri {
+        self.c_ori
+    }
+
+    pub fn c_prm(&self) -> CPrm {
+        self.c_prm
+    }
+
+    pub fn e_ori(&self) -> EOri {
+        self.e_ori
+    }
+
+    pub fn e_slice_loc(&self) -> ESliceLoc {
+        ESliceLoc::new(self.z_loc_prm.loc())
+    }
+
+    pub fn e_slice_prm(&self) -> ESlicePrm {
+        ESlicePrm::new(self.z_loc_prm.prm())
+    }
+
     pub fn corner_index(&self) -> usize {
-        self.c_prm * Corners::ORI_SIZE + self.c_ori
+        Corners::combine_indices(self.c_prm.index(), self.c_ori.index())
     }
 
     pub fn from_corner_index(index: usize) -> Self {
         assert!(index < Self::CORNER_INDEX_SIZE);
         const E: Edges = Edges::solved();
+        let (c_prm, c_ori) = Corners::split_index(index);
         Self {
-            c_ori: index % Corners::ORI_SIZE,
-            c_prm: index / Corners::ORI_SIZE,
-            e_ori: E.ori_index(),
+            c_ori: COri::new(c_ori),
+            c_prm: CPrm::new(c_prm),
+            e_ori: EOri::new(E.ori_index()),
             x_loc_prm: E.loc_prm(Axis::X),
             y_loc_prm: E.loc_prm(Axis::Y),
             z_loc_prm: E.loc_prm(Axis::Z),
         }
     }
     
-    pub fn subset_cube(&self) -> SubsetCube {
-        SubsetCube {
-            c_prm: self.c_prm,
-            xy_prm: SUBSET_INDEX.e_xy_prm(self.x_loc_prm, self.y_loc_prm),
-            z_prm: self.z_loc_prm.prm(),
-        }
+    pub fn coset_index(&self) -> usize {
+        coset_index(self.c_ori, self.e_ori, self.e_slice_loc())
     }
 
-    pub fn coset_index(&self) -> usize {
-        self.c_ori * (Edges::ORI_SIZE * binomial(12, 4))
-            + self.e_ori * binomial(12, 4)
-            + self.z_loc_prm.loc()
+    /// Whether the cube is in the subset of phase 2, see `in_subset`.
+    pub fn in_subset(&self) -> bool {
+        in_subset(self.c_ori, self.e_ori, self.e_slice_loc())
     }
 
-    pub fn from_coset_index(mut index: usize) -> Self {
+    pub fn from_coset_index(index: usize) -> Self {
         assert!(index < Self::COSETS_INDEX_SIZE);
         const C: Corners = Corners::solved();
         const E: Edges = Edges::solved();
-        let z_loc = index % binomial(12, 4);
-        index /= binomial(12, 4);
-        let e_ori = index % Edges::ORI_SIZE;
-        index /= Edges::ORI_SIZE;
-        let c_ori = index;
+        let (c_ori, e_ori, z_loc) = coset_coordinates(index);
         Self {
             c_ori,
-            c_prm: C.prm_index(),
+            c_prm: CPrm::new(C.prm_index()),
             e_ori,
             x_loc_prm: E.loc_prm(Axis::X),
             y_loc_prm: E.loc_prm(Axis::Y),
-            z_loc_prm: LocPrm::new(z_loc, E.loc_prm(Axis::Z).prm()),
+            z_loc_prm: LocPrm::new(z_loc.index(), E.loc_prm(Axis::Z).prm()),
         }
     }
 
-    pub fn inverse(&self) -> Self {
-        let corners = Corners::from_indices(self.c_prm, self.c_ori).inverse();
-        let mut edges = Edges::from_indices(self.x_loc_prm, self.y_loc_prm, self.z_loc_prm, self.e_ori);
-        edges = edges.inverse();
+    /// An index of all states of the cube, in [0, FULL_INDEX_SIZE).
+    pub fn full_index(&self) -> u128 {
+        // The parity of the edges is defined by the corners.
+        let e_prm_half = even_permutation_index(self.edges().prm_index());
+        (self.corner_index() * (Edges::PRM_SIZE / 2) + e_prm_half) as u128 * Edges::ORI_SIZE as u128 + self.e_ori.index() as u128
+    }
+
+    pub fn from_full_index(index: u128) -> Self {
+        assert!(index < Self::FULL_INDEX_SIZE);
+        let e_ori = (index % Edges::ORI_SIZE as u128) as usize;
+        let index = (index / Edges::ORI_SIZE as u128) as usize;
+        let e_prm_half = index % (Edges::PRM_SIZE / 2);
+        let corners = Self::from_corner_index(index / (Edges::PRM_SIZE / 2)).corners();
+        let e_prm = nth_even_permutation(e_prm_half, is_even_permutation(corners.prm_index()));
+        Self::from_cubies(&corners, &Edges::from_prm_index(e_prm, e_ori))
+    }
+
+    pub fn from_cubies(corners: &Corners, edges: &Edges) -> Self {
         Self {
-            c_ori: corners.ori_index(),
-            c_prm: corners.prm_index(),
-            e_ori: edges.ori_index(),
+            c_ori: COri::new(corners.ori_index()),
+            c_prm: CPrm::new(corners.prm_index()),
+            e_ori: EOri::new(edges.ori_index()),
             x_loc_prm: edges.loc_prm(Axis::X),
             y_loc_prm: edges.loc_prm(Axis::Y),
             z_loc_prm: edges.loc_prm(Axis::Z),
         }
     }
 
+    pub fn corners(&self) -> Corners {
+        Corners::from_indices(self.c_prm.index(), self.c_ori.index())
+    }
+
+    pub fn edges(&self) -> Edges {
+        Edges::from_indices(self.x_loc_prm, self.y_loc_prm, self.z_loc_prm, self.e_ori.index())
+    }
+
+    pub fn from_facelets(facelets: &Facelets) -> Result<Self, FaceletError> {
+        let (corners, edges) = facelets.to_cubies()?;
+        Ok(Self::from_cubies(&corners, &edges))
+    }
+
+    /// The cube from the stickers of a cube held in any of the 24 orientations, e.g. by a robot, and that orientation.
+    /// The cube is as held, so its solutions are twists of the sides as held, and need no conversion.
+    pub fn from_held_facelets(facelets: &Facelets) -> Result<(Self, Orientation), FaceletError> {
+        let orientation = Orientation::from_centers(facelets).ok_or(FaceletError::HeldCenters)?;
+        Ok((Self::from_facelets(&orientation.to_identity_colours(facelets))?, orientation))
+    }
+
+    pub fn facelets(&self) -> Facelets {
+        Facelets::from_cubies(&self.corners(), &self.edges())
+    }
+
+    /// The sticker at a row and column of a face, numbered like in `Facelets`,
+    /// e.g. for painting the cube without computing all its facelets.
+    pub fn sticker(&self, face: Face, row: usize, col: usize) -> Face {
+        assert!(row < 3 && col < 3);
+        let facelet = face as usize * 9 + row * 3 + col;
+        match (row, col) {
+            (1, 1) => face,
+            (1, _) | (_, 1) => Facelets::edge_sticker(facelet, |position| self.edge_at(position)),
+            _ => Facelets::corner_sticker(facelet, |position| self.corner_at(position)),
+        }
+    }
+
+    /// Like `Corners::piece_at`, but decodes only the one corner from the coordinates.
+    fn corner_at(&self, position: usize) -> (usize, usize) {
+        let ori = self.c_ori.index();
+        let digit = |i: usize| ori / 3usize.pow(6 - i as u32) % 3;
+        // The orientation of the last corner makes the sum a multiple of 3.
+        let ori = if position < 7 { digit(position) } else { (21 - (0..7).map(digit).sum::<usize>()) % 3 };
+        (nth_permutation_element(self.c_prm.index(), 8, position), ori)
+    }
+
+    /// Like `Edges::piece_at`, but decodes only the one edge from the coordinates.
+    fn edge_at(&self, position: usize) -> (usize, usize) {
+        let ori = self.e_ori.index();
+        // The orientation of the last edge makes the sum even.
+        let ori = if position < 11 { ori >> (10 - position) & 1 } else { ori.count_ones() as usize % 2 };
+        let (offset, slice, j) = [(0, self.x_loc_prm), (4, self.y_loc_prm), (8, self.z_loc_prm)]
+            .into_iter()
+            .find_map(|(offset, slice)| {
+                let j = nth_combination_array::<4>(12, slice.loc()).iter().position(|&loc| loc == position)?;
+                Some((offset, slice, j))
+            })
+            .unwrap();
+        (offset + nth_permutation_element(slice.prm(), 4, j), ori)
+    }
+
+    /// The cube as seen in a mirror between L and R, see `Facelets::mirrored`.
+    pub fn mirrored(&self) -> Self {
+        Self::from_facelets(&self.facelets().mirrored()).unwrap()
+    }
+
+    /// The cube conjugated by each of the 48 symmetries of the cube, the 24 rotations and their mirror images.
+    /// Cubes with symmetries occur more than once.
+    pub fn symmetric_cubes(&self) -> Vec<Self> {
+        let mut cubes = Vec::with_capacity(48);
+        for up in UP {
+            let mut corners = up.iter().fold(self.corners(), |c, &rot| c.conjugated_by(rot));
+            let mut edges = up.iter().fold(self.edges(), |e, &rot| e.conjugated_by(rot));
+            for _ in 0..4 {
+                let cube = Self::from_cubies(&corners, &edges);
+                cubes.push(cube);
+                cubes.push(cube.mirrored());
+                corners = corners.conjugated_by(Axis::Z);
+                edges = edges.conjugated_by(Axis::Z);
+            }
+        }
+        cubes
+    }
+
+    /// The cube with the premoves done before the twists that led to `self`.
+    pub fn premoved(&self, premoves: &[Twist]) -> Self {
+        let start = Self::solved().twisted_by(premoves);
+        Self::from_cubies(&(self.corners() * start.corners()), &(self.edges() * start.edges()))
+    }
+
+    /// The cube after a rotation of the whole cube, see `rotations`.
+    pub fn rotated(&self, rotation: &[Axis]) -> Self {
+        rotation.iter().fold(*self, |cube, &axis| cube.conjugated_by(axis))
+    }
+
+    /// The representative of the cubes symmetric to this one, the one whose facelets are lexicographically smallest.
+    pub fn canonical(&self) -> Self {
+        self.symmetric_cubes().into_iter().min_by_key(Self::facelets).unwrap()
+    }
+
+    /// Whether the cubes are the same up to a rotation or reflection of the whole cube.
+    pub fn equivalent_under_symmetry(&self, other: &Self) -> bool {
+        self.canonical() == other.canonical()
+    }
+
+    /// Parses a cube string in Reid's notation, see `Facelets::from_reid`.
+    pub fn from_reid(s: &str) -> Result<Self, ReidError> {
+        Ok(Self::from_facelets(&Facelets::from_reid(s)?)?)
+    }
+
+    pub fn to_reid(&self) -> String {
+        self.facelets().to_reid()
+    }
+
+    /// The unfolded cube in ANSI colours, see `Facelets::to_net_string`.
+    pub fn to_net_string(&self) -> String {
+        self.facelets().to_net_string()
+    }
+
+    /// The cycles of the pieces, see `CycleStructure`.
+    pub fn cycles(&self) -> CycleStructure {
+        CycleStructure::new(&self.corners(), &self.edges())
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self::from_cubies(&self.corners().inverse(), &self.edges().inverse())
+    }
+
+    /// The cube whose solutions take `self` to `other`.
+    pub fn relative_to(&self, other: &Cube) -> Self {
+        let corners = self.corners() * other.corners().inverse();
+        let edges = self.edges() * other.edges().inverse();
+        Self::from_cubies(&corners, &edges)
+    }
+
     pub fn conjugated_by(&self, rot: Axis) -> Self {
-        let corners = Corners::from_indices(self.c_prm, self.c_ori).conjugated_by(rot);
-        let mut edges = Edges::from_indices(self.x_loc_prm, self.y_loc_prm, self.z_loc_prm, self.e_ori);
-        edges = edges.conjugated_by(rot);
+        Self::from_cubies(&self.corners().conjugated_by(rot), &self.edges().conjugated_by(rot))
+    }
+}
+
+/// The parts of `Cube` that use the move tables and the subset index.
+#[cfg(feature = "std")]
+impl Cube {
+    pub fn subset_cube(&self) -> SubsetCube {
+        SubsetCube {
+            c_prm: self.c_prm,
+            xy_prm: SUBSET_INDEX.e_xy_prm(self.x_loc_prm, self.y_loc_prm),
+            z_prm: self.e_slice_prm(),
+        }
+    }
+
+    /// Like `twisted`, but uses the move tables of the given context instead of the global ones.
+    pub fn twisted_in(&self, context: &TwistContext, twist: Twist) -> Self {
+        self.twisted_with(context.twister, twist)
+    }
+
+    pub fn twisted_by_in(&self, context: &TwistContext, twists: &[Twist]) -> Self {
+        twists
+            .iter()
+            .fold(*self, |cube, &twist| cube.twisted_with(context.twister, twist))
+    }
+
+    /// Twists the cube by a sequence of the multi twister, with one lookup per coordinate.
+    pub fn twisted_by_sequence(&self, multi_twister: &MultiTwister, sequence: usize) -> Self {
         Self {
-            c_ori: corners.ori_index(),
-            c_prm: corners.prm_index(),
-            e_ori: edges.ori_index(),
-            x_loc_prm: edges.loc_prm(Axis::X),
-            y_loc_prm: edges.loc_prm(Axis::Y),
-            z_loc_prm: edges.loc_prm(Axis::Z),
+            c_ori: multi_twister.twisted_c_ori(self.c_ori, sequence),
+            c_prm: multi_twister.twisted_c_prm(self.c_prm, sequence),
+            e_ori: multi_twister.twisted_e_ori(self.e_ori, sequence),
+            x_loc_prm: multi_twister.twisted_e_loc_prm(self.x_loc_prm, sequence),
+            y_loc_prm: multi_twister.twisted_e_loc_prm(self.y_loc_prm, sequence),
+            z_loc_prm: multi_twister.twisted_e_loc_prm(self.z_loc_prm, sequence),
+        }
+    }
+
+    #[inline(always)]
+    fn twisted_with(&self, twister: &Twister, twist: Twist) -> Self {
+        Self {
+            c_ori: twister.twisted_c_ori(self.c_ori, twist),
+            c_prm: twister.twisted_c_prm(self.c_prm, twist),
+            e_ori: twister.twisted_e_ori(self.e_ori, twist),
+            x_loc_prm: twister.twisted_e_loc_prm(self.x_loc_prm, twist),
+            y_loc_prm: twister.twisted_e_loc_prm(self.y_loc_prm, twist),
+            z_loc_prm: twister.twisted_e_loc_prm(self.z_loc_prm, twist),
         }
     }
 }
 
 impl Twistable for Cube {
+    #[cfg(feature = "std")]
     #[inline(always)]
     fn twisted(&self, twist: Twist) -> Self {
-        Self {
-            c_ori: TWISTER.twisted_c_ori(self.c_ori, twist),
-            c_prm: TWISTER.twisted_c_prm(self.c_prm, twist),
-            e_ori: TWISTER.twisted_e_ori(self.e_ori, twist),
-            x_loc_prm: TWISTER.twisted_e_loc_prm(self.x_loc_prm, twist),
-            y_loc_prm: TWISTER.twisted_e_loc_prm(self.y_loc_prm, twist),
-            z_loc_prm: TWISTER.twisted_e_loc_prm(self.z_loc_prm, twist),
-        }
+        self.twisted_with(&TWISTER, twist)
     }
 
-    fn twisted_by(&self, twists: &[Twist]) -> Self {
-        twists
-            .iter()
-            .fold(*self, |cube, &twist| cube.twisted(twist))
+    /// Without the move tables, through the corners and edges.
+    #[cfg(not(feature = "std"))]
+    fn twisted(&self, twist: Twist) -> Self {
+        Self::from_cubies(&(twist * self.corners()), &(twist * self.edges()))
+    }
+}
+
+/// Cubes are serialized as their facelet string, which is validated when deserializing.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Cube {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.facelets())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Cube {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let facelets: Facelets = String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)?;
+        Cube::from_facelets(&facelets).map_err(serde::de::Error::custom)
     }
 }
 
@@ -133,23 +354,131 @@ mod tests {
     use super::*;
     use rand::{rngs::StdRng, RngExt, SeedableRng};
 
-    // Tests 'corner_index' and 'from_corner_index'
     #[test]
-    fn test_corners_index() {
-        let mut rnd = StdRng::seed_from_u64(42);
-        for _ in 0..100_000 {
-            let rnd_index = rnd.random_range(0..Cube::CORNER_INDEX_SIZE);
-            assert_eq!(Cube::from_corner_index(rnd_index).corner_index(), rnd_index);
+    fn test_twisted() {
+        // With the move tables, or without them through the corners and edges.
+        let twists = crate::twist_generator::RandomTwistGen::new(47, &ALL_TWISTS).gen_twists(50);
+        let cube = Cube::solved().twisted_by(&twists);
+        assert_eq!(cube.corners(), Corners::twists(&twists));
+        assert_eq!(cube.edges(), Edges::twists(&twists));
+    }
+
+    #[test]
+    fn test_facelets() {
+        let mut rnd = crate::RandomTwistGen::new(42, &ALL_TWISTS);
+        for _ in 0..1_000 {
+            let cube = Cube::solved().twisted_by(&rnd.gen_twists(30));
+            assert_eq!(Cube::from_facelets(&cube.facelets()), Ok(cube));
+            assert_eq!(Cube::from_reid(&cube.to_reid()), Ok(cube));
         }
     }
 
-    // Tests 'coset_index' and 'from_coset_index'
     #[test]
-    fn test_coset_index() {
-        let mut rnd = StdRng::seed_from_u64(42);
-        for _ in 0..100_000 {
-            let rnd_index = rnd.random_range(0..Cube::COSETS_INDEX_SIZE);
-            assert_eq!(Cube::from_coset_index(rnd_index).coset_index(), rnd_index);
+    fn test_sticker() {
+        let mut rnd = crate::RandomTwistGen::new(7, &ALL_TWISTS);
+        for _ in 0..100 {
+            let cube = Cube::solved().twisted_by(&rnd.gen_twists(30));
+            let facelets = cube.facelets();
+            for (i, &expected) in facelets.faces().iter().enumerate() {
+                let face = facelets.faces()[i / 9 * 9 + 4];
+                assert_eq!(cube.sticker(face, i % 9 / 3, i % 3), expected);
+            }
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_from_held_facelets() {
+        // A robot holds the cube after a y rotation, and scrambles it with twists of the sides as held.
+        let orientation = normalize_moves("y").unwrap().1;
+        let scramble = parse_twist_sequence("R U2 F' L D B2").unwrap();
+        let seen = orientation.from_identity_colours(&Cube::solved().twisted_by(&scramble).facelets());
+        assert!(Cube::from_facelets(&seen).is_err());
+
+        let (cube, held) = Cube::from_held_facelets(&seen).unwrap();
+        assert_eq!(held, orientation);
+        assert_eq!(cube.twisted_by(&inverse(&scramble)), Cube::solved());
+        assert_eq!(Cube::from_held_facelets(&Facelets::solved().recoloured(|face| match face {
+            Face::U => Face::D,
+            Face::D => Face::U,
+            _ => face,
+        })), Err(FaceletError::HeldCenters));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let cube = Cube::solved().twisted(Twist::U1);
+        let json = serde_json::to_string(&cube).unwrap();
+        assert_eq!(json, "\"UUUUUUUUUBBBRRRRRRRRRFFFFFFDDDDDDDDDFFFLLLLLLLLLBBBBBB\"");
+        assert_eq!(serde_json::from_str::<Cube>(&json).unwrap(), cube);
+        assert!(serde_json::from_str::<Cube>("\"UUU\"").is_err());
+    }
+
+    #[test]
+    fn test_symmetry() {
+        let solved = Cube::solved();
+        assert!(solved.symmetric_cubes().iter().all(|&cube| cube == solved));
+        assert_eq!(solved.canonical(), solved);
+
+        let r = Cube::solved().twisted(Twist::R1);
+        let l3 = Cube::solved().twisted(Twist::L3);
+        assert_eq!(r.mirrored(), l3);
+        assert!(r.equivalent_under_symmetry(&Cube::solved().twisted(Twist::D3)));
+        assert!(!r.equivalent_under_symmetry(&Cube::solved().twisted(Twist::R2)));
+
+        let mut rnd = RandomTwistGen::new(13, &ALL_TWISTS);
+        for _ in 0..10 {
+            let cube = Cube::solved().twisted_by(&rnd.gen_twists(20));
+            let symmetric = cube.symmetric_cubes();
+            let mut distinct = symmetric.clone();
+            distinct.sort_by_key(Cube::facelets);
+            distinct.dedup();
+            assert_eq!(distinct.len(), 48);
+            let canonical = cube.canonical();
+            assert!(symmetric.iter().all(|c| c.canonical() == canonical));
+            assert!(cube.equivalent_under_symmetry(&cube.conjugated_by(Axis::Y).mirrored()));
+        }
+    }
+
+    #[test]
+    fn test_rotations() {
+        let cube = Cube::solved().twisted_by(&[Twist::R1, Twist::U1, Twist::F3]);
+        let mut rotated: Vec<Cube> = rotations().iter().map(|rotation| cube.rotated(rotation)).collect();
+        assert_eq!(rotated.len(), 24);
+        assert!(rotated.iter().all(|c| cube.symmetric_cubes().contains(c)));
+        rotated.sort_by_key(Cube::facelets);
+        rotated.dedup();
+        assert_eq!(rotated.len(), 24);
+    }
+
+    #[test]
+    fn test_full_index() {
+        assert_eq!(Cube::FULL_INDEX_SIZE, 43_252_003_274_489_856_000);
+        assert_eq!(Cube::solved().full_index(), 0);
+        assert_eq!(Cube::from_full_index(0), Cube::solved());
+        let last = Cube::from_full_index(Cube::FULL_INDEX_SIZE - 1);
+        assert_eq!(last.full_index(), Cube::FULL_INDEX_SIZE - 1);
+        assert!(Cube::from_facelets(&last.facelets()).is_ok());
+
+        let mut rnd = StdRng::seed_from_u64(7);
+        for _ in 0..1_000 {
+            let index = rnd.random_range(0..Cube::FULL_INDEX_SIZE);
+            let cube = Cube::from_full_index(index);
+            assert_eq!(cube.full_index(), index);
+            assert!(Cube::from_facelets(&cube.facelets()).is_ok());
+        }
+
+        let cubes: std::collections::HashSet<Cube> = ALL_TWISTS.iter().map(|&t| Cube::solved().twisted(t)).collect();
+        assert_eq!(cubes.len(), 18);
+    }
+
+    #[test]
+    fn test_relative_to() {
+        let from = Cube::solved().twisted_by(&[Twist::R1, Twist::U3, Twist::F2]);
+        let twists = [Twist::D1, Twist::L3, Twist::B2, Twist::U1];
+        let to = from.twisted_by(&twists);
+        assert_eq!(from.relative_to(&to).twisted_by(&twists), Cube::solved());
+        assert_eq!(from.relative_to(&from), Cube::solved());
+        assert_eq!(Cube::solved().relative_to(&to), to.inverse());
+    }
+}
@@ -1,11 +1,37 @@
+pub mod coordinates;
 pub mod cube;
+pub mod subset;
+pub mod twistable;
+#[cfg(feature = "std")]
 pub mod subset_cube;
+#[cfg(feature = "std")]
 pub mod twister;
+#[cfg(feature = "std")]
 pub mod subset_twister;
+#[cfg(feature = "std")]
 pub mod subset_index;
+#[cfg(feature = "std")]
+pub mod subset_inverse;
+#[cfg(feature = "std")]
+pub mod twist_context;
+#[cfg(feature = "std")]
+pub mod multi_twister;
 
+pub use coordinates::*;
 pub use cube::*;
+pub use subset::*;
+pub use twistable::*;
+#[cfg(feature = "std")]
 pub use subset_cube::*;
+#[cfg(feature = "std")]
 pub use twister::*;
+#[cfg(feature = "std")]
 pub use subset_twister::*;
-pub use subset_index::*;
\ No newline at end of file
+#[cfg(feature = "std")]
+pub use subset_index::*;
+#[cfg(feature = "std")]
+pub use subset_inverse::*;
+#[cfg(feature = "std")]
+pub use twist_context::*;
+#[cfg(feature = "std")]
+pub use multi_twister::*;
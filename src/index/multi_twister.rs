@@ -0,0 +1,173 @@
+use super::{COri, CPrm, Cube, EOri, ENonSlicePrm, ESlicePrm, SubsetCube, Twistable, TWISTER};
+use crate::corners::*;
+use crate::edges::*;
+use crate::math::*;
+use crate::twist::*;
+use crate::parallel::*;
+
+/// Move tables for fixed twist sequences, which apply a whole sequence with one lookup per coordinate.
+///
+/// The tables for `Cube` are built by replaying the sequences with the `Twister`.
+/// The tables for `SubsetCube` are only built if every sequence, as a whole, keeps the subset.
+/// The sequences themselves may contain any twists.
+pub struct MultiTwister {
+    sequences: Vec<Vec<Twist>>,
+    c_ori: Vec<u16>, // count * 3^7
+    c_prm: Vec<u16>, // count * 8!
+    e_ori: Vec<u16>, // count * 2^11
+    e_loc_prm: Vec<LocPrm>, // count * (12 choose 4) * 4!
+    subset: Option<SubsetTables>,
+}
+
+struct SubsetTables {
+    xy_prm: Vec<u16>, // count * 8!
+    z_prm: Vec<u8>, // count * 4!
+}
+
+/// Fills a table whose rows hold the images of a coordinate under each sequence.
+fn fill<T: Send>(table: &mut [T], count: usize, image: impl Fn(usize, usize) -> T + Sync) {
+    table
+        .par_chunks_mut(count.max(1))
+        .enumerate()
+        .for_each(|(i, chunk)| {
+            for (j, value) in chunk.iter_mut().enumerate() {
+                *value = image(i, j);
+            }
+        });
+}
+
+impl MultiTwister {
+    pub fn new(sequences: Vec<Vec<Twist>>) -> Self {
+        let count = sequences.len();
+        let replay = |start: usize, sequence: &[Twist], twisted: fn(usize, Twist) -> usize| {
+            sequence.iter().fold(start, |coord, &twist| twisted(coord, twist)) as u16
+        };
+
+        let mut c_ori = vec![0u16; count * Corners::ORI_SIZE];
+        let mut c_prm = vec![0u16; count * Corners::PRM_SIZE];
+        let mut e_ori = vec![0u16; count * Edges::ORI_SIZE];
+        let mut e_loc_prm = vec![LocPrm::new(0, 0); count * LocPrm::INDEX_SIZE];
+        fill(&mut c_ori, count, |i, j| replay(i, &sequences[j], |c, t| TWISTER.twisted_c_ori(COri::new(c), t).index()));
+        fill(&mut c_prm, count, |i, j| replay(i, &sequences[j], |c, t| TWISTER.twisted_c_prm(CPrm::new(c), t).index()));
+        fill(&mut e_ori, count, |i, j| replay(i, &sequences[j], |c, t| TWISTER.twisted_e_ori(EOri::new(c), t).index()));
+        fill(&mut e_loc_prm, count, |i, j| {
+            sequences[j].iter().fold(LocPrm::from_index(i), |coord, &twist| TWISTER.twisted_e_loc_prm(coord, twist))
+        });
+
+        let keeps_subset = sequences
+            .iter()
+            .all(|sequence| Cube::solved().twisted_by(sequence).coset_index() == Cube::solved().coset_index());
+        let subset = keeps_subset.then(|| {
+            let edges: Vec<Edges> = sequences.iter().map(|s| Edges::twists(s)).collect();
+            let mut xy_prm = vec![0u16; count * factorial(8)];
+            let mut z_prm = vec![0u8; count * factorial(4)];
+            fill(&mut xy_prm, count, |i, j| (edges[j] * Edges::from_subset_indices(i, 0)).xy_prm_index() as u16);
+            fill(&mut z_prm, count, |i, j| (edges[j] * Edges::from_subset_indices(0, i)).loc_prm(Axis::Z).prm() as u8);
+            SubsetTables { xy_prm, z_prm }
+        });
+
+        Self { sequences, c_ori, c_prm, e_ori, e_loc_prm, subset }
+    }
+
+    pub fn sequences(&self) -> &[Vec<Twist>] {
+        &self.sequences
+    }
+
+    /// Whether every sequence keeps the subset, so subset cubes can be twisted.
+    pub fn keeps_subset(&self) -> bool {
+        self.subset.is_some()
+    }
+
+    fn row(&self, coord: usize, sequence: usize) -> usize {
+        assert!(sequence < self.sequences.len());
+        coord * self.sequences.len() + sequence
+    }
+
+    pub fn twisted_c_ori(&self, c_ori: COri, sequence: usize) -> COri {
+        COri::from_table(self.c_ori[self.row(c_ori.index(), sequence)])
+    }
+    pub fn twisted_c_prm(&self, c_prm: CPrm, sequence: usize) -> CPrm {
+        CPrm::from_table(self.c_prm[self.row(c_prm.index(), sequence)])
+    }
+    pub fn twisted_e_ori(&self, e_ori: EOri, sequence: usize) -> EOri {
+        EOri::from_table(self.e_ori[self.row(e_ori.index(), sequence)])
+    }
+    pub fn twisted_e_loc_prm(&self, e_loc_prm: LocPrm, sequence: usize) -> LocPrm {
+        self.e_loc_prm[self.row(e_loc_prm.index(), sequence)]
+    }
+
+    // The following functions panic if a sequence leaves the subset.
+    pub fn twisted_xy_prm(&self, e_xy_prm: ENonSlicePrm, sequence: usize) -> ENonSlicePrm {
+        let subset = self.subset.as_ref().expect("A twist sequence leaves the subset");
+        ENonSlicePrm::from_table(subset.xy_prm[self.row(e_xy_prm.index(), sequence)])
+    }
+    pub fn twisted_z_prm(&self, e_z_prm: ESlicePrm, sequence: usize) -> ESlicePrm {
+        let subset = self.subset.as_ref().expect("A twist sequence leaves the subset");
+        ESlicePrm::from_table(subset.z_prm[self.row(e_z_prm.index(), sequence)] as u16)
+    }
+
+    /// Twists all cubes by the sequence with the given index.
+    pub fn twist_cubes(&self, cubes: &mut [Cube], sequence: usize) {
+        cubes
+            .par_iter_mut()
+            .for_each(|cube| *cube = cube.twisted_by_sequence(self, sequence));
+    }
+
+    /// Twists all subset cubes by the sequence with the given index.
+    pub fn twist_subset_cubes(&self, cubes: &mut [SubsetCube], sequence: usize) {
+        cubes
+            .par_iter_mut()
+            .for_each(|cube| *cube = cube.twisted_by_sequence(self, sequence));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::twist_generator::*;
+
+    #[test]
+    fn test_bulk() {
+        let scramble = RandomTwistGen::new(42, &ALL_TWISTS).gen_twists(20);
+        let sequences = vec![
+            RandomTwistGen::new(43, &H0_TWISTS).gen_twists(20),
+            [scramble.as_slice(), &inverse(&scramble), &[Twist::U1]].concat(),
+        ];
+        let multi_twister = MultiTwister::new(sequences.clone());
+        assert!(multi_twister.keeps_subset());
+
+        let mut rnd = RandomTwistGen::new(44, &H0_TWISTS);
+        let cubes: Vec<Cube> = (0..1_000).map(|_| Cube::solved().twisted_by(&rnd.gen_twists(30))).collect();
+        for (i, sequence) in sequences.iter().enumerate() {
+            let mut twisted = cubes.clone();
+            multi_twister.twist_cubes(&mut twisted, i);
+            let mut twisted_subset: Vec<SubsetCube> = cubes.iter().map(|c| c.subset_cube()).collect();
+            multi_twister.twist_subset_cubes(&mut twisted_subset, i);
+            for j in 0..cubes.len() {
+                let expected = cubes[j].twisted_by(sequence);
+                assert_eq!(twisted[j], expected);
+                assert!(twisted_subset[j] == expected.subset_cube());
+            }
+        }
+    }
+
+    #[test]
+    fn test_leaving_subset() {
+        let sequence = RandomTwistGen::new(42, &ALL_TWISTS).gen_twists(20);
+        let multi_twister = MultiTwister::new(vec![sequence.clone()]);
+        assert!(!multi_twister.keeps_subset());
+
+        let mut rnd = RandomTwistGen::new(43, &ALL_TWISTS);
+        let mut cubes: Vec<Cube> = (0..1_000).map(|_| Cube::solved().twisted_by(&rnd.gen_twists(30))).collect();
+        let expected: Vec<Cube> = cubes.iter().map(|c| c.twisted_by(&sequence)).collect();
+        multi_twister.twist_cubes(&mut cubes, 0);
+        assert_eq!(cubes, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_subset_cube_leaving_subset() {
+        let multi_twister = MultiTwister::new(vec![vec![Twist::L1]]);
+        SubsetCube::solved().twisted_by_sequence(&multi_twister, 0);
+    }
+}
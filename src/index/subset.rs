@@ -0,0 +1,102 @@
+use super::{COri, CPrm, EOri, ENonSlicePrm, ESliceLoc, ESlicePrm};
+use crate::corners::*;
+use crate::edges::*;
+use crate::permutation::*;
+use crate::twist::Axis;
+
+// The subset of phase 2 holds the cubes with oriented corners and edges, and the E slice edges in the E slice.
+// The coset index tells apart the cosets of the subset, and the subset index the cubes within the subset.
+
+/// The coset of the subset that a cube with these coordinates is in, in [0, Cube::COSETS_INDEX_SIZE).
+pub fn coset_index(c_ori: COri, e_ori: EOri, e_slice_loc: ESliceLoc) -> usize {
+    (c_ori.index() * EOri::SIZE + e_ori.index()) * ESliceLoc::SIZE + e_slice_loc.index()
+}
+
+/// The coordinates of a coset index, the inverse of `coset_index`.
+pub fn coset_coordinates(index: usize) -> (COri, EOri, ESliceLoc) {
+    (
+        COri::new(index / (EOri::SIZE * ESliceLoc::SIZE)),
+        EOri::new(index / ESliceLoc::SIZE % EOri::SIZE),
+        ESliceLoc::new(index % ESliceLoc::SIZE),
+    )
+}
+
+/// Whether a cube with these coordinates is in the subset, i.e. in the coset of the solved cube.
+pub fn in_subset(c_ori: COri, e_ori: EOri, e_slice_loc: ESliceLoc) -> bool {
+    c_ori.index() == Corners::solved().ori_index()
+        && e_ori.index() == Edges::solved().ori_index()
+        && e_slice_loc.index() == Edges::solved().loc_prm(Axis::Z).loc()
+}
+
+/// The index of a cube in the subset, in [0, SubsetCube::INDEX_SIZE).
+/// The permutations of the corners and of the edges have the same parity, so the index leaves out the corners' one.
+pub fn subset_index(c_prm: CPrm, xy_prm: ENonSlicePrm, z_prm: ESlicePrm) -> usize {
+    (even_permutation_index(c_prm.index()) * ENonSlicePrm::SIZE + xy_prm.index()) * ESlicePrm::SIZE + z_prm.index()
+}
+
+/// The coordinates of a subset index, the inverse of `subset_index`, with the parity of the corners restored from the edges.
+pub fn subset_coordinates(index: usize) -> (CPrm, ENonSlicePrm, ESlicePrm) {
+    let z_prm = index % ESlicePrm::SIZE;
+    let xy_prm = index / ESlicePrm::SIZE % ENonSlicePrm::SIZE;
+    // In the subset, the E slice edges are in their own slice, so the permutation of all edges is even if both parts have the same parity.
+    let even_edges = is_even_permutation(xy_prm) == is_even_permutation(z_prm);
+    let c_prm = nth_even_permutation(index / (ESlicePrm::SIZE * ENonSlicePrm::SIZE), even_edges);
+    (CPrm::new(c_prm), ENonSlicePrm::new(xy_prm), ESlicePrm::new(z_prm))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cube, Twistable};
+    use crate::twist::*;
+    use crate::twist_generator::*;
+
+    #[test]
+    fn test_coset_index() {
+        let mut rnd = RandomTwistGen::new(42, &ALL_TWISTS);
+        for _ in 0..1_000 {
+            let twists = rnd.gen_twists(20);
+            let (corners, edges) = (Corners::twists(&twists), Edges::twists(&twists));
+            let c_ori = COri::new(corners.ori_index());
+            let e_ori = EOri::new(edges.ori_index());
+            let e_slice_loc = ESliceLoc::new(edges.loc_prm(Axis::Z).loc());
+            let index = coset_index(c_ori, e_ori, e_slice_loc);
+            assert_eq!(index, Cube::from_cubies(&corners, &edges).coset_index());
+            assert_eq!(coset_coordinates(index), (c_ori, e_ori, e_slice_loc));
+        }
+        let solved = Cube::solved();
+        assert!(in_subset(solved.c_ori(), solved.e_ori(), solved.e_slice_loc()));
+    }
+
+    #[test]
+    fn test_in_subset() {
+        let mut rnd = RandomTwistGen::new(43, &H0_TWISTS);
+        for _ in 0..100 {
+            let cube = Cube::solved().twisted_by(&rnd.gen_twists(20));
+            assert!(in_subset(cube.c_ori(), cube.e_ori(), cube.e_slice_loc()));
+            for twist in [Twist::L1, Twist::R3, Twist::F1, Twist::B3] {
+                let cube = cube.twisted(twist);
+                assert!(!in_subset(cube.c_ori(), cube.e_ori(), cube.e_slice_loc()));
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_subset_index() {
+        use crate::SubsetCube;
+
+        let mut rnd = RandomTwistGen::new(44, &H0_TWISTS);
+        for _ in 0..1_000 {
+            let twists = rnd.gen_twists(20);
+            let (corners, edges) = (Corners::twists(&twists), Edges::twists(&twists));
+            let c_prm = CPrm::new(corners.prm_index());
+            let xy_prm = ENonSlicePrm::new(edges.xy_prm_index());
+            let z_prm = ESlicePrm::new(edges.loc_prm(Axis::Z).prm());
+            let index = subset_index(c_prm, xy_prm, z_prm);
+            assert!(index < SubsetCube::INDEX_SIZE);
+            assert_eq!(index, Cube::from_cubies(&corners, &edges).subset_cube().index());
+            assert_eq!(subset_coordinates(index), (c_prm, xy_prm, z_prm));
+        }
+    }
+}
@@ -1,76 +1,86 @@
-use super::Twistable;
-use crate::TWISTER;
+use super::{CPrm, ENonSlicePrm, ESlicePrm, subset_coordinates, subset_index, Twistable, TwistContext, MultiTwister, SubsetInverse, SUBSET_INVERSE};
 use crate::SUBSET_TWISTER;
 use crate::cubies::*;
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct SubsetCube {
-    pub c_prm: usize, // 8! = 40'320
-    pub xy_prm: usize, // 8! = 40'320
-    pub z_prm: usize, // 4! = 24
+    pub c_prm: CPrm, // 8! = 40'320
+    pub xy_prm: ENonSlicePrm, // 8! = 40'320
+    pub z_prm: ESlicePrm, // 4! = 24
 }
 
 impl SubsetCube {
     pub const INDEX_SIZE: usize = Corners::PRM_SIZE / 2 * factorial(8) * factorial(4);  // 19'508'428'800
+    pub const REDUCED_INDEX_SIZE: usize = SubsetInverse::ROWS * factorial(8) * factorial(4) / 2;  // 9'939'041'280
 
     pub fn solved() -> Self {
         let c = Corners::solved();
         let e = Edges::solved();
         Self {
-            c_prm: c.prm_index(),
-            xy_prm: e.xy_prm_index(),
-            z_prm: e.loc_prm(Axis::Z).prm(),
+            c_prm: CPrm::new(c.prm_index()),
+            xy_prm: ENonSlicePrm::new(e.xy_prm_index()),
+            z_prm: ESlicePrm::new(e.loc_prm(Axis::Z).prm()),
         }
     }
 
+    /// See `subset_index`.
     pub fn index(&self) -> usize {
-        (self.c_prm / 2) * factorial(8) * factorial(4)
-            + self.xy_prm * factorial(4)
-            + self.z_prm
+        subset_index(self.c_prm, self.xy_prm, self.z_prm)
     }
 
-    pub fn from_index(mut index: usize) -> Self {
+    pub fn from_index(index: usize) -> Self {
         assert!(index < Self::INDEX_SIZE);
-        let z_prm = index % factorial(4); index /= factorial(4);
-        let xy_prm = index % factorial(8); index /= factorial(8);
-        let mut c_prm = index * 2;
-        let e_even_prm = is_even_permutation(xy_prm)
-            ^ is_even_permutation(z_prm)
-            ^ true; // in subset z_prm is an even permutation
-        if e_even_prm != is_even_permutation(c_prm) {
-            c_prm += 1;
-        }
+        let (c_prm, xy_prm, z_prm) = subset_coordinates(index);
         Self { c_prm, xy_prm, z_prm }
     }
-}
 
-impl Twistable for SubsetCube {
-    fn twisted(&self, twist: Twist) -> Self {
+    pub fn inverse(&self) -> Self {
+        SUBSET_INVERSE.inverse(self)
+    }
+
+    /// The index of the cube and of its inverse, which are equally far from solved, see `SubsetInverse`.
+    pub fn reduced_index(&self) -> usize {
+        SUBSET_INVERSE.reduced_index(self)
+    }
+
+    /// The cube or the inverse of the cube of a reduced index.
+    pub fn from_reduced_index(index: usize) -> Self {
+        SUBSET_INVERSE.from_reduced_index(index)
+    }
+
+    /// Like `twisted`, but uses the move tables of the given context instead of the global ones.
+    pub fn twisted_in(&self, context: &TwistContext, twist: Twist) -> Self {
         Self {
-            c_prm: TWISTER.twisted_c_prm(self.c_prm, twist),
-            xy_prm: SUBSET_TWISTER.twisted_xy_prm(self.xy_prm, twist),
-            z_prm: SUBSET_TWISTER.twisted_z_prm(self.z_prm, twist),
+            c_prm: context.subset_twister.twisted_c_prm(self.c_prm, twist),
+            xy_prm: context.subset_twister.twisted_xy_prm(self.xy_prm, twist),
+            z_prm: context.subset_twister.twisted_z_prm(self.z_prm, twist),
         }
     }
-    fn twisted_by(&self, twists: &[Twist]) -> Self {
+
+    /// Twists the cube by a sequence of the multi twister, with one lookup per coordinate.
+    /// Panics if the sequences of the multi twister leave the subset.
+    pub fn twisted_by_sequence(&self, multi_twister: &MultiTwister, sequence: usize) -> Self {
+        Self {
+            c_prm: multi_twister.twisted_c_prm(self.c_prm, sequence),
+            xy_prm: multi_twister.twisted_xy_prm(self.xy_prm, sequence),
+            z_prm: multi_twister.twisted_z_prm(self.z_prm, sequence),
+        }
+    }
+
+    pub fn twisted_by_in(&self, context: &TwistContext, twists: &[Twist]) -> Self {
         twists
             .iter()
-            .fold(*self, |index, &twist| index.twisted(twist))
+            .fold(*self, |index, &twist| index.twisted_in(context, twist))
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use rand::{rngs::StdRng, RngExt, SeedableRng};
-
-    // Tests 'index' and 'from_index'
-    #[test]
-    fn test_subset_index() {
-        let mut rnd = StdRng::seed_from_u64(42);
-        for _ in 0..100_000 {
-            let rnd_index = rnd.random_range(0..SubsetCube::INDEX_SIZE);
-            assert_eq!(SubsetCube::from_index(rnd_index).index(), rnd_index);
+// Panics if a twist is not in H0, since it would leave the subset.
+impl Twistable for SubsetCube {
+    fn twisted(&self, twist: Twist) -> Self {
+        Self {
+            c_prm: SUBSET_TWISTER.twisted_c_prm(self.c_prm, twist),
+            xy_prm: SUBSET_TWISTER.twisted_xy_prm(self.xy_prm, twist),
+            z_prm: SUBSET_TWISTER.twisted_z_prm(self.z_prm, twist),
         }
     }
 }
@@ -1,7 +1,8 @@
 use crate::math::*;
 use crate::edges::*;
 use crate::permutation::*;
-use rayon::prelude::*;
+use crate::parallel::*;
+use super::ENonSlicePrm;
 
 // Size: 141’134’400 bytes (~134.6 MiB)
 pub struct SubsetIndex {
@@ -19,8 +20,8 @@ impl SubsetIndex {
             .for_each(|(i, val)| {
                 let x_loc_prm = LocPrm::from_index(i / Edges::LOC_PRM_SIZE);
                 let y_loc_prm = LocPrm::from_index(i % Edges::LOC_PRM_SIZE);
-                let x_loc = nth_combination(12, 4, x_loc_prm.loc());
-                let y_loc = nth_combination(12, 4, y_loc_prm.loc());
+                let x_loc = nth_combination_array::<4>(12, x_loc_prm.loc());
+                let y_loc = nth_combination_array::<4>(12, y_loc_prm.loc());
                 let x_prm = Permutation::<4>::from_index(x_loc_prm.prm());
                 let y_prm = Permutation::<4>::from_index(y_loc_prm.prm());
                 let mut prm = [12; 12];
@@ -42,8 +43,15 @@ impl SubsetIndex {
         Self { e_xy_prm }
     }
 
-    pub fn e_xy_prm(&self, x_loc_prm: LocPrm, y_loc_prm: LocPrm) -> usize {
-        self.e_xy_prm[x_loc_prm.index() * Edges::LOC_PRM_SIZE + y_loc_prm.index()] as usize
+    /// The permutation of the U and D edges of a cube in the subset, from the locations and permutations of the M and S slice edges.
+    pub fn e_xy_prm(&self, x_loc_prm: LocPrm, y_loc_prm: LocPrm) -> ENonSlicePrm {
+        ENonSlicePrm::from_table(self.e_xy_prm[x_loc_prm.index() * Edges::LOC_PRM_SIZE + y_loc_prm.index()])
+    }
+}
+
+impl Default for SubsetIndex {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -0,0 +1,133 @@
+use crate::math::*;
+use crate::permutation::*;
+use super::{CPrm, ENonSlicePrm, ESlicePrm, SubsetCube};
+
+// Size: 202’388 bytes (~0.19 MiB)
+//
+// A cube and its inverse are equally far from solved, so a table of the subset needs only one of them.
+// The reduced index keeps the cubes whose corner permutation has an index no larger than its inverse's,
+// which are the rows of the reduced index: 20’542 of the 8! corner permutations, of which the 764 involutions are their own inverse.
+// For these, the index keeps both the cube and its inverse.
+pub struct SubsetInverse {
+    inverse_8: Vec<u16>, // 8! = 40’320, of the corners and of the U and D edges
+    inverse_4: [u8; 24], // 4! = 24, of the E slice edges
+    row: Vec<u16>, // 8! = 40’320, the row of each corner permutation that has one
+    c_prm_of_row: Vec<u16>, // 20’542
+}
+
+impl SubsetInverse {
+    /// The number of corner permutations whose index is no larger than its inverse's.
+    pub const ROWS: usize = 20_542;
+
+    pub fn new() -> Self {
+        let inverse = |size: usize, index: usize| -> usize {
+            let prm = nth_permutation(index, size);
+            let mut inv = vec![0; size];
+            for (i, &p) in prm.iter().enumerate() {
+                inv[p] = i;
+            }
+            permutation_index(&inv)
+        };
+        let inverse_8: Vec<u16> = (0..factorial(8)).map(|i| inverse(8, i) as u16).collect();
+        let inverse_4 = core::array::from_fn(|i| inverse(4, i) as u8);
+
+        let mut row = vec![u16::MAX; factorial(8)];
+        let mut c_prm_of_row = Vec::with_capacity(Self::ROWS);
+        for (i, &inv) in inverse_8.iter().enumerate() {
+            if i <= inv as usize {
+                row[i] = c_prm_of_row.len() as u16;
+                c_prm_of_row.push(i as u16);
+            }
+        }
+        assert_eq!(c_prm_of_row.len(), Self::ROWS);
+
+        Self { inverse_8, inverse_4, row, c_prm_of_row }
+    }
+
+    /// The inverse of a cube in the subset, which is in the subset too.
+    pub fn inverse(&self, cube: &SubsetCube) -> SubsetCube {
+        SubsetCube {
+            c_prm: CPrm::new(self.inverse_8[cube.c_prm.index()] as usize),
+            xy_prm: ENonSlicePrm::new(self.inverse_8[cube.xy_prm.index()] as usize),
+            z_prm: ESlicePrm::new(self.inverse_4[cube.z_prm.index()] as usize),
+        }
+    }
+
+    /// The index of the cube or of its inverse, in [0, SubsetCube::REDUCED_INDEX_SIZE).
+    /// The E slice permutation leaves out its parity, which follows from the others.
+    pub fn reduced_index(&self, cube: &SubsetCube) -> usize {
+        let cube = if (self.inverse_8[cube.c_prm.index()] as usize) < cube.c_prm.index() { self.inverse(cube) } else { *cube };
+        let row = self.row[cube.c_prm.index()] as usize;
+        (row * ENonSlicePrm::SIZE + cube.xy_prm.index()) * (ESlicePrm::SIZE / 2) + even_permutation_index(cube.z_prm.index())
+    }
+
+    /// The cube of a reduced index, the inverse of `reduced_index` up to inversion.
+    pub fn from_reduced_index(&self, index: usize) -> SubsetCube {
+        assert!(index < SubsetCube::REDUCED_INDEX_SIZE);
+        let z_half = index % (ESlicePrm::SIZE / 2);
+        let xy_prm = index / (ESlicePrm::SIZE / 2) % ENonSlicePrm::SIZE;
+        let c_prm = self.c_prm_of_row[index / (ESlicePrm::SIZE / 2 * ENonSlicePrm::SIZE)] as usize;
+        // The permutation of all edges has the parity of the corners'.
+        let even_z = is_even_permutation(xy_prm) == is_even_permutation(c_prm);
+        SubsetCube {
+            c_prm: CPrm::new(c_prm),
+            xy_prm: ENonSlicePrm::new(xy_prm),
+            z_prm: ESlicePrm::new(nth_even_permutation(z_half, even_z)),
+        }
+    }
+
+    /// The number of cubes of the subset that a reduced index stands for:
+    /// 2 for a cube and its inverse, or 1 if the corner permutation is its own inverse and the index keeps both.
+    pub fn multiplicity(&self, index: usize) -> u64 {
+        let c_prm = self.c_prm_of_row[index / (ESlicePrm::SIZE / 2 * ENonSlicePrm::SIZE)] as usize;
+        if self.inverse_8[c_prm] as usize == c_prm { 1 } else { 2 }
+    }
+}
+
+impl Default for SubsetInverse {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub static SUBSET_INVERSE: std::sync::LazyLock<SubsetInverse> = std::sync::LazyLock::new(SubsetInverse::new);
+
+pub fn init_subset_inverse() {
+    std::sync::LazyLock::force(&SUBSET_INVERSE);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::twist::*;
+    use crate::twist_generator::*;
+    use crate::{Cube, Twistable};
+
+    #[test]
+    fn test_inverse() {
+        let mut rnd = RandomTwistGen::new(45, &H0_TWISTS);
+        for _ in 0..1_000 {
+            let cube = Cube::solved().twisted_by(&rnd.gen_twists(20));
+            assert!(cube.subset_cube().inverse() == cube.inverse().subset_cube());
+        }
+    }
+
+    #[test]
+    fn test_reduced_index() {
+        let mut rnd = RandomTwistGen::new(46, &H0_TWISTS);
+        for _ in 0..1_000 {
+            let cube = SubsetCube::solved().twisted_by(&rnd.gen_twists(20));
+            let index = cube.reduced_index();
+            assert!(index < SubsetCube::REDUCED_INDEX_SIZE);
+            // Only corner permutations that are their own inverse keep the cube and its inverse apart.
+            assert_eq!(cube.inverse().reduced_index() == index, cube.inverse().c_prm != cube.c_prm || cube.inverse() == cube);
+            let from_index = SubsetCube::from_reduced_index(index);
+            assert!(from_index == cube || from_index == cube.inverse());
+            assert_eq!(from_index.reduced_index(), index);
+        }
+        // The cubes the reduced indices stand for are all cubes of the subset.
+        let per_row = SubsetCube::REDUCED_INDEX_SIZE / SubsetInverse::ROWS;
+        let represented: u64 = (0..SubsetInverse::ROWS).map(|row| SUBSET_INVERSE.multiplicity(row * per_row) * per_row as u64).sum();
+        assert_eq!(represented, SubsetCube::INDEX_SIZE as u64);
+    }
+}
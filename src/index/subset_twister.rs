@@ -1,47 +1,111 @@
 use crate::math::*;
+use crate::corners::*;
 use crate::edges::*;
 use crate::twist::*;
-use rayon::prelude::*;
+use crate::table::TableFileError;
+use crate::parallel::*;
+use super::{CPrm, ENonSlicePrm, ESlicePrm};
 
-// Size: 1’451’952 bytes (~1.4 MiB)
+// Size: 1’613’040 bytes (~1.5 MiB)
+//
+// Only contains the twists of H0, which are the only ones that keep a cube in the subset.
 pub struct SubsetTwister {
-    subset_e_xy_prm: Vec<u16>, // 18 * 8! = 725’760
-    subset_e_z_prm: Vec<u8>, // 18 * 4! = 432
+    c_prm: Vec<[u16; COUNT]>, // 10 * 8! = 403’200
+    subset_e_xy_prm: Vec<[u16; COUNT]>, // 10 * 8! = 403’200
+    subset_e_z_prm: Vec<[u8; COUNT]>, // 10 * 4! = 240
 }
 
-const COUNT: usize = ALL_TWISTS.len();
+const COUNT: usize = H0_TWISTS.len();
+
+/// Column of a twist in the rows of the tables, or `COUNT` if the twist is not in H0.
+const COLUMN: [usize; ALL_TWISTS.len()] = {
+    let mut column = [COUNT; ALL_TWISTS.len()];
+    let mut i = 0;
+    while i < COUNT {
+        column[H0_TWISTS[i] as usize] = i;
+        i += 1;
+    }
+    column
+};
 
 impl SubsetTwister {
     pub fn new() -> Self {
-        let mut subset_e_xy_prm = vec![0u16; COUNT * factorial(8)];
-        let mut subset_e_z_prm = vec![0u8; COUNT * factorial(4)];
+        let mut c_prm = vec![[0u16; COUNT]; Corners::PRM_SIZE];
+        let mut subset_e_xy_prm = vec![[0u16; COUNT]; factorial(8)];
+        let mut subset_e_z_prm = vec![[0u8; COUNT]; factorial(4)];
 
+        c_prm
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(i, row)| {
+                let obj = Corners::from_indices(i, 0);
+                for (column, twist) in H0_TWISTS.into_iter().enumerate() {
+                    row[column] = (twist * obj).prm_index() as u16;
+                }
+            });
         subset_e_xy_prm
-            .par_chunks_mut(COUNT)
+            .par_iter_mut()
             .enumerate()
-            .for_each(|(i, chunk)| {
+            .for_each(|(i, row)| {
                 let obj = Edges::from_subset_indices(i, 0);
-                for twist in ALL_TWISTS {
-                    chunk[twist as usize] = (twist * obj).xy_prm_index() as u16;
+                for (column, twist) in H0_TWISTS.into_iter().enumerate() {
+                    row[column] = (twist * obj).xy_prm_index() as u16;
                 }
             });
         subset_e_z_prm
-            .par_chunks_mut(COUNT)
+            .par_iter_mut()
             .enumerate()
-            .for_each(|(i, chunk)| {
+            .for_each(|(i, row)| {
                 let obj = Edges::from_subset_indices(0, i);
-                for twist in ALL_TWISTS {
-                    chunk[twist as usize] = (twist * obj).loc_prm(Axis::Z).prm() as u8;
+                for (column, twist) in H0_TWISTS.into_iter().enumerate() {
+                    row[column] = (twist * obj).loc_prm(Axis::Z).prm() as u8;
                 }
             });
-        Self { subset_e_xy_prm, subset_e_z_prm }
+        Self { c_prm, subset_e_xy_prm, subset_e_z_prm }
+    }
+
+    // The following functions panic if the twist is not in H0.
+    pub fn twisted_c_prm(&self, c_prm: CPrm, twist: Twist) -> CPrm {
+        CPrm::from_table(self.c_prm[c_prm.index()][COLUMN[twist as usize]])
+    }
+    pub fn twisted_xy_prm(&self, e_xy_prm: ENonSlicePrm, twist: Twist) -> ENonSlicePrm {
+        ENonSlicePrm::from_table(self.subset_e_xy_prm[e_xy_prm.index()][COLUMN[twist as usize]])
+    }
+    pub fn twisted_z_prm(&self, e_z_prm: ESlicePrm, twist: Twist) -> ESlicePrm {
+        ESlicePrm::from_table(self.subset_e_z_prm[e_z_prm.index()][COLUMN[twist as usize]] as u16)
+    }
+
+    const BYTE_SIZE: usize = COUNT * (2 * Corners::PRM_SIZE + 2 * factorial(8) + factorial(4));
+
+    /// Serializes the move tables, the permutations of 8 elements as little endian u16 values.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::BYTE_SIZE);
+        for value in self.c_prm.iter().chain(&self.subset_e_xy_prm).flatten() {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend(self.subset_e_z_prm.iter().flatten());
+        bytes
     }
 
-    pub fn twisted_xy_prm(&self, e_xy_prm: usize, twist: Twist) -> usize {
-        self.subset_e_xy_prm[e_xy_prm * COUNT + twist as usize] as usize
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, TableFileError> {
+        if bytes.len() != Self::BYTE_SIZE {
+            return Err(TableFileError::PayloadSize { expected: Self::BYTE_SIZE as u64, found: bytes.len() as u64 });
+        }
+        let (u16_bytes, z_bytes) = bytes.split_at(2 * COUNT * (Corners::PRM_SIZE + factorial(8)));
+        let mut values = u16_bytes.chunks_exact(2).map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]));
+        let mut rows = |size: usize| -> Vec<[u16; COUNT]> {
+            (0..size).map(|_| std::array::from_fn(|_| values.next().unwrap())).collect()
+        };
+        let c_prm = rows(Corners::PRM_SIZE);
+        let subset_e_xy_prm = rows(factorial(8));
+        let subset_e_z_prm = z_bytes.chunks_exact(COUNT).map(|row| row.try_into().unwrap()).collect();
+        Ok(Self { c_prm, subset_e_xy_prm, subset_e_z_prm })
     }
-    pub fn twisted_z_prm(&self, e_z_prm: usize, twist: Twist) -> usize {
-        self.subset_e_z_prm[e_z_prm * COUNT + twist as usize] as usize
+}
+
+impl Default for SubsetTwister {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -50,4 +114,41 @@ pub static SUBSET_TWISTER: std::sync::LazyLock<SubsetTwister> = std::sync::LazyL
 pub fn init_subset_twister() {
     std::sync::LazyLock::force(&SUBSET_TWISTER);
 }
-// TODO: Add tests for subset_twister
\ No newline at end of file
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::twist_generator::*;
+
+    #[test]
+    fn test_twisted() {
+        let twister = SubsetTwister::new();
+        let mut rnd = RandomTwistGen::new(42, &H0_TWISTS);
+        let mut corners = Corners::solved();
+        let mut edges = Edges::solved();
+        for _ in 0..1_000 {
+            let twist = rnd.gen_twist();
+            let c_prm = twister.twisted_c_prm(CPrm::new(corners.prm_index()), twist);
+            let xy_prm = twister.twisted_xy_prm(ENonSlicePrm::new(edges.xy_prm_index()), twist);
+            let z_prm = twister.twisted_z_prm(ESlicePrm::new(edges.loc_prm(Axis::Z).prm()), twist);
+            corners = twist * corners;
+            edges = twist * edges;
+            assert_eq!(c_prm.index(), corners.prm_index());
+            assert_eq!(xy_prm.index(), edges.xy_prm_index());
+            assert_eq!(z_prm.index(), edges.loc_prm(Axis::Z).prm());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_non_h0_twist() {
+        SUBSET_TWISTER.twisted_c_prm(CPrm::new(0), Twist::L1);
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let bytes = SUBSET_TWISTER.to_bytes();
+        assert_eq!(SubsetTwister::from_bytes(&bytes).unwrap().to_bytes(), bytes);
+        assert!(SubsetTwister::from_bytes(&bytes[1..]).is_err());
+    }
+}
@@ -0,0 +1,45 @@
+use super::{Twister, SubsetTwister, TWISTER, SUBSET_TWISTER};
+
+/// The move tables used to twist cube coordinates.
+///
+/// The `Twistable` impls use the lazily initialized globals `TWISTER` and `SUBSET_TWISTER`.
+/// A `TwistContext` allows to use move tables that are owned, e.g. loaded from bytes, instead.
+#[derive(Clone, Copy)]
+pub struct TwistContext<'a> {
+    pub twister: &'a Twister,
+    pub subset_twister: &'a SubsetTwister,
+}
+
+impl<'a> TwistContext<'a> {
+    pub fn new(twister: &'a Twister, subset_twister: &'a SubsetTwister) -> Self {
+        Self { twister, subset_twister }
+    }
+}
+
+impl TwistContext<'static> {
+    /// The context of the global move tables. Initializes them if needed.
+    pub fn global() -> Self {
+        Self::new(&TWISTER, &SUBSET_TWISTER)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cube, SubsetCube, Twistable};
+    use crate::twist_generator::*;
+    use crate::twist::*;
+
+    #[test]
+    fn test_owned_context() {
+        let twister = Twister::from_bytes(&TWISTER.to_bytes()).unwrap();
+        let subset_twister = SubsetTwister::from_bytes(&SUBSET_TWISTER.to_bytes()).unwrap();
+        let context = TwistContext::new(&twister, &subset_twister);
+
+        let twists = RandomTwistGen::new(42, &ALL_TWISTS).gen_twists(100);
+        assert_eq!(Cube::solved().twisted_by_in(&context, &twists), Cube::solved().twisted_by(&twists));
+
+        let twists = RandomTwistGen::new(43, &H0_TWISTS).gen_twists(100);
+        assert!(SubsetCube::solved().twisted_by_in(&context, &twists) == SubsetCube::solved().twisted_by(&twists));
+    }
+}
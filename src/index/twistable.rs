@@ -0,0 +1,64 @@
+use crate::corners::*;
+use crate::edges::*;
+use crate::twist::*;
+
+pub trait Twistable: Sized + Copy {
+    fn twisted(&self, twist: Twist) -> Self;
+
+    fn twisted_by(&self, twists: &[Twist]) -> Self {
+        twists
+            .iter()
+            .fold(*self, |cube, &twist| cube.twisted(twist))
+    }
+}
+
+/// Twisting in place, e.g. for trackers that follow a cube twist by twist.
+pub trait TwistableMut {
+    fn twist_in_place(&mut self, twist: Twist);
+
+    fn twist_seq(&mut self, twists: &[Twist]) {
+        for &twist in twists {
+            self.twist_in_place(twist);
+        }
+    }
+}
+
+impl<T: Twistable> TwistableMut for T {
+    #[inline(always)]
+    fn twist_in_place(&mut self, twist: Twist) {
+        *self = self.twisted(twist);
+    }
+}
+
+impl TwistableMut for Corners {
+    fn twist_in_place(&mut self, twist: Twist) {
+        *self = twist * *self;
+    }
+}
+
+impl TwistableMut for Edges {
+    fn twist_in_place(&mut self, twist: Twist) {
+        *self = twist * *self;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::twist_generator::*;
+
+    #[test]
+    fn test_twist_in_place() {
+        let twists = RandomTwistGen::new(42, &ALL_TWISTS).gen_twists(100);
+        let mut cube = crate::Cube::solved();
+        cube.twist_seq(&twists);
+        assert_eq!(cube, crate::Cube::solved().twisted_by(&twists));
+
+        let mut corners = Corners::solved();
+        let mut edges = Edges::solved();
+        corners.twist_seq(&twists);
+        edges.twist_seq(&twists);
+        assert_eq!(corners, Corners::twists(&twists));
+        assert_eq!(edges, Edges::twists(&twists));
+    }
+}
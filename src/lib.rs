@@ -1,11 +1,107 @@
+//! Without the default `std` feature, the crate is `no_std` + `alloc` and only has the cube representation of `cubies`
+//! and `Cube` with its coordinates, which then twists through its corners and edges instead of the move tables.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
 pub mod cubies;
 pub mod index;
+#[cfg(feature = "std")]
 pub mod table;
+#[cfg(feature = "std")]
+mod parallel;
+#[cfg(feature = "std")]
 pub mod process_tuning;
+#[cfg(feature = "std")]
+mod properties;
+#[cfg(feature = "std")]
 pub mod two_phase;
+#[cfg(feature = "std")]
+pub mod coset_solver;
+// Not re-exported, since `Solver::solve` would shadow `MaskedSolver::solve` wherever both are in scope.
+#[cfg(feature = "std")]
+pub mod solver;
+#[cfg(feature = "std")]
+pub mod solver_pool;
+#[cfg(feature = "std")]
+pub mod masked_solver;
+#[cfg(feature = "std")]
+pub mod bandaged;
+#[cfg(feature = "std")]
+pub mod f2l;
+#[cfg(feature = "std")]
+pub mod roux;
+#[cfg(feature = "std")]
+pub mod fmc;
+#[cfg(feature = "std")]
+pub mod corners_explorer;
+#[cfg(feature = "std")]
+pub mod distance_scramble;
+#[cfg(feature = "std")]
+pub mod scramble_check;
+#[cfg(feature = "std")]
+pub mod alg_search;
+#[cfg(feature = "std")]
+pub mod big_cubes;
+#[cfg(feature = "puzzles")]
+pub mod puzzles;
+#[cfg(feature = "std")]
+pub mod render;
+#[cfg(feature = "smartcube")]
+pub mod smartcube;
+#[cfg(feature = "std")]
+pub mod tracker;
+#[cfg(feature = "std")]
+pub mod robot;
+#[cfg(feature = "std")]
+pub mod session;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 pub use cubies::*;
 pub use index::*;
+#[cfg(feature = "std")]
 pub use table::*;
+#[cfg(feature = "std")]
 pub use process_tuning::*;
+#[cfg(feature = "std")]
 pub use two_phase::*;
+#[cfg(feature = "std")]
+pub use coset_solver::*;
+#[cfg(feature = "std")]
+pub use solver_pool::*;
+#[cfg(feature = "std")]
+pub use masked_solver::*;
+#[cfg(feature = "std")]
+pub use bandaged::*;
+#[cfg(feature = "std")]
+pub use f2l::*;
+#[cfg(feature = "std")]
+pub use roux::*;
+#[cfg(feature = "std")]
+pub use fmc::*;
+#[cfg(feature = "std")]
+pub use corners_explorer::*;
+#[cfg(feature = "std")]
+pub use distance_scramble::*;
+#[cfg(feature = "std")]
+pub use scramble_check::*;
+#[cfg(feature = "std")]
+pub use alg_search::*;
+#[cfg(feature = "std")]
+pub use big_cubes::*;
+#[cfg(feature = "puzzles")]
+pub use puzzles::*;
+#[cfg(feature = "std")]
+pub use render::*;
+#[cfg(feature = "smartcube")]
+pub use smartcube::*;
+#[cfg(feature = "std")]
+pub use tracker::*;
+#[cfg(feature = "std")]
+pub use robot::*;
+#[cfg(feature = "std")]
+pub use session::*;
+#[cfg(feature = "wasm")]
+pub use wasm::*;
@@ -1,43 +1,719 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use indicatif::{ProgressBar, ProgressStyle};
 use rubikscube::*;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::Duration;
 
-fn main() {
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() < 2 {
-        eprintln!("Usage: {} <path_to_pos_file>", args[0]);
-        std::process::exit(1);
+#[derive(Parser)]
+#[command(about = "Two-phase solver for the 3x3x3 Rubik's cube")]
+struct Cli {
+    /// Print the results as JSON.
+    #[cfg(feature = "serde")]
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+impl Cli {
+    fn json(&self) -> bool {
+        #[cfg(feature = "serde")]
+        return self.json;
+        #[cfg(not(feature = "serde"))]
+        return false;
     }
-    let pos_file_path = &args[1];
+}
 
-    init_twister();
-    init_subset_twister();
-    init_subset_index();
+#[cfg(feature = "serde")]
+fn format_output(output: &(impl std::fmt::Display + serde::Serialize), json: bool) -> String {
+    if json {
+        serde_json::to_string(output).unwrap()
+    } else {
+        output.to_string()
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+fn format_output(output: &impl std::fmt::Display, _json: bool) -> String {
+    output.to_string()
+}
+
+#[cfg(feature = "serde")]
+fn format_error(err: &str, json: bool) -> String {
+    if json {
+        serde_json::json!({ "error": err }).to_string()
+    } else {
+        format!("Error: {err}")
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+fn format_error(err: &str, _json: bool) -> String {
+    format!("Error: {err}")
+}
+
+#[cfg(feature = "serde")]
+fn print_output(output: &(impl std::fmt::Display + serde::Serialize), json: bool) {
+    println!("{}", format_output(output, json));
+}
+
+#[cfg(not(feature = "serde"))]
+fn print_output(output: &impl std::fmt::Display, json: bool) {
+    println!("{}", format_output(output, json));
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solve a cube state and print the solution in WCA notation.
+    Solve(SolveArgs),
+    /// Solve every position of a file and print timings and statistics.
+    Bench {
+        /// File with a space-separated sequence of twists on each line.
+        pos_file: String,
+    },
+    /// Generate random-state scrambles in WCA notation.
+    Scramble(ScrambleArgs),
+    /// Read a scramble or facelet string per line from stdin and write a solution per line to stdout.
+    Pipe {
+        /// Maximum number of twists of the solutions.
+        #[arg(long, default_value_t = 20)]
+        max_length: u8,
+    },
+    /// Solve uniformly random cube states and print the distribution of solution lengths and solve times as CSV.
+    Analysis(AnalysisArgs),
+    /// Manage the lookup tables.
+    Tables {
+        #[command(flatten)]
+        paths: TablePaths,
+        #[command(subcommand)]
+        command: TablesCommand,
+    },
+}
+
+#[derive(Args)]
+struct ScrambleArgs {
+    /// Number of scrambles.
+    #[arg(short = 'n', long, default_value_t = 1)]
+    count: usize,
+    /// Seed of the random cube states [default: random]
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Size of the cube: random-state scrambles for 3, random-move scrambles with wide twists for 4 to 7.
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u8).range(3..=7))]
+    size: u8,
+}
+
+#[derive(Args)]
+struct AnalysisArgs {
+    /// Number of random cube states.
+    #[arg(short = 'n', long, default_value_t = 1000)]
+    count: usize,
+    /// Seed of the random cube states [default: random]
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Maximum number of twists of the first solution of each state; states without one are counted as unsolved.
+    #[arg(long, default_value_t = 20)]
+    max_length: u8,
+    /// Seconds per state; after each solution the search continues for a shorter one until this runs out.
+    #[arg(long, default_value_t = 1.0)]
+    max_time: f64,
+    /// Phase 1 nodes per state, over all of its searches [default: no limit]
+    #[arg(long)]
+    max_nodes: Option<usize>,
+}
+
+#[derive(Args)]
+struct TablePaths {
+    /// Config file with the table paths [default: config.txt, if it exists]
+    #[arg(long, global = true)]
+    config: Option<String>,
+    /// Path of the corners table, overrides the config file.
+    #[arg(long, global = true)]
+    corners: Option<String>,
+    /// Path of the subset table, overrides the config file.
+    #[arg(long, global = true)]
+    subset: Option<String>,
+    /// Path of the coset table, overrides the config file.
+    #[arg(long, global = true)]
+    coset: Option<String>,
+    /// Only manage the given tables [default: all]
+    #[arg(long, global = true, value_enum, value_delimiter = ',')]
+    only: Vec<TableName>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum TableName {
+    Corners,
+    Subset,
+    Coset,
+}
+
+#[derive(Subcommand)]
+enum TablesCommand {
+    /// Create the missing tables and save them.
+    Generate {
+        /// Also recreate tables that already exist.
+        #[arg(long)]
+        force: bool,
+        /// Store one entry of the subset table for a cube and its inverse, which about halves it.
+        #[arg(long)]
+        inverse_reduced: bool,
+    },
+    /// Check the checksums and the distance histograms of the tables.
+    Verify,
+    /// Print the headers of the tables.
+    Info,
+}
+
+#[derive(Args)]
+struct SolveArgs {
+    /// Scramble in WCA notation, e.g. "R U R' U'".
+    #[arg(required_unless_present = "facelets", conflicts_with = "facelets")]
+    scramble: Option<String>,
+    /// Cube state as a facelet string in URFDLB order.
+    #[arg(long)]
+    facelets: Option<String>,
+    /// Maximum number of twists of the solution.
+    #[arg(long, default_value_t = 20)]
+    max_length: u8,
+    /// Give up after this many seconds.
+    #[arg(long)]
+    max_time: Option<f64>,
+    /// Explain the solution: the phase of each twist and the table distances that the search looked up.
+    #[arg(long)]
+    annotate: bool,
+    /// Solve all 24 orientations of the cube and keep the shortest solution.
+    #[arg(long, conflicts_with = "annotate")]
+    orientation_neutral: bool,
+    /// Setup moves in WCA notation, done before the scramble; the printed solution includes them.
+    #[arg(long, conflicts_with_all = ["annotate", "orientation_neutral"])]
+    premoves: Option<String>,
+    /// Also print the solution rewritten for a human, with rotations and wide moves where they avoid awkward twists.
+    #[arg(long)]
+    ergonomic: bool,
+}
+
+fn exit_with(err: impl std::fmt::Display) -> ! {
+    eprintln!("{}", err);
+    std::process::exit(1);
+}
+
+fn load_tables() -> TableSet {
+    TableSet::builder()
+        .config_file("config.txt")
+        .build()
+        .unwrap_or_else(|err| exit_with(err))
+}
+
+// Some fields are only printed as JSON.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(not(feature = "serde"), allow(dead_code))]
+struct SolveOutput {
+    cube: Cube,
+    solution: Solution,
+    seconds: f64,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    ergonomic: Option<String>,
+}
+
+impl std::fmt::Display for SolveOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.solution.alg)?;
+        if let Some(ergonomic) = &self.ergonomic {
+            write!(f, "\nErgonomic: {ergonomic}")?;
+        }
+        if let Some(annotation) = &self.solution.annotation {
+            write!(f, "\n{annotation}")?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_scramble(scramble: &str) -> Result<Cube, String> {
+    Ok(Cube::solved().twisted_by(&normalize_moves(scramble)?.0))
+}
+
+fn parse_facelets(facelets: &str) -> Result<Cube, String> {
+    facelets
+        .parse()
+        .and_then(|facelets| Cube::from_facelets(&facelets))
+        .map_err(|err| err.to_string())
+}
+
+fn solve(args: SolveArgs, json: bool) {
+    let cube = match (&args.scramble, &args.facelets) {
+        (Some(scramble), _) => parse_scramble(scramble),
+        (None, Some(facelets)) => parse_facelets(facelets),
+        (None, None) => unreachable!("clap requires a scramble or facelets"),
+    }
+    .unwrap_or_else(|err| exit_with(err));
+    let premoves = args
+        .premoves
+        .as_deref()
+        .map(parse_twist_sequence)
+        .transpose()
+        .unwrap_or_else(|err| exit_with(err));
+
+    let tables = load_tables();
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            let mut solver = TwoPhaseSolver::with_tables(&tables);
+            let start = std::time::Instant::now();
+            let result = if args.annotate {
+                solver.solve_annotated(cube, args.max_length)
+            } else if args.orientation_neutral {
+                solver.solve_orientation_neutral(cube, args.max_length)
+            } else if let Some(premoves) = &premoves {
+                solver.solve_with_premoves(cube, premoves, args.max_length)
+            } else {
+                solver.solve_with_stats(cube, args.max_length)
+            };
+            // The receiver is gone if the time limit passed.
+            let _ = sender.send(result.map(|solution| (solution, start.elapsed())));
+        });
+        let timeout = args.max_time.map_or(Duration::MAX, Duration::from_secs_f64);
+        match receiver.recv_timeout(timeout) {
+            Ok(Ok((solution, time))) => {
+                let ergonomic = args
+                    .ergonomic
+                    .then(|| held_moves_to_wca(&ergonomic_moves(solution.alg.to_linear().twists(), &ErgonomicCosts::default())));
+                print_output(&SolveOutput { cube, solution, seconds: time.as_secs_f64(), ergonomic }, json)
+            }
+            Ok(Err(err)) => exit_with(err),
+            Err(RecvTimeoutError::Timeout) => exit_with("No solution found within the time limit"),
+            Err(RecvTimeoutError::Disconnected) => exit_with("The solver failed"),
+        }
+    });
+}
+
+// Some fields are only printed as JSON.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(not(feature = "serde"), allow(dead_code))]
+struct ScrambleOutput {
+    seed: u64,
+    scrambles: Vec<String>,
+}
+
+impl std::fmt::Display for ScrambleOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.scrambles.join("\n"))
+    }
+}
+
+fn scramble(args: ScrambleArgs, json: bool) {
+    let seed = args.seed.unwrap_or_else(rand::random);
+    if args.size > 3 {
+        let mut generator = NxNScrambleGen::new(args.size as usize, seed);
+        let scrambles = (0..args.count).map(|_| generator.gen_scramble()).collect();
+        return print_output(&ScrambleOutput { seed, scrambles }, json);
+    }
+    let mut states = RandomStateGen::new(seed);
+    let tables = load_tables();
+    let solver = TwoPhaseSolver::with_tables(&tables);
+    let scrambles = (0..args.count)
+        .map(|_| random_state_scramble(&solver, &mut states).unwrap_or_else(|err| exit_with(err)).to_string())
+        .collect();
+    print_output(&ScrambleOutput { seed, scrambles }, json);
+}
+
+/// Solves a line of the pipe protocol, which is a facelet string if it has no spaces and 54 characters, or a scramble otherwise.
+fn solve_line(solver: &mut TwoPhaseSolver, line: &str, max_length: u8) -> Result<SolveOutput, String> {
+    let line = line.trim();
+    let cube = if line.len() == 54 && !line.contains(' ') { parse_facelets(line)? } else { parse_scramble(line)? };
+    let start = std::time::Instant::now();
+    let solution = solver.solve_with_stats(cube, max_length)?;
+    Ok(SolveOutput { cube, solution, seconds: start.elapsed().as_secs_f64(), ergonomic: None })
+}
+
+fn pipe(max_length: u8, json: bool) {
+    use std::io::{BufRead, Write};
+
+    let tables = load_tables();
+    let mut solver = TwoPhaseSolver::with_tables(&tables);
+    let mut stdout = std::io::stdout().lock();
+    for line in std::io::stdin().lock().lines() {
+        let line = line.unwrap_or_else(|err| exit_with(err));
+        // Errors take the line of their solution, so that output lines match input lines.
+        let output = match solve_line(&mut solver, &line, max_length) {
+            Ok(output) => format_output(&output, json),
+            Err(err) => format_error(&err, json),
+        };
+        // Stop quietly when the reader closed the pipe.
+        if writeln!(stdout, "{output}").and_then(|_| stdout.flush()).is_err() {
+            return;
+        }
+    }
+}
+
+// Some fields are only printed as JSON.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(not(feature = "serde"), allow(dead_code))]
+struct BenchOutput {
+    positions: usize,
+    total_seconds: f64,
+    average_seconds: f64,
+    stats: SolveStats,
+}
+
+impl std::fmt::Display for BenchOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Total time taken: {:?}", Duration::from_secs_f64(self.total_seconds))?;
+        writeln!(f, "Average time per solve: {:?}", Duration::from_secs_f64(self.average_seconds))?;
+        write!(f, "{}", self.stats)
+    }
+}
+
+fn bench(pos_file_path: &str, json: bool) {
     pin_process_to_core().unwrap_or_else(|err| eprintln!("Warning: could not pin process to one core: {err}"));
     set_process_priority().unwrap_or_else(|err| eprintln!("Warning: could not raise process priority: {err}"));
 
     let twist_sequences = read_twist_file(pos_file_path);
-    assert!(twist_sequences.len() > 0, "No twist sequences found in the file!");
+    assert!(!twist_sequences.is_empty(), "No twist sequences found in the file!");
     let positions = Vec::from_iter(twist_sequences.iter().map(|twists| Cube::solved().twisted_by(twists)));
 
-    let (corners_table, subset_table, coset_table) = get_tables();
+    let tables = load_tables();
+    // The stats are printed, so phase 2 is timed apart from phase 1.
+    let mut solver = TwoPhaseSolver::with_tables(&tables).with_options(SolveOptions { collect_timing: true, ..SolveOptions::default() });
 
-    let mut solver = TwoPhaseSolver::new(
-        &coset_table,
-        &subset_table,
-        &corners_table,
-    );
-        
     let mut total_time = std::time::Duration::ZERO;
+    let mut stats = SolveStats::default();
     for (i, cube) in positions.iter().enumerate() {
         let start = std::time::Instant::now();
-        let solution = solver.solve(*cube, 20).unwrap();
+        let solution = solver.solve_with_stats(*cube, 20).unwrap();
         let elapsed = start.elapsed();
         total_time += elapsed;
+        stats += &solution.stats;
 
         // Verify solution
-        assert!(cube.twisted_by(&solution) == Cube::solved(), "Incorrect solution found on line {}! Solution: {:?}", i + 1, solution);
+        assert!(cube.twisted_by(&solution.alg) == Cube::solved(), "Incorrect solution found on line {}! Solution: {}", i + 1, solution.alg);
     }
 
-    println!("Total time taken: {:?}", total_time);
-    println!("Average time per solve: {:?}", total_time / twist_sequences.len() as u32);
-    solver.print_stats();
+    let output = BenchOutput {
+        positions: positions.len(),
+        total_seconds: total_time.as_secs_f64(),
+        average_seconds: total_time.as_secs_f64() / positions.len() as f64,
+        stats,
+    };
+    print_output(&output, json);
+}
+
+/// Number of solutions of a length, and percentiles of their solve times in seconds.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+struct LengthRow {
+    length: RowLength,
+    count: usize,
+    p50_seconds: f64,
+    p90_seconds: f64,
+    p99_seconds: f64,
+    max_seconds: f64,
+}
+
+/// The states of a row of the analysis.
+enum RowLength {
+    /// The states of a solution length.
+    Length(usize),
+    /// The states without a solution within the maximum length and the budget.
+    Unsolved,
+    /// All states.
+    All,
+}
+
+impl std::fmt::Display for RowLength {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RowLength::Length(length) => write!(f, "{length}"),
+            RowLength::Unsolved => write!(f, "unsolved"),
+            RowLength::All => write!(f, "all"),
+        }
+    }
+}
+
+// A number for the lengths and a string otherwise, like in the CSV.
+#[cfg(feature = "serde")]
+impl serde::Serialize for RowLength {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            RowLength::Length(length) => serializer.serialize_u64(*length as u64),
+            _ => serializer.collect_str(self),
+        }
+    }
+}
+
+impl LengthRow {
+    fn new(length: RowLength, times: &mut [f64]) -> Self {
+        times.sort_by(f64::total_cmp);
+        let percentile = |fraction: f64| times[((times.len() - 1) as f64 * fraction).round() as usize];
+        LengthRow {
+            length,
+            count: times.len(),
+            p50_seconds: percentile(0.5),
+            p90_seconds: percentile(0.9),
+            p99_seconds: percentile(0.99),
+            max_seconds: percentile(1.0),
+        }
+    }
+}
+
+// Some fields are only printed as JSON.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(not(feature = "serde"), allow(dead_code))]
+struct AnalysisOutput {
+    seed: u64,
+    max_length: u8,
+    rows: Vec<LengthRow>,
+}
+
+impl std::fmt::Display for AnalysisOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "length,count,p50_seconds,p90_seconds,p99_seconds,max_seconds")?;
+        for row in &self.rows {
+            write!(f, "\n{},{},{},{},{},{}", row.length, row.count, row.p50_seconds, row.p90_seconds, row.p99_seconds, row.max_seconds)?;
+        }
+        Ok(())
+    }
+}
+
+fn analysis(args: AnalysisArgs, json: bool) {
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let mut states = RandomStateGen::new(seed);
+    let tables = load_tables();
+    let max_time = Duration::from_secs_f64(args.max_time);
+
+    let bar = ProgressBar::new(args.count as u64)
+        .with_style(ProgressStyle::with_template("Solving [{bar:40}] {pos}/{len} {elapsed_precise}").unwrap().progress_chars("=> "));
+    // The times of the states by the length of their shortest solution found.
+    let mut times: Vec<Vec<f64>> = Vec::new();
+    let mut unsolved_times = Vec::new();
+    for _ in 0..args.count {
+        let (corners, edges) = states.gen_cubies();
+        let cube = Cube::from_cubies(&corners, &edges);
+        let start = std::time::Instant::now();
+        let length = shortest_solution_length(&tables, cube, args.max_length, max_time, args.max_nodes);
+        let seconds = start.elapsed().as_secs_f64();
+        match length {
+            Some(length) => {
+                if times.len() <= length {
+                    times.resize(length + 1, Vec::new());
+                }
+                times[length].push(seconds);
+            }
+            None => unsolved_times.push(seconds),
+        }
+        bar.inc(1);
+    }
+    bar.finish_and_clear();
+
+    let mut rows: Vec<LengthRow> = times
+        .iter_mut()
+        .enumerate()
+        .filter(|(_, times)| !times.is_empty())
+        .map(|(length, times)| LengthRow::new(RowLength::Length(length), times))
+        .collect();
+    if !unsolved_times.is_empty() {
+        rows.push(LengthRow::new(RowLength::Unsolved, &mut unsolved_times));
+    }
+    if args.count > 0 {
+        let mut all_times = times.concat();
+        all_times.extend(unsolved_times);
+        rows.push(LengthRow::new(RowLength::All, &mut all_times));
+    }
+    print_output(&AnalysisOutput { seed, max_length: args.max_length, rows }, json);
+}
+
+/// The length of the shortest solution found within `max_length`, searching for a shorter one after each solution
+/// until the search fails or the budget of the state runs out. None if there is no solution within the budget.
+fn shortest_solution_length(tables: &TableSet, cube: Cube, max_length: u8, max_time: Duration, max_nodes: Option<usize>) -> Option<usize> {
+    let deadline = std::time::Instant::now() + max_time;
+    let mut nodes = 0;
+    let mut shortest = None;
+    let mut max_length = max_length;
+    loop {
+        let options = SolveOptions {
+            max_time: Some(deadline.saturating_duration_since(std::time::Instant::now())),
+            max_nodes: max_nodes.map(|max_nodes| max_nodes.saturating_sub(nodes)),
+            ..SolveOptions::default()
+        };
+        let mut solver = TwoPhaseSolver::with_tables(tables).with_options(options);
+        let Ok(solution) = solver.solve_with_stats(cube, max_length) else {
+            return shortest;
+        };
+        nodes += solution.stats.phase_1_probes;
+        let length = solution.alg.twists().len();
+        shortest = Some(length);
+        if length == 0 {
+            return shortest;
+        }
+        max_length = length as u8 - 1;
+    }
+}
+
+impl TablePaths {
+    /// Returns the paths of the corners, subset and coset table.
+    fn resolve(&self) -> [String; 3] {
+        let mut builder = TableSet::builder();
+        match &self.config {
+            Some(config) => builder = builder.config_file(config),
+            None if std::path::Path::new("config.txt").exists() => builder = builder.config_file("config.txt"),
+            None => {}
+        }
+        if let Some(path) = &self.corners {
+            builder = builder.corners_path(path);
+        }
+        if let Some(path) = &self.subset {
+            builder = builder.subset_path(path);
+        }
+        if let Some(path) = &self.coset {
+            builder = builder.coset_path(path);
+        }
+        builder.resolve_paths().unwrap_or_else(|err| exit_with(err))
+    }
+
+    fn includes(&self, table: TableName) -> bool {
+        self.only.is_empty() || self.only.contains(&table)
+    }
+}
+
+fn spinner(message: String) -> ProgressBar {
+    let spinner = ProgressBar::new_spinner().with_message(message);
+    spinner.enable_steady_tick(Duration::from_millis(100));
+    spinner
+}
+
+fn generate_table<T>(
+    name: &str,
+    path: &str,
+    index_size: usize,
+    force: bool,
+    create: impl FnOnce(&mut dyn FnMut(u8, u64)) -> T,
+    to_file: impl Fn(&T, &str) -> Result<(), TableFileError>,
+) {
+    if !force && std::path::Path::new(path).exists() {
+        println!("The {name} table already exists at '{path}'");
+        return;
+    }
+    let bar = ProgressBar::new(index_size as u64).with_style(
+        ProgressStyle::with_template("{msg:32} [{bar:40}] {percent:>3}% {elapsed_precise}")
+            .unwrap()
+            .progress_chars("=> "),
+    );
+    let table = create(&mut |distance, reached| {
+        bar.set_message(format!("Creating {name} table, distance {distance}"));
+        bar.set_position(reached);
+    });
+    bar.finish_and_clear();
+
+    let spinner = spinner(format!("Saving the {name} table to '{path}'"));
+    to_file(&table, path).unwrap_or_else(|err| exit_with(err));
+    spinner.finish_and_clear();
+    println!("Created the {name} table at '{path}'");
+}
+
+fn verify_table<T>(
+    name: &str,
+    path: &str,
+    from_file: impl Fn(&str) -> Result<T, TableFileError>,
+    check: impl Fn(&T) -> VerificationReport,
+) -> bool {
+    let spinner = spinner(format!("Verifying the {name} table at '{path}'"));
+    let result = from_file(path).map(|table| check(&table));
+    spinner.finish_and_clear();
+    match result {
+        Ok(report) if report.is_ok() => {
+            println!("The {name} table at '{path}' is OK");
+            true
+        }
+        Ok(report) => {
+            println!("The {name} table at '{path}' is corrupt:\n{report}");
+            false
+        }
+        Err(err) => {
+            println!("The {name} table at '{path}' is invalid: {err}");
+            false
+        }
+    }
+}
+
+fn print_table_info(name: &str, path: &str) {
+    println!("{name} table: {path}");
+    let header = match read_table_header(path) {
+        Ok(header) => header,
+        Err(err) => {
+            println!("  {err}");
+            return;
+        }
+    };
+    if let Ok(metadata) = std::fs::metadata(path) {
+        println!("  File size:    {} bytes", metadata.len());
+    }
+    println!("  Kind:         {:?}", header.kind);
+    println!("  Twists:       {}", to_wca(&header.twists.iter().collect::<Vec<_>>()));
+    println!("  Index size:   {}", header.index_size);
+    println!("  Max distance: {}", header.max_distance);
+    println!("  SHA-256:      {}", header.checksum.iter().map(|b| format!("{b:02x}")).collect::<String>());
+}
+
+fn tables(paths: TablePaths, command: TablesCommand) {
+    let [corners, subset, coset] = paths.resolve();
+    match command {
+        TablesCommand::Generate { force, inverse_reduced } => {
+            if paths.includes(TableName::Corners) {
+                generate_table("corners", &corners, Cube::CORNER_INDEX_SIZE, force, |progress| create_corners_table_with_progress(progress), DistanceTable::to_file);
+            }
+            if paths.includes(TableName::Subset) {
+                if inverse_reduced {
+                    generate_table("subset", &subset, SubsetCube::REDUCED_INDEX_SIZE, force, |progress| create_inverse_reduced_subset_table_with_progress(progress), DistanceTable::to_file);
+                } else {
+                    generate_table("subset", &subset, SubsetCube::INDEX_SIZE, force, |progress| create_subset_table_with_progress(progress), DistanceTable::to_file);
+                }
+            }
+            if paths.includes(TableName::Coset) {
+                generate_table("coset", &coset, Cube::COSETS_INDEX_SIZE, force, |progress| create_coset_table_with_progress(progress), DirectionsTable::to_file);
+            }
+        }
+        TablesCommand::Verify => {
+            let mut ok = true;
+            if paths.includes(TableName::Corners) {
+                ok &= verify_table("corners", &corners, DistanceTable::from_file, check_corners_table);
+            }
+            if paths.includes(TableName::Subset) {
+                ok &= verify_table("subset", &subset, DistanceTable::from_file, check_subset_table);
+            }
+            if paths.includes(TableName::Coset) {
+                ok &= verify_table("coset", &coset, DirectionsTable::from_file, check_coset_table);
+            }
+            if !ok {
+                std::process::exit(1);
+            }
+        }
+        TablesCommand::Info => {
+            if paths.includes(TableName::Corners) {
+                print_table_info("Corners", &corners);
+            }
+            if paths.includes(TableName::Subset) {
+                print_table_info("Subset", &subset);
+            }
+            if paths.includes(TableName::Coset) {
+                print_table_info("Coset", &coset);
+            }
+        }
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    init_twister();
+    init_subset_twister();
+    init_subset_index();
+
+    let json = cli.json();
+    match cli.command {
+        Command::Solve(args) => solve(args, json),
+        Command::Bench { pos_file } => bench(&pos_file, json),
+        Command::Scramble(args) => scramble(args, json),
+        Command::Pipe { max_length } => pipe(max_length, json),
+        Command::Analysis(args) => analysis(args, json),
+        Command::Tables { paths, command } => tables(paths, command),
+    }
 }
@@ -0,0 +1,455 @@
+use crate::*;
+
+/// The pieces that a partial solve cares about, e.g. the cross or a 2x2x2 block.
+/// All other pieces are ignored. Pieces can be solved completely, or only placed with their orientation ignored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PieceMask {
+    corners: u8,
+    corner_orientations: u8,
+    edges: u16,
+    edge_orientations: u16,
+}
+
+impl PieceMask {
+    pub const NONE: Self = Self { corners: 0, corner_orientations: 0, edges: 0, edge_orientations: 0 };
+    pub const ALL: Self = Self { corners: 0xFF, corner_orientations: 0xFF, edges: 0xFFF, edge_orientations: 0xFFF };
+
+    /// The edges DB, DF, DR and DL.
+    pub const CROSS: Self = Self::NONE.with_edge(2).with_edge(3).with_edge(6).with_edge(7);
+
+    /// The corner DFL and the edges DF, DL and FL.
+    pub const BLOCK_2X2X2: Self = Self::NONE.with_corner(4).with_edge(3).with_edge(7).with_edge(8);
+
+    /// The first two layers except the DFR corner and the FR edge.
+    pub const F2L_MINUS_ONE: Self = Self::CROSS
+        .with_corner(4)
+        .with_corner(6)
+        .with_corner(7)
+        .with_edge(8)
+        .with_edge(10)
+        .with_edge(11);
+
+    /// Additionally solves the corner, numbered like in the README.
+    pub const fn with_corner(mut self, corner: usize) -> Self {
+        self.corners |= 1 << corner;
+        self.corner_orientations |= 1 << corner;
+        self
+    }
+
+    /// Additionally places the corner, ignoring its orientation.
+    pub const fn with_corner_position(mut self, corner: usize) -> Self {
+        self.corners |= 1 << corner;
+        self
+    }
+
+    /// Additionally solves the edge, numbered like in the README.
+    pub const fn with_edge(mut self, edge: usize) -> Self {
+        self.edges |= 1 << edge;
+        self.edge_orientations |= 1 << edge;
+        self
+    }
+
+    /// Additionally places the edge, ignoring its orientation.
+    pub const fn with_edge_position(mut self, edge: usize) -> Self {
+        self.edges |= 1 << edge;
+        self
+    }
+
+    /// The pieces of both masks.
+    pub const fn union(self, other: Self) -> Self {
+        Self {
+            corners: self.corners | other.corners,
+            corner_orientations: self.corner_orientations | other.corner_orientations,
+            edges: self.edges | other.edges,
+            edge_orientations: self.edge_orientations | other.edge_orientations,
+        }
+    }
+
+    /// Whether all pieces of `other` are in this mask, with at least the same orientations.
+    pub const fn contains(&self, other: &Self) -> bool {
+        self.union(*other).corners == self.corners
+            && self.union(*other).corner_orientations == self.corner_orientations
+            && self.union(*other).edges == self.edges
+            && self.union(*other).edge_orientations == self.edge_orientations
+    }
+
+    /// The mask restricted to the given pieces.
+    fn only(&self, corners: &[usize], edges: &[usize]) -> Self {
+        let corner_bits = corners.iter().fold(0u8, |bits, &c| bits | (1 << c));
+        let edge_bits = edges.iter().fold(0u16, |bits, &e| bits | (1 << e));
+        Self {
+            corners: self.corners & corner_bits,
+            corner_orientations: self.corner_orientations & corner_bits,
+            edges: self.edges & edge_bits,
+            edge_orientations: self.edge_orientations & edge_bits,
+        }
+    }
+
+    pub fn corners(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..8).filter(|&i| self.corners & (1 << i) != 0)
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..12).filter(|&i| self.edges & (1 << i) != 0)
+    }
+
+    /// Whether the masked-in pieces are solved.
+    pub fn is_solved(&self, corners: &Corners, edges: &Edges) -> bool {
+        self.corners().all(|c| {
+            let (piece, ori) = corners.piece_at(c);
+            piece == c && (ori == 0 || self.corner_orientations & (1 << c) == 0)
+        }) && self.edges().all(|e| {
+            let (piece, ori) = edges.piece_at(e);
+            piece == e && (ori == 0 || self.edge_orientations & (1 << e) == 0)
+        })
+    }
+}
+
+/// Corners and edges, twisted piece by piece.
+#[derive(Clone, Copy)]
+struct Pieces {
+    corners: Corners,
+    edges: Edges,
+}
+
+impl Twistable for Pieces {
+    fn twisted(&self, twist: Twist) -> Self {
+        Self { corners: twist * self.corners, edges: twist * self.edges }
+    }
+}
+
+/// Rank of distinct locations among `count` locations, as an index below count! / (count - locations.len())!.
+fn arrangement_index(locations: &[usize], count: usize) -> usize {
+    let mut used = 0u16;
+    let mut index = 0;
+    for (i, &location) in locations.iter().enumerate() {
+        let smaller_unused = (0..location).filter(|&l| used & (1 << l) == 0).count();
+        index = index * (count - i) + smaller_unused;
+        used |= 1 << location;
+    }
+    index
+}
+
+/// Inverse of `arrangement_index`.
+fn nth_arrangement(mut index: usize, count: usize, len: usize) -> Vec<usize> {
+    let mut digits = vec![0; len];
+    for i in (0..len).rev() {
+        digits[i] = index % (count - i);
+        index /= count - i;
+    }
+    let mut unused: Vec<usize> = (0..count).collect();
+    digits.into_iter().map(|digit| unused.remove(digit)).collect()
+}
+
+/// The locations and orientations of some of the masked-in pieces.
+struct PieceCoordinate {
+    corners: Vec<usize>,
+    edges: Vec<usize>,
+    oriented_corners: usize,
+    oriented_edges: usize,
+}
+
+impl PieceCoordinate {
+    fn index_size(mask: &PieceMask, corners: &[usize], edges: &[usize]) -> usize {
+        let oriented_corners = corners.iter().filter(|&&c| mask.corner_orientations & (1 << c) != 0).count();
+        let oriented_edges = edges.iter().filter(|&&e| mask.edge_orientations & (1 << e) != 0).count();
+        (8 - corners.len() + 1..=8).product::<usize>()
+            * 3usize.pow(oriented_corners as u32)
+            * (12 - edges.len() + 1..=12).product::<usize>()
+            * 2usize.pow(oriented_edges as u32)
+    }
+
+    fn new(mask: &PieceMask, corners: Vec<usize>, edges: Vec<usize>) -> Self {
+        // Oriented pieces come first, so their orientations are the leading digits.
+        let mut corners = corners;
+        let mut edges = edges;
+        corners.sort_by_key(|&c| mask.corner_orientations & (1 << c) == 0);
+        edges.sort_by_key(|&e| mask.edge_orientations & (1 << e) == 0);
+        Self {
+            oriented_corners: corners.iter().filter(|&&c| mask.corner_orientations & (1 << c) != 0).count(),
+            oriented_edges: edges.iter().filter(|&&e| mask.edge_orientations & (1 << e) != 0).count(),
+            corners,
+            edges,
+        }
+    }
+
+    fn index(&self, pieces: &Pieces) -> usize {
+        let corner_locations: Vec<usize> = self.corners.iter().map(|&c| pieces.corners.position_of(c)).collect();
+        let edge_locations: Vec<usize> = self.edges.iter().map(|&e| pieces.edges.position_of(e)).collect();
+        let mut index = arrangement_index(&corner_locations, 8);
+        for &location in &corner_locations[..self.oriented_corners] {
+            index = index * 3 + pieces.corners.piece_at(location).1;
+        }
+        index = index * (12 - self.edges.len() + 1..=12).product::<usize>() + arrangement_index(&edge_locations, 12);
+        for &location in &edge_locations[..self.oriented_edges] {
+            index = index * 2 + pieces.edges.piece_at(location).1;
+        }
+        index
+    }
+
+    /// Pieces with the tracked ones as given by the index, and the others filling the remaining locations.
+    fn pieces(&self, mut index: usize) -> Pieces {
+        let mut edge_ori = [0; 12];
+        let mut edge_oris = vec![0; self.oriented_edges];
+        for ori in edge_oris.iter_mut().rev() {
+            *ori = index % 2;
+            index /= 2;
+        }
+        let edge_arrangements = (12 - self.edges.len() + 1..=12).product::<usize>();
+        let edge_locations = nth_arrangement(index % edge_arrangements, 12, self.edges.len());
+        index /= edge_arrangements;
+        let mut corner_oris = vec![0; self.oriented_corners];
+        for ori in corner_oris.iter_mut().rev() {
+            *ori = index % 3;
+            index /= 3;
+        }
+        let corner_locations = nth_arrangement(index, 8, self.corners.len());
+
+        let mut corner_prm = [usize::MAX; 8];
+        let mut corner_ori = [0; 8];
+        for (i, (&piece, &location)) in self.corners.iter().zip(&corner_locations).enumerate() {
+            corner_prm[location] = piece;
+            corner_ori[location] = corner_oris.get(i).copied().unwrap_or(0);
+        }
+        fill_untracked(&mut corner_prm);
+        let mut edge_prm = [usize::MAX; 12];
+        for (i, (&piece, &location)) in self.edges.iter().zip(&edge_locations).enumerate() {
+            edge_prm[location] = piece;
+            edge_ori[location] = edge_oris.get(i).copied().unwrap_or(0);
+        }
+        fill_untracked(&mut edge_prm);
+        Pieces { corners: Corners::new(corner_prm, corner_ori), edges: Edges::new(edge_prm, edge_ori) }
+    }
+
+}
+
+/// A group of the masked-in pieces with a distance table over their coordinate.
+struct PieceGroup {
+    mask: PieceMask,
+    coordinate: PieceCoordinate,
+    table: DistanceTable,
+}
+
+impl PieceGroup {
+    fn new(mask: PieceMask, costs: &TwistCosts) -> Self {
+        let (corners, edges): (Vec<usize>, Vec<usize>) = (mask.corners().collect(), mask.edges().collect());
+        let index_size = PieceCoordinate::index_size(&mask, &corners, &edges);
+        let coordinate = PieceCoordinate::new(&mask, corners, edges);
+        let origin = Pieces { corners: Corners::solved(), edges: Edges::solved() };
+        let table = DistanceTable::create_with_costs(&ALL_TWISTS, costs, origin, |p| coordinate.index(&p), |i| coordinate.pieces(i), index_size);
+        Self { mask, coordinate, table }
+    }
+
+    fn distance(&self, pieces: &Pieces) -> u8 {
+        self.table.distance(self.coordinate.index(pieces))
+    }
+}
+
+/// Puts the pieces that aren't in the permutation into its free slots.
+fn fill_untracked<const N: usize>(prm: &mut [usize; N]) {
+    let mut untracked = (0..N).filter(|piece| !prm.contains(piece)).collect::<Vec<_>>().into_iter();
+    for slot in prm.iter_mut().filter(|slot| **slot == usize::MAX) {
+        *slot = untracked.next().unwrap();
+    }
+}
+
+/// Solves only the masked-in pieces, optimally, e.g. the cross or a 2x2x2 block.
+///
+/// The masked-in pieces are split into groups with at most `MAX_GROUP_SIZE` states each.
+/// Each group has a distance table over the locations and orientations of its pieces,
+/// and the largest distance of all groups prunes an iterative deepening search.
+/// With costs, the distances and the solutions minimize the total cost of the twists instead of their number.
+pub struct MaskedSolver {
+    mask: PieceMask,
+    groups: Vec<PieceGroup>,
+    costs: TwistCosts,
+    legal_moves: Option<Box<dyn LegalMoves>>,
+}
+
+impl MaskedSolver {
+    pub const MAX_GROUP_SIZE: usize = 1 << 22;
+
+    /// Creates the distance tables of the mask.
+    pub fn new(mask: PieceMask) -> Self {
+        Self::with_costs(mask, TwistCosts::HTM)
+    }
+
+    /// Creates the distance tables of the mask, with distances in total costs of the twists.
+    pub fn with_costs(mask: PieceMask, costs: TwistCosts) -> Self {
+        let mut groups = Vec::new();
+        let mut push_group = |corners: &[usize], edges: &[usize]| groups.push(mask.only(corners, edges));
+        let (mut corners, mut edges) = (Vec::new(), Vec::new());
+        for (corner, edge) in mask.corners().map(|c| (Some(c), None)).chain(mask.edges().map(|e| (None, Some(e)))) {
+            let (mut more_corners, mut more_edges) = (corners.clone(), edges.clone());
+            more_corners.extend(corner);
+            more_edges.extend(edge);
+            if PieceCoordinate::index_size(&mask, &more_corners, &more_edges) > Self::MAX_GROUP_SIZE {
+                push_group(&corners, &edges);
+                (corners, edges) = (corner.into_iter().collect(), edge.into_iter().collect());
+            } else {
+                (corners, edges) = (more_corners, more_edges);
+            }
+        }
+        if !corners.is_empty() || !edges.is_empty() {
+            push_group(&corners, &edges);
+        }
+        Self::with_groups_and_costs(&groups, costs)
+    }
+
+    /// Creates one distance table per group, without limiting their size.
+    /// The mask is the union of the groups, and any union of groups can be solved with `solve_part`.
+    pub fn with_groups(groups: &[PieceMask]) -> Self {
+        Self::with_groups_and_costs(groups, TwistCosts::HTM)
+    }
+
+    /// Like `with_groups`, with distances in total costs of the twists.
+    pub fn with_groups_and_costs(groups: &[PieceMask], costs: TwistCosts) -> Self {
+        Self {
+            mask: groups.iter().fold(PieceMask::NONE, |mask, &group| mask.union(group)),
+            groups: groups.iter().map(|&group| PieceGroup::new(group, &costs)).collect(),
+            costs,
+            legal_moves: None,
+        }
+    }
+
+    /// Only searches the twists that are legal in each state, e.g. of a `Bandaging`.
+    /// The distance tables still allow all twists, so they stay lower bounds.
+    pub fn with_legal_moves(mut self, legal_moves: impl LegalMoves + 'static) -> Self {
+        self.legal_moves = Some(Box::new(legal_moves));
+        self
+    }
+
+    pub fn mask(&self) -> PieceMask {
+        self.mask
+    }
+
+    pub fn costs(&self) -> TwistCosts {
+        self.costs
+    }
+
+    /// A lower bound of the number of twists, or their total cost, to solve the masked-in pieces.
+    /// It's exact if the mask fits into a single group.
+    pub fn lower_bound(&self, corners: &Corners, edges: &Edges) -> u8 {
+        let pieces = Pieces { corners: *corners, edges: *edges };
+        self.groups.iter().map(|group| group.distance(&pieces)).max().unwrap_or(0)
+    }
+
+    /// A shortest sequence of twists, or one of the least total cost, that solves the masked-in pieces.
+    /// With costs, `max_solution_length` bounds the total cost.
+    pub fn solve(&self, cube: Cube, max_solution_length: u8) -> Result<Vec<Twist>, String> {
+        self.solve_part(cube, self.mask, max_solution_length)
+    }
+
+    /// A shortest sequence of twists that solves the pieces of `goal`, which has to be within the mask.
+    /// Only the groups within `goal` prune the search.
+    pub fn solve_part(&self, cube: Cube, goal: PieceMask, max_solution_length: u8) -> Result<Vec<Twist>, String> {
+        if !self.mask.contains(&goal) {
+            return Err("The goal isn't within the mask".into());
+        }
+        let groups: Vec<&PieceGroup> = self.groups.iter().filter(|group| goal.contains(&group.mask)).collect();
+        let pieces = Pieces { corners: cube.corners(), edges: cube.edges() };
+        let lower_bound = groups.iter().map(|group| group.distance(&pieces)).max().unwrap_or(0);
+        let mut twists = Vec::new();
+        for depth in lower_bound..=max_solution_length {
+            if self.search(&groups, goal, pieces, depth, &mut twists) {
+                return Ok(twists);
+            }
+        }
+        Err("No solution found".into())
+    }
+
+    /// Searches twists of exactly the total cost `depth`, which every total cost is searched with, from the lower bound up.
+    fn search(&self, groups: &[&PieceGroup], goal: PieceMask, pieces: Pieces, depth: u8, twists: &mut Vec<Twist>) -> bool {
+        if depth == 0 {
+            return goal.is_solved(&pieces.corners, &pieces.edges);
+        }
+        if groups.iter().any(|group| group.distance(&pieces) > depth) {
+            return false;
+        }
+        let candidates = match self.legal_moves.as_deref() {
+            Some(legal_moves) => legal_twists_after(legal_moves, &Cube::from_cubies(&pieces.corners, &pieces.edges), twists.last().copied()),
+            None => twists.last().map_or(TwistSet::FULL, |&last| unique_twists_after(last)),
+        };
+        for twist in candidates.iter() {
+            let cost = self.costs.cost(twist);
+            if cost > depth {
+                continue;
+            }
+            twists.push(twist);
+            if self.search(groups, goal, pieces.twisted(twist), depth - cost, twists) {
+                return true;
+            }
+            twists.pop();
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arrangement_index() {
+        for index in 0..12 * 11 * 10 {
+            let locations = nth_arrangement(index, 12, 3);
+            assert_eq!(arrangement_index(&locations, 12), index);
+        }
+        assert_eq!(nth_arrangement(0, 8, 2), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_cross() {
+        let solver = MaskedSolver::new(PieceMask::CROSS);
+        let scramble = [Twist::F1, Twist::R2, Twist::D3, Twist::B1, Twist::L3, Twist::U2, Twist::F2];
+        let cube = Cube::solved().twisted_by(&scramble);
+        let solution = solver.solve(cube, 8).unwrap();
+        assert!(solution.len() <= scramble.len());
+        let solved = cube.twisted_by(&solution);
+        assert!(PieceMask::CROSS.is_solved(&solved.corners(), &solved.edges()));
+        assert_eq!(solver.solve(Cube::solved(), 8), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_block() {
+        let solver = MaskedSolver::new(PieceMask::BLOCK_2X2X2);
+        // R and U don't move the DFL block.
+        let cube = Cube::solved().twisted_by(&[Twist::R1, Twist::U1, Twist::F1]);
+        assert_eq!(solver.solve(cube, 5), Ok(vec![Twist::F3]));
+    }
+
+    #[test]
+    fn test_costs() {
+        let costs = TwistCosts::QTM.with_face(Face::F, 3, 5).with_face(Face::L, 2, 3);
+        let solver = MaskedSolver::with_costs(PieceMask::BLOCK_2X2X2, costs);
+        assert_eq!(solver.costs(), costs);
+        let scramble = parse_twist_sequence("F2 L' D R2 F U'").unwrap();
+        let cube = Cube::solved().twisted_by(&scramble);
+        let solution = solver.solve(cube, 30).unwrap();
+        let solved = cube.twisted_by(&solution);
+        assert!(PieceMask::BLOCK_2X2X2.is_solved(&solved.corners(), &solved.edges()));
+        // The mask fits into a single group, so the lower bound is the least total cost.
+        assert_eq!(costs.total(&solution), solver.lower_bound(&cube.corners(), &cube.edges()) as usize);
+        assert!(costs.total(&solution) <= costs.total(&inverse(&scramble)));
+        let fewest_twists = MaskedSolver::new(PieceMask::BLOCK_2X2X2).solve(cube, 10).unwrap();
+        assert!(costs.total(&solution) <= costs.total(&fewest_twists));
+        assert!(solver.solve(cube, costs.total(&solution) as u8 - 1).is_err());
+    }
+
+    #[test]
+    fn test_position_only() {
+        let flipped = Edges::new(core::array::from_fn(|i| i), [1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert!(PieceMask::NONE.with_edge_position(0).is_solved(&Corners::solved(), &flipped));
+        assert!(!PieceMask::NONE.with_edge(0).is_solved(&Corners::solved(), &flipped));
+
+        let mask = PieceMask::NONE.with_edge_position(0).with_corner_position(1);
+        let solver = MaskedSolver::new(mask);
+        let cube = Cube::solved().twisted_by(&[Twist::F1, Twist::U2]);
+        let solution = solver.solve(cube, 5).unwrap();
+        // The mask fits into a single group, so the lower bound is exact.
+        assert_eq!(solution.len(), solver.lower_bound(&cube.corners(), &cube.edges()) as usize);
+        assert!(solution.len() <= 2);
+        let solved = cube.twisted_by(&solution);
+        assert!(mask.is_solved(&solved.corners(), &solved.edges()));
+    }
+}
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use proptest::prelude::*;
+
+    /// Sequences of up to 100 twists of the given set.
+    fn twist_sequence(twists: &[Twist]) -> impl Strategy<Value = Vec<Twist>> {
+        prop::collection::vec(prop::sample::select(twists.to_vec()), 0..=100)
+    }
+
+    proptest! {
+        #[test]
+        fn inverse_returns_to_start(twists in twist_sequence(&ALL_TWISTS)) {
+            let corners = Corners::twists(&twists);
+            let edges = Edges::twists(&twists);
+            prop_assert_eq!(corners * corners.inverse(), Corners::solved());
+            prop_assert_eq!(corners.inverse() * corners, Corners::solved());
+            prop_assert_eq!(edges * edges.inverse(), Edges::solved());
+            prop_assert_eq!(edges.inverse() * edges, Edges::solved());
+            prop_assert_eq!(Corners::twists(&inverse(&twists)), corners.inverse());
+
+            let cube = Cube::solved().twisted_by(&twists);
+            prop_assert_eq!(cube.twisted_by(&inverse(&twists)), Cube::solved());
+            prop_assert_eq!(cube.inverse(), Cube::solved().twisted_by(&inverse(&twists)));
+        }
+
+        #[test]
+        fn cubies_index_round_trip(twists in twist_sequence(&ALL_TWISTS)) {
+            let c = Corners::twists(&twists);
+            prop_assert_eq!(Corners::from_indices(c.prm_index(), c.ori_index()), c);
+            let e = Edges::twists(&twists);
+            prop_assert_eq!(Edges::from_indices(e.loc_prm(Axis::X), e.loc_prm(Axis::Y), e.loc_prm(Axis::Z), e.ori_index()), e);
+        }
+
+        #[test]
+        fn subset_cubies_index_round_trip(twists in twist_sequence(&H0_TWISTS)) {
+            let e = Edges::twists(&twists);
+            prop_assert_eq!(Edges::from_subset_indices(e.xy_prm_index(), e.loc_prm(Axis::Z).prm()), e);
+        }
+
+        #[test]
+        fn corner_index_round_trip(index in 0..Cube::CORNER_INDEX_SIZE) {
+            prop_assert_eq!(Cube::from_corner_index(index).corner_index(), index);
+        }
+
+        #[test]
+        fn coset_index_round_trip(index in 0..Cube::COSETS_INDEX_SIZE) {
+            prop_assert_eq!(Cube::from_coset_index(index).coset_index(), index);
+        }
+
+        #[test]
+        fn subset_index_round_trip(index in 0..SubsetCube::INDEX_SIZE) {
+            prop_assert_eq!(SubsetCube::from_index(index).index(), index);
+        }
+
+        /// Twisting the coordinates with the twisters agrees with twisting the pieces.
+        #[test]
+        fn coordinates_agree_with_cubies(twists in twist_sequence(&ALL_TWISTS)) {
+            let corners = Corners::twists(&twists);
+            let edges = Edges::twists(&twists);
+            let cube = Cube::solved().twisted_by(&twists);
+            prop_assert_eq!(cube, Cube::from_cubies(&corners, &edges));
+            prop_assert_eq!(cube.corners(), corners);
+            prop_assert_eq!(cube.edges(), edges);
+        }
+
+        #[test]
+        fn subset_coordinates_agree_with_cubies(twists in twist_sequence(&H0_TWISTS)) {
+            let cube = Cube::from_cubies(&Corners::twists(&twists), &Edges::twists(&twists));
+            prop_assert_eq!(SubsetCube::solved().twisted_by(&twists).index(), cube.subset_cube().index());
+        }
+    }
+}
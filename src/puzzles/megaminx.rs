@@ -0,0 +1,64 @@
+use rand::{rngs::StdRng, RngExt, SeedableRng};
+
+/// Random-move scramble generator for the Megaminx, in the notation of Stefan Pochmann that WCA scrambles use:
+/// seven lines of ten twists that alternate between "R" and "D", each turning two fifths, "++" or "--",
+/// and a twist of U by one fifth, "U" after "D++" and "U'" after "D--".
+pub struct MegaminxScrambleGen {
+    rng: StdRng,
+}
+
+impl MegaminxScrambleGen {
+    pub const LINES: usize = 7;
+    pub const TWISTS_PER_LINE: usize = 10;
+
+    pub fn new(seed: u64) -> Self {
+        Self { rng: StdRng::seed_from_u64(seed) }
+    }
+
+    /// Seeded from the operating system's entropy.
+    pub fn from_entropy() -> Self {
+        Self::new(rand::random())
+    }
+
+    /// The lines of a scramble.
+    pub fn gen_lines(&mut self) -> Vec<String> {
+        (0..Self::LINES)
+            .map(|_| {
+                let mut twists = Vec::with_capacity(Self::TWISTS_PER_LINE + 1);
+                let mut clockwise = false;
+                for i in 0..Self::TWISTS_PER_LINE {
+                    clockwise = self.rng.random();
+                    twists.push(format!("{}{}", if i % 2 == 0 { "R" } else { "D" }, if clockwise { "++" } else { "--" }));
+                }
+                twists.push(if clockwise { "U" } else { "U'" }.to_string());
+                twists.join(" ")
+            })
+            .collect()
+    }
+
+    /// A scramble with a line break after each line.
+    pub fn gen_scramble(&mut self) -> String {
+        self.gen_lines().join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gen_scramble() {
+        let lines = MegaminxScrambleGen::new(42).gen_lines();
+        assert_eq!(lines.len(), 7);
+        for line in &lines {
+            let twists: Vec<&str> = line.split(' ').collect();
+            assert_eq!(twists.len(), 11);
+            for (i, twist) in twists[..10].iter().enumerate() {
+                assert!(twist.starts_with(if i % 2 == 0 { 'R' } else { 'D' }));
+                assert!(twist.ends_with("++") || twist.ends_with("--"));
+            }
+            assert_eq!(twists[10], if twists[9] == "D++" { "U" } else { "U'" });
+        }
+        assert_ne!(MegaminxScrambleGen::new(1).gen_scramble(), MegaminxScrambleGen::new(2).gen_scramble());
+    }
+}
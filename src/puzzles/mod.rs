@@ -0,0 +1,7 @@
+//! Other twisty puzzles than cubes: the Pyraminx with random-state scrambles and Megaminx random-move scrambles.
+
+pub mod pyraminx;
+pub mod megaminx;
+
+pub use pyraminx::*;
+pub use megaminx::*;
@@ -0,0 +1,355 @@
+use crate::*;
+use rand::{rngs::StdRng, RngExt, SeedableRng};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::LazyLock;
+
+// The pieces of the Pyraminx sit on a tetrahedron whose vertices are alternate corners of a cube:
+// U (1, 1, 1) on top, L (-1, -1, 1) and R (1, -1, -1) in front and B (-1, 1, -1) at the back.
+// The edges are at the middles of its edges, on the axes. A twist turns the pieces around a vertex by a third.
+
+/// The vertices of the Pyraminx, which its twists turn around.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Vertex {
+    U, L, R, B,
+}
+
+const VERTICES: [Vertex; 4] = [Vertex::U, Vertex::L, Vertex::R, Vertex::B];
+
+type Vector = [i32; 3];
+
+const fn position(vertex: Vertex) -> Vector {
+    match vertex {
+        Vertex::U => [1, 1, 1],
+        Vertex::L => [-1, -1, 1],
+        Vertex::R => [1, -1, -1],
+        Vertex::B => [-1, 1, -1],
+    }
+}
+
+/// The edge positions UL, UR, UB, LR, RB and BL, by their vertices.
+const EDGES: [(Vertex, Vertex); 6] = [
+    (Vertex::U, Vertex::L),
+    (Vertex::U, Vertex::R),
+    (Vertex::U, Vertex::B),
+    (Vertex::L, Vertex::R),
+    (Vertex::R, Vertex::B),
+    (Vertex::B, Vertex::L),
+];
+
+fn edge_position(edge: usize) -> Vector {
+    let (a, b) = EDGES[edge];
+    core::array::from_fn(|i| (position(a)[i] + position(b)[i]) / 2)
+}
+
+/// The outward normal of the face of an edge position that its orientation is measured against:
+/// the face opposite the first vertex that the edge doesn't touch.
+fn reference_normal(edge: usize) -> Vector {
+    let (a, b) = EDGES[edge];
+    let other = VERTICES.into_iter().find(|&v| v != a && v != b).unwrap();
+    position(other).map(|c| -c)
+}
+
+/// The vector turned by a third clockwise, as seen when looking at the vertex.
+fn rotated(v: Vector, vertex: Vertex) -> Vector {
+    // Mirroring by the signs of the vertex takes it to U, where the turn cycles the coordinates.
+    let s = position(vertex);
+    let [x, y, z] = core::array::from_fn(|i| v[i] * s[i]);
+    [y * s[0], z * s[1], x * s[2]]
+}
+
+/// The clockwise twist of the vertex on the edges: the position each edge position comes from, and whether the edge flips.
+static EDGE_MOVES: LazyLock<[[(u8, u8); 6]; 4]> = LazyLock::new(|| {
+    VERTICES.map(|vertex| {
+        let mut moves = [(0, 0); 6];
+        for from in 0..6 {
+            let p = edge_position(from);
+            if p.iter().zip(position(vertex)).map(|(a, b)| a * b).sum::<i32>() <= 0 {
+                moves[from] = (from as u8, 0);
+                continue;
+            }
+            let to = (0..6).find(|&to| edge_position(to) == rotated(p, vertex)).unwrap();
+            moves[to] = (from as u8, u8::from(rotated(reference_normal(from), vertex) != reference_normal(to)));
+        }
+        moves
+    })
+});
+
+/// A twist of the Pyraminx: a third of the layer around the vertex, clockwise as seen when looking at it if `power` is 1,
+/// counterclockwise if it is 2, or of the tip only.
+/// In WCA notation, "U" twists the layer clockwise, "U'" counterclockwise and "u" the tip.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PyraminxTwist {
+    pub vertex: Vertex,
+    pub power: u8,
+    pub tip: bool,
+}
+
+impl PyraminxTwist {
+    /// The twists of the layers, without the tips.
+    pub const LAYER_TWISTS: [Self; 8] = {
+        let mut twists = [Self { vertex: Vertex::U, power: 1, tip: false }; 8];
+        let mut i = 0;
+        while i < 8 {
+            twists[i] = Self { vertex: VERTICES[i / 2], power: 1 + (i % 2) as u8, tip: false };
+            i += 1;
+        }
+        twists
+    };
+
+    pub fn inverse(&self) -> Self {
+        Self { power: 3 - self.power, ..*self }
+    }
+}
+
+impl fmt::Display for PyraminxTwist {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = format!("{:?}", self.vertex);
+        write!(f, "{}{}", if self.tip { name.to_lowercase() } else { name }, if self.power == 2 { "'" } else { "" })
+    }
+}
+
+impl core::str::FromStr for PyraminxTwist {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, power) = match s.strip_suffix('\'') {
+            Some(name) => (name, 2),
+            None => (s, 1),
+        };
+        let vertex = match name.to_uppercase().as_str() {
+            "U" => Vertex::U,
+            "L" => Vertex::L,
+            "R" => Vertex::R,
+            "B" => Vertex::B,
+            _ => return Err(format!("Unknown twist: '{s}'")),
+        };
+        Ok(Self { vertex, power, tip: name.chars().all(|c| c.is_lowercase()) })
+    }
+}
+
+/// Parses whitespace-separated Pyraminx twists in WCA notation, e.g. "U L' r b'".
+pub fn parse_pyraminx_twists(input: &str) -> Result<Vec<PyraminxTwist>, String> {
+    input.split_whitespace().map(str::parse).collect()
+}
+
+/// The Pyraminx by its pieces: the six edges at the positions of `EDGES` with their orientations,
+/// and the orientations of the axial piece and of the tip at each vertex, in thirds of a clockwise turn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Pyraminx {
+    edges: [u8; 6],
+    edge_oris: [u8; 6],
+    axials: [u8; 4],
+    tips: [u8; 4],
+}
+
+impl Pyraminx {
+    /// The number of states without the tips: even permutations and orientations of the edges, and orientations of the axials.
+    pub const INDEX_SIZE: usize = factorial(6) / 2 * (1 << 5) * 81;
+
+    /// Fails for states that twists can't reach: the edges must be an even permutation with an even number of flips.
+    pub fn try_new(edges: [usize; 6], edge_oris: [usize; 6], axials: [usize; 4], tips: [usize; 4]) -> Result<Self, CubieError> {
+        check_cubies(&edges, &edge_oris, 2)?;
+        if !is_even_permutation(permutation_index(&edges)) {
+            return Err(CubieError::OddPermutation);
+        }
+        for ori in [axials, tips] {
+            if let Some(position) = ori.iter().position(|&o| o >= 3) {
+                return Err(CubieError::InvalidOrientation { position, orientation: ori[position] });
+            }
+        }
+        Ok(Self { edges: edges.map(|e| e as u8), edge_oris: edge_oris.map(|o| o as u8), axials: axials.map(|o| o as u8), tips: tips.map(|o| o as u8) })
+    }
+
+    pub const fn solved() -> Self {
+        Self { edges: [0, 1, 2, 3, 4, 5], edge_oris: [0; 6], axials: [0; 4], tips: [0; 4] }
+    }
+
+    pub fn is_solved(&self) -> bool {
+        *self == Self::solved()
+    }
+
+    /// The edge at the position and its orientation.
+    pub fn edge(&self, position: usize) -> (usize, usize) {
+        (self.edges[position] as usize, self.edge_oris[position] as usize)
+    }
+
+    pub fn axials(&self) -> [usize; 4] {
+        self.axials.map(|o| o as usize)
+    }
+
+    pub fn tips(&self) -> [usize; 4] {
+        self.tips.map(|o| o as usize)
+    }
+
+    pub fn twisted(&self, twist: PyraminxTwist) -> Self {
+        let v = twist.vertex as usize;
+        let mut result = *self;
+        result.tips[v] = (result.tips[v] + twist.power) % 3;
+        if twist.tip {
+            return result;
+        }
+        result.axials[v] = (result.axials[v] + twist.power) % 3;
+        for _ in 0..twist.power {
+            let before = result;
+            for (to, &(from, flip)) in EDGE_MOVES[v].iter().enumerate() {
+                result.edges[to] = before.edges[from as usize];
+                result.edge_oris[to] = before.edge_oris[from as usize] ^ flip;
+            }
+        }
+        result
+    }
+
+    pub fn twisted_by(&self, twists: &[PyraminxTwist]) -> Self {
+        twists.iter().fold(*self, |p, &twist| p.twisted(twist))
+    }
+
+    /// The state without the tips, in [0, INDEX_SIZE).
+    pub fn index(&self) -> usize {
+        let prm = even_permutation_index(permutation_index(&self.edges));
+        let ori = encode(&self.edge_oris[..5].iter().map(|&o| o as usize).collect::<Vec<_>>(), 2);
+        (prm * (1 << 5) + ori) * 81 + encode(&self.axials(), 3)
+    }
+
+    /// The state of the index with solved tips, the inverse of `index` up to the tips.
+    pub fn from_index(index: usize) -> Self {
+        let edges = nth_permutation_array::<6>(nth_even_permutation(index / 81 / (1 << 5), true));
+        let ori: [usize; 5] = decode_array(index / 81 % (1 << 5), 2);
+        let edge_oris: [usize; 6] = core::array::from_fn(|i| if i < 5 { ori[i] } else { ori.iter().sum::<usize>() % 2 });
+        let axials = decode_array(index % 81, 3);
+        Self::try_new(edges, edge_oris, axials, [0; 4]).expect("an index is a reachable state")
+    }
+}
+
+/// The number of layer twists that solve each state without the tips, by `Pyraminx::index`.
+static DISTANCES: LazyLock<Vec<u8>> = LazyLock::new(|| {
+    let mut distances = vec![u8::MAX; Pyraminx::INDEX_SIZE];
+    distances[Pyraminx::solved().index()] = 0;
+    let mut queue = VecDeque::from([(Pyraminx::solved(), 0)]);
+    while let Some((pyraminx, distance)) = queue.pop_front() {
+        for twist in PyraminxTwist::LAYER_TWISTS {
+            let next = pyraminx.twisted(twist);
+            let index = next.index();
+            if distances[index] == u8::MAX {
+                distances[index] = distance + 1;
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    distances
+});
+
+/// Random-state scramble generator for the Pyraminx, where every state is equally likely.
+/// The scramble twists the layers of a random state without the tips, in the fewest twists, and then the tips at random.
+pub struct PyraminxScrambleGen {
+    rng: StdRng,
+}
+
+impl PyraminxScrambleGen {
+    /// States that fewer layer twists solve are drawn again, so that no scramble is trivially short.
+    pub const MIN_DISTANCE: u8 = 6;
+
+    /// The first generator creates a table of the distances of the states, which takes a moment.
+    pub fn new(seed: u64) -> Self {
+        Self { rng: StdRng::seed_from_u64(seed) }
+    }
+
+    /// Seeded from the operating system's entropy.
+    pub fn from_entropy() -> Self {
+        Self::new(rand::random())
+    }
+
+    pub fn gen_twists(&mut self) -> Vec<PyraminxTwist> {
+        let index = loop {
+            let index = self.rng.random_range(0..Pyraminx::INDEX_SIZE);
+            if DISTANCES[index] >= Self::MIN_DISTANCE {
+                break index;
+            }
+        };
+        // The twists that solve the state but the tips, one that gets closer at a time, taken back.
+        let mut pyraminx = Pyraminx::from_index(index);
+        let mut solution = Vec::new();
+        loop {
+            let distance = DISTANCES[pyraminx.index()];
+            if distance == 0 {
+                break;
+            }
+            let twist = PyraminxTwist::LAYER_TWISTS.into_iter().find(|&t| DISTANCES[pyraminx.twisted(t).index()] < distance).unwrap();
+            pyraminx = pyraminx.twisted(twist);
+            solution.push(twist);
+        }
+        let mut twists: Vec<PyraminxTwist> = solution.iter().rev().map(|t| t.inverse()).collect();
+        for vertex in VERTICES {
+            let power = self.rng.random_range(0..3);
+            if power > 0 {
+                twists.push(PyraminxTwist { vertex, power, tip: true });
+            }
+        }
+        twists
+    }
+
+    /// A scramble in WCA notation, e.g. "U L' R B' U' L R' u b'".
+    pub fn gen_scramble(&mut self) -> String {
+        self.gen_twists().iter().map(|t| t.to_string()).collect::<Vec<_>>().join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_twisted() {
+        let solved = Pyraminx::solved();
+        for twist in PyraminxTwist::LAYER_TWISTS {
+            let once = solved.twisted(twist);
+            assert!(!once.is_solved());
+            assert!(once.twisted(twist).twisted(twist).is_solved());
+            assert!(once.twisted(twist.inverse()).is_solved());
+        }
+        // "U" takes the edge of UL to UB, as seen from above.
+        let pyraminx = solved.twisted("U".parse().unwrap());
+        assert_eq!(pyraminx.edge(2).0, 0);
+        assert_eq!(pyraminx.axials(), [1, 0, 0, 0]);
+        assert_eq!(solved.twisted("b'".parse().unwrap()).tips(), [0, 0, 0, 2]);
+
+        let twists = parse_pyraminx_twists("U L' R B' U' l r' b").unwrap();
+        assert_eq!(twists.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(" "), "U L' R B' U' l r' b");
+        let back: Vec<PyraminxTwist> = twists.iter().rev().map(|t| t.inverse()).collect();
+        assert!(solved.twisted_by(&twists).twisted_by(&back).is_solved());
+        assert!(parse_pyraminx_twists("U F").is_err());
+    }
+
+    #[test]
+    fn test_index() {
+        // The twists reach every index, so the states of the indices are the reachable ones.
+        assert!(DISTANCES.iter().all(|&d| d != u8::MAX));
+        assert_eq!(*DISTANCES.iter().max().unwrap(), 11);
+        // The layer twists turn the tips along, which the index leaves out.
+        let pyraminx = Pyraminx::solved().twisted_by(&parse_pyraminx_twists("U L' R B' U' L").unwrap());
+        let tips: Vec<PyraminxTwist> = VERTICES
+            .into_iter()
+            .zip(pyraminx.tips())
+            .filter(|&(_, power)| power > 0)
+            .map(|(vertex, power)| PyraminxTwist { vertex, power: 3 - power as u8, tip: true })
+            .collect();
+        assert_eq!(Pyraminx::from_index(pyraminx.index()), pyraminx.twisted_by(&tips));
+
+        assert_eq!(Pyraminx::try_new([1, 0, 2, 3, 4, 5], [0; 6], [0; 4], [0; 4]), Err(CubieError::OddPermutation));
+        assert_eq!(Pyraminx::try_new([0, 1, 2, 3, 4, 5], [1, 0, 0, 0, 0, 0], [0; 4], [0; 4]), Err(CubieError::OrientationSum(2)));
+        assert!(Pyraminx::try_new([0, 1, 2, 3, 4, 5], [0; 6], [0, 3, 0, 0], [0; 4]).is_err());
+    }
+
+    #[test]
+    fn test_gen_scramble() {
+        let mut scrambles = PyraminxScrambleGen::new(42);
+        for _ in 0..10 {
+            let twists = scrambles.gen_twists();
+            let layers = twists.iter().filter(|t| !t.tip).count();
+            assert!((PyraminxScrambleGen::MIN_DISTANCE as usize..=11).contains(&layers));
+            let pyraminx = Pyraminx::solved().twisted_by(&twists);
+            assert_eq!(DISTANCES[pyraminx.index()] as usize, layers);
+        }
+        assert_ne!(PyraminxScrambleGen::new(1).gen_scramble(), PyraminxScrambleGen::new(2).gen_scramble());
+    }
+}
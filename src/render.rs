@@ -0,0 +1,165 @@
+use crate::*;
+use std::fmt::Write;
+
+/// CSS colours of the faces.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColorScheme {
+    colors: [String; 6], // In the order U, R, F, D, L, B
+}
+
+impl ColorScheme {
+    /// Takes the colours of the faces in the order U, R, F, D, L, B.
+    pub fn new(colors: [&str; 6]) -> Self {
+        Self { colors: colors.map(str::to_string) }
+    }
+
+    pub fn with_color(mut self, face: Face, color: &str) -> Self {
+        self.colors[face as usize] = color.to_string();
+        self
+    }
+
+    pub fn color(&self, face: Face) -> &str {
+        &self.colors[face as usize]
+    }
+}
+
+impl Default for ColorScheme {
+    /// The colours of the README: white U, blue R, red F, yellow D, green L and orange B.
+    fn default() -> Self {
+        Self::new(["#ffffff", "#0046ad", "#b71234", "#ffd500", "#009b48", "#ff5800"])
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SvgStyle {
+    /// The unfolded cube, laid out like the facelet numbering of `Facelets`.
+    Net,
+    /// An isometric view of the U, F and R faces.
+    Isometric,
+}
+
+/// Edge length of a sticker in SVG units.
+const STICKER: f64 = 30.0;
+const MARGIN: f64 = 5.0;
+
+pub fn render_svg(facelets: &Facelets, style: SvgStyle, colors: &ColorScheme) -> String {
+    match style {
+        SvgStyle::Net => net_svg(facelets, colors),
+        SvgStyle::Isometric => isometric_svg(facelets, colors),
+    }
+}
+
+fn svg(width: f64, height: f64, content: &str) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n{content}</svg>\n"
+    )
+}
+
+fn color_of(facelets: &Facelets, colors: &ColorScheme, face: Face, row: usize, col: usize) -> String {
+    colors.color(facelets.faces()[face as usize * 9 + row * 3 + col]).to_string()
+}
+
+pub fn net_svg(facelets: &Facelets, colors: &ColorScheme) -> String {
+    // Position of each face in the net, in stickers.
+    let offsets = [
+        (Face::U, 3, 0),
+        (Face::L, 0, 3),
+        (Face::F, 3, 3),
+        (Face::R, 6, 3),
+        (Face::B, 9, 3),
+        (Face::D, 3, 6),
+    ];
+    let mut content = String::new();
+    for (face, x, y) in offsets {
+        for row in 0..3 {
+            for col in 0..3 {
+                let _ = writeln!(
+                    content,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{STICKER}\" height=\"{STICKER}\" fill=\"{}\" stroke=\"#000000\"/>",
+                    MARGIN + (x + col) as f64 * STICKER,
+                    MARGIN + (y + row) as f64 * STICKER,
+                    color_of(facelets, colors, face, row, col),
+                );
+            }
+        }
+    }
+    svg(12.0 * STICKER + 2.0 * MARGIN, 9.0 * STICKER + 2.0 * MARGIN, &content)
+}
+
+/// The corners of the sticker in the given row and column of a face visible in the isometric view.
+fn sticker_corners(face: Face, r: f64, c: f64) -> [(f64, f64, f64); 4] {
+    match face {
+        Face::U => [(c, 3.0, r), (c + 1.0, 3.0, r), (c + 1.0, 3.0, r + 1.0), (c, 3.0, r + 1.0)],
+        Face::F => [(c, 3.0 - r, 3.0), (c + 1.0, 3.0 - r, 3.0), (c + 1.0, 2.0 - r, 3.0), (c, 2.0 - r, 3.0)],
+        Face::R => [(3.0, 3.0 - r, 3.0 - c), (3.0, 3.0 - r, 2.0 - c), (3.0, 2.0 - r, 2.0 - c), (3.0, 2.0 - r, 3.0 - c)],
+        _ => unreachable!("Only U, F and R are visible"),
+    }
+}
+
+pub fn isometric_svg(facelets: &Facelets, colors: &ColorScheme) -> String {
+    // Projects a point of the cube [0, 3]^3, in the coordinate system of the README, onto the screen.
+    let cos30 = 3f64.sqrt() / 2.0;
+    let project = |x: f64, y: f64, z: f64| {
+        (
+            MARGIN + (3.0 * cos30 + (x - z) * cos30) * STICKER,
+            MARGIN + (3.0 - y + (x + z) / 2.0) * STICKER,
+        )
+    };
+    let mut content = String::new();
+    for face in [Face::U, Face::F, Face::R] {
+        for row in 0..3 {
+            for col in 0..3 {
+                let points: Vec<String> = sticker_corners(face, row as f64, col as f64)
+                    .iter()
+                    .map(|&(x, y, z)| {
+                        let (px, py) = project(x, y, z);
+                        format!("{px:.2},{py:.2}")
+                    })
+                    .collect();
+                let _ = writeln!(
+                    content,
+                    "<polygon points=\"{}\" fill=\"{}\" stroke=\"#000000\" stroke-linejoin=\"round\"/>",
+                    points.join(" "),
+                    color_of(facelets, colors, face, row, col),
+                );
+            }
+        }
+    }
+    svg(6.0 * cos30 * STICKER + 2.0 * MARGIN, 6.0 * STICKER + 2.0 * MARGIN, &content)
+}
+
+impl Cube {
+    pub fn to_svg(&self, style: SvgStyle, colors: &ColorScheme) -> String {
+        render_svg(&self.facelets(), style, colors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_net_svg() {
+        let colors = ColorScheme::default().with_color(Face::U, "gray");
+        let svg = Cube::solved().twisted(Twist::R1).to_svg(SvgStyle::Net, &colors);
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(svg.matches("<rect").count(), 54);
+        // Twists move stickers around, but keep the number of stickers per colour.
+        assert_eq!(svg.matches("fill=\"gray\"").count(), 9);
+        assert_eq!(svg.matches(&format!("fill=\"{}\"", colors.color(Face::F))).count(), 9);
+    }
+
+    #[test]
+    fn test_isometric_svg() {
+        let colors = ColorScheme::default();
+        let svg = Cube::solved().to_svg(SvgStyle::Isometric, &colors);
+        assert_eq!(svg.matches("<polygon").count(), 27);
+        for face in [Face::U, Face::F, Face::R] {
+            assert_eq!(svg.matches(&format!("fill=\"{}\"", colors.color(face))).count(), 9);
+        }
+
+        // U moves the stickers of B into the top row of R.
+        let svg = Cube::solved().twisted(Twist::U1).to_svg(SvgStyle::Isometric, &colors);
+        assert_eq!(svg.matches(&format!("fill=\"{}\"", colors.color(Face::B))).count(), 3);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::solver::Solver;
+use crate::*;
+
+/// What a robot reports after it was told to execute a twist.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Acknowledgement {
+    /// The twist was executed as told.
+    Done,
+    /// The robot executed these twists instead, e.g. none if a gripper slipped, or a quarter turn instead of a half turn.
+    Misexecuted(Vec<Twist>),
+}
+
+/// A physical executor of twists, e.g. a cube-solving robot.
+pub trait MoveSink {
+    /// Executes the twist and reports what was done, or fails if the robot can't go on.
+    fn execute(&mut self, twist: Twist) -> Result<Acknowledgement, String>;
+}
+
+/// How a robot solved a cube.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RobotRun {
+    /// The twists the robot executed, including misexecuted ones.
+    pub twists: Vec<Twist>,
+    /// The number of times the cube was solved anew after a misexecution.
+    pub resolves: usize,
+}
+
+/// Feeds solutions to a robot twist by twist, and solves the cube anew whenever the robot reports a misexecution.
+pub struct RobotSolver<'s> {
+    solver: &'s dyn Solver,
+    options: SolveOptions,
+    max_resolves: usize,
+}
+
+impl<'s> RobotSolver<'s> {
+    pub fn new(solver: &'s dyn Solver) -> Self {
+        Self { solver, options: SolveOptions::default(), max_resolves: 10 }
+    }
+
+    pub fn with_options(mut self, options: SolveOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Gives up after this many misexecutions, since a robot that keeps failing needs attention.
+    pub fn with_max_resolves(mut self, max_resolves: usize) -> Self {
+        self.max_resolves = max_resolves;
+        self
+    }
+
+    /// Solves the cube with the robot.
+    pub fn run(&self, cube: Cube, sink: &mut impl MoveSink) -> Result<RobotRun, String> {
+        let mut tracker = Tracker::new(cube);
+        let mut resolves = 0;
+        'solve: while !tracker.is_solved() {
+            let solution = self.solver.solve(tracker.cube(), &self.options)?;
+            for &twist in solution.alg.twists() {
+                match sink.execute(twist)? {
+                    Acknowledgement::Done => {
+                        tracker.push(twist);
+                    }
+                    Acknowledgement::Misexecuted(twists) => {
+                        tracker.extend(twists);
+                        if resolves == self.max_resolves {
+                            return Err(format!("The robot misexecuted {} times", resolves + 1));
+                        }
+                        resolves += 1;
+                        continue 'solve;
+                    }
+                }
+            }
+        }
+        Ok(RobotRun { twists: tracker.twists().to_vec(), resolves })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Solves cubes at most two twists away.
+    struct ShortSolver;
+
+    impl Solver for ShortSolver {
+        fn solve(&self, cube: Cube, _: &SolveOptions) -> Result<Solution, String> {
+            let sequences = std::iter::once(vec![])
+                .chain(ALL_TWISTS.iter().map(|&t| vec![t]))
+                .chain(ALL_TWISTS.iter().flat_map(|&t| ALL_TWISTS.iter().map(move |&u| vec![t, u])));
+            sequences
+                .into_iter()
+                .find(|twists| cube.twisted_by(twists) == Cube::solved())
+                .map(|twists| Solution { alg: Alg::new(twists), stats: SolveStats::default(), annotation: None })
+                .ok_or_else(|| "No solution found".to_string())
+        }
+    }
+
+    /// Executes the twists, but the given ones only halfway.
+    struct SlippingRobot {
+        slips: Vec<usize>,
+        executed: usize,
+    }
+
+    impl MoveSink for SlippingRobot {
+        fn execute(&mut self, twist: Twist) -> Result<Acknowledgement, String> {
+            self.executed += 1;
+            if !self.slips.contains(&(self.executed - 1)) {
+                return Ok(Acknowledgement::Done);
+            }
+            // A half turn slips to a quarter turn, and a quarter turn doesn't happen at all.
+            let face = twist as u32 / 3 * 3;
+            Ok(Acknowledgement::Misexecuted(match twist as u32 % 3 {
+                1 => vec![Twist::from(face)],
+                _ => vec![],
+            }))
+        }
+    }
+
+    #[test]
+    fn test_robot_solver() {
+        let cube = Cube::solved().twisted_by(&[Twist::R2, Twist::U1]);
+        let robot_solver = RobotSolver::new(&ShortSolver);
+
+        let mut robot = SlippingRobot { slips: vec![], executed: 0 };
+        let run = robot_solver.run(cube, &mut robot).unwrap();
+        assert_eq!(run, RobotRun { twists: vec![Twist::U3, Twist::R2], resolves: 0 });
+
+        // The R2 slips to R, so R is added.
+        let mut robot = SlippingRobot { slips: vec![1], executed: 0 };
+        let run = robot_solver.run(cube, &mut robot).unwrap();
+        assert_eq!(run, RobotRun { twists: vec![Twist::U3, Twist::R1, Twist::R1], resolves: 1 });
+
+        let mut robot = SlippingRobot { slips: vec![0, 1], executed: 0 };
+        assert!(robot_solver.with_max_resolves(1).run(cube, &mut robot).is_err());
+    }
+}
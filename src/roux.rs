@@ -0,0 +1,347 @@
+use crate::*;
+
+/// The 1x2x3 block on the left: the corners DFL and DBL and the edges DL, FL and BL.
+pub const FIRST_BLOCK: PieceMask = PieceMask::NONE.with_corner(4).with_corner(6).with_edge(7).with_edge(8).with_edge(11);
+
+/// The 1x2x3 block on the right: the corners DFR and DBR and the edges DR, FR and BR.
+pub const SECOND_BLOCK: PieceMask = PieceMask::NONE.with_corner(5).with_corner(7).with_edge(6).with_edge(9).with_edge(10);
+
+// Overlapping groups of the blocks, each small enough to create its distance table quickly.
+const BLOCK_GROUPS: [PieceMask; 4] = [
+    PieceMask::NONE.with_corner(4).with_corner(6).with_edge(7).with_edge(8),
+    PieceMask::NONE.with_corner(4).with_corner(6).with_edge(7).with_edge(11),
+    PieceMask::NONE.with_corner(5).with_corner(7).with_edge(6).with_edge(9),
+    PieceMask::NONE.with_corner(5).with_corner(7).with_edge(6).with_edge(10),
+];
+
+/// How the U corners are oriented, named like the CMLL sets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CmllOrientation {
+    O,
+    H,
+    Pi,
+    U,
+    T,
+    S,
+    AS,
+    L,
+}
+
+/// How the U corners are permuted, up to a U twist.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CmllPermutation {
+    Solved,
+    Adjacent,
+    Diagonal,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CmllCase {
+    pub orientation: CmllOrientation,
+    pub permutation: CmllPermutation,
+}
+
+// The U corners clockwise, seen from above: UFL, UBL, UBR, UFR.
+const U_CORNERS: [usize; 4] = [0, 2, 3, 1];
+
+/// The CMLL case of the U corners, or None if they aren't all in the U layer.
+pub fn cmll_case(cube: &Cube) -> Option<CmllCase> {
+    let corners = cube.corners();
+    let facelets = cube.facelets();
+    let faces = facelets.faces();
+
+    // The sticker of each corner that has the U color, as an index into its facelets.
+    let mut u_stickers = [0; 4];
+    let mut pieces = [0; 4];
+    for (i, &position) in U_CORNERS.iter().enumerate() {
+        let piece = corners.piece_at(position).0;
+        pieces[i] = U_CORNERS.iter().position(|&c| c == piece)?;
+        u_stickers[i] = CORNER_FACELETS[position].iter().position(|&f| faces[f] == Face::U)?;
+    }
+
+    let twisted: Vec<usize> = (0..4).filter(|&i| u_stickers[i] != 0).collect();
+    let faces_of_twisted: Vec<usize> = twisted.iter().map(|&i| CORNER_FACELETS[U_CORNERS[i]][u_stickers[i]] / 9).collect();
+    let orientation = match twisted.len() {
+        0 => CmllOrientation::O,
+        3 if u_stickers[twisted[0]] == 2 => CmllOrientation::S,
+        3 => CmllOrientation::AS,
+        2 if twisted[1] - twisted[0] == 2 => CmllOrientation::L,
+        // The U stickers of the twisted corners look the same way (headlights) or away from each other.
+        2 if faces_of_twisted[0] == faces_of_twisted[1] => CmllOrientation::U,
+        2 => CmllOrientation::T,
+        // Two pairs of headlights, or one pair and two corners looking sideways.
+        _ if faces_of_twisted.iter().all(|f| faces_of_twisted.iter().filter(|&g| g == f).count() == 2) => CmllOrientation::H,
+        _ => CmllOrientation::Pi,
+    };
+
+    // The corners in clockwise order, starting with UFL, relative to the first one.
+    let first = pieces.iter().position(|&p| p == 0)?;
+    let order: [usize; 4] = core::array::from_fn(|i| pieces[(first + i) % 4]);
+    let permutation = match order {
+        [0, 1, 2, 3] => CmllPermutation::Solved,
+        [0, 3, 2, 1] => CmllPermutation::Diagonal,
+        _ => CmllPermutation::Adjacent,
+    };
+    Some(CmllCase { orientation, permutation })
+}
+
+/// A twist of the last six edges step: M follows L, U as usual.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LseMove {
+    M1, M2, M3,
+    U1, U2, U3,
+}
+
+pub const ALL_LSE_MOVES: [LseMove; 6] = [LseMove::M1, LseMove::M2, LseMove::M3, LseMove::U1, LseMove::U2, LseMove::U3];
+
+impl LseMove {
+    pub fn to_wca(&self) -> &'static str {
+        ["M", "M2", "M'", "U", "U2", "U'"][*self as usize]
+    }
+
+    /// Number of quarter twists clockwise.
+    fn amount(self) -> usize {
+        self as usize % 3 + 1
+    }
+}
+
+/// The state of the last six edges, after the blocks and CMLL.
+///
+/// Unlike `Cube`, it's relative to the blocks, so the centers of the M slice can move.
+/// The edges are at UF, UB, DB, DF, UL and UR, and they are flipped if their U or D sticker isn't on U or D.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LseState {
+    edges: [usize; 6],
+    flips: [usize; 6],
+    /// Quarter U twists of the U corners.
+    auf: usize,
+    /// Quarter M twists of the centers.
+    centers: usize,
+}
+
+impl LseState {
+    pub const INDEX_SIZE: usize = factorial(6) * 64 * 4 * 4;
+
+    pub fn solved() -> Self {
+        Self { edges: [0, 1, 2, 3, 4, 5], flips: [0; 6], auf: 0, centers: 0 }
+    }
+
+    /// The last six edges of a cube with the blocks solved and the U corners solved up to a U twist.
+    pub fn from_cube(cube: &Cube) -> Result<Self, String> {
+        let (corners, edges) = (cube.corners(), cube.edges());
+        if !FIRST_BLOCK.union(SECOND_BLOCK).is_solved(&corners, &edges) {
+            return Err("The blocks aren't solved".into());
+        }
+        let auf = (0..4)
+            .find(|&auf| Corners::twists(&vec![Twist::U1; auf]) == corners)
+            .ok_or("The U corners aren't solved")?;
+        let facelets = cube.facelets();
+        let faces = facelets.faces();
+        let mut state = Self { auf, ..Self::solved() };
+        for position in 0..6 {
+            state.edges[position] = edges.piece_at(position).0;
+            let primary = faces[EDGE_FACELETS[position][0]];
+            state.flips[position] = usize::from(primary != Face::U && primary != Face::D);
+        }
+        Ok(state)
+    }
+
+    pub fn is_solved(&self) -> bool {
+        *self == Self::solved()
+    }
+
+    pub fn moved(&self, m: LseMove) -> Self {
+        let mut state = *self;
+        for _ in 0..m.amount() {
+            state = match m {
+                LseMove::M1 | LseMove::M2 | LseMove::M3 => state.quarter_m(),
+                _ => state.quarter_u(),
+            };
+        }
+        state
+    }
+
+    pub fn moved_by(&self, moves: &[LseMove]) -> Self {
+        moves.iter().fold(*self, |state, &m| state.moved(m))
+    }
+
+    fn quarter_m(mut self) -> Self {
+        // UB -> UF -> DF -> DB -> UB, and each edge flips.
+        for array in [&mut self.edges, &mut self.flips] {
+            let [uf, ub, db, df, ..] = *array;
+            array[..4].copy_from_slice(&[ub, db, df, uf]);
+        }
+        for flip in &mut self.flips[..4] {
+            *flip ^= 1;
+        }
+        self.centers = (self.centers + 1) % 4;
+        self
+    }
+
+    fn quarter_u(mut self) -> Self {
+        // UF -> UL -> UB -> UR -> UF.
+        for array in [&mut self.edges, &mut self.flips] {
+            let [uf, ub, _, _, ul, ur] = *array;
+            (array[0], array[1], array[4], array[5]) = (ur, ul, uf, ub);
+        }
+        self.auf = (self.auf + 1) % 4;
+        self
+    }
+
+    pub fn index(&self) -> usize {
+        let flips = self.flips.iter().fold(0, |bits, &flip| bits * 2 + flip);
+        ((permutation_index(&self.edges) * 64 + flips) * 4 + self.auf) * 4 + self.centers
+    }
+}
+
+/// Solvers for the steps of the Roux method: the first block, the second block and the last six edges.
+/// Between these, `cmll_case` recognizes the case of the U corners.
+///
+/// The blocks are solved relative to the centers, with any twists.
+/// The last six edges are solved optimally with M and U, using a table of all their states.
+pub struct RouxSolver {
+    blocks: MaskedSolver,
+    lse: Vec<u8>,
+}
+
+impl RouxSolver {
+    pub fn new() -> Self {
+        Self { blocks: MaskedSolver::with_groups(&BLOCK_GROUPS), lse: create_lse_table() }
+    }
+
+    /// A shortest sequence of twists that solves the first block.
+    pub fn first_block(&self, cube: Cube, max_solution_length: u8) -> Result<Vec<Twist>, String> {
+        self.blocks.solve_part(cube, FIRST_BLOCK, max_solution_length)
+    }
+
+    /// A shortest sequence of twists that solves the second block, keeping the first one.
+    pub fn second_block(&self, cube: Cube, max_solution_length: u8) -> Result<Vec<Twist>, String> {
+        if !FIRST_BLOCK.is_solved(&cube.corners(), &cube.edges()) {
+            return Err("The first block isn't solved".into());
+        }
+        self.blocks.solve_part(cube, FIRST_BLOCK.union(SECOND_BLOCK), max_solution_length)
+    }
+
+    /// A shortest sequence of M and U twists that solves the last six edges.
+    pub fn last_six_edges(&self, state: LseState) -> Result<Vec<LseMove>, String> {
+        let mut state = state;
+        let mut moves = Vec::new();
+        let mut distance = self.lse[state.index()];
+        if distance == u8::MAX {
+            return Err("The state can't be solved with M and U".into());
+        }
+        while distance > 0 {
+            let m = ALL_LSE_MOVES
+                .into_iter()
+                .find(|&m| self.lse[state.moved(m).index()] == distance - 1)
+                .unwrap();
+            state = state.moved(m);
+            moves.push(m);
+            distance -= 1;
+        }
+        Ok(moves)
+    }
+}
+
+impl Default for RouxSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Distances of all states of the last six edges to the solved one, u8::MAX for unreachable ones.
+fn create_lse_table() -> Vec<u8> {
+    let mut table = vec![u8::MAX; LseState::INDEX_SIZE];
+    table[LseState::solved().index()] = 0;
+    let mut frontier = vec![LseState::solved()];
+    let mut distance = 0;
+    while !frontier.is_empty() {
+        distance += 1;
+        let mut next = Vec::new();
+        for state in frontier {
+            for m in ALL_LSE_MOVES {
+                let moved = state.moved(m);
+                if table[moved.index()] == u8::MAX {
+                    table[moved.index()] = distance;
+                    next.push(moved);
+                }
+            }
+        }
+        frontier = next;
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocks() {
+        let solver = RouxSolver::new();
+        let cube = Cube::solved().twisted_by(&[Twist::R1, Twist::U1, Twist::L1, Twist::D2, Twist::B3]);
+        let first = solver.first_block(cube, 8).unwrap();
+        let cube = cube.twisted_by(&first);
+        assert!(FIRST_BLOCK.is_solved(&cube.corners(), &cube.edges()));
+
+        let second = solver.second_block(cube, 10).unwrap();
+        let cube = cube.twisted_by(&second);
+        assert!(FIRST_BLOCK.union(SECOND_BLOCK).is_solved(&cube.corners(), &cube.edges()));
+        assert!(first.len() + second.len() <= 5);
+    }
+
+    #[test]
+    fn test_cmll_case() {
+        let case = |orientation, permutation| Some(CmllCase { orientation, permutation });
+        let sune = parse_twist_sequence("R U R' U R U2 R'").unwrap();
+        let antisune = parse_twist_sequence("R U2 R' U' R U' R'").unwrap();
+        let solved = Cube::solved();
+        assert_eq!(cmll_case(&solved), case(CmllOrientation::O, CmllPermutation::Solved));
+        assert_eq!(cmll_case(&solved.twisted(Twist::U1)), case(CmllOrientation::O, CmllPermutation::Solved));
+        // The case that an algorithm solves is its inverse.
+        assert_eq!(cmll_case(&solved.twisted_by(&antisune)), case(CmllOrientation::S, CmllPermutation::Solved));
+        assert_eq!(cmll_case(&solved.twisted_by(&sune)), case(CmllOrientation::AS, CmllPermutation::Solved));
+        let h = parse_twist_sequence("R U R' U R U' R' U R U2 R'").unwrap();
+        assert_eq!(cmll_case(&solved.twisted_by(&h)), case(CmllOrientation::H, CmllPermutation::Solved));
+        let pi = parse_twist_sequence("R U2 R2 U' R2 U' R2 U2 R").unwrap();
+        assert_eq!(cmll_case(&solved.twisted_by(&pi)).unwrap().orientation, CmllOrientation::Pi);
+        // R' D' R D twice twists the UFR corner, four times back.
+        let twist = |twists: &str| format!("R' D' R D R' D' R D {twists}");
+        let (cw, ccw) = (twist(""), twist(&twist("")));
+        // The U stickers at UFR and UBR look to F and B, to R and R, or they're diagonal.
+        let t = parse_twist_sequence(&format!("{cw} U {ccw} U'")).unwrap();
+        assert_eq!(cmll_case(&solved.twisted_by(&t)).unwrap().orientation, CmllOrientation::T);
+        let u = parse_twist_sequence(&format!("{ccw} U {cw} U'")).unwrap();
+        assert_eq!(cmll_case(&solved.twisted_by(&u)).unwrap().orientation, CmllOrientation::U);
+        let l = parse_twist_sequence(&format!("{cw} U2 {ccw} U2")).unwrap();
+        assert_eq!(cmll_case(&solved.twisted_by(&l)).unwrap().orientation, CmllOrientation::L);
+        // The A permutation cycles three corners, which is an adjacent swap up to a U twist.
+        let a = parse_twist_sequence("R' F R' B2 R F' R' B2 R2").unwrap();
+        assert_eq!(cmll_case(&solved.twisted_by(&a)), case(CmllOrientation::O, CmllPermutation::Adjacent));
+        assert_eq!(cmll_case(&solved.twisted(Twist::R1)), None);
+    }
+
+    #[test]
+    fn test_last_six_edges() {
+        let solver = RouxSolver::new();
+        assert_eq!(LseState::from_cube(&Cube::solved().twisted(Twist::U1)), Ok(LseState::solved().moved(LseMove::U1)));
+        // M2 U M2 U2 M2 U M2 relative to the centers, where M2 is R2 L2 and moves D to the top.
+        let h_perm = parse_twist_sequence("R2 L2 D R2 L2 U2 R2 L2 D R2 L2").unwrap();
+        let lse_h_perm = [LseMove::M2, LseMove::U1, LseMove::M2, LseMove::U2, LseMove::M2, LseMove::U1, LseMove::M2];
+        assert_eq!(LseState::from_cube(&Cube::solved().twisted_by(&h_perm)), Ok(LseState::solved().moved_by(&lse_h_perm)));
+        assert!(LseState::from_cube(&Cube::solved().twisted(Twist::R1)).is_err());
+
+        let scramble = [LseMove::M1, LseMove::U1, LseMove::M3, LseMove::U2, LseMove::M2, LseMove::U3, LseMove::M1];
+        let state = LseState::solved().moved_by(&scramble);
+        assert!(!state.is_solved());
+        let solution = solver.last_six_edges(state).unwrap();
+        assert!(solution.len() <= scramble.len());
+        assert!(state.moved_by(&solution).is_solved());
+        assert_eq!(solver.last_six_edges(LseState::solved()), Ok(vec![]));
+
+        // Flipping a single edge isn't possible.
+        let mut flipped = LseState::solved();
+        flipped.flips[0] = 1;
+        assert!(solver.last_six_edges(flipped).is_err());
+    }
+}
@@ -0,0 +1,82 @@
+use crate::*;
+use thiserror::Error;
+
+/// Why a scramble breaks the WCA scramble rules.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ScrambleError {
+    #[error("{0}")]
+    Parse(String),
+    #[error("The scramble has {length} twists, expected {min} to {max}")]
+    Length { length: usize, min: usize, max: usize },
+    #[error("Twist {0} turns the same face as the twist before")]
+    SameFace(usize),
+    #[error("Twist {0} turns the same face as the twist two before, with only the opposite face between")]
+    SameAxis(usize),
+    #[error("The scrambled cube is solved in less than 2 twists")]
+    NearlySolved,
+}
+
+/// The rules a scramble of a competition is checked against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScrambleRules {
+    pub min_length: usize,
+    pub max_length: usize,
+}
+
+impl ScrambleRules {
+    /// The random-state scrambles of TNoodle, the WCA scramble program, have about 19 twists.
+    pub const WCA: Self = Self { min_length: 15, max_length: 25 };
+}
+
+impl Default for ScrambleRules {
+    fn default() -> Self {
+        Self::WCA
+    }
+}
+
+/// Checks an external scramble in WCA notation, e.g. of TNoodle, and returns the scrambled cube.
+///
+/// Like TNoodle's scrambles, it must not twist a face twice in a row, nor twist a face again after only its opposite face,
+/// as in "R L R", since these are redundant.
+/// The scrambled cube, which is solvable as it is reached by twists, must need at least 2 twists to be solved, as WCA regulation 4b3 requires.
+pub fn check_scramble(scramble: &str, rules: &ScrambleRules) -> Result<Cube, ScrambleError> {
+    let twists = parse_twist_sequence(scramble).map_err(ScrambleError::Parse)?;
+    if !(rules.min_length..=rules.max_length).contains(&twists.len()) {
+        return Err(ScrambleError::Length { length: twists.len(), min: rules.min_length, max: rules.max_length });
+    }
+    for i in 1..twists.len() {
+        if twists[i].face() == twists[i - 1].face() {
+            return Err(ScrambleError::SameFace(i));
+        }
+        if i >= 2 && twists[i].face() == twists[i - 2].face() && same_axis(twists[i], twists[i - 1]) {
+            return Err(ScrambleError::SameAxis(i));
+        }
+    }
+    let cube = Cube::solved().twisted_by(&twists);
+    if cube == Cube::solved() || ALL_TWISTS.iter().any(|&twist| cube.twisted(twist) == Cube::solved()) {
+        return Err(ScrambleError::NearlySolved);
+    }
+    Ok(cube)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_scramble() {
+        let rules = ScrambleRules { min_length: 2, max_length: 25 };
+        let scramble = "D2 F' U2 L2 B2 U' R2 D' F2 U L2 B' L R' D2 F' U' R B2";
+        assert_eq!(check_scramble(scramble, &ScrambleRules::WCA), Ok(Cube::solved().twisted_by(&parse_twist_sequence(scramble).unwrap())));
+
+        assert!(matches!(check_scramble("R U X", &rules), Err(ScrambleError::Parse(_))));
+        assert_eq!(check_scramble("R U", &ScrambleRules::WCA), Err(ScrambleError::Length { length: 2, min: 15, max: 25 }));
+        assert_eq!(check_scramble("R U U2 F", &rules), Err(ScrambleError::SameFace(2)));
+        assert_eq!(check_scramble("R L R U", &rules), Err(ScrambleError::SameAxis(2)));
+        assert_eq!(check_scramble("R L U", &rules).map(|_| ()), Ok(()));
+        assert_eq!(check_scramble("U D'", &rules).map(|_| ()), Ok(()));
+        assert_eq!(check_scramble("R R'", &ScrambleRules { min_length: 0, max_length: 2 }), Err(ScrambleError::SameFace(1)));
+        assert_eq!(check_scramble("", &ScrambleRules { min_length: 0, max_length: 2 }), Err(ScrambleError::NearlySolved));
+        assert_eq!(check_scramble("F2", &ScrambleRules { min_length: 0, max_length: 2 }), Err(ScrambleError::NearlySolved));
+    }
+}
@@ -0,0 +1,153 @@
+use clap::Parser;
+use rubikscube::*;
+use std::io::Read;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Solves cubes posted to `POST /solve`.
+///
+/// The request body is a JSON object with either a "scramble" in WCA notation or "facelets" in URFDLB order,
+/// and optionally a "max_length" of the solution.
+/// The tables are loaded once and shared by all workers.
+#[derive(Parser)]
+struct Cli {
+    /// Address to listen on.
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    address: String,
+    /// Number of requests solved at the same time [default: number of CPUs]
+    #[arg(long)]
+    threads: Option<usize>,
+    /// Give up on a request after this many seconds.
+    #[arg(long, default_value_t = 10.0)]
+    max_time: f64,
+    /// Give up on a request after this many phase-1 nodes, which also stops abandoned searches [default: no limit]
+    #[arg(long)]
+    max_nodes: Option<usize>,
+    /// Ignore the time limit, so that the solutions only depend on the requests; bound the work with --max-nodes instead.
+    #[arg(long)]
+    deterministic: bool,
+    /// Config file with the table paths.
+    #[arg(long, default_value = "config.txt")]
+    config: String,
+}
+
+#[derive(serde::Deserialize)]
+struct SolveRequest {
+    scramble: Option<String>,
+    facelets: Option<String>,
+    #[serde(default = "default_max_length")]
+    max_length: u8,
+}
+
+fn default_max_length() -> u8 {
+    20
+}
+
+#[derive(serde::Serialize)]
+struct SolveResponse {
+    cube: Cube,
+    solution: Solution,
+    seconds: f64,
+}
+
+/// The largest request body that is read, far more than any request needs.
+const MAX_BODY: u64 = 64 * 1024;
+
+/// An HTTP status code and a message for the client.
+struct HttpError(u16, String);
+
+impl SolveRequest {
+    fn cube(&self) -> Result<Cube, HttpError> {
+        let bad_request = |err: String| HttpError(400, err);
+        match (&self.scramble, &self.facelets) {
+            (Some(scramble), None) => Ok(Cube::solved().twisted_by(&parse_twist_sequence(scramble).map_err(bad_request)?)),
+            (None, Some(facelets)) => facelets
+                .parse()
+                .and_then(|facelets| Cube::from_facelets(&facelets))
+                .map_err(|err| bad_request(err.to_string())),
+            _ => Err(bad_request("Expected either \"scramble\" or \"facelets\"".to_string())),
+        }
+    }
+}
+
+fn json_response(status: u16, body: String) -> Response<std::io::Cursor<Vec<u8>>> {
+    let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+    Response::from_string(body).with_status_code(status).with_header(content_type)
+}
+
+/// Solves the request on its own thread, so it can be abandoned after the time limit.
+/// The solver stops an abandoned search at the time limit or the node limit.
+fn solve(pool: &'static SolverPool<'static>, request: &mut Request, max_time: Duration, deterministic: bool) -> Result<SolveResponse, HttpError> {
+    if (request.method(), request.url()) != (&Method::Post, "/solve") {
+        return Err(HttpError(404, "Only POST /solve is supported".to_string()));
+    }
+    let mut body = String::new();
+    // One byte more than the limit tells a body at the limit from a longer one.
+    request.as_reader().take(MAX_BODY + 1).read_to_string(&mut body).map_err(|err| HttpError(400, err.to_string()))?;
+    if body.len() as u64 > MAX_BODY {
+        return Err(HttpError(413, format!("The request body exceeds {MAX_BODY} bytes")));
+    }
+    let solve_request: SolveRequest = serde_json::from_str(&body).map_err(|err| HttpError(400, err.to_string()))?;
+    let cube = solve_request.cube()?;
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let start = Instant::now();
+        let result = pool.solve(cube, solve_request.max_length);
+        // The receiver is gone if the time limit passed.
+        let _ = sender.send(result.map(|solution| (solution, start.elapsed())));
+    });
+    let received = if deterministic { receiver.recv().map_err(|_| RecvTimeoutError::Disconnected) } else { receiver.recv_timeout(max_time) };
+    match received {
+        Ok(Ok((solution, time))) => Ok(SolveResponse { cube, solution, seconds: time.as_secs_f64() }),
+        Ok(Err(err)) => Err(HttpError(422, err)),
+        Err(RecvTimeoutError::Timeout) => Err(HttpError(504, "No solution found within the time limit".to_string())),
+        Err(RecvTimeoutError::Disconnected) => Err(HttpError(500, "The solver failed".to_string())),
+    }
+}
+
+fn serve(server: &Server, pool: &'static SolverPool<'static>, max_time: Duration, deterministic: bool) {
+    while let Ok(mut request) = server.recv() {
+        let response = match solve(pool, &mut request, max_time, deterministic) {
+            Ok(response) => json_response(200, serde_json::to_string(&response).unwrap()),
+            Err(HttpError(status, message)) => json_response(status, serde_json::json!({ "error": message }).to_string()),
+        };
+        if let Err(err) = request.respond(response) {
+            eprintln!("Failed to respond: {err}");
+        }
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    init_twister();
+    init_subset_twister();
+    init_subset_index();
+
+    let tables = TableSet::builder().config_file(&cli.config).build().unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(1);
+    });
+    // The tables are needed until the server stops, and by searches that outlive their request.
+    let tables: &'static TableSet = Box::leak(Box::new(tables));
+
+    let server = Server::http(&cli.address).unwrap_or_else(|err| {
+        eprintln!("Failed to listen on {}: {err}", cli.address);
+        std::process::exit(1);
+    });
+    let threads = cli.threads.unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+    let max_time = Duration::from_secs_f64(cli.max_time);
+    // The solver also stops at the time limit, so that abandoned searches don't keep running.
+    let options = SolveOptions { max_nodes: cli.max_nodes, max_time: Some(max_time), deterministic: cli.deterministic, ..SolveOptions::default() };
+    // Searches that outlive their request keep their worker, so new requests wait for them.
+    let pool: &'static SolverPool = Box::leak(Box::new(SolverPool::new(TwoPhaseSolver::with_tables(tables).with_options(options), threads)));
+    println!("Listening on http://{} with {threads} threads", cli.address);
+
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| serve(&server, pool, max_time, cli.deterministic));
+        }
+    });
+}
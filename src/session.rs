@@ -0,0 +1,218 @@
+use crate::*;
+use std::time::Duration;
+
+/// A penalty of a timed solve, by the WCA regulations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Penalty {
+    #[default]
+    None,
+    /// Two seconds are added, e.g. for a cube a quarter turn away from solved.
+    PlusTwo,
+    /// Did not finish.
+    Dnf,
+}
+
+/// The result of a solve or an average. A DNF is worse than any time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SolveTime {
+    Time(Duration),
+    Dnf,
+}
+
+impl core::fmt::Display for SolveTime {
+    /// In the format of timers, e.g. "9.87", "1:02.34" or "DNF".
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Time(time) => {
+                let centis = time.as_millis() / 10;
+                let (minutes, seconds) = (centis / 6000, centis % 6000);
+                if minutes > 0 {
+                    write!(f, "{}:{:02}.{:02}", minutes, seconds / 100, seconds % 100)
+                } else {
+                    write!(f, "{}.{:02}", seconds / 100, seconds % 100)
+                }
+            }
+            Self::Dnf => write!(f, "DNF"),
+        }
+    }
+}
+
+/// A timed solve.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolveRecord {
+    pub scramble: Alg,
+    /// The time on the timer, without the penalty.
+    pub time: Duration,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub penalty: Penalty,
+}
+
+impl SolveRecord {
+    pub fn new(scramble: Alg, time: Duration) -> Self {
+        Self { scramble, time, penalty: Penalty::None }
+    }
+
+    /// The time with the penalty.
+    pub fn result(&self) -> SolveTime {
+        match self.penalty {
+            Penalty::None => SolveTime::Time(self.time),
+            Penalty::PlusTwo => SolveTime::Time(self.time + Duration::from_secs(2)),
+            Penalty::Dnf => SolveTime::Dnf,
+        }
+    }
+}
+
+/// The solves of a speedcubing session and their statistics, e.g. for a timer application.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Session {
+    solves: Vec<SolveRecord>,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The solves, oldest first.
+    pub fn solves(&self) -> &[SolveRecord] {
+        &self.solves
+    }
+
+    pub fn push(&mut self, solve: SolveRecord) {
+        self.solves.push(solve);
+    }
+
+    /// Changes the penalty of a solve, e.g. after a judge's decision.
+    pub fn set_penalty(&mut self, index: usize, penalty: Penalty) {
+        self.solves[index].penalty = penalty;
+    }
+
+    pub fn remove(&mut self, index: usize) -> SolveRecord {
+        self.solves.remove(index)
+    }
+
+    /// The best single, if there are solves.
+    pub fn best(&self) -> Option<SolveTime> {
+        self.solves.iter().map(SolveRecord::result).min()
+    }
+
+    /// The mean of the finished solves, if there are any.
+    pub fn mean(&self) -> Option<Duration> {
+        let times: Vec<Duration> = self
+            .solves
+            .iter()
+            .filter_map(|solve| match solve.result() {
+                SolveTime::Time(time) => Some(time),
+                SolveTime::Dnf => None,
+            })
+            .collect();
+        (!times.is_empty()).then(|| times.iter().sum::<Duration>() / times.len() as u32)
+    }
+
+    /// The average of the last `count` solves, if there are that many.
+    ///
+    /// Like the WCA averages, the best and the worst 5% of the solves, but at least one each, are left out,
+    /// and the average is a DNF if more solves than that are DNFs.
+    pub fn average_of(&self, count: usize) -> Option<SolveTime> {
+        if count < 3 || self.solves.len() < count {
+            return None;
+        }
+        let mut results: Vec<SolveTime> = self.solves[self.solves.len() - count..].iter().map(SolveRecord::result).collect();
+        results.sort();
+        let trimmed = count.div_ceil(20);
+        let counted = &results[trimmed..count - trimmed];
+        let times: Option<Vec<Duration>> = counted
+            .iter()
+            .map(|result| match result {
+                SolveTime::Time(time) => Some(*time),
+                SolveTime::Dnf => None,
+            })
+            .collect();
+        Some(match times {
+            Some(times) => SolveTime::Time(times.iter().sum::<Duration>() / times.len() as u32),
+            None => SolveTime::Dnf,
+        })
+    }
+
+    /// The average of the last 5 solves.
+    pub fn ao5(&self) -> Option<SolveTime> {
+        self.average_of(5)
+    }
+
+    /// The average of the last 12 solves.
+    pub fn ao12(&self) -> Option<SolveTime> {
+        self.average_of(12)
+    }
+
+    /// Writes the session as JSON.
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Reads a session written by `save`.
+    #[cfg(feature = "serde")]
+    pub fn load(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session_of(millis: &[u64]) -> Session {
+        let mut session = Session::new();
+        for &time in millis {
+            session.push(SolveRecord::new(Alg::new(vec![Twist::R1]), Duration::from_millis(time)));
+        }
+        session
+    }
+
+    fn time(millis: u64) -> Option<SolveTime> {
+        Some(SolveTime::Time(Duration::from_millis(millis)))
+    }
+
+    #[test]
+    fn test_statistics() {
+        let mut session = session_of(&[12_000, 10_000, 11_000, 14_000, 9_000]);
+        assert_eq!(session.best(), time(9_000));
+        assert_eq!(session.mean(), Some(Duration::from_millis(11_200)));
+        assert_eq!(session.ao5(), time(11_000));
+        assert_eq!(session.ao12(), None);
+
+        // One DNF is left out as the worst solve, two make the average a DNF.
+        session.set_penalty(1, Penalty::Dnf);
+        assert_eq!(session.ao5(), Some(SolveTime::Time(Duration::from_secs(37) / 3)));
+        session.set_penalty(4, Penalty::PlusTwo);
+        assert_eq!(session.best(), time(11_000));
+        session.set_penalty(0, Penalty::Dnf);
+        assert_eq!(session.ao5(), Some(SolveTime::Dnf));
+        assert_eq!(session.mean(), Some(Duration::from_millis(12_000)));
+
+        assert_eq!(session.remove(0).penalty, Penalty::Dnf);
+        assert_eq!(session.ao5(), None);
+        assert_eq!(session_of(&[10_000; 12]).ao12(), time(10_000));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(SolveTime::Time(Duration::from_millis(9876)).to_string(), "9.87");
+        assert_eq!(SolveTime::Time(Duration::from_millis(62_340)).to_string(), "1:02.34");
+        assert_eq!(SolveTime::Dnf.to_string(), "DNF");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_save_and_load() {
+        let mut session = session_of(&[12_000, 10_000]);
+        session.set_penalty(1, Penalty::PlusTwo);
+        let path = std::env::temp_dir().join("rubikscube_test_session.json");
+        session.save(&path).unwrap();
+        assert_eq!(Session::load(&path).unwrap(), session);
+        std::fs::remove_file(path).unwrap();
+    }
+}
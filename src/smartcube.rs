@@ -0,0 +1,249 @@
+//! Decoders of the move notifications of Bluetooth smart cubes.
+//!
+//! The Bluetooth connection itself is left to the caller, who passes the raw notification bytes to a decoder
+//! and feeds the resulting twists into a `Tracker`.
+
+use crate::*;
+use aes::Aes128;
+use aes::cipher::{BlockDecrypt, KeyInit, generic_array::GenericArray};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum SmartCubeError {
+    #[error("Expected a packet of {expected} bytes, found {actual}")]
+    Length { expected: usize, actual: usize },
+
+    #[error("The packet doesn't report a move")]
+    NotAMove,
+
+    #[error("Invalid move code {0:#04x}")]
+    InvalidMove(u8),
+}
+
+/// The twist of the given face, in the order L, R, U, D, F, B of `Twist`, and quarter turns.
+fn twist_of(face: usize, quarter_turns: usize) -> Twist {
+    Twist::from((face * 3 + quarter_turns - 1) as u32)
+}
+
+/// Index of a face in `Twist`, by its letter.
+fn face_index(face: u8) -> usize {
+    match face {
+        b'L' => 0,
+        b'R' => 1,
+        b'U' => 2,
+        b'D' => 3,
+        b'F' => 4,
+        _ => 5,
+    }
+}
+
+/// Decodes the state notifications of Giiker cubes (Xiaomi Mi Smart Cube).
+///
+/// Each notification holds the state of the cube followed by the last moves, the newest first.
+/// Newer cubes obfuscate the packet, which is marked by 0xA7 in byte 18.
+pub fn decode_giiker(packet: &[u8]) -> Result<Twist, SmartCubeError> {
+    const KEY: [u8; 36] = [
+        176, 81, 104, 224, 86, 137, 237, 119, 38, 26, 193, 161, 210, 126, 150, 81, 93, 13,
+        236, 249, 89, 235, 88, 24, 113, 81, 214, 131, 130, 199, 2, 169, 39, 165, 171, 41,
+    ];
+    if packet.len() != 20 {
+        return Err(SmartCubeError::Length { expected: 20, actual: packet.len() });
+    }
+    let mut packet: [u8; 20] = packet.try_into().unwrap();
+    if packet[18] == 0xA7 {
+        let k1 = (packet[19] >> 4) as usize;
+        let k2 = (packet[19] & 0x0F) as usize;
+        for i in 0..18 {
+            packet[i] = packet[i].wrapping_add(KEY[i + k1]).wrapping_add(KEY[i + k2]);
+        }
+    }
+
+    // The face is numbered 1 to 6 in the order B, D, L, U, R, F,
+    // the turn is 1 for clockwise, 2 or 9 for a half turn and 3 for counterclockwise.
+    let code = packet[16];
+    let (face, turn) = ((code >> 4) as usize, code & 0x0F);
+    if !(1..=6).contains(&face) {
+        return Err(SmartCubeError::InvalidMove(code));
+    }
+    let quarter_turns = match turn {
+        1 => 1,
+        2 | 9 => 2,
+        3 => 3,
+        _ => return Err(SmartCubeError::InvalidMove(code)),
+    };
+    Ok(twist_of(face_index(b"BDLURF"[face - 1]), quarter_turns))
+}
+
+/// The AES-128 cipher of GAN cubes of the second generation protocol.
+/// Key and initialization vector are specific to each cube, derived from its MAC address.
+pub struct GanCipher {
+    cipher: Aes128,
+    iv: [u8; 16],
+}
+
+impl GanCipher {
+    pub fn new(key: [u8; 16], iv: [u8; 16]) -> Self {
+        Self { cipher: Aes128::new(&GenericArray::from(key)), iv }
+    }
+
+    /// Decrypts a packet of at least 16 bytes in place: first its last 16 bytes, then its first 16 bytes.
+    pub fn decrypt(&self, packet: &mut [u8]) {
+        assert!(packet.len() >= 16);
+        let offset = packet.len() - 16;
+        self.decrypt_block(&mut packet[offset..]);
+        self.decrypt_block(&mut packet[..16]);
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let mut data = GenericArray::clone_from_slice(block);
+        self.cipher.decrypt_block(&mut data);
+        for ((byte, decrypted), iv) in block.iter_mut().zip(data).zip(self.iv) {
+            *byte = decrypted ^ iv;
+        }
+    }
+}
+
+/// Decodes the move notifications of GAN cubes of the second generation protocol.
+///
+/// Each move notification holds a move counter and the last 7 moves, the newest first,
+/// so moves of lost notifications can be recovered.
+pub struct GanDecoder {
+    cipher: Option<GanCipher>,
+    move_count: Option<u8>,
+}
+
+impl GanDecoder {
+    /// A decoder of unencrypted packets.
+    pub fn new() -> Self {
+        Self { cipher: None, move_count: None }
+    }
+
+    pub fn with_cipher(cipher: GanCipher) -> Self {
+        Self { cipher: Some(cipher), move_count: None }
+    }
+
+    /// The moves since the previous move notification, the oldest first.
+    /// The first move notification only establishes the move counter.
+    pub fn decode(&mut self, packet: &[u8]) -> Result<Vec<Twist>, SmartCubeError> {
+        if packet.len() != 20 {
+            return Err(SmartCubeError::Length { expected: 20, actual: packet.len() });
+        }
+        let mut packet = packet.to_vec();
+        if let Some(cipher) = &self.cipher {
+            cipher.decrypt(&mut packet);
+        }
+        // Reads `len` bits at bit offset `start`, most significant bit first.
+        let bits = |start: usize, len: usize| -> u8 {
+            (start..start + len).fold(0, |acc, bit| (acc << 1) | ((packet[bit / 8] >> (7 - bit % 8)) & 1))
+        };
+        if bits(0, 4) != 1 {
+            return Err(SmartCubeError::NotAMove);
+        }
+        let move_count = bits(4, 8);
+        let new_moves = match self.move_count.replace(move_count) {
+            Some(previous) => (move_count.wrapping_sub(previous) as usize).min(7),
+            None => 0,
+        };
+        // Each move is the face in the order U, R, F, D, L, B and a bit for counterclockwise.
+        (0..new_moves)
+            .rev()
+            .map(|i| {
+                let code = bits(12 + 5 * i, 5);
+                let face = (code >> 1) as usize;
+                if face >= 6 {
+                    return Err(SmartCubeError::InvalidMove(code));
+                }
+                let quarter_turns = if code & 1 == 0 { 1 } else { 3 };
+                Ok(twist_of(face_index(b"URFDLB"[face]), quarter_turns))
+            })
+            .collect()
+    }
+}
+
+impl Default for GanDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes::cipher::BlockEncrypt;
+
+    fn giiker_packet(moves: [u8; 4]) -> [u8; 20] {
+        let mut packet = [0; 20];
+        packet[16..20].copy_from_slice(&moves);
+        packet
+    }
+
+    #[test]
+    fn test_giiker() {
+        assert_eq!(decode_giiker(&giiker_packet([0x51, 0, 0, 0])), Ok(Twist::R1));
+        assert_eq!(decode_giiker(&giiker_packet([0x43, 0x51, 0, 0])), Ok(Twist::U3));
+        assert_eq!(decode_giiker(&giiker_packet([0x12, 0, 0, 0])), Ok(Twist::B2));
+        assert_eq!(decode_giiker(&giiker_packet([0x29, 0, 0, 0])), Ok(Twist::D2));
+        assert_eq!(decode_giiker(&giiker_packet([0x71, 0, 0, 0])), Err(SmartCubeError::InvalidMove(0x71)));
+        assert_eq!(decode_giiker(&[0; 19]), Err(SmartCubeError::Length { expected: 20, actual: 19 }));
+
+        // Obfuscated packets with both key offsets 0 subtract twice the key.
+        let mut packet = giiker_packet([0x61, 0, 0xA7, 0x00]);
+        packet[16] = packet[16].wrapping_sub(93).wrapping_sub(93);
+        assert_eq!(decode_giiker(&packet), Ok(Twist::F1));
+    }
+
+    /// A move notification with the move counter and the moves, the newest first.
+    fn gan_packet(move_count: u8, moves: &[u8]) -> Vec<u8> {
+        let mut bits = vec![0, 0, 0, 1];
+        bits.extend((0..8).rev().map(|i| (move_count >> i) & 1));
+        for &m in moves {
+            bits.extend((0..5).rev().map(|i| (m >> i) & 1));
+        }
+        bits.resize(160, 0);
+        bits.chunks(8).map(|byte| byte.iter().fold(0, |acc, &bit| (acc << 1) | bit)).collect()
+    }
+
+    #[test]
+    fn test_gan() {
+        let mut decoder = GanDecoder::new();
+        assert_eq!(decoder.decode(&gan_packet(10, &[0])), Ok(vec![]));
+        // R' then U.
+        assert_eq!(decoder.decode(&gan_packet(12, &[0, 3])), Ok(vec![Twist::R3, Twist::U1]));
+        // The counter wraps around.
+        let mut decoder = GanDecoder::new();
+        decoder.decode(&gan_packet(255, &[])).unwrap();
+        assert_eq!(decoder.decode(&gan_packet(0, &[10])), Ok(vec![Twist::B1]));
+
+        let mut packet = gan_packet(1, &[]);
+        packet[0] = 0x20;
+        assert_eq!(decoder.decode(&packet), Err(SmartCubeError::NotAMove));
+    }
+
+    #[test]
+    fn test_gan_cipher() {
+        let key: [u8; 16] = std::array::from_fn(|i| i as u8 * 7);
+        let iv: [u8; 16] = std::array::from_fn(|i| 100 + i as u8);
+        let aes = Aes128::new(&GenericArray::from(key));
+        let encrypt_block = |block: &mut [u8]| {
+            for (byte, iv) in block.iter_mut().zip(iv) {
+                *byte ^= iv;
+            }
+            let mut data = GenericArray::clone_from_slice(block);
+            aes.encrypt_block(&mut data);
+            block.copy_from_slice(&data);
+        };
+
+        let plain = gan_packet(5, &[4]);
+        let mut packet = plain.clone();
+        encrypt_block(&mut packet[..16]);
+        encrypt_block(&mut packet[4..]);
+        assert_ne!(packet, plain);
+
+        let mut decoder = GanDecoder::with_cipher(GanCipher::new(key, iv));
+        decoder.decode(&packet).unwrap();
+        let mut next = gan_packet(6, &[4]);
+        encrypt_block(&mut next[..16]);
+        encrypt_block(&mut next[4..]);
+        assert_eq!(decoder.decode(&next), Ok(vec![Twist::F1]));
+    }
+}
@@ -0,0 +1,41 @@
+use crate::*;
+use std::time::Instant;
+
+/// A solving engine, so that applications can swap engines, e.g. in benchmarks.
+pub trait Solver {
+    fn solve(&self, cube: Cube, options: &SolveOptions) -> Result<Solution, String>;
+}
+
+/// Solves the whole cube, with all the options.
+impl Solver for TwoPhaseSolver<'_> {
+    fn solve(&self, cube: Cube, options: &SolveOptions) -> Result<Solution, String> {
+        // The solver keeps the state of a search, so each solve takes its own, without copying the state of this one.
+        self.idle_copy().solve_with_options(cube, options)
+    }
+}
+
+/// Solves the masked-in pieces optimally. Of the options, only `max_length` applies.
+impl Solver for MaskedSolver {
+    fn solve(&self, cube: Cube, options: &SolveOptions) -> Result<Solution, String> {
+        let start = Instant::now();
+        let alg = Alg::new(MaskedSolver::solve(self, cube, options.max_length)?);
+        let stats = SolveStats { phase_1_time: start.elapsed(), ..SolveStats::default() };
+        Ok(Solution { alg, stats, annotation: None })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_masked_solver() {
+        let solver: Box<dyn Solver> = Box::new(MaskedSolver::new(PieceMask::BLOCK_2X2X2));
+        // R and U don't move the DFL block.
+        let cube = Cube::solved().twisted_by(&[Twist::R1, Twist::U1, Twist::F1]);
+        let options = SolveOptions { max_length: 5, ..SolveOptions::default() };
+        assert_eq!(solver.solve(cube, &options).map(|solution| solution.alg), Ok(Alg::new(vec![Twist::F3])));
+        let options = SolveOptions { max_length: 0, ..SolveOptions::default() };
+        assert!(solver.solve(cube, &options).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use crate::solver::Solver;
+use crate::*;
+use std::sync::{Condvar, Mutex};
+
+/// Solves cubes on many threads at once, e.g. for a web service.
+///
+/// The workers are clones of one solver, so they share its tables and each has its own search state.
+/// A solve waits for an idle worker, which limits the number of concurrent searches.
+pub struct SolverPool<'a> {
+    idle: Mutex<Vec<TwoPhaseSolver<'a>>>,
+    returned: Condvar,
+    workers: usize,
+}
+
+/// A worker taken out of the pool, which returns it when dropped, even if the solve panics.
+struct Lease<'p, 'a> {
+    pool: &'p SolverPool<'a>,
+    solver: Option<TwoPhaseSolver<'a>>,
+}
+
+impl<'a> Lease<'_, 'a> {
+    fn solver(&mut self) -> &mut TwoPhaseSolver<'a> {
+        self.solver.as_mut().unwrap()
+    }
+}
+
+impl Drop for Lease<'_, '_> {
+    fn drop(&mut self) {
+        self.pool.idle.lock().unwrap().push(self.solver.take().unwrap());
+        self.pool.returned.notify_one();
+    }
+}
+
+impl<'a> SolverPool<'a> {
+    pub fn new(solver: TwoPhaseSolver<'a>, workers: usize) -> Self {
+        assert!(workers > 0);
+        Self { idle: Mutex::new(vec![solver; workers]), returned: Condvar::new(), workers }
+    }
+
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    /// The number of workers that aren't solving.
+    pub fn idle_workers(&self) -> usize {
+        self.idle.lock().unwrap().len()
+    }
+
+    fn lease(&self, wait: bool) -> Option<Lease<'_, 'a>> {
+        let mut idle = self.idle.lock().unwrap();
+        loop {
+            if let Some(solver) = idle.pop() {
+                return Some(Lease { pool: self, solver: Some(solver) });
+            }
+            if !wait {
+                return None;
+            }
+            idle = self.returned.wait(idle).unwrap();
+        }
+    }
+
+    /// Like `TwoPhaseSolver::solve_with_stats`, but waits for an idle worker first.
+    pub fn solve(&self, cube: Cube, max_solution_length: u8) -> Result<Solution, String> {
+        self.lease(true).unwrap().solver().solve_with_stats(cube, max_solution_length)
+    }
+
+    /// Like `solve`, but returns `None` instead of waiting if all workers are busy,
+    /// e.g. to reject requests of an overloaded service.
+    pub fn try_solve(&self, cube: Cube, max_solution_length: u8) -> Option<Result<Solution, String>> {
+        self.lease(false).map(|mut lease| lease.solver().solve_with_stats(cube, max_solution_length))
+    }
+}
+
+/// Solves with the given options instead of the ones of the workers.
+impl Solver for SolverPool<'_> {
+    fn solve(&self, cube: Cube, options: &SolveOptions) -> Result<Solution, String> {
+        self.lease(true).unwrap().solver().solve_with_options(cube, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::two_phase::empty_table_solver;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_solver_pool() {
+        let pool = SolverPool::new(empty_table_solver(), 2);
+        let cube = Cube::solved().twisted(Twist::R1);
+        assert!(pool.solve(cube, 31).is_err());
+        assert!(pool.solve(Cube::solved(), 20).unwrap().alg.twists().is_empty());
+        assert_eq!(pool.idle_workers(), 2);
+
+        // Solves on other threads wait for a worker.
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4).map(|_| scope.spawn(|| pool.solve(Cube::solved(), 20))).collect();
+            assert!(handles.into_iter().all(|handle| handle.join().unwrap().is_ok()));
+        });
+        assert_eq!(pool.idle_workers(), 2);
+    }
+
+    #[test]
+    fn test_backpressure() {
+        let pool = SolverPool::new(empty_table_solver(), 2);
+        let lease = pool.lease(false);
+        assert!(pool.try_solve(Cube::solved(), 20).is_some());
+        let other_lease = pool.lease(false);
+        assert_eq!(pool.idle_workers(), 0);
+        assert!(pool.try_solve(Cube::solved(), 20).is_none());
+
+        let (sender, receiver) = mpsc::channel();
+        std::thread::scope(|scope| {
+            scope.spawn(|| sender.send(pool.solve(Cube::solved(), 20)).unwrap());
+            // The solve waits while all workers are busy, and takes the first one that returns.
+            assert_eq!(receiver.recv_timeout(Duration::from_millis(100)).unwrap_err(), mpsc::RecvTimeoutError::Timeout);
+            drop(lease);
+            assert!(receiver.recv_timeout(Duration::from_secs(10)).unwrap().is_ok());
+        });
+        assert_eq!(pool.idle_workers(), 1);
+        drop(other_lease);
+        assert_eq!(pool.idle_workers(), 2);
+    }
+}
@@ -1,8 +1,8 @@
 use std::collections::HashMap;
 
-pub fn read_config_file(path: &str) -> HashMap<String, String> {
-    let content = std::fs::read_to_string(path).expect("Failed to read config file");
-    parse_config(&content)
+pub fn read_config_file(path: &str) -> std::io::Result<HashMap<String, String>> {
+    let content = std::fs::read_to_string(path)?;
+    Ok(parse_config(&content))
 }
 
 fn parse_config(content: &str) -> HashMap<String, String> {
@@ -0,0 +1,160 @@
+use crate::cubies::*;
+use crate::index::*;
+use crate::table::*;
+
+/// The state of some pieces of the cube, numbered from 0 to `SIZE - 1`, that twists move along, e.g. the orientation of the corners.
+/// `DistanceTable::of_coordinate` creates a pruning table of it, and `impl_coordinate!` declares one in a few lines.
+pub trait Coordinate: Twistable + Send {
+    const SIZE: usize;
+
+    /// The coordinate of the solved cube, the origin of its distance table.
+    fn solved() -> Self;
+    fn index(&self) -> usize;
+    fn from_index(index: usize) -> Self;
+}
+
+/// Implements `Coordinate` and `Twistable` for a type from its size and expressions of its solved value, index, value from an index and twist,
+/// like for `COri` below. The expressions name their arguments like closures, e.g. `twisted: |c_ori, twist| TWISTER.twisted_c_ori(c_ori, twist)`.
+#[macro_export]
+macro_rules! impl_coordinate {
+    (
+        $type:ty,
+        size: $size:expr,
+        solved: $solved:expr,
+        index: |$value:ident| $index:expr,
+        from_index: |$i:ident| $from_index:expr,
+        twisted: |$twisted_value:ident, $twist:ident| $twisted:expr $(,)?
+    ) => {
+        impl $crate::Twistable for $type {
+            fn twisted(&self, $twist: $crate::Twist) -> Self {
+                let $twisted_value = *self;
+                $twisted
+            }
+        }
+
+        impl $crate::Coordinate for $type {
+            const SIZE: usize = $size;
+
+            fn solved() -> Self {
+                $solved
+            }
+
+            fn index(&self) -> usize {
+                let $value = *self;
+                $index
+            }
+
+            fn from_index($i: usize) -> Self {
+                $from_index
+            }
+        }
+    };
+}
+
+impl_coordinate!(COri,
+    size: COri::SIZE,
+    solved: COri::new(Corners::solved().ori_index()),
+    index: |c_ori| c_ori.index(),
+    from_index: |i| COri::new(i),
+    twisted: |c_ori, twist| TWISTER.twisted_c_ori(c_ori, twist),
+);
+
+impl_coordinate!(CPrm,
+    size: CPrm::SIZE,
+    solved: CPrm::new(Corners::solved().prm_index()),
+    index: |c_prm| c_prm.index(),
+    from_index: |i| CPrm::new(i),
+    twisted: |c_prm, twist| TWISTER.twisted_c_prm(c_prm, twist),
+);
+
+impl_coordinate!(EOri,
+    size: EOri::SIZE,
+    solved: EOri::new(Edges::solved().ori_index()),
+    index: |e_ori| e_ori.index(),
+    from_index: |i| EOri::new(i),
+    twisted: |e_ori, twist| TWISTER.twisted_e_ori(e_ori, twist),
+);
+
+/// The positions and the permutation of the four E slice edges, the ones between U and D, see `Edges::loc_prm`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ESlice(pub LocPrm);
+
+impl_coordinate!(ESlice,
+    size: LocPrm::INDEX_SIZE,
+    solved: ESlice(Edges::solved().loc_prm(Axis::Z)),
+    index: |slice| slice.0.index(),
+    from_index: |i| ESlice(LocPrm::from_index(i)),
+    twisted: |slice, twist| ESlice(TWISTER.twisted_e_loc_prm(slice.0, twist)),
+);
+
+impl DistanceTable {
+    /// The distance table of a coordinate, e.g. `DistanceTable::of_coordinate::<EOri>(&ALL_TWISTS)`.
+    pub fn of_coordinate<C: Coordinate>(twists: &[Twist]) -> Self {
+        Self::create(twists, C::solved(), |c: C| c.index(), C::from_index, C::SIZE)
+    }
+
+    /// Like `of_coordinate`, but the distances are the least total costs of the twists, see `create_with_costs`.
+    pub fn of_coordinate_with_costs<C: Coordinate>(twists: &[Twist], costs: &TwistCosts) -> Self {
+        Self::create_with_costs(twists, costs, C::solved(), |c: C| c.index(), C::from_index, C::SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_orientation_tables() {
+        assert_eq!(DistanceTable::of_coordinate::<COri>(&ALL_TWISTS).histogram(), [1, 4, 34, 186, 816, 1018, 128]);
+        assert_eq!(DistanceTable::of_coordinate::<EOri>(&ALL_TWISTS).histogram(), [1, 2, 25, 202, 620, 900, 285, 13]);
+        // In H0, the orientations don't change.
+        assert_eq!(DistanceTable::of_coordinate::<EOri>(&H0_TWISTS).unreachable_count(), EOri::SIZE - 1);
+    }
+
+    #[test]
+    fn test_coordinates() {
+        let twists = RandomTwistGen::new(7, &ALL_TWISTS).gen_twists(30);
+        let edges = Edges::twists(&twists);
+        assert_eq!(ESlice::solved().twisted_by(&twists), ESlice(edges.loc_prm(Axis::Z)));
+        assert_eq!(EOri::solved().twisted_by(&twists).index(), edges.ori_index());
+        assert_eq!(COri::solved().twisted_by(&twists).index(), Corners::twists(&twists).ori_index());
+        assert_eq!(CPrm::solved().twisted_by(&twists).index(), Corners::twists(&twists).prm_index());
+        assert_eq!(<EOri as Coordinate>::from_index(100).index(), 100);
+
+        let slice = DistanceTable::of_coordinate::<ESlice>(&ALL_TWISTS);
+        assert_eq!(slice.histogram().iter().sum::<u64>(), ESlice::SIZE as u64);
+        assert_eq!(slice.distance(ESlice::solved().index()), 0);
+        let qtm = DistanceTable::of_coordinate_with_costs::<ESlice>(&ALL_TWISTS, &TwistCosts::QTM);
+        assert!((0..ESlice::SIZE).all(|i| slice.distance(i) <= qtm.distance(i)));
+    }
+
+    /// The orientation of the edges and the positions of the E slice edges, phase 1 of Kociemba's algorithm without the corners.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    struct Domino(EOri, ESliceLoc);
+
+    impl_coordinate!(Domino,
+        size: EOri::SIZE * ESliceLoc::SIZE,
+        solved: Domino(EOri::solved(), ESliceLoc::new(ESlice::solved().0.loc())),
+        index: |d| d.0.index() * ESliceLoc::SIZE + d.1.index(),
+        from_index: |i| Domino(EOri::new(i / ESliceLoc::SIZE), ESliceLoc::new(i % ESliceLoc::SIZE)),
+        twisted: |d, twist| {
+            let slice = TWISTER.twisted_e_loc_prm(LocPrm::new(d.1.index(), 0), twist);
+            Domino(d.0.twisted(twist), ESliceLoc::new(slice.loc()))
+        },
+    );
+
+    #[test]
+    fn test_custom_coordinate() {
+        let table = DistanceTable::of_coordinate::<Domino>(&ALL_TWISTS);
+        let e_ori = DistanceTable::of_coordinate::<EOri>(&ALL_TWISTS);
+        assert_eq!(table.len(), 2048 * 495);
+        assert!((0..Domino::SIZE).all(|i| table.distance(i) >= e_ori.distance(Domino::from_index(i).0.index())));
+        let mut rnd = RandomTwistGen::new(8, &ALL_TWISTS);
+        for _ in 0..20 {
+            let twists = rnd.gen_twists(5);
+            let domino = Domino::solved().twisted_by(&twists);
+            assert!(table.distance(domino.index()) <= 5);
+            assert_eq!(domino.0.index(), Edges::twists(&twists).ori_index());
+        }
+    }
+}
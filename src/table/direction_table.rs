@@ -1,10 +1,21 @@
 use crate::cubies::*;
 use crate::index::*;
 use crate::table::DistanceTable;
-use rayon::prelude::*;
+use crate::table::file_format::*;
+use crate::table::statistics::*;
+use crate::table::verification::*;
+use crate::parallel::*;
 
+/// Table files store the entries as little endian u64, so that on little endian targets the payload is the memory of the table,
+/// which loads without converting the entries one by one.
+#[repr(transparent)]
+#[derive(Clone, Copy)]
 pub struct DirectionsAndDistance(u64);
 
+// SAFETY: A transparent wrapper of a u64, for which every bit pattern is valid.
+unsafe impl bytemuck::Zeroable for DirectionsAndDistance {}
+unsafe impl bytemuck::Pod for DirectionsAndDistance {}
+
 impl DirectionsAndDistance {
     pub fn new(less: TwistSet, more: TwistSet, distance: u8) -> Self {
         let less = less.bits() as u64;
@@ -32,6 +43,8 @@ impl DirectionsAndDistance {
 
 pub struct DirectionsTable {
     table: Vec<DirectionsAndDistance>,
+    twists: TwistSet,
+    costs: TwistCosts,
 }
 
 impl DirectionsTable {
@@ -42,7 +55,44 @@ impl DirectionsTable {
         from_index: impl Fn(usize) -> Obj + Sync,
         index_size: usize,
     ) -> Self {
-        let distance_table = DistanceTable::create(twists, origin, &index, &from_index, index_size);
+        Self::create_with_progress(twists, origin, index, from_index, index_size, |_, _| {})
+    }
+
+    /// Like `create`, but calls `progress` after each breadth-first layer of the underlying distance table.
+    pub fn create_with_progress<Obj: Twistable + Send>(
+        twists: &[Twist],
+        origin: Obj,
+        index: impl Fn(Obj) -> usize + Sync,
+        from_index: impl Fn(usize) -> Obj + Sync,
+        index_size: usize,
+        progress: impl FnMut(u8, u64),
+    ) -> Self {
+        Self::create_with_costs_and_progress(twists, &TwistCosts::HTM, origin, index, from_index, index_size, progress)
+    }
+
+    /// Like `create`, but the distances are the least total costs of the twists, see `DistanceTable::create_with_costs`.
+    pub fn create_with_costs<Obj: Twistable + Send>(
+        twists: &[Twist],
+        costs: &TwistCosts,
+        origin: Obj,
+        index: impl Fn(Obj) -> usize + Sync,
+        from_index: impl Fn(usize) -> Obj + Sync,
+        index_size: usize,
+    ) -> Self {
+        Self::create_with_costs_and_progress(twists, costs, origin, index, from_index, index_size, |_, _| {})
+    }
+
+    /// Like `create_with_costs`, but calls `progress` like `DistanceTable::create_with_costs_and_progress`.
+    pub fn create_with_costs_and_progress<Obj: Twistable + Send>(
+        twists: &[Twist],
+        costs: &TwistCosts,
+        origin: Obj,
+        index: impl Fn(Obj) -> usize + Sync,
+        from_index: impl Fn(usize) -> Obj + Sync,
+        index_size: usize,
+        progress: impl FnMut(u8, u64),
+    ) -> Self {
+        let distance_table = DistanceTable::create_with_costs_and_progress(twists, costs, origin, &index, &from_index, index_size, progress);
         let table: Vec<DirectionsAndDistance> = (0..index_size)
             .into_par_iter()
             .map(|i| {
@@ -64,27 +114,73 @@ impl DirectionsTable {
                 DirectionsAndDistance::new(less, more, d)
             })
             .collect();
-        Self { table }
+        Self { table, twists: TwistSet::from_twists(twists), costs: *costs }
     }
 
-    pub fn from_file(path: &str) -> Result<Self, std::io::Error> {
-        let data = std::fs::read(path)?;
-        let table: Vec<DirectionsAndDistance> = data
-            .chunks_exact(8)
-            .map(|chunk| {
-                let value = u64::from_le_bytes(chunk.try_into().unwrap());
-                DirectionsAndDistance::from_u64(value)
-            })
-            .collect();
-        Ok(Self { table })
+    /// Reads the file a chunk at a time, so that memory stays close to the size of the table.
+    pub fn from_file(path: &str) -> Result<Self, TableFileError> {
+        Self::read_from(std::fs::File::open(path)?)
+    }
+
+    /// Reads the table from the content of a table file.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, TableFileError> {
+        Self::read_from(bytes.as_slice())
+    }
+
+    fn read_from(reader: impl std::io::Read) -> Result<Self, TableFileError> {
+        let (header, mut table) = read_table_with(reader, TableKind::Directions, read_pod_entries::<DirectionsAndDistance>)?;
+        if cfg!(target_endian = "big") {
+            table.iter_mut().for_each(|entry| entry.0 = u64::from_le(entry.0));
+        }
+        Ok(Self { table, twists: header.twists, costs: TwistCosts::HTM })
     }
 
-    pub fn save_to_file(&self, path: &str) -> std::io::Result<()> {
-        let mut data = Vec::with_capacity(self.table.len() * size_of::<DirectionsAndDistance>());
-        for entry in &self.table {
-            data.extend_from_slice(&entry.0.to_le_bytes());
+    /// Writes the memory of the table as it is, or a chunk at a time on big endian targets, so that memory stays close to the size of the table.
+    /// Fails for a table with other costs than `TwistCosts::HTM`, which table files don't record.
+    pub fn to_file(&self, path: &str) -> Result<(), TableFileError> {
+        if !self.costs.is_unit() {
+            return Err(TableFileError::UnsupportedCosts);
         }
-        std::fs::write(path, data)
+        write_table_file_with(path, TableKind::Directions, self.twists, self.table.len(), self.max_distance(), |writer| {
+            if cfg!(target_endian = "little") {
+                return writer.write_all(bytemuck::cast_slice(&self.table));
+            }
+            let mut bytes = Vec::new();
+            for chunk in self.table.chunks(1 << 16) {
+                bytes.clear();
+                bytes.extend(chunk.iter().flat_map(|entry| entry.0.to_le_bytes()));
+                writer.write_all(&bytes)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// The twists the table was generated with.
+    pub fn twists(&self) -> TwistSet {
+        self.twists
+    }
+
+    /// The costs of the twists that the distances are the totals of.
+    pub fn costs(&self) -> TwistCosts {
+        self.costs
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    /// The largest distance of a reachable entry.
+    pub fn max_distance(&self) -> u8 {
+        self.table.par_iter().map(|entry| entry.distance()).filter(|&d| d != DistanceTable::UNREACHABLE).max().unwrap_or(0)
+    }
+
+    /// The number of entries that the twists don't reach from the origin, see `DistanceTable::unreachable_count`.
+    pub fn unreachable_count(&self) -> usize {
+        self.table.par_iter().filter(|entry| entry.distance() == DistanceTable::UNREACHABLE).count()
     }
 
     pub fn distance(&self, index: usize) -> u8 {
@@ -98,6 +194,16 @@ impl DirectionsTable {
     pub fn more_distance(&self, index: usize) -> TwistSet {
         self.table[index].more_distance()
     }
+
+    /// Number of entries per distance.
+    pub fn histogram(&self) -> Vec<u64> {
+        distance_histogram(self.table.len(), |i| self.distance(i))
+    }
+
+    /// Compares the number of entries per distance against the expected counts.
+    pub fn verify(&self, expected_histogram: &[u64]) -> VerificationReport {
+        VerificationReport::new(expected_histogram, self.histogram())
+    }
 }
 
 #[cfg(test)]
@@ -115,6 +221,7 @@ mod tests {
             |i: usize| Cube::from_corner_index(i),
             Cube::CORNER_INDEX_SIZE,
         );
+        assert!(table.verify(&crate::CORNERS_HISTOGRAM).is_ok());
 
         for _ in 0..100_000 {
             let i = rnd.random_range(0..Cube::CORNER_INDEX_SIZE);
@@ -134,4 +241,30 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let path = std::env::temp_dir().join("rubikscube_test_directions_table.dat");
+        let path = path.to_str().unwrap();
+        let table = DirectionsTable::create(
+            &ALL_TWISTS,
+            Cube::solved(),
+            |c: Cube| Corners::split_index(c.corner_index()).1,
+            |i: usize| Cube::from_corner_index(i),
+            Corners::ORI_SIZE,
+        );
+        table.to_file(path).unwrap();
+
+        let loaded = DirectionsTable::from_bytes(std::fs::read(path).unwrap()).unwrap();
+        assert_eq!(loaded.twists(), TwistSet::FULL);
+        for i in 0..Corners::ORI_SIZE {
+            assert_eq!(loaded.table[i].0, table.table[i].0);
+        }
+        // The payload holds the entries as little endian u64.
+        let (_, payload) = read_table_file(path, TableKind::Directions).unwrap();
+        assert_eq!(payload[8..16], table.table[1].0.to_le_bytes());
+        assert_eq!(DirectionsTable::from_file(path).unwrap().distance(1), table.distance(1));
+        assert!(matches!(DistanceTable::from_bytes(std::fs::read(path).unwrap()), Err(TableFileError::WrongKind { .. })));
+        std::fs::remove_file(path).unwrap();
+    }
 }
@@ -1,13 +1,24 @@
 use crate::cubies::*;
 use crate::index::*;
-use rayon::prelude::*;
-use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use crate::table::file_format::*;
+use crate::table::statistics::*;
+use crate::table::verification::*;
+use crate::parallel::*;
+use std::sync::atomic::{AtomicU8, Ordering};
 
 pub struct DistanceTable {
     table: Vec<u8>,
+    twists: TwistSet,
+    costs: TwistCosts,
+    inverse_reduced: bool,
 }
 
 impl DistanceTable {
+    /// The distance of the entries that the twists don't reach from the origin,
+    /// e.g. illegal states of a coordinate, see `unreachable_count`.
+    pub const UNREACHABLE: u8 = u8::MAX;
+
+    /// Entries that the twists don't reach keep the distance `UNREACHABLE`, see `unreachable_count`.
     pub fn create<Obj>(
         twists: &[Twist],
         origin: Obj,
@@ -18,61 +29,232 @@ impl DistanceTable {
     where
         Obj: Twistable + Send,
     {
-        const SENTINEL: u8 = u8::MAX;
+        Self::create_with_progress(twists, origin, index, from_index, index_size, |_, _| {})
+    }
+
+    /// Like `create`, but calls `progress` after each breadth-first layer
+    /// with the distance of the layer and the number of entries reached so far.
+    pub fn create_with_progress<Obj>(
+        twists: &[Twist],
+        origin: Obj,
+        index: impl Fn(Obj) -> usize + Sync,
+        from_index: impl Fn(usize) -> Obj + Sync,
+        index_size: usize,
+        progress: impl FnMut(u8, u64),
+    ) -> Self
+    where
+        Obj: Twistable + Send,
+    {
+        Self::create_with_costs_and_progress(twists, &TwistCosts::HTM, origin, index, from_index, index_size, progress)
+    }
+
+    /// Like `create`, but the distances are the least total costs of the twists to the origin.
+    pub fn create_with_costs<Obj>(
+        twists: &[Twist],
+        costs: &TwistCosts,
+        origin: Obj,
+        index: impl Fn(Obj) -> usize + Sync,
+        from_index: impl Fn(usize) -> Obj + Sync,
+        index_size: usize,
+    ) -> Self
+    where
+        Obj: Twistable + Send,
+    {
+        Self::create_with_costs_and_progress(twists, costs, origin, index, from_index, index_size, |_, _| {})
+    }
+
+    /// Like `create_with_costs`, but calls `progress` after each layer of a distance
+    /// with the distance and the number of entries reached so far, which with costs above 1 includes farther ones.
+    pub fn create_with_costs_and_progress<Obj>(
+        twists: &[Twist],
+        costs: &TwistCosts,
+        origin: Obj,
+        index: impl Fn(Obj) -> usize + Sync,
+        from_index: impl Fn(usize) -> Obj + Sync,
+        index_size: usize,
+        progress: impl FnMut(u8, u64),
+    ) -> Self
+    where
+        Obj: Twistable + Send,
+    {
+        Self::create_reduced(twists, costs, origin, index, from_index, None, index_size, progress)
+    }
+
+    /// Like `create_with_progress`, but one entry stands for an object and its inverse, which are equally far from the origin,
+    /// e.g. `SubsetCube::reduced_index`, which about halves the table. `index` maps both to the entry, and `from_index` to either.
+    /// The twists have to contain their inverses.
+    pub fn create_inverse_reduced_with_progress<Obj>(
+        twists: &[Twist],
+        origin: Obj,
+        index: impl Fn(Obj) -> usize + Sync,
+        from_index: impl Fn(usize) -> Obj + Sync,
+        inverse: impl Fn(Obj) -> Obj + Sync,
+        index_size: usize,
+        progress: impl FnMut(u8, u64),
+    ) -> Self
+    where
+        Obj: Twistable + Send,
+    {
+        assert!(twists.iter().all(|t| twists.contains(&t.inverse())), "the twists contain their inverses");
+        Self::create_reduced(twists, &TwistCosts::HTM, origin, index, from_index, Some(&inverse), index_size, progress)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_reduced<Obj>(
+        twists: &[Twist],
+        costs: &TwistCosts,
+        origin: Obj,
+        index: impl Fn(Obj) -> usize + Sync,
+        from_index: impl Fn(usize) -> Obj + Sync,
+        inverse: Option<&(dyn Fn(Obj) -> Obj + Sync)>,
+        index_size: usize,
+        mut progress: impl FnMut(u8, u64),
+    ) -> Self
+    where
+        Obj: Twistable + Send,
+    {
+        const SENTINEL: u8 = DistanceTable::UNREACHABLE;
         let table: Vec<AtomicU8> = (0..index_size)
             .into_par_iter()
             .map(|_| AtomicU8::new(SENTINEL))
             .collect();
 
         table[index(origin)].store(0, Ordering::Release);
+        let mut reached = 1;
+        progress(0, reached);
 
         for d in 0..SENTINEL - 1 {
-            let changed = AtomicBool::new(false);
-
-            (0..table.len()).into_par_iter().for_each(|i| {
-                if table[i].load(Ordering::Relaxed) == d {
-                    let obj = from_index(i);
-                    for twist in twists.iter() {
-                        let next_index = index(obj.twisted(*twist));
-                        if table[next_index]
-                            .compare_exchange(SENTINEL, d + 1, Ordering::Relaxed, Ordering::Relaxed)
-                            .is_ok()
-                        {
-                            changed.store(true, Ordering::Relaxed);
+            let found: u64 = (0..table.len())
+                .into_par_iter()
+                .map(|i| {
+                    let mut found = 0;
+                    if table[i].load(Ordering::Relaxed) == d {
+                        let obj = from_index(i);
+                        // The neighbours of the inverse aren't the inverses of the neighbours, so a reduced entry expands both.
+                        for obj in core::iter::once(obj).chain(inverse.map(|inverse| inverse(obj))) {
+                            for twist in twists.iter() {
+                                // Costs are positive, so the entries of this layer are final, and the ones it reaches are farther.
+                                // The distance is the cost of the way back, with the inverse twist.
+                                let next_d = d.saturating_add(costs.cost(twist.inverse())).min(SENTINEL - 1);
+                                let next_index = index(obj.twisted(*twist));
+                                if table[next_index].fetch_min(next_d, Ordering::Relaxed) == SENTINEL {
+                                    found += 1;
+                                }
+                            }
                         }
                     }
-                }
-            });
+                    found
+                })
+                .sum();
 
-            if changed.load(Ordering::Relaxed) == false {
-                break;
+            if found == 0 {
+                // With costs above 1, a layer can be empty while farther ones aren't.
+                let farther = |x: &AtomicU8| (d + 1..SENTINEL).contains(&x.load(Ordering::Relaxed));
+                if !table.par_iter().any(farther) {
+                    break;
+                }
+                continue;
             }
+            reached += found;
+            progress(d + 1, reached);
         }
         Self {
             table: table
                 .iter()
                 .map(|x| x.load(Ordering::Relaxed))
                 .collect::<Vec<u8>>(),
+            twists: TwistSet::from_twists(twists),
+            costs: *costs,
+            inverse_reduced: inverse.is_some(),
         }
     }
 
-    pub fn from_file(path: &str) -> Result<Self, std::io::Error> {
-        Ok(Self { table: std::fs::read(path)?,})
+    /// Reads a table, also one reduced by inversion and also a compressed one.
+    /// The payload is read with `read_to_end` into a Vec of its size, which is the table.
+    pub fn from_file(path: &str) -> Result<Self, TableFileError> {
+        Self::read_from(std::fs::File::open(path)?)
     }
 
-    pub fn save_to_file(&self, path: &str) -> std::io::Result<()> {
-        std::fs::write(path, &self.table)
+    /// Reads the table from the content of a table file.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, TableFileError> {
+        Self::read_from(bytes.as_slice())
+    }
+
+    fn read_from(reader: impl std::io::Read) -> Result<Self, TableFileError> {
+        let (header, table) = read_table_with(reader, TableKind::Distance, read_payload)?;
+        Ok(Self { table, twists: header.twists, costs: TwistCosts::HTM, inverse_reduced: header.kind == TableKind::InverseReducedDistance })
+    }
+
+    /// The entries are the payload of the file, which is written without a copy.
+    /// Fails for a table with other costs than `TwistCosts::HTM`, which table files don't record.
+    pub fn to_file(&self, path: &str) -> Result<(), TableFileError> {
+        if !self.costs.is_unit() {
+            return Err(TableFileError::UnsupportedCosts);
+        }
+        let kind = if self.inverse_reduced { TableKind::InverseReducedDistance } else { TableKind::Distance };
+        let header = TableHeader::new(kind, self.twists, self.table.len(), self.max_distance(), &self.table);
+        write_table_file(path, &header, &self.table)
+    }
+
+    /// The twists the table was generated with.
+    pub fn twists(&self) -> TwistSet {
+        self.twists
+    }
+
+    /// The costs of the twists that the distances are the totals of.
+    pub fn costs(&self) -> TwistCosts {
+        self.costs
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    /// The largest distance of a reachable entry.
+    pub fn max_distance(&self) -> u8 {
+        self.table.par_iter().copied().filter(|&d| d != Self::UNREACHABLE).max().unwrap_or(0)
+    }
+
+    /// The number of entries that the twists don't reach from the origin, e.g. because the index includes illegal states.
+    pub fn unreachable_count(&self) -> usize {
+        self.table.par_iter().filter(|&&d| d == Self::UNREACHABLE).count()
     }
 
     pub fn distance(&self, index: usize) -> u8 {
         self.table[index]
     }
+
+    /// Whether an entry stands for an object and its inverse, see `create_inverse_reduced_with_progress`.
+    pub fn is_inverse_reduced(&self) -> bool {
+        self.inverse_reduced
+    }
+
+    /// The distance of a cube in the subset, from a subset table that may be reduced by inversion.
+    #[inline(always)]
+    pub fn subset_distance(&self, cube: &SubsetCube) -> u8 {
+        if self.inverse_reduced { self.table[cube.reduced_index()] } else { self.table[cube.index()] }
+    }
+
+    /// Number of entries per distance.
+    pub fn histogram(&self) -> Vec<u64> {
+        distance_histogram(self.table.len(), |i| self.table[i])
+    }
+
+    /// Compares the number of entries per distance against the expected counts.
+    pub fn verify(&self, expected_histogram: &[u64]) -> VerificationReport {
+        VerificationReport::new(expected_histogram, self.histogram())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::twist_generator::RandomTwistGen;
+    use crate::{Coordinate, DirectionsTable};
 
     #[test]
     fn test_distance_table() {
@@ -91,6 +273,9 @@ mod tests {
         }
         // According to https://oeis.org/A080629
         assert_eq!(counts, vec![1, 18, 243, 2874, 28000, 205416, 1168516, 5402628, 20776176, 45391616, 15139616, 64736]);
+        assert_eq!(table.histogram(), counts);
+        assert!(table.verify(&counts).is_ok());
+        assert_eq!(table.verify(&counts[..11]).mismatches(), vec![(11, 0, 64736)]);
 
         let mut rnd = RandomTwistGen::new(5989, &ALL_TWISTS);
         let mut cube = Cube::solved();
@@ -126,4 +311,128 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_file_round_trip() {
+        let path = std::env::temp_dir().join("rubikscube_test_distance_table.dat");
+        let path = path.to_str().unwrap();
+        let table = DistanceTable::create(
+            &H0_TWISTS,
+            Cube::solved(),
+            |c: Cube| Corners::split_index(c.corner_index()).1,
+            |i: usize| Cube::from_corner_index(i),
+            Corners::ORI_SIZE,
+        );
+        table.to_file(path).unwrap();
+
+        let loaded = DistanceTable::from_file(path).unwrap();
+        assert_eq!(loaded.table, table.table);
+        assert_eq!(loaded.twists(), TwistSet::H0);
+        let loaded = DistanceTable::from_bytes(std::fs::read(path).unwrap()).unwrap();
+        assert_eq!(loaded.table, table.table);
+        assert!(matches!(crate::DirectionsTable::from_file(path), Err(TableFileError::WrongKind { .. })));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_costs() {
+        let create = |twists: &[Twist], costs: &TwistCosts| {
+            DistanceTable::create_with_costs(
+                twists,
+                costs,
+                Cube::solved(),
+                |c: Cube| Corners::split_index(c.corner_index()).1,
+                |i: usize| Cube::from_corner_index(i),
+                Corners::ORI_SIZE,
+            )
+        };
+        // In the quarter turn metric, the distance is the number of quarter turns.
+        let quarter_turns: Vec<Twist> = ALL_TWISTS.into_iter().filter(|t| t.power() != 2).collect();
+        assert_eq!(create(&ALL_TWISTS, &TwistCosts::QTM).table, create(&quarter_turns, &TwistCosts::HTM).table);
+
+        // The distance is the least total cost of the twists back to the origin, even if a twist costs other than its inverse.
+        let costs = TwistCosts::new(core::array::from_fn(|i| 1 + (i % 5) as u8));
+        let table = create(&ALL_TWISTS, &costs);
+        assert_eq!(table.costs(), costs);
+        for i in 0..Corners::ORI_SIZE {
+            let cube = Cube::from_corner_index(i);
+            let d = table.distance(i);
+            let through = |twist: Twist| table.distance(Corners::split_index(cube.twisted(twist).corner_index()).1) + costs.cost(twist);
+            assert!(ALL_TWISTS.iter().all(|&twist| d <= through(twist)));
+            assert!(d == 0 || ALL_TWISTS.iter().any(|&twist| d == through(twist)));
+        }
+        let path = std::env::temp_dir().join("rubikscube_test_costs_table.dat");
+        assert!(matches!(table.to_file(path.to_str().unwrap()), Err(TableFileError::UnsupportedCosts)));
+    }
+
+    #[test]
+    fn test_progress() {
+        let mut layers = Vec::new();
+        let table = DistanceTable::create_with_progress(
+            &ALL_TWISTS,
+            Cube::solved(),
+            |c: Cube| Corners::split_index(c.corner_index()).1,
+            |i: usize| Cube::from_corner_index(i),
+            Corners::ORI_SIZE,
+            |d, reached| layers.push((d, reached)),
+        );
+        let mut reached = 0;
+        let expected: Vec<(u8, u64)> = table
+            .histogram()
+            .iter()
+            .enumerate()
+            .map(|(d, &count)| {
+                reached += count;
+                (d as u8, reached)
+            })
+            .collect();
+        assert_eq!(layers, expected);
+        assert_eq!(reached, Corners::ORI_SIZE as u64);
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let create = |twists: &[Twist]| {
+            DistanceTable::create(
+                twists,
+                Cube::solved(),
+                |c: Cube| Corners::split_index(c.corner_index()).1,
+                |i: usize| Cube::from_corner_index(i),
+                Corners::ORI_SIZE,
+            )
+        };
+        let table = create(&ALL_TWISTS);
+        assert_eq!((table.unreachable_count(), table.max_distance()), (0, 6));
+        // H0 doesn't twist the corners, so only the solved orientation is reached.
+        let table = create(&H0_TWISTS);
+        assert_eq!((table.unreachable_count(), table.max_distance()), (Corners::ORI_SIZE - 1, 0));
+        assert_eq!(table.distance(1), DistanceTable::UNREACHABLE);
+        let directions = DirectionsTable::create(
+            &H0_TWISTS,
+            Cube::solved(),
+            |c: Cube| Corners::split_index(c.corner_index()).1,
+            |i: usize| Cube::from_corner_index(i),
+            Corners::ORI_SIZE,
+        );
+        assert_eq!((directions.unreachable_count(), directions.max_distance()), (Corners::ORI_SIZE - 1, 0));
+    }
+
+    #[test]
+    fn test_inverse_reduced() {
+        // The corner permutations, with one entry at the smaller index of a permutation and its inverse.
+        let inverse = |c: CPrm| CPrm::new(Corners::from_indices(c.index(), 0).inverse().prm_index());
+        let index = |c: CPrm| c.index().min(inverse(c).index());
+        let reduced = DistanceTable::create_inverse_reduced_with_progress(&ALL_TWISTS, CPrm::solved(), index, CPrm::new, inverse, CPrm::SIZE, |_, _| {});
+        let full = DistanceTable::of_coordinate::<CPrm>(&ALL_TWISTS);
+        assert!(reduced.is_inverse_reduced() && !full.is_inverse_reduced());
+        assert!((0..CPrm::SIZE).all(|i| reduced.distance(index(CPrm::new(i))) == full.distance(i)));
+
+        let path = std::env::temp_dir().join("rubikscube_test_inverse_reduced_table.dat");
+        let path = path.to_str().unwrap();
+        reduced.to_file(path).unwrap();
+        let loaded = DistanceTable::from_file(path).unwrap();
+        assert!(loaded.is_inverse_reduced());
+        assert_eq!(loaded.table, reduced.table);
+        std::fs::remove_file(path).unwrap();
+    }
 }
@@ -0,0 +1,126 @@
+use super::file_format::{TableFileError, read_table_header, read_table_with};
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use std::time::Duration;
+use thiserror::Error;
+
+/// Gives up on connecting and on the response headers after this long.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Error, Debug)]
+pub enum DownloadError {
+    #[error("HTTP error: {0}")]
+    Http(#[from] ureq::Error),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Checksum mismatch: expected SHA-256 {expected}, got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+
+    #[error("The downloaded file is not a valid table file: {0}")]
+    InvalidTable(#[from] TableFileError),
+}
+
+/// Returns the URL of a table file, given the base URL and the local path of the table.
+pub fn table_url(base_url: &str, path: &str) -> String {
+    let file_name = std::path::Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(path);
+    format!("{}/{}", base_url.trim_end_matches('/'), file_name)
+}
+
+/// Downloads a table file to `path`, giving up if the download takes longer than `timeout`.
+///
+/// The file is first written to `<path>.part` and only renamed to `path` once it is complete, its header and the checksum of its payload are valid,
+/// and, if `expected_sha256` is given, its SHA-256 (hex encoded) matches. Otherwise the `.part` file is deleted,
+/// so that a broken download never becomes the table file, which would then not be downloaded again.
+pub fn download_table(url: &str, path: &str, expected_sha256: Option<&str>, timeout: Duration) -> Result<(), DownloadError> {
+    let part_path = format!("{}.part", path);
+    let downloaded = download_file(url, &part_path, expected_sha256, timeout).and_then(|()| validate_table_file(&part_path));
+    if let Err(err) = downloaded {
+        let _ = std::fs::remove_file(&part_path);
+        return Err(err);
+    }
+    std::fs::rename(&part_path, path)?;
+    Ok(())
+}
+
+fn download_file(url: &str, path: &str, expected_sha256: Option<&str>, timeout: Duration) -> Result<(), DownloadError> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_connect(Some(RESPONSE_TIMEOUT))
+        .timeout_recv_response(Some(RESPONSE_TIMEOUT))
+        .timeout_global(Some(timeout))
+        .build()
+        .into();
+    let mut reader = agent.get(url).call()?.into_body().into_reader();
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1 << 20];
+    loop {
+        let n = reader.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+        file.write_all(&buffer[..n])?;
+    }
+    file.flush()?;
+
+    let actual = to_hex(&hasher.finalize());
+    if let Some(expected) = expected_sha256
+        && !expected.eq_ignore_ascii_case(&actual)
+    {
+        return Err(DownloadError::ChecksumMismatch { expected: expected.to_string(), actual });
+    }
+    Ok(())
+}
+
+/// Checks the header of the table file and the size and checksum of its payload.
+fn validate_table_file(path: &str) -> Result<(), DownloadError> {
+    let header = read_table_header(path)?;
+    read_table_with(std::fs::File::open(path)?, header.kind, |_, payload| std::io::copy(payload, &mut std::io::sink()))?;
+    Ok(())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_url() {
+        assert_eq!(table_url("https://example.com/tables", "D:/corners_table.dat"), "https://example.com/tables/corners_table.dat");
+        assert_eq!(table_url("https://example.com/tables/", "subset_table.dat"), "https://example.com/tables/subset_table.dat");
+    }
+
+    #[test]
+    fn test_validate_table_file() {
+        use super::super::file_format::{TableHeader, TableKind, write_table_file};
+        use crate::cubies::TwistSet;
+
+        let path = std::env::temp_dir().join("rubikscube_test_validate_table_file.dat");
+        let path = path.to_str().unwrap();
+        let payload = [0, 1, 2, 3, 2, 1];
+        let header = TableHeader::new(TableKind::Distance, TwistSet::FULL, payload.len(), 3, &payload);
+        write_table_file(path, &header, &payload).unwrap();
+        assert!(validate_table_file(path).is_ok());
+
+        // A download that broke off
+        let data = std::fs::read(path).unwrap();
+        std::fs::write(path, &data[..data.len() - 1]).unwrap();
+        assert!(matches!(validate_table_file(path), Err(DownloadError::InvalidTable(_))));
+        std::fs::write(path, b"<html>Not Found</html>").unwrap();
+        assert!(matches!(validate_table_file(path), Err(DownloadError::InvalidTable(_))));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_to_hex() {
+        assert_eq!(to_hex(&[0x00, 0xAB, 0x7F]), "00ab7f");
+    }
+}
@@ -0,0 +1,465 @@
+use crate::cubies::*;
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use thiserror::Error;
+
+pub const MAGIC: [u8; 8] = *b"RUBIKTBL";
+pub const FORMAT_VERSION: u32 = 1;
+pub const HEADER_SIZE: usize = 64;
+
+/// Magic bytes of a zstd frame. Compressed table files start with these instead of `MAGIC`.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableKind {
+    Distance = 0,
+    Directions = 1,
+    Twister = 2,
+    /// A distance table whose entries stand for an object and its inverse, see `DistanceTable::create_inverse_reduced_with_progress`.
+    InverseReducedDistance = 3,
+}
+
+impl TableKind {
+    /// Number of payload bytes per table entry.
+    pub fn entry_size(&self) -> usize {
+        match self {
+            TableKind::Distance | TableKind::InverseReducedDistance => 1,
+            TableKind::Directions => 8,
+            TableKind::Twister => 2,
+        }
+    }
+
+    /// Whether a table of this kind is read where the expected kind is, e.g. a reduced distance table as a distance table.
+    pub fn is_read_as(&self, expected: TableKind) -> bool {
+        *self == expected || (*self == TableKind::InverseReducedDistance && expected == TableKind::Distance)
+    }
+}
+
+impl TryFrom<u8> for TableKind {
+    type Error = TableFileError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(TableKind::Distance),
+            1 => Ok(TableKind::Directions),
+            2 => Ok(TableKind::Twister),
+            3 => Ok(TableKind::InverseReducedDistance),
+            _ => Err(TableFileError::UnknownKind(value)),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum TableFileError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Not a table file (bad magic bytes)")]
+    BadMagic,
+
+    #[error("Unsupported table format version {found} (expected {FORMAT_VERSION})")]
+    UnsupportedVersion { found: u32 },
+
+    #[error("Unknown table kind {0}")]
+    UnknownKind(u8),
+
+    #[error("Expected a {expected:?} table, found a {found:?} table")]
+    WrongKind { expected: TableKind, found: TableKind },
+
+    #[error("Payload has {found} bytes, but the header declares {expected} bytes")]
+    PayloadSize { expected: u64, found: u64 },
+
+    #[error("Checksum mismatch, the table file is corrupt")]
+    ChecksumMismatch,
+
+    #[error("The table file is zstd-compressed, but the 'compress' feature is disabled")]
+    CompressionUnsupported,
+
+    #[error("Table files only store tables whose twists cost 1 each")]
+    UnsupportedCosts,
+}
+
+/// Header of a table file.
+///
+/// Every table file starts with a 64 byte header, followed by the payload:
+///
+/// | Offset | Size | Field                                    |
+/// | -----: | ---: | ---------------------------------------- |
+/// |      0 |    8 | Magic bytes `RUBIKTBL`                   |
+/// |      8 |    4 | Format version (little endian)           |
+/// |     12 |    1 | Table kind                               |
+/// |     13 |    1 | Max distance                             |
+/// |     14 |    2 | Reserved (zero)                          |
+/// |     16 |    4 | Generator twist set bits (little endian) |
+/// |     20 |    8 | Index size (little endian)               |
+/// |     28 |    4 | Reserved (zero)                          |
+/// |     32 |   32 | SHA-256 of the payload                   |
+///
+/// The payload starts 64 bytes in, aligned for its entries, which are little endian integers.
+/// A memory-mapped little endian file can thus be cast to the entries in place, e.g. with `bytemuck::try_cast_slice`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableHeader {
+    pub kind: TableKind,
+    pub twists: TwistSet,
+    pub index_size: u64,
+    pub max_distance: u8,
+    pub checksum: [u8; 32],
+}
+
+impl TableHeader {
+    /// Creates a header describing the given payload.
+    pub fn new(kind: TableKind, twists: TwistSet, index_size: usize, max_distance: u8, payload: &[u8]) -> Self {
+        Self {
+            kind,
+            twists,
+            index_size: index_size as u64,
+            max_distance,
+            checksum: Sha256::digest(payload).into(),
+        }
+    }
+
+    /// Number of payload bytes following the header.
+    pub fn payload_size(&self) -> u64 {
+        self.index_size * self.kind.entry_size() as u64
+    }
+
+    pub fn to_bytes(&self) -> [u8; HEADER_SIZE] {
+        let mut bytes = [0u8; HEADER_SIZE];
+        bytes[0..8].copy_from_slice(&MAGIC);
+        bytes[8..12].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes[12] = self.kind as u8;
+        bytes[13] = self.max_distance;
+        bytes[16..20].copy_from_slice(&self.twists.bits().to_le_bytes());
+        bytes[20..28].copy_from_slice(&self.index_size.to_le_bytes());
+        bytes[32..64].copy_from_slice(&self.checksum);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, TableFileError> {
+        if bytes.len() < HEADER_SIZE || bytes[0..8] != MAGIC {
+            return Err(TableFileError::BadMagic);
+        }
+        let version = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+        if version != FORMAT_VERSION {
+            return Err(TableFileError::UnsupportedVersion { found: version });
+        }
+        Ok(Self {
+            kind: TableKind::try_from(bytes[12])?,
+            max_distance: bytes[13],
+            twists: TwistSet::new(u32::from_le_bytes(bytes[16..20].try_into().unwrap())),
+            index_size: u64::from_le_bytes(bytes[20..28].try_into().unwrap()),
+            checksum: bytes[32..64].try_into().unwrap(),
+        })
+    }
+}
+
+/// Writes the header and the payload to a file.
+/// With the 'compress' feature, the file is zstd-compressed as a whole.
+pub fn write_table_file(path: &str, header: &TableHeader, payload: &[u8]) -> Result<(), TableFileError> {
+    write_file(path, header, |writer| writer.write_all(payload))
+}
+
+/// Like `write_table_file`, but `write_payload` writes the payload in pieces, e.g. entries encoded a chunk at a time,
+/// so that the payload is never in memory next to the table. It is called twice: for the checksum of the header, and for the file.
+pub fn write_table_file_with(
+    path: &str,
+    kind: TableKind,
+    twists: TwistSet,
+    index_size: usize,
+    max_distance: u8,
+    write_payload: impl Fn(&mut dyn Write) -> std::io::Result<()>,
+) -> Result<(), TableFileError> {
+    let mut hasher = HashingWriter(Sha256::new());
+    write_payload(&mut hasher)?;
+    let header = TableHeader { kind, twists, index_size: index_size as u64, max_distance, checksum: hasher.0.finalize().into() };
+    write_file(path, &header, write_payload)
+}
+
+fn write_file(path: &str, header: &TableHeader, write_payload: impl Fn(&mut dyn Write) -> std::io::Result<()>) -> Result<(), TableFileError> {
+    let file = std::fs::File::create(path)?;
+
+    #[cfg(feature = "compress")]
+    {
+        let mut encoder = zstd::Encoder::new(file, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+        encoder.write_all(&header.to_bytes())?;
+        write_payload(&mut encoder)?;
+        encoder.finish()?;
+    }
+    #[cfg(not(feature = "compress"))]
+    {
+        let mut writer = std::io::BufWriter::new(file);
+        writer.write_all(&header.to_bytes())?;
+        write_payload(&mut writer)?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Reads the payload straight into the memory of the entries, without decoding them one by one,
+/// for entries stored as they are in memory, e.g. little endian integers on a little endian target.
+pub fn read_pod_entries<T: bytemuck::Pod>(header: &TableHeader, reader: &mut dyn Read) -> std::io::Result<Vec<T>> {
+    assert_eq!(size_of::<T>(), header.kind.entry_size());
+    let mut entries = bytemuck::allocation::try_zeroed_vec(header.index_size as usize)
+        .map_err(|_| std::io::Error::from(std::io::ErrorKind::OutOfMemory))?;
+    reader.read_exact(bytemuck::cast_slice_mut(&mut entries))?;
+    Ok(entries)
+}
+
+/// A corrupt header may declare any size, which fails here rather than aborting.
+fn reserve_exact<T>(vec: &mut Vec<T>, additional: usize) -> std::io::Result<()> {
+    vec.try_reserve_exact(additional).map_err(|_| std::io::Error::from(std::io::ErrorKind::OutOfMemory))
+}
+
+/// Hashes what is written to it.
+struct HashingWriter(Sha256);
+
+impl Write for HashingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Hashes and counts what is read through it.
+struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+    len: u64,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        self.len += n as u64;
+        Ok(n)
+    }
+}
+
+/// Reads only the header of a table file, compressed or not, without validating the payload.
+pub fn read_table_header(path: &str) -> Result<TableHeader, TableFileError> {
+    // Files too short for a header are not table files.
+    let too_short = |err: std::io::Error| match err.kind() {
+        std::io::ErrorKind::UnexpectedEof => TableFileError::BadMagic,
+        _ => TableFileError::Io(err),
+    };
+    let mut file = std::fs::File::open(path)?;
+    let mut bytes = [0u8; HEADER_SIZE];
+    file.read_exact(&mut bytes[..ZSTD_MAGIC.len()]).map_err(too_short)?;
+    if bytes[..ZSTD_MAGIC.len()] != ZSTD_MAGIC {
+        file.read_exact(&mut bytes[ZSTD_MAGIC.len()..]).map_err(too_short)?;
+        return TableHeader::from_bytes(&bytes);
+    }
+
+    #[cfg(feature = "compress")]
+    {
+        use std::io::Seek;
+        file.rewind()?;
+        zstd::Decoder::new(file)?.read_exact(&mut bytes).map_err(too_short)?;
+        TableHeader::from_bytes(&bytes)
+    }
+
+    #[cfg(not(feature = "compress"))]
+    Err(TableFileError::CompressionUnsupported)
+}
+
+/// Reads a table file, compressed or not, and validates its header against the payload.
+/// Returns the header and the payload.
+pub fn read_table_file(path: &str, expected_kind: TableKind) -> Result<(TableHeader, Vec<u8>), TableFileError> {
+    read_table_with(std::fs::File::open(path)?, expected_kind, read_payload)
+}
+
+/// Reads a table, compressed or not, and validates its header against the payload, which `read_payload` reads in pieces,
+/// e.g. with `read_entries`, so that neither the file nor the payload is in memory as a whole next to the table.
+/// Returns the header and what `read_payload` returns.
+pub fn read_table_with<T>(
+    reader: impl Read,
+    expected_kind: TableKind,
+    read_payload: impl FnOnce(&TableHeader, &mut dyn Read) -> std::io::Result<T>,
+) -> Result<(TableHeader, T), TableFileError> {
+    use std::io::BufRead;
+    let mut reader = std::io::BufReader::new(reader);
+    if !reader.fill_buf()?.starts_with(&ZSTD_MAGIC) {
+        return read_uncompressed_table(reader, expected_kind, read_payload);
+    }
+
+    #[cfg(feature = "compress")]
+    return read_uncompressed_table(zstd::Decoder::with_buffer(reader)?, expected_kind, read_payload);
+
+    #[cfg(not(feature = "compress"))]
+    return Err(TableFileError::CompressionUnsupported);
+}
+
+fn read_uncompressed_table<T>(
+    mut reader: impl Read,
+    expected_kind: TableKind,
+    read_payload: impl FnOnce(&TableHeader, &mut dyn Read) -> std::io::Result<T>,
+) -> Result<(TableHeader, T), TableFileError> {
+    let mut bytes = [0u8; HEADER_SIZE];
+    reader.read_exact(&mut bytes).map_err(|err| match err.kind() {
+        std::io::ErrorKind::UnexpectedEof => TableFileError::BadMagic,
+        _ => TableFileError::Io(err),
+    })?;
+    let header = TableHeader::from_bytes(&bytes)?;
+    if !header.kind.is_read_as(expected_kind) {
+        return Err(TableFileError::WrongKind { expected: expected_kind, found: header.kind });
+    }
+
+    let mut payload = HashingReader { inner: reader.by_ref().take(header.payload_size()), hasher: Sha256::new(), len: 0 };
+    let value = read_payload(&header, &mut payload);
+    // What `read_payload` left, e.g. after the file ended early, and what follows the payload count too.
+    std::io::copy(&mut payload, &mut std::io::sink())?;
+    let HashingReader { hasher, len, .. } = payload;
+    let payload_size = len + std::io::copy(&mut reader, &mut std::io::sink())?;
+    if payload_size != header.payload_size() {
+        return Err(TableFileError::PayloadSize { expected: header.payload_size(), found: payload_size });
+    }
+    let value = value?;
+    if <[u8; 32]>::from(hasher.finalize()) != header.checksum {
+        return Err(TableFileError::ChecksumMismatch);
+    }
+    Ok((header, value))
+}
+
+/// Reads the whole payload with `read_to_end` into a Vec of the payload size, for `read_table_with`.
+pub fn read_payload(header: &TableHeader, reader: &mut dyn Read) -> std::io::Result<Vec<u8>> {
+    let mut payload = Vec::new();
+    reserve_exact(&mut payload, header.payload_size() as usize)?;
+    reader.read_to_end(&mut payload)?;
+    Ok(payload)
+}
+
+/// Reads the entries of the payload a chunk at a time, each from `entry_size` bytes of the header's kind.
+pub fn read_entries<T>(header: &TableHeader, reader: &mut dyn Read, decode: impl Fn(&[u8]) -> T) -> std::io::Result<Vec<T>> {
+    const CHUNK_ENTRIES: usize = 1 << 16;
+    let entry_size = header.kind.entry_size();
+    let mut remaining = header.index_size as usize;
+    let mut entries = Vec::new();
+    reserve_exact(&mut entries, remaining)?;
+    let mut chunk = vec![0u8; CHUNK_ENTRIES.min(remaining) * entry_size];
+    while remaining > 0 {
+        let n = CHUNK_ENTRIES.min(remaining);
+        reader.read_exact(&mut chunk[..n * entry_size])?;
+        entries.extend(chunk[..n * entry_size].chunks_exact(entry_size).map(&decode));
+        remaining -= n;
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir().join(name).to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_header_round_trip() {
+        let payload = [0, 1, 2, 3, 2, 1];
+        let header = TableHeader::new(TableKind::Distance, TwistSet::H0, payload.len(), 3, &payload);
+        assert_eq!(TableHeader::from_bytes(&header.to_bytes()).unwrap(), header);
+    }
+
+    #[test]
+    fn test_file_round_trip() {
+        let path = temp_path("rubikscube_test_file_round_trip.dat");
+        let payload = [0, 1, 2, 3, 2, 1];
+        let header = TableHeader::new(TableKind::Distance, TwistSet::FULL, payload.len(), 3, &payload);
+        write_table_file(&path, &header, &payload).unwrap();
+
+        let (read_header, read_payload) = read_table_file(&path, TableKind::Distance).unwrap();
+        assert_eq!(read_header, header);
+        assert_eq!(read_table_header(&path).unwrap(), header);
+        assert_eq!(read_payload, payload);
+
+        assert!(matches!(
+            read_table_file(&path, TableKind::Directions),
+            Err(TableFileError::WrongKind { .. })
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_streaming() {
+        let path = temp_path("rubikscube_test_streaming.dat");
+        let payload: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        write_table_file_with(&path, TableKind::Twister, TwistSet::FULL, payload.len() / 2, 0, |writer| {
+            payload.chunks(30_000).try_for_each(|chunk| writer.write_all(chunk))
+        })
+        .unwrap();
+        let header = TableHeader::new(TableKind::Twister, TwistSet::FULL, payload.len() / 2, 0, &payload);
+        assert_eq!(read_table_header(&path).unwrap(), header);
+        assert_eq!(read_table_file(&path, TableKind::Twister).unwrap().1, payload);
+
+        let (_, entries) = read_table_with(std::fs::File::open(&path).unwrap(), TableKind::Twister, |header, reader| {
+            read_entries(header, reader, |bytes| u16::from_le_bytes(bytes.try_into().unwrap()))
+        })
+        .unwrap();
+        assert_eq!(entries.len(), payload.len() / 2);
+        assert_eq!(entries[1], u16::from_le_bytes([payload[2], payload[3]]));
+
+        // A payload that is only partly read still has its size and checksum checked.
+        let (_, first) = read_table_with(std::fs::File::open(&path).unwrap(), TableKind::Twister, |_, reader| {
+            let mut bytes = [0u8; 3];
+            reader.read_exact(&mut bytes).map(|_| bytes)
+        })
+        .unwrap();
+        assert_eq!(first, [0, 1, 2]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_corrupt_files() {
+        let path = temp_path("rubikscube_test_corrupt_files.dat");
+        let payload = [0, 1, 2, 3, 2, 1];
+        let header = TableHeader::new(TableKind::Distance, TwistSet::FULL, payload.len(), 3, &payload);
+        let mut data = header.to_bytes().to_vec();
+        data.extend_from_slice(&payload);
+
+        // Raw byte dump without header
+        std::fs::write(&path, payload).unwrap();
+        assert!(matches!(read_table_file(&path, TableKind::Distance), Err(TableFileError::BadMagic)));
+        assert!(matches!(read_table_header(&path), Err(TableFileError::BadMagic)));
+
+        // Flipped payload byte
+        let mut corrupt = data.clone();
+        corrupt[HEADER_SIZE + 2] ^= 1;
+        std::fs::write(&path, &corrupt).unwrap();
+        assert!(matches!(read_table_file(&path, TableKind::Distance), Err(TableFileError::ChecksumMismatch)));
+
+        // Truncated payload
+        std::fs::write(&path, &data[..data.len() - 1]).unwrap();
+        assert!(matches!(read_table_file(&path, TableKind::Distance), Err(TableFileError::PayloadSize { .. })));
+
+        // Future version
+        let mut future = data.clone();
+        future[8] = 99;
+        std::fs::write(&path, &future).unwrap();
+        assert!(matches!(read_table_file(&path, TableKind::Distance), Err(TableFileError::UnsupportedVersion { found: 99 })));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_compression() {
+        let path = temp_path("rubikscube_test_compression.dat");
+        let payload = vec![7u8; 100_000];
+        let header = TableHeader::new(TableKind::Distance, TwistSet::FULL, payload.len(), 7, &payload);
+        write_table_file(&path, &header, &payload).unwrap();
+
+        let file_size = std::fs::metadata(&path).unwrap().len() as usize;
+        if cfg!(feature = "compress") {
+            assert!(file_size < payload.len() / 100);
+        } else {
+            assert_eq!(file_size, HEADER_SIZE + payload.len());
+        }
+        assert_eq!(read_table_file(&path, TableKind::Distance).unwrap().1, payload);
+        assert_eq!(read_table_header(&path).unwrap(), header);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,8 +1,24 @@
 pub mod direction_table;
 mod config_file;
+pub mod coordinate_table;
 pub mod distance_table;
+#[cfg(feature = "download")]
+pub mod download;
+pub mod file_format;
+pub mod statistics;
 pub mod stored_tables;
+pub mod table_loader;
+pub mod table_set;
+pub mod verification;
 
+pub use coordinate_table::*;
 pub use direction_table::*;
 pub use distance_table::*;
+#[cfg(feature = "download")]
+pub use download::*;
+pub use file_format::*;
+pub use statistics::*;
 pub use stored_tables::*;
+pub use table_loader::*;
+pub use table_set::*;
+pub use verification::*;
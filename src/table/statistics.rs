@@ -0,0 +1,94 @@
+#[cfg(feature = "parallel")]
+use crate::parallel::*;
+
+/// Counts how many indices in 0..len have each distance.
+/// The result is as long as the maximum distance plus one.
+pub fn distance_histogram(len: usize, distance: impl Fn(usize) -> u8 + Sync) -> Vec<u64> {
+    weighted_distance_histogram(len, distance, |_| 1)
+}
+
+/// Like `distance_histogram`, but an index counts `weight` times, e.g. the number of objects it stands for in a reduced table.
+pub fn weighted_distance_histogram(len: usize, distance: impl Fn(usize) -> u8 + Sync, weight: impl Fn(usize) -> u64 + Sync) -> Vec<u64> {
+    #[cfg(feature = "parallel")]
+    let mut counts = (0..len)
+        .into_par_iter()
+        .fold(
+            || vec![0u64; u8::MAX as usize + 1],
+            |mut counts, i| {
+                counts[distance(i) as usize] += weight(i);
+                counts
+            },
+        )
+        .reduce(
+            || vec![0u64; u8::MAX as usize + 1],
+            |mut a, b| {
+                a.iter_mut().zip(b).for_each(|(a, b)| *a += b);
+                a
+            },
+        );
+    #[cfg(not(feature = "parallel"))]
+    let mut counts = (0..len).fold(vec![0u64; u8::MAX as usize + 1], |mut counts, i| {
+        counts[distance(i) as usize] += weight(i);
+        counts
+    });
+    let used = counts.iter().rposition(|&count| count != 0).map_or(0, |d| d + 1);
+    counts.truncate(used);
+    counts
+}
+
+/// Mean distance of a histogram, where histogram[d] is the number of entries with distance d.
+pub fn mean_distance(histogram: &[u64]) -> f64 {
+    let total: u64 = histogram.iter().sum();
+    let weighted: f64 = histogram.iter().enumerate().map(|(d, &count)| d as f64 * count as f64).sum();
+    weighted / total as f64
+}
+
+/// Smallest distance d such that at least the given fraction of all entries have a distance <= d.
+pub fn percentile_distance(histogram: &[u64], fraction: f64) -> u8 {
+    assert!((0.0..=1.0).contains(&fraction), "Fraction must be in [0, 1]");
+    let total: u64 = histogram.iter().sum();
+    let mut cumulative = 0;
+    for (d, &count) in histogram.iter().enumerate() {
+        cumulative += count;
+        if cumulative as f64 >= fraction * total as f64 {
+            return d as u8;
+        }
+    }
+    histogram.len().saturating_sub(1) as u8
+}
+
+/// Distance that at least half of all entries don't exceed.
+pub fn median_distance(histogram: &[u64]) -> u8 {
+    percentile_distance(histogram, 0.5)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance_histogram() {
+        let distances = [0u8, 1, 1, 3, 1];
+        assert_eq!(distance_histogram(distances.len(), |i| distances[i]), vec![1, 3, 0, 1]);
+        assert_eq!(distance_histogram(0, |_| 0), Vec::<u64>::new());
+        assert_eq!(weighted_distance_histogram(distances.len(), |i| distances[i], |i| i as u64), vec![0, 7, 0, 3]);
+    }
+
+    #[test]
+    fn test_mean_distance() {
+        assert_eq!(mean_distance(&[1, 3, 0, 1]), 1.2);
+        assert_eq!(mean_distance(&[0, 0, 5]), 2.0);
+    }
+
+    #[test]
+    fn test_percentile_distance() {
+        let histogram = [1, 3, 0, 1];
+        assert_eq!(percentile_distance(&histogram, 0.0), 0);
+        assert_eq!(percentile_distance(&histogram, 0.2), 0);
+        assert_eq!(percentile_distance(&histogram, 0.21), 1);
+        assert_eq!(percentile_distance(&histogram, 0.8), 1);
+        assert_eq!(percentile_distance(&histogram, 0.81), 3);
+        assert_eq!(percentile_distance(&histogram, 1.0), 3);
+        assert_eq!(median_distance(&histogram), 1);
+    }
+}
@@ -1,45 +1,70 @@
 use crate::cubies::*;
 use crate::index::*;
 use crate::table::*;
-use crate::table::config_file::read_config_file;
 
-pub fn get_tables() -> (DistanceTable, DistanceTable, DirectionsTable) {
-    let config = read_config_file("config.txt");
+/// Number of corner states per distance, according to https://oeis.org/A080629
+pub const CORNERS_HISTOGRAM: [u64; 12] = [1, 18, 243, 2874, 28000, 205416, 1168516, 5402628, 20776176, 45391616, 15139616, 64736];
 
-    let corners_table = DistanceTable::from_file(&config["corners_table"]).unwrap_or_else(|_| create_corners_table());
-    let subset_table = DistanceTable::from_file(&config["subset_table"]).unwrap_or_else(|_| create_subset_table());
-    let coset_table = DirectionsTable::from_file(&config["coset_table"]).unwrap_or_else(|_| create_coset_table());
+/// Number of subset states per distance, using only H0 twists.
+pub const SUBSET_HISTOGRAM: [u64; 19] = [
+    1, 10, 67, 456, 3079, 19948, 123074, 736850, 4185118, 22630733, 116767872, 552538680,
+    2176344160, 5627785188, 7172925794, 3608731814, 224058996, 1575608, 1352
+];
 
-    check_corners_table(&corners_table);
-    check_subset_table(&subset_table);
-    check_coset_table(&coset_table);
-    
-    (corners_table, subset_table, coset_table)
-}
+/// Number of cosets per distance to the subset.
+pub const COSET_HISTOGRAM: [u64; 13] = [1, 4, 50, 592, 7156, 87236, 1043817, 12070278, 124946368, 821605960, 1199128738, 58202444, 476];
 
 pub fn create_corners_table() -> DistanceTable {
-    DistanceTable::create(
+    create_corners_table_with_progress(|_, _| {})
+}
+
+pub fn create_corners_table_with_progress(progress: impl FnMut(u8, u64)) -> DistanceTable {
+    DistanceTable::create_with_progress(
         &ALL_TWISTS,
         Cube::solved(),
         |c: Cube| c.corner_index(),
         |i: usize| Cube::from_corner_index(i),
         Cube::CORNER_INDEX_SIZE,
+        progress,
     )
 }
 
-pub fn check_corners_table(table: &DistanceTable) {
-    // Verify data integrity
-    let mut counts = vec![0; 12];
-    for i in 0..Cube::CORNER_INDEX_SIZE {
-        counts[table.distance(i) as usize] += 1;
-    }
-    // According to https://oeis.org/A080629
-    assert_eq!(counts, vec![1, 18, 243, 2874, 28000, 205416, 1168516, 5402628, 20776176, 45391616, 15139616, 64736]);
+/// The corners table with distances in total costs of the twists, e.g. `TwistCosts::QTM`.
+pub fn create_corners_table_with_costs(costs: &TwistCosts) -> DistanceTable {
+    DistanceTable::create_with_costs(
+        &ALL_TWISTS,
+        costs,
+        Cube::solved(),
+        |c: Cube| c.corner_index(),
+        |i: usize| Cube::from_corner_index(i),
+        Cube::CORNER_INDEX_SIZE,
+    )
+}
+
+pub fn check_corners_table(table: &DistanceTable) -> VerificationReport {
+    table.verify(&CORNERS_HISTOGRAM)
 }
 
 pub fn create_subset_table() -> DistanceTable {
-    DistanceTable::create(
+    create_subset_table_with_progress(|_, _| {})
+}
+
+pub fn create_subset_table_with_progress(progress: impl FnMut(u8, u64)) -> DistanceTable {
+    DistanceTable::create_with_progress(
+        &H0_TWISTS,
+        SubsetCube::solved(),
+        |s: SubsetCube| s.index(),
+        |i: usize| SubsetCube::from_index(i),
+        SubsetCube::INDEX_SIZE,
+        progress,
+    )
+}
+
+/// The subset table with distances in total costs of the twists, e.g. `TwistCosts::QTM`.
+pub fn create_subset_table_with_costs(costs: &TwistCosts) -> DistanceTable {
+    DistanceTable::create_with_costs(
         &H0_TWISTS,
+        costs,
         SubsetCube::solved(),
         |s: SubsetCube| s.index(),
         |i: usize| SubsetCube::from_index(i),
@@ -47,36 +72,59 @@ pub fn create_subset_table() -> DistanceTable {
     )
 }
 
-pub fn check_subset_table(table: &DistanceTable) {
-    // Verify data integrity
-    let mut counts = vec![0u64; 19];
-    for i in 0..SubsetCube::INDEX_SIZE {
-        counts[table.distance(i) as usize] += 1;
+/// The subset table reduced by inversion, about half the size, see `SubsetInverse`.
+pub fn create_inverse_reduced_subset_table() -> DistanceTable {
+    create_inverse_reduced_subset_table_with_progress(|_, _| {})
+}
+
+pub fn create_inverse_reduced_subset_table_with_progress(progress: impl FnMut(u8, u64)) -> DistanceTable {
+    DistanceTable::create_inverse_reduced_with_progress(
+        &H0_TWISTS,
+        SubsetCube::solved(),
+        |s: SubsetCube| s.reduced_index(),
+        |i: usize| SubsetCube::from_reduced_index(i),
+        |s: SubsetCube| s.inverse(),
+        SubsetCube::REDUCED_INDEX_SIZE,
+        progress,
+    )
+}
+
+/// Also checks a table reduced by inversion, whose entries count as the cubes they stand for.
+pub fn check_subset_table(table: &DistanceTable) -> VerificationReport {
+    if table.is_inverse_reduced() {
+        let histogram = weighted_distance_histogram(table.len(), |i| table.distance(i), |i| SUBSET_INVERSE.multiplicity(i));
+        return VerificationReport::new(&SUBSET_HISTOGRAM, histogram);
     }
-    assert_eq!(
-        counts,
-        vec![
-            1, 10, 67, 456, 3079, 19948, 123074, 736850, 4185118, 22630733, 116767872, 552538680,
-            2176344160, 5627785188, 7172925794, 3608731814, 224058996, 1575608, 1352
-        ]
-    );
+    table.verify(&SUBSET_HISTOGRAM)
 }
 
 pub fn create_coset_table() -> DirectionsTable {
-    DirectionsTable::create(
+    create_coset_table_with_progress(|_, _| {})
+}
+
+pub fn create_coset_table_with_progress(progress: impl FnMut(u8, u64)) -> DirectionsTable {
+    DirectionsTable::create_with_progress(
         &ALL_TWISTS,
         Cube::solved(),
         |c: Cube| c.coset_index(),
         |i: usize| Cube::from_coset_index(i),
         Cube::COSETS_INDEX_SIZE,
+        progress,
     )
 }
 
-pub fn check_coset_table(table: &DirectionsTable) {
-    // Verify data integrity
-    let mut counts = vec![0u64; 13];
-    for i in 0..Cube::COSETS_INDEX_SIZE {
-        counts[table.distance(i) as usize] += 1;
-    }
-    assert_eq!(counts, vec![1, 4, 50, 592, 7156, 87236, 1043817, 12070278, 124946368, 821605960, 1199128738, 58202444, 476]);
+/// The coset table with distances in total costs of the twists, e.g. `TwistCosts::QTM`.
+pub fn create_coset_table_with_costs(costs: &TwistCosts) -> DirectionsTable {
+    DirectionsTable::create_with_costs(
+        &ALL_TWISTS,
+        costs,
+        Cube::solved(),
+        |c: Cube| c.coset_index(),
+        |i: usize| Cube::from_coset_index(i),
+        Cube::COSETS_INDEX_SIZE,
+    )
+}
+
+pub fn check_coset_table(table: &DirectionsTable) -> VerificationReport {
+    table.verify(&COSET_HISTOGRAM)
 }
@@ -0,0 +1,72 @@
+use crate::index::*;
+use crate::table::*;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+
+/// Handle to tables that are loaded on a background thread.
+pub struct TableLoader {
+    ready: Arc<AtomicBool>,
+    handle: JoinHandle<Result<TableSet, TableError>>,
+}
+
+impl TableLoader {
+    /// Whether loading has finished, successfully or not.
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Acquire)
+    }
+
+    /// Blocks until loading has finished and returns the tables.
+    pub fn wait(self) -> Result<TableSet, TableError> {
+        self.handle.join().expect("Table loader thread panicked")
+    }
+}
+
+impl TableSetBuilder {
+    /// Builds the tables on a background thread and warms them up.
+    pub fn build_in_background(self) -> TableLoader {
+        let ready = Arc::new(AtomicBool::new(false));
+        let thread_ready = Arc::clone(&ready);
+        let handle = std::thread::spawn(move || {
+            let tables = self.build();
+            if let Ok(tables) = &tables {
+                tables.warm_up();
+            }
+            thread_ready.store(true, Ordering::Release);
+            tables
+        });
+        TableLoader { ready, handle }
+    }
+}
+
+impl TableSet {
+    /// Forces the lazy initialization of the twisters and the subset index, and of the inversion of subset cubes for a reduced subset table,
+    /// so the first solve doesn't pay for it. The tables themselves need no warming up, since `from_file` reads them fully into memory.
+    pub fn warm_up(&self) {
+        init_twister();
+        init_subset_twister();
+        init_subset_index();
+        if self.subset.is_inverse_reduced() {
+            init_subset_inverse();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_in_background() {
+        let loader = TableSet::builder()
+            .corners_path("does_not_exist.dat")
+            .subset_path("does_not_exist.dat")
+            .coset_path("does_not_exist.dat")
+            .create_missing(false)
+            .build_in_background();
+        while !loader.is_ready() {
+            std::thread::yield_now();
+        }
+        assert!(matches!(loader.wait(), Err(TableError::File { name: "corners", .. })));
+    }
+}
@@ -0,0 +1,268 @@
+use crate::cubies::TwistCosts;
+use crate::table::*;
+use crate::table::config_file::read_config_file;
+use thiserror::Error;
+
+/// Environment variables that override the configured table paths.
+pub const CORNERS_TABLE_ENV: &str = "RUBIKSCUBE_CORNERS_TABLE";
+pub const SUBSET_TABLE_ENV: &str = "RUBIKSCUBE_SUBSET_TABLE";
+pub const COSET_TABLE_ENV: &str = "RUBIKSCUBE_COSET_TABLE";
+
+#[derive(Error, Debug)]
+pub enum TableError {
+    #[error("Failed to read config file '{path}': {source}")]
+    Config { path: String, source: std::io::Error },
+
+    #[error("No path configured for the {0} table")]
+    MissingPath(&'static str),
+
+    #[error("Failed to load the {name} table from '{path}': {source}")]
+    File { name: &'static str, path: String, source: TableFileError },
+
+    #[error("Failed to read the {name} table: {source}")]
+    Bytes { name: &'static str, source: TableFileError },
+
+    #[error("The {name} table is corrupt:\n{report}")]
+    Verification { name: &'static str, report: VerificationReport },
+
+    #[cfg(feature = "download")]
+    #[error("Failed to download the {name} table from '{url}': {source}")]
+    Download { name: &'static str, url: String, source: DownloadError },
+}
+
+/// The lookup tables needed by the two-phase solver.
+pub struct TableSet {
+    pub corners: DistanceTable,
+    pub subset: DistanceTable,
+    pub coset: DirectionsTable,
+}
+
+impl TableSet {
+    pub fn builder() -> TableSetBuilder {
+        TableSetBuilder::default()
+    }
+
+    /// Creates the tables with distances in total costs of the twists, which the solver then minimizes, e.g. `TwistCosts::QTM`.
+    /// Table files don't record the costs, so this takes hours each time.
+    pub fn create_with_costs(costs: &TwistCosts) -> Self {
+        Self {
+            corners: create_corners_table_with_costs(costs),
+            subset: create_subset_table_with_costs(costs),
+            coset: create_coset_table_with_costs(costs),
+        }
+    }
+
+    /// Reads the tables from the content of their files, e.g. where there is no file system.
+    pub fn from_bytes(corners: Vec<u8>, subset: Vec<u8>, coset: Vec<u8>) -> Result<Self, TableError> {
+        let bytes_error = |name| move |source| TableError::Bytes { name, source };
+        Ok(Self {
+            corners: DistanceTable::from_bytes(corners).map_err(bytes_error("corners"))?,
+            subset: DistanceTable::from_bytes(subset).map_err(bytes_error("subset"))?,
+            coset: DirectionsTable::from_bytes(coset).map_err(bytes_error("coset"))?,
+        })
+    }
+}
+
+/// Configures where the tables are stored.
+///
+/// Paths are resolved in this order of precedence:
+/// 1. The environment variables `RUBIKSCUBE_CORNERS_TABLE`, `RUBIKSCUBE_SUBSET_TABLE` and `RUBIKSCUBE_COSET_TABLE`.
+/// 2. Paths set on the builder.
+/// 3. Paths from the config file, with the keys `corners_table`, `subset_table` and `coset_table`.
+#[derive(Clone, Debug)]
+pub struct TableSetBuilder {
+    corners_path: Option<String>,
+    subset_path: Option<String>,
+    coset_path: Option<String>,
+    config_file: Option<String>,
+    #[cfg(feature = "download")]
+    download_url: Option<String>,
+    #[cfg(feature = "download")]
+    download_timeout: std::time::Duration,
+    create_missing: bool,
+    verify: bool,
+}
+
+impl Default for TableSetBuilder {
+    fn default() -> Self {
+        Self {
+            corners_path: None,
+            subset_path: None,
+            coset_path: None,
+            config_file: None,
+            #[cfg(feature = "download")]
+            download_url: None,
+            #[cfg(feature = "download")]
+            download_timeout: std::time::Duration::from_secs(60 * 60),
+            create_missing: true,
+            verify: true,
+        }
+    }
+}
+
+impl TableSetBuilder {
+    pub fn corners_path(mut self, path: impl Into<String>) -> Self {
+        self.corners_path = Some(path.into());
+        self
+    }
+
+    pub fn subset_path(mut self, path: impl Into<String>) -> Self {
+        self.subset_path = Some(path.into());
+        self
+    }
+
+    pub fn coset_path(mut self, path: impl Into<String>) -> Self {
+        self.coset_path = Some(path.into());
+        self
+    }
+
+    /// Reads the table paths from a config file with lines like `corners_table = <path>`.
+    pub fn config_file(mut self, path: impl Into<String>) -> Self {
+        self.config_file = Some(path.into());
+        self
+    }
+
+    /// Downloads missing tables from `<base_url>/<file name of the table path>` before resorting to creating them.
+    /// Downloaded tables are validated against the checksum in their header.
+    #[cfg(feature = "download")]
+    pub fn download_from(mut self, base_url: impl Into<String>) -> Self {
+        self.download_url = Some(base_url.into());
+        self
+    }
+
+    /// How long the download of a table may take before it is given up (default 1 hour), so that a stalled download doesn't block `build`.
+    #[cfg(feature = "download")]
+    pub fn download_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.download_timeout = timeout;
+        self
+    }
+
+    /// Whether missing tables are created and saved to their path (default), or reported as an error.
+    /// Creating the subset and coset tables takes hours.
+    pub fn create_missing(mut self, create_missing: bool) -> Self {
+        self.create_missing = create_missing;
+        self
+    }
+
+    /// Whether the distance histograms of the tables are checked against the known counts (default).
+    pub fn verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    /// Returns the paths of the corners, subset and coset table.
+    pub fn resolve_paths(&self) -> Result<[String; 3], TableError> {
+        let config = match &self.config_file {
+            Some(path) => read_config_file(path).map_err(|source| TableError::Config { path: path.clone(), source })?,
+            None => Default::default(),
+        };
+        let resolve = |name: &'static str, env_key: &str, path: &Option<String>, config_key: &str| {
+            std::env::var(env_key)
+                .ok()
+                .or_else(|| path.clone())
+                .or_else(|| config.get(config_key).cloned())
+                .ok_or(TableError::MissingPath(name))
+        };
+        Ok([
+            resolve("corners", CORNERS_TABLE_ENV, &self.corners_path, "corners_table")?,
+            resolve("subset", SUBSET_TABLE_ENV, &self.subset_path, "subset_table")?,
+            resolve("coset", COSET_TABLE_ENV, &self.coset_path, "coset_table")?,
+        ])
+    }
+
+    pub fn build(self) -> Result<TableSet, TableError> {
+        let [corners_path, subset_path, coset_path] = self.resolve_paths()?;
+
+        let corners = self.load("corners", &corners_path, DistanceTable::from_file, DistanceTable::to_file, create_corners_table)?;
+        if self.verify {
+            check("corners", check_corners_table(&corners))?;
+        }
+        let subset = self.load("subset", &subset_path, DistanceTable::from_file, DistanceTable::to_file, create_subset_table)?;
+        if self.verify {
+            check("subset", check_subset_table(&subset))?;
+        }
+        let coset = self.load("coset", &coset_path, DirectionsTable::from_file, DirectionsTable::to_file, create_coset_table)?;
+        if self.verify {
+            check("coset", check_coset_table(&coset))?;
+        }
+        Ok(TableSet { corners, subset, coset })
+    }
+
+    /// Loads a table from a file. If the file does not exist, the table is downloaded,
+    /// or created and saved.
+    fn load<T>(
+        &self,
+        name: &'static str,
+        path: &str,
+        from_file: impl Fn(&str) -> Result<T, TableFileError>,
+        to_file: impl Fn(&T, &str) -> Result<(), TableFileError>,
+        create: impl Fn() -> T,
+    ) -> Result<T, TableError> {
+        let file_error = |source| TableError::File { name, path: path.to_string(), source };
+
+        #[cfg(feature = "download")]
+        if let Some(base_url) = &self.download_url
+            && !std::path::Path::new(path).exists()
+        {
+            let url = table_url(base_url, path);
+            download_table(&url, path, None, self.download_timeout).map_err(|source| TableError::Download { name, url, source })?;
+        }
+
+        match from_file(path) {
+            Ok(table) => Ok(table),
+            Err(TableFileError::Io(err)) if err.kind() == std::io::ErrorKind::NotFound && self.create_missing => {
+                let table = create();
+                to_file(&table, path).map_err(file_error)?;
+                Ok(table)
+            }
+            Err(err) => Err(file_error(err)),
+        }
+    }
+}
+
+fn check(name: &'static str, report: VerificationReport) -> Result<(), TableError> {
+    if report.is_ok() {
+        Ok(())
+    } else {
+        Err(TableError::Verification { name, report })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_paths() {
+        let builder = TableSet::builder().corners_path("a.dat").subset_path("b.dat");
+        assert!(matches!(builder.resolve_paths(), Err(TableError::MissingPath("coset"))));
+
+        let builder = builder.coset_path("c.dat");
+        assert_eq!(builder.resolve_paths().unwrap(), ["a.dat", "b.dat", "c.dat"]);
+    }
+
+    #[test]
+    fn test_config_file() {
+        let path = std::env::temp_dir().join("rubikscube_test_config.txt");
+        let path = path.to_str().unwrap();
+        std::fs::write(path, "corners_table = x.dat\nsubset_table = y.dat\ncoset_table = z.dat\n").unwrap();
+
+        let builder = TableSet::builder().config_file(path).subset_path("b.dat");
+        assert_eq!(builder.resolve_paths().unwrap(), ["x.dat", "b.dat", "z.dat"]);
+        std::fs::remove_file(path).unwrap();
+
+        let builder = TableSet::builder().config_file("does_not_exist.txt");
+        assert!(matches!(builder.resolve_paths(), Err(TableError::Config { .. })));
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let result = TableSet::builder()
+            .corners_path("does_not_exist.dat")
+            .subset_path("does_not_exist.dat")
+            .coset_path("does_not_exist.dat")
+            .create_missing(false)
+            .build();
+        assert!(matches!(result, Err(TableError::File { name: "corners", .. })));
+    }
+}
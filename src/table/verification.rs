@@ -0,0 +1,56 @@
+use std::fmt;
+
+/// Result of comparing the distance histogram of a table against known counts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerificationReport {
+    pub expected: Vec<u64>,
+    pub actual: Vec<u64>,
+}
+
+impl VerificationReport {
+    pub fn new(expected: &[u64], actual: Vec<u64>) -> Self {
+        Self { expected: expected.to_vec(), actual }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.expected == self.actual
+    }
+
+    /// Returns (distance, expected count, actual count) for every distance where the counts differ.
+    pub fn mismatches(&self) -> Vec<(usize, u64, u64)> {
+        (0..self.expected.len().max(self.actual.len()))
+            .map(|d| (d, self.expected.get(d).copied().unwrap_or(0), self.actual.get(d).copied().unwrap_or(0)))
+            .filter(|(_, expected, actual)| expected != actual)
+            .collect()
+    }
+}
+
+impl fmt::Display for VerificationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:>8} {:>14} {:>14}", "Distance", "Expected", "Actual")?;
+        for d in 0..self.expected.len().max(self.actual.len()) {
+            let expected = self.expected.get(d).copied().unwrap_or(0);
+            let actual = self.actual.get(d).copied().unwrap_or(0);
+            let marker = if expected != actual { "  <- mismatch" } else { "" };
+            writeln!(f, "{:>8} {:>14} {:>14}{}", d, expected, actual, marker)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let ok = VerificationReport::new(&[1, 3, 0, 1], vec![1, 3, 0, 1]);
+        assert!(ok.is_ok());
+        assert!(ok.mismatches().is_empty());
+
+        let bad = VerificationReport::new(&[1, 3, 0, 1], vec![1, 2, 1, 1, 1]);
+        assert!(!bad.is_ok());
+        assert_eq!(bad.mismatches(), vec![(1, 3, 2), (2, 0, 1), (4, 0, 1)]);
+        assert_eq!(bad.to_string().matches("mismatch").count(), 3);
+    }
+}
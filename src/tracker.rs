@@ -0,0 +1,122 @@
+use crate::*;
+
+/// Something noteworthy that happened with a tracked cube.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrackerEvent {
+    /// The cube got solved.
+    Solved,
+    /// The cube is back in the state the tracking started in, e.g. the scramble.
+    ReturnedToStart,
+}
+
+/// Follows a cube twist by twist, e.g. the moves reported by a smart cube or typed on a keyboard.
+#[derive(Clone, Debug)]
+pub struct Tracker {
+    start: Cube,
+    cube: Cube,
+    twists: Vec<Twist>,
+}
+
+impl Tracker {
+    /// Starts tracking at the given cube, e.g. the scrambled cube.
+    pub fn new(start: Cube) -> Self {
+        Self { start, cube: start, twists: Vec::new() }
+    }
+
+    /// The cube the tracking started at.
+    pub fn start(&self) -> Cube {
+        self.start
+    }
+
+    /// The current cube.
+    pub fn cube(&self) -> Cube {
+        self.cube
+    }
+
+    /// The twists since the start.
+    pub fn twists(&self) -> &[Twist] {
+        &self.twists
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.cube == Cube::solved()
+    }
+
+    pub fn is_at_start(&self) -> bool {
+        self.cube == self.start
+    }
+
+    /// Applies the twist, and reports if it solved the cube or returned it to the start.
+    pub fn push(&mut self, twist: Twist) -> Option<TrackerEvent> {
+        self.cube = self.cube.twisted(twist);
+        self.twists.push(twist);
+        self.event()
+    }
+
+    /// Reverts the last twist, if any.
+    pub fn undo(&mut self) -> Option<Twist> {
+        let twist = self.twists.pop()?;
+        self.cube = self.cube.twisted(twist.inverse());
+        Some(twist)
+    }
+
+    /// Starts tracking anew at the given cube.
+    pub fn reset(&mut self, start: Cube) {
+        *self = Self::new(start);
+    }
+
+    fn event(&self) -> Option<TrackerEvent> {
+        if self.is_solved() {
+            Some(TrackerEvent::Solved)
+        } else if self.is_at_start() {
+            Some(TrackerEvent::ReturnedToStart)
+        } else {
+            None
+        }
+    }
+
+    /// A solution of the current cube.
+    pub fn remaining_solution(&self, solver: &mut TwoPhaseSolver, max_solution_length: u8) -> Result<Alg, String> {
+        solver.solve_twists(self.cube, max_solution_length).map(Alg::new)
+    }
+}
+
+impl Extend<Twist> for Tracker {
+    fn extend<I: IntoIterator<Item = Twist>>(&mut self, twists: I) {
+        for twist in twists {
+            self.push(twist);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker() {
+        let scramble: Alg = "R U R' F2 D".parse().unwrap();
+        let mut tracker = Tracker::new(Cube::solved().twisted_by(&scramble));
+        assert!(tracker.is_at_start());
+        assert!(!tracker.is_solved());
+
+        assert_eq!(tracker.push(Twist::L1), None);
+        assert_eq!(tracker.push(Twist::L3), Some(TrackerEvent::ReturnedToStart));
+        assert_eq!(tracker.undo(), Some(Twist::L3));
+        assert_eq!(tracker.undo(), Some(Twist::L1));
+        assert_eq!(tracker.undo(), None);
+
+        let solution = scramble.inverse();
+        let (last, rest) = solution.split_last().unwrap();
+        tracker.extend(rest.iter().copied());
+        assert_eq!(tracker.cube(), Cube::solved().twisted(last.inverse()));
+        assert_eq!(tracker.push(*last), Some(TrackerEvent::Solved));
+        assert!(tracker.is_solved());
+        assert_eq!(tracker.twists(), solution.twists());
+
+        tracker.reset(Cube::solved());
+        assert!(tracker.twists().is_empty());
+        assert_eq!(tracker.push(Twist::U2), None);
+        assert_eq!(tracker.push(Twist::U2), Some(TrackerEvent::Solved));
+    }
+}
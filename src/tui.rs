@@ -0,0 +1,219 @@
+use clap::Parser;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use rubikscube::*;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Shows the cube in the terminal, takes twists from the keyboard and plays the solver's suggestion.
+///
+/// Keys: L R U D F B twist clockwise, with Shift counterclockwise. Backspace undoes a twist,
+/// Space plays the suggested solution, N scrambles, and Q or Esc quits.
+#[derive(Parser)]
+struct Cli {
+    /// The scramble to start with [default: solved]
+    #[arg(long)]
+    scramble: Option<String>,
+    /// Seconds between the twists of a played solution.
+    #[arg(long, default_value_t = 0.4)]
+    step: f64,
+    /// Config file with the table paths.
+    #[arg(long, default_value = "config.txt")]
+    config: String,
+}
+
+// The faces of the net, laid out like the facelet numbering of `Facelets`.
+const NET: [[Option<Face>; 4]; 3] = [
+    [None, Some(Face::U), None, None],
+    [Some(Face::L), Some(Face::F), Some(Face::R), Some(Face::B)],
+    [None, Some(Face::D), None, None],
+];
+
+/// The colours of the README, like `Cube::to_net_string`.
+fn color(face: Face) -> Color {
+    match face {
+        Face::U => Color::Indexed(15),  // White
+        Face::R => Color::Indexed(21),  // Blue
+        Face::F => Color::Indexed(196), // Red
+        Face::D => Color::Indexed(226), // Yellow
+        Face::L => Color::Indexed(34),  // Green
+        Face::B => Color::Indexed(208), // Orange
+    }
+}
+
+/// The twist of a key, by the face's letter, counterclockwise with Shift.
+fn twist_of(key: char) -> Option<Twist> {
+    let face = "LRUDFB".find(key.to_ascii_uppercase())?;
+    let quarter_turns = if key.is_ascii_uppercase() { 3 } else { 1 };
+    Some(Twist::from((face * 3 + quarter_turns - 1) as u32))
+}
+
+struct App<'a> {
+    solver: TwoPhaseSolver<'a>,
+    states: RandomStateGen,
+    tracker: Tracker,
+    /// A solution of the current cube, or why there is none.
+    suggestion: Result<Alg, String>,
+    /// The twists of the suggestion that are still to be played.
+    playing: VecDeque<Twist>,
+    message: String,
+}
+
+impl<'a> App<'a> {
+    fn new(solver: TwoPhaseSolver<'a>, start: Cube) -> Self {
+        let mut app = Self {
+            solver,
+            states: RandomStateGen::new(rand::random()),
+            tracker: Tracker::new(start),
+            suggestion: Ok(Alg::new(vec![])),
+            playing: VecDeque::new(),
+            message: String::new(),
+        };
+        app.suggest();
+        app
+    }
+
+    fn suggest(&mut self) {
+        self.suggestion = self.tracker.remaining_solution(&mut self.solver, 20);
+    }
+
+    fn twist(&mut self, twist: Twist) {
+        self.message = match self.tracker.push(twist) {
+            Some(TrackerEvent::Solved) => "Solved!".to_string(),
+            Some(TrackerEvent::ReturnedToStart) => "Back at the start".to_string(),
+            None => String::new(),
+        };
+    }
+
+    /// Handles a key and returns whether to go on.
+    fn handle(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => return false,
+            KeyCode::Char(' ') => self.playing = self.suggestion.iter().flat_map(|alg| alg.twists().to_vec()).collect(),
+            KeyCode::Backspace => {
+                self.playing.clear();
+                self.tracker.undo();
+                self.message.clear();
+                self.suggest();
+            }
+            KeyCode::Char('n') => {
+                self.playing.clear();
+                match random_state_scramble(&self.solver, &mut self.states) {
+                    Ok(scramble) => {
+                        self.tracker.reset(Cube::solved().twisted_by(&scramble));
+                        self.message = format!("Scramble: {scramble}");
+                    }
+                    Err(err) => self.message = err,
+                }
+                self.suggest();
+            }
+            KeyCode::Char(c) => {
+                if let Some(twist) = twist_of(c) {
+                    self.playing.clear();
+                    self.twist(twist);
+                    self.suggest();
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// Plays the next twist of the suggestion, if it is playing.
+    fn step(&mut self) {
+        if let Some(twist) = self.playing.pop_front() {
+            self.twist(twist);
+            if self.playing.is_empty() {
+                self.suggest();
+            }
+        }
+    }
+
+    fn net(&self) -> Vec<Line<'static>> {
+        let cube = self.tracker.cube();
+        (0..9)
+            .map(|line| {
+                let spans = NET[line / 3].iter().flat_map(|&face| match face {
+                    Some(face) => (0..3)
+                        .map(|col| Span::styled("  ", Style::default().bg(color(cube.sticker(face, line % 3, col)))))
+                        .collect(),
+                    None => vec![Span::raw("      ")],
+                });
+                Line::from(spans.collect::<Vec<_>>())
+            })
+            .collect()
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [cube_area, info_area] = Layout::horizontal([Constraint::Length(26), Constraint::Min(30)]).areas(frame.area());
+        frame.render_widget(Paragraph::new(self.net()).block(Block::bordered().title("Cube")), cube_area);
+
+        let suggestion = match (&self.suggestion, self.playing.is_empty()) {
+            (_, false) => format!("Playing: {}", to_wca(&self.playing.iter().copied().collect::<Vec<_>>())),
+            (Ok(alg), true) => format!("Suggestion: {alg} ({} twists)", alg.twists().len()),
+            (Err(err), true) => format!("Suggestion: {err}"),
+        };
+        let lines = vec![
+            Line::from(format!("Moves: {}", self.tracker.twists().len())),
+            Line::from(format!("Twists: {}", to_wca(self.tracker.twists()))),
+            Line::from(suggestion),
+            Line::from(self.message.clone()),
+            Line::from(""),
+            Line::from("L R U D F B twist, with Shift counterclockwise"),
+            Line::from("Backspace undo, Space play, N scramble, Q quit"),
+        ];
+        let info = Paragraph::new(lines).wrap(Wrap { trim: false }).block(Block::bordered().title("Solver"));
+        frame.render_widget(info, info_area);
+    }
+}
+
+fn run(terminal: &mut DefaultTerminal, app: &mut App, step: Duration) -> std::io::Result<()> {
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+        // The next twist of a played solution is due when no key was pressed for a step.
+        if !event::poll(step)? {
+            app.step();
+            continue;
+        }
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && !app.handle(key.code)
+        {
+            return Ok(());
+        }
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let start = match &cli.scramble {
+        Some(scramble) => match normalize_moves(scramble) {
+            Ok((twists, _)) => Cube::solved().twisted_by(&twists),
+            Err(err) => {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
+        },
+        None => Cube::solved(),
+    };
+
+    init_twister();
+    init_subset_twister();
+    init_subset_index();
+    println!("Loading tables...");
+    let tables = TableSet::builder().config_file(&cli.config).build().unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(1);
+    });
+
+    let mut app = App::new(TwoPhaseSolver::with_tables(&tables), start);
+    let result = ratatui::run(|terminal| run(terminal, &mut app, Duration::from_secs_f64(cli.step)));
+    if let Err(err) = result {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
@@ -1,20 +1,259 @@
 use crate::*;
 use num_format::ToFormattedString;
+use std::time::{Duration, Instant};
 
 #[derive(Clone)]
 pub struct TwoPhaseSolver<'a> {
     phase_1: &'a DirectionsTable,
     phase_2: &'a DistanceTable,
     corners: &'a DistanceTable,
+    tail: Option<&'a MultiTwister>,
+    legal_moves: Option<&'a dyn LegalMoves>,
+    /// Of the tables, whose distances are total costs.
+    costs: TwistCosts,
     twists: Vec<Twist>,
-    fkt_phase_1: usize,
-    fkt_phase_2: usize,
-    fkt_phase_1_dst: usize,
-    fkt_phase_2_dst: usize,
-    fkt_corner_dst: usize,
-    corner_cuts: usize,
-    fkt_twist: usize,
-    slack_cuts: usize,
+    stats: SolveStats,
+    found: Option<Found>,
+    options: SolveOptions,
+    /// Why the current search was aborted.
+    error: Option<String>,
+    deadline: Option<Instant>,
+}
+
+/// The solution of the last solve as the search found it, before it was taken out of its frame.
+#[derive(Clone)]
+struct Found {
+    frame: (Option<Axis>, bool),
+    phase_1_length: usize,
+    twists: Vec<Twist>,
+}
+
+/// The search runs on the cube, rotated about an axis or not, and on its inverse.
+const SEARCH_FRAMES: [(Option<Axis>, bool); 6] = [
+    (None, false),
+    (Some(Axis::X), false),
+    (Some(Axis::Y), false),
+    (None, true),
+    (Some(Axis::X), true),
+    (Some(Axis::Y), true),
+];
+
+fn into_frame(cube: &Cube, (rotation, inverted): (Option<Axis>, bool)) -> Cube {
+    let cube = if inverted { cube.inverse() } else { *cube };
+    rotation.map_or(cube, |rot| cube.conjugated_by(rot))
+}
+
+fn out_of_frame(twists: &[Twist], (rotation, inverted): (Option<Axis>, bool)) -> Vec<Twist> {
+    let twists = rotation.map_or(twists.to_vec(), |rot| conjugate_by_inv(twists, rot));
+    if inverted { inverse(&twists) } else { twists }
+}
+
+/// Settings of the search.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SolveOptions {
+    /// The maximum number of twists of the solution, for `Solver::solve`.
+    pub max_length: u8,
+    /// The corners table is only looked up while fewer twists than this remain,
+    /// since it rarely cuts deeper searches. `None` never looks it up.
+    pub corner_threshold: Option<u8>,
+    /// The search fails after visiting this many phase-1 nodes, e.g. to bound the work per request of a server.
+    pub max_nodes: Option<usize>,
+    /// Solutions longer than this aren't searched, which bounds the recursion depth.
+    pub max_depth: u8,
+    /// The search fails after this time.
+    pub max_time: Option<Duration>,
+    /// Ignores `max_time`, so that the solution only depends on the cube and the options,
+    /// since the search itself tries the twists in a fixed order. Bound the work with `max_nodes` instead.
+    pub deterministic: bool,
+    /// Measures the time of phase 2 apart from phase 1 in `SolveStats`, which reads the clock around every phase 2 search.
+    /// Without it, `phase_1_time` is the time of the whole solve and `phase_2_time` is zero.
+    pub collect_timing: bool,
+}
+
+impl Default for SolveOptions {
+    fn default() -> Self {
+        Self { max_length: 20, corner_threshold: Some(10), max_nodes: None, max_depth: 30, max_time: None, deterministic: false, collect_timing: false }
+    }
+}
+
+impl SolveOptions {
+    /// Options with the corner threshold derived from the distances in the corners table:
+    /// the lookup is worth it for remaining depths at which more than `min_cut_rate` of the corner states are cut.
+    /// A faster lookup, relative to the search, makes a lower rate worth it.
+    pub fn tuned(corners: &DistanceTable, min_cut_rate: f64) -> Self {
+        let histogram = corners.histogram();
+        let total: u64 = histogram.iter().sum();
+        // The states that are cut at a remaining depth are the ones farther away.
+        let cut_rate = |depth: usize| histogram.iter().skip(depth + 1).sum::<u64>() as f64 / total as f64;
+        let threshold = (0..histogram.len()).find(|&depth| cut_rate(depth) <= min_cut_rate).unwrap_or(histogram.len());
+        Self { corner_threshold: (threshold > 0).then_some(threshold as u8), ..Self::default() }
+    }
+
+    /// When a search that starts now has to stop.
+    fn deadline(&self) -> Option<Instant> {
+        self.max_time.filter(|_| !self.deterministic).map(|max_time| Instant::now() + max_time)
+    }
+}
+
+/// The work done by the solver for one solve.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolveStats {
+    /// Nodes of the phase 1 search.
+    pub phase_1_probes: usize,
+    /// Phase 2 searches, one per cube reaching the subset.
+    pub phase_2_probes: usize,
+    pub phase_1_lookups: usize,
+    pub phase_2_lookups: usize,
+    pub corner_lookups: usize,
+    /// Phase 1 nodes cut because the corners are too far from solved.
+    pub corner_cuts: usize,
+    /// Phase 1 nodes cut because they reach the subset too early.
+    pub slack_cuts: usize,
+    /// Twists applied in phase 1.
+    pub twists: usize,
+    /// Phase 1 nodes at each depth, where the root is at depth 0.
+    pub nodes_per_depth: Vec<usize>,
+    /// Of phase 1, or of the whole solve without `SolveOptions::collect_timing`.
+    pub phase_1_time: Duration,
+    /// Only measured with `SolveOptions::collect_timing`.
+    pub phase_2_time: Duration,
+}
+
+impl SolveStats {
+    /// The solve makes room for the nodes at each depth up front, so the search only counts them.
+    #[inline(always)]
+    fn count_node(&mut self, depth: usize) {
+        self.nodes_per_depth[depth] += 1;
+    }
+}
+
+/// Accumulates the stats of several solves.
+impl std::ops::AddAssign<&SolveStats> for SolveStats {
+    fn add_assign(&mut self, rhs: &SolveStats) {
+        self.phase_1_probes += rhs.phase_1_probes;
+        self.phase_2_probes += rhs.phase_2_probes;
+        self.phase_1_lookups += rhs.phase_1_lookups;
+        self.phase_2_lookups += rhs.phase_2_lookups;
+        self.corner_lookups += rhs.corner_lookups;
+        self.corner_cuts += rhs.corner_cuts;
+        self.slack_cuts += rhs.slack_cuts;
+        self.twists += rhs.twists;
+        if self.nodes_per_depth.len() < rhs.nodes_per_depth.len() {
+            self.nodes_per_depth.resize(rhs.nodes_per_depth.len(), 0);
+        }
+        for (nodes, rhs_nodes) in self.nodes_per_depth.iter_mut().zip(&rhs.nodes_per_depth) {
+            *nodes += rhs_nodes;
+        }
+        self.phase_1_time += rhs.phase_1_time;
+        self.phase_2_time += rhs.phase_2_time;
+    }
+}
+
+impl std::fmt::Display for SolveStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let locale = &num_format::Locale::de_CH;
+        writeln!(f, "Phase 1 probes: {}", self.phase_1_probes.to_formatted_string(locale))?;
+        writeln!(f, "Phase 2 probes: {}", self.phase_2_probes.to_formatted_string(locale))?;
+        writeln!(f, "Phase 1 lookups: {}", self.phase_1_lookups.to_formatted_string(locale))?;
+        writeln!(f, "Phase 2 lookups: {}", self.phase_2_lookups.to_formatted_string(locale))?;
+        writeln!(f, "Corner lookups: {}", self.corner_lookups.to_formatted_string(locale))?;
+        writeln!(f, "Corner cuts: {} ({:.2}%)", self.corner_cuts.to_formatted_string(locale), (self.corner_cuts as f64 / self.corner_lookups as f64) * 100.0)?;
+        writeln!(f, "Twists: {}", self.twists.to_formatted_string(locale))?;
+        writeln!(f, "Slack cuts: {}", self.slack_cuts.to_formatted_string(locale))?;
+        for (depth, nodes) in self.nodes_per_depth.iter().enumerate() {
+            writeln!(f, "Nodes at depth {depth}: {}", nodes.to_formatted_string(locale))?;
+        }
+        writeln!(f, "Phase 1 time: {:?}", self.phase_1_time)?;
+        write!(f, "Phase 2 time: {:?}", self.phase_2_time)
+    }
+}
+
+/// A solution found by the solver.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Solution {
+    pub alg: Alg,
+    /// The work done to find this solution.
+    pub stats: SolveStats,
+    /// How the solution was found, if requested.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub annotation: Option<Annotation>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Phase {
+    One,
+    Two,
+}
+
+/// Distances of a cube in the tables of the solver, as the search looked them up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Heuristics {
+    /// Distance to the subset.
+    pub phase_1: u8,
+    pub corners: u8,
+    /// Distance to solved, only for cubes in the subset.
+    pub phase_2: Option<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnnotatedTwist {
+    pub twist: Twist,
+    pub phase: Phase,
+    /// The cube after the twist.
+    pub cube: Cube,
+    /// Of the cube that the search reached with this twist,
+    /// which is the cube after the twist in the frame of the search, unless the search ran on the inverse.
+    pub heuristics: Heuristics,
+}
+
+/// Explains a solution twist by twist, e.g. for teaching or to debug the pruning.
+///
+/// The search runs on the cube, or on its inverse, and possibly rotated. This is its frame.
+/// On the inverse, the solution is reversed, so phase 2 comes first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Annotation {
+    pub rotation: Option<Axis>,
+    pub inverse: bool,
+    /// Of the cube the search started with.
+    pub start: Heuristics,
+    pub twists: Vec<AnnotatedTwist>,
+}
+
+impl std::fmt::Display for Heuristics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "phase 1: {}, corners: {}", self.phase_1, self.corners)?;
+        if let Some(phase_2) = self.phase_2 {
+            write!(f, ", phase 2: {phase_2}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Annotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let frame = match (self.rotation, self.inverse) {
+            (None, false) => "the cube".to_string(),
+            (None, true) => "the inverse".to_string(),
+            (Some(axis), false) => format!("the cube rotated about {axis:?}"),
+            (Some(axis), true) => format!("the inverse rotated about {axis:?}"),
+        };
+        writeln!(f, "Searched on {frame}")?;
+        write!(f, "Start: {}", self.start)?;
+        for (i, twist) in self.twists.iter().enumerate() {
+            let phase = match twist.phase {
+                Phase::One => 1,
+                Phase::Two => 2,
+            };
+            write!(f, "\n{:>2}. {:<2} phase {phase}, {}", i + 1, twist.twist.to_wca(), twist.heuristics)?;
+        }
+        Ok(())
+    }
 }
 
 impl<'a> TwoPhaseSolver<'a> {
@@ -27,52 +266,204 @@ impl<'a> TwoPhaseSolver<'a> {
             phase_1,
             phase_2,
             corners,
+            tail: None,
+            legal_moves: None,
+            costs: phase_1.costs(),
             twists: Vec::new(),
-            fkt_phase_1: 0,
-            fkt_phase_2: 0,
-            fkt_phase_1_dst: 0,
-            fkt_phase_2_dst: 0,
-            fkt_corner_dst: 0,
-            corner_cuts: 0,
-            fkt_twist: 0,
-            slack_cuts: 0,
+            stats: SolveStats::default(),
+            found: None,
+            options: SolveOptions::default(),
+            error: None,
+            deadline: None,
         }
     }
 
-    pub fn print_stats(&self) {
-        let locale = &num_format::Locale::de_CH;
-        println!("Phase 1: {}", self.fkt_phase_1.to_formatted_string(locale));
-        println!("Phase 2: {}", self.fkt_phase_2.to_formatted_string(locale));
-        println!("Phase 1 dst: {}", self.fkt_phase_1_dst.to_formatted_string(locale));
-        println!("Phase 2 dst: {}", self.fkt_phase_2_dst.to_formatted_string(locale));
-        println!("Corner dst: {}", self.fkt_corner_dst.to_formatted_string(locale));
-        println!("Corner cuts: {} ({:.2}%)", self.corner_cuts.to_formatted_string(locale), (self.corner_cuts as f64 / self.fkt_corner_dst as f64) * 100.0);
-        println!("Twists: {}", self.fkt_twist.to_formatted_string(locale));
-        println!("Slack cuts: {}", self.slack_cuts.to_formatted_string(locale));
-    }
-
-    pub fn solve(&mut self, cube: Cube, max_solution_length: u8) -> Result<Vec<Twist>, String> {
-        let cubes = [
-            cube,
-            cube.conjugated_by(Axis::X),
-            cube.conjugated_by(Axis::Y),
-            cube.inverse(),
-            cube.inverse().conjugated_by(Axis::X),
-            cube.inverse().conjugated_by(Axis::Y),
-        ];
-        let solution_transforms = [
-            |twists: &[Twist]| twists.to_vec(),
-            |twists: &[Twist]| conjugate_by_inv(twists, Axis::X),
-            |twists: &[Twist]| conjugate_by_inv(twists, Axis::Y),
-            |twists: &[Twist]| inverse(twists),
-            |twists: &[Twist]| inverse(&conjugate_by_inv(twists, Axis::X)),
-            |twists: &[Twist]| inverse(&conjugate_by_inv(twists, Axis::Y)),
-        ];
+    pub fn with_tables(tables: &'a TableSet) -> Self {
+        Self::new(&tables.coset, &tables.subset, &tables.corners)
+    }
+
+    /// Finishes phase 1 with the precomputed sequences of a `phase_1_tail`, in an order in which neighbours share their prefixes,
+    /// instead of searching the last twists one by one.
+    pub fn with_tail(mut self, tail: &'a MultiTwister) -> Self {
+        self.tail = Some(tail);
+        self
+    }
+
+    /// Only searches the twists that are legal in each state, e.g. of a `Bandaging`.
+    /// The tables still allow all twists, so they stay lower bounds, but the search then only runs on the cube itself,
+    /// not on its inverse or rotated, doesn't use the tail, and searches phase 2 instead of following the subset table.
+    pub fn with_legal_moves(mut self, legal_moves: &'a dyn LegalMoves) -> Self {
+        self.legal_moves = Some(legal_moves);
+        self
+    }
+
+    pub fn with_options(mut self, options: SolveOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// A solver with the same tables and settings, but without the state of a search, e.g. to solve from a shared solver.
+    pub(crate) fn idle_copy(&self) -> Self {
+        Self { tail: self.tail, legal_moves: self.legal_moves, options: self.options, ..Self::new(self.phase_1, self.phase_2, self.corners) }
+    }
+
+    /// With tables created with costs, e.g. by `TableSet::create_with_costs`, `max_solution_length` bounds the total cost of the twists.
+    pub fn solve_twists(&mut self, cube: Cube, max_solution_length: u8) -> Result<Vec<Twist>, String> {
+        let start = Instant::now();
+        // Phase 1 is never longer than the solution.
+        self.stats = SolveStats { nodes_per_depth: vec![0; max_solution_length as usize + 1], ..SolveStats::default() };
+        self.deadline = self.options.deadline();
+        let result = self.search(cube, max_solution_length);
+        while self.stats.nodes_per_depth.last() == Some(&0) {
+            self.stats.nodes_per_depth.pop();
+        }
+        self.stats.phase_1_time = start.elapsed().saturating_sub(self.stats.phase_2_time);
+        result
+    }
+
+    /// Like `solve_twists`, but also returns the work done to find the solution.
+    /// Twists that merge where the phases meet, e.g. "R" at the end of phase 1 and "L R2" after it, are compressed, see `compressed`.
+    pub fn solve_with_stats(&mut self, cube: Cube, max_solution_length: u8) -> Result<Solution, String> {
+        let alg = Alg::new(compressed(&self.solve_twists(cube, max_solution_length)?));
+        Ok(Solution { alg, stats: std::mem::take(&mut self.stats), annotation: None })
+    }
+
+    /// Like `solve_with_stats`, but with the given options instead of the solver's for this solve, up to `options.max_length`.
+    pub fn solve_with_options(&mut self, cube: Cube, options: &SolveOptions) -> Result<Solution, String> {
+        let own_options = std::mem::replace(&mut self.options, *options);
+        let result = self.solve_with_stats(cube, options.max_length);
+        self.options = own_options;
+        result
+    }
+
+    /// Like `solve_with_stats`, but also explains the solution.
+    /// The twists are the searched ones, not compressed, so that each has its phase and heuristics.
+    pub fn solve_annotated(&mut self, cube: Cube, max_solution_length: u8) -> Result<Solution, String> {
+        let alg = Alg::new(self.solve_twists(cube, max_solution_length)?);
+        let annotation = self.found.as_ref().map(|found| self.annotate(cube, &alg, found));
+        Ok(Solution { alg, stats: std::mem::take(&mut self.stats), annotation })
+    }
+
+    /// Like `solve_with_stats`, but solves the cube with the premoves done before it, see `Cube::premoved`.
+    /// The premoves are folded into the returned solution, which then solves `cube` itself.
+    pub fn solve_with_premoves(&mut self, cube: Cube, premoves: &[Twist], max_solution_length: u8) -> Result<Solution, String> {
+        let mut solution = self.solve_with_stats(cube.premoved(premoves), max_solution_length)?;
+        solution.alg = Alg::new(compressed(&[&solution.alg, premoves].concat()));
+        Ok(solution)
+    }
+
+    /// Like `solve_with_stats`, but solves each of the 24 orientations of the cube
+    /// and returns the solution of the least total cost, in the original orientation, with the work done for all of them.
+    pub fn solve_orientation_neutral(&mut self, cube: Cube, max_solution_length: u8) -> Result<Solution, String> {
+        let mut stats = SolveStats::default();
+        let mut best: Option<(usize, Vec<Twist>)> = None;
+        for rotation in rotations() {
+            let result = self.solve_twists(cube.rotated(&rotation), max_solution_length);
+            stats += &self.stats;
+            if let Ok(twists) = result {
+                // The costs apply to the twists as returned, in the original orientation.
+                let twists = unrotated(&twists, &rotation);
+                let cost = self.costs.total(&twists);
+                if best.as_ref().is_none_or(|(best_cost, _)| cost < *best_cost) {
+                    best = Some((cost, twists));
+                }
+            }
+        }
+        let (_, best) = best.ok_or("No solution found")?;
+        let alg = Alg::new(compressed(&best));
+        Ok(Solution { alg, stats, annotation: None })
+    }
+
+    /// A lower bound of the number of twists of an optimal solution, from the tables without a search:
+    /// the largest distance of the cube, rotated or inverted, to the subset and of its corners.
+    pub fn lower_bound(&self, cube: Cube) -> u8 {
+        SEARCH_FRAMES
+            .iter()
+            .filter(|&&frame| self.searches_frame(frame))
+            .map(|&frame| self.phase_1.distance(into_frame(&cube, frame).coset_index()))
+            .fold(self.corners.distance(cube.corner_index()), u8::max)
+    }
+
+    /// An upper bound of the number of twists of an optimal solution, without a search:
+    /// the shortest way into the subset followed by the shortest way to solved, in the best frame.
+    pub fn estimate_distance(&self, cube: Cube) -> u8 {
+        SEARCH_FRAMES
+            .iter()
+            .filter(|&&frame| self.searches_frame(frame))
+            .map(|&frame| {
+                let mut cube = into_frame(&cube, frame);
+                let mut length = 0;
+                while self.phase_1.distance(cube.coset_index()) > 0 {
+                    let twist = self.phase_1.less_distance(cube.coset_index()).iter().next().unwrap();
+                    cube = cube.twisted(twist);
+                    length += self.costs.cost(twist);
+                }
+                length + self.phase_2.subset_distance(&cube.subset_cube())
+            })
+            .min()
+            .unwrap()
+    }
+
+    /// Whether the search runs in the frame: the legal twists are those of the cube itself,
+    /// and the twists taken out of the frame have to cost the same as in it.
+    fn searches_frame(&self, frame: (Option<Axis>, bool)) -> bool {
+        frame == SEARCH_FRAMES[0]
+            || (self.legal_moves.is_none()
+                && ALL_TWISTS.iter().all(|&twist| self.costs.cost(out_of_frame(&[twist], frame)[0]) == self.costs.cost(twist)))
+    }
+
+    fn heuristics(&self, cube: &Cube) -> Heuristics {
+        let in_subset = cube.in_subset();
+        Heuristics {
+            phase_1: self.phase_1.distance(cube.coset_index()),
+            corners: self.corners.distance(cube.corner_index()),
+            phase_2: in_subset.then(|| self.phase_2.subset_distance(&cube.subset_cube())),
+        }
+    }
+
+    fn annotate(&self, cube: Cube, solution: &[Twist], found: &Found) -> Annotation {
+        let (rotation, inverted) = found.frame;
+        let mut frame_cubes = vec![into_frame(&cube, found.frame)];
+        for &twist in &found.twists {
+            frame_cubes.push(frame_cubes.last().unwrap().twisted(twist));
+        }
+        let length = solution.len();
+        let mut cube = cube;
+        let twists = solution
+            .iter()
+            .enumerate()
+            .map(|(i, &twist)| {
+                cube = cube.twisted(twist);
+                // On the inverse, the solution is the search's twists reversed.
+                let search_index = if inverted { length - 1 - i } else { i };
+                AnnotatedTwist {
+                    twist,
+                    phase: if search_index < found.phase_1_length { Phase::One } else { Phase::Two },
+                    cube,
+                    heuristics: self.heuristics(&frame_cubes[search_index + 1]),
+                }
+            })
+            .collect();
+        Annotation { rotation, inverse: inverted, start: self.heuristics(&frame_cubes[0]), twists }
+    }
+
+    fn search(&mut self, cube: Cube, max_solution_length: u8) -> Result<Vec<Twist>, String> {
+        self.found = None;
+        self.error = None;
+        self.twists.clear();
+        if max_solution_length > self.options.max_depth {
+            return Err(format!("The maximum solution length {} exceeds the depth limit of {}", max_solution_length, self.options.max_depth));
+        }
+        if self.phase_2.costs() != self.costs || self.corners.costs() != self.costs {
+            return Err("The tables were created with different twist costs".into());
+        }
+        let frames: Vec<usize> = (0..SEARCH_FRAMES.len()).filter(|&i| self.searches_frame(SEARCH_FRAMES[i])).collect();
+        let cubes = SEARCH_FRAMES.map(|frame| into_frame(&cube, frame));
         let subset_distances = cubes.map(|c| self.phase_1.distance(c.coset_index()));
-        let min_distance = *subset_distances.iter().min().unwrap();
+        let min_distance = frames.iter().map(|&i| subset_distances[i]).min().unwrap();
 
         for p1_depth in min_distance..=max_solution_length {
-            for i in 0..cubes.len() {
+            for &i in &frames {
                 let cube = cubes[i];
                 let subset_distance = subset_distances[i];
 
@@ -80,9 +471,15 @@ impl<'a> TwoPhaseSolver<'a> {
                     continue;
                 }
                 let result = self.search_phase_1(cube, p1_depth, max_solution_length - p1_depth);
+                if let Some(error) = self.error.take() {
+                    return Err(error);
+                }
                 if result {
-                    let drained_solution: Vec<Twist> = self.twists.drain(..).collect();
-                    let solution = solution_transforms[i](&drained_solution);
+                    let twists: Vec<Twist> = self.twists.drain(..).collect();
+                    let solution = out_of_frame(&twists, SEARCH_FRAMES[i]);
+                    // Phase 1 is the twists that cost `p1_depth`.
+                    let phase_1_length = (0..=twists.len()).find(|&n| self.costs.total(&twists[..n]) == p1_depth as usize).unwrap();
+                    self.found = Some(Found { frame: SEARCH_FRAMES[i], phase_1_length, twists });
                     return Ok(solution);
                 }
             }
@@ -91,79 +488,149 @@ impl<'a> TwoPhaseSolver<'a> {
     }
 
     pub fn search_phase_2(&mut self, mut subset_cube: SubsetCube, depth: u8) -> bool {
-        self.fkt_phase_2 += 1;
+        self.stats.phase_2_probes += 1;
 
-        self.fkt_phase_2_dst += 1;
-        let solution_distance = self.phase_2.distance(subset_cube.index());
+        self.stats.phase_2_lookups += 1;
+        let solution_distance = self.phase_2.subset_distance(&subset_cube);
         if solution_distance > depth {
             return false;
         }
 
-        for d in (1..=solution_distance).rev() {
+        let mut d = solution_distance;
+        while d > 0 {
+            let mut descended = false;
             for twist in H0_TWISTS {
                 let next = subset_cube.twisted(twist);
-                self.fkt_phase_2_dst += 1;
-                let next_d = self.phase_2.distance(next.index());
-                if next_d < d {
+                self.stats.phase_2_lookups += 1;
+                let next_d = self.phase_2.subset_distance(&next);
+                if next_d.saturating_add(self.costs.cost(twist)) <= d {
                     self.twists.push(twist);
                     subset_cube = next;
+                    d = next_d;
+                    descended = true;
                     break;
                 }
             }
+            if !descended {
+                self.error = Some("The subset table is inconsistent: a cube has no neighbour closer to solved".into());
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Like `search_phase_2`, but searches the legal H0 twists, since the twists down the subset table may not be legal.
+    fn search_phase_2_legal(&mut self, cube: Cube, depth: u8, legal_moves: &dyn LegalMoves) -> bool {
+        self.stats.phase_2_lookups += 1;
+        let solution_distance = self.phase_2.subset_distance(&cube.subset_cube());
+        if solution_distance > depth {
+            return false;
+        }
+        if solution_distance == 0 {
+            return true;
+        }
+        for twist in legal_twists_after(legal_moves, &cube, self.twists.last().copied()) & TwistSet::H0 {
+            let cost = self.costs.cost(twist);
+            if cost > depth {
+                continue;
+            }
+            self.twists.push(twist);
+            if self.search_phase_2_legal(cube.twisted(twist), depth - cost, legal_moves) {
+                return true;
+            }
+            self.twists.pop();
+        }
+        false
+    }
+
+    /// Whether the search has to stop, because of an error or because it ran out of nodes or time, which sets the error.
+    fn exceeds_limits(&mut self) -> bool {
+        if self.error.is_some() {
+            return true;
+        }
+        if self.options.max_nodes.is_some_and(|max_nodes| self.stats.phase_1_probes >= max_nodes) {
+            self.error = Some(format!("The search exceeded the node limit of {}", self.stats.phase_1_probes));
+            return true;
+        }
+        // Checking the time at every node would take longer than the node itself.
+        if let Some(deadline) = self.deadline
+            && self.stats.phase_1_probes.is_multiple_of(1024)
+            && Instant::now() >= deadline
+        {
+            self.error = Some("The search exceeded the time limit".into());
+            return true;
         }
-        return true;
+        false
     }
 
     fn search_phase_1(&mut self, cube: Cube, p1_depth: u8, p2_depth: u8) -> bool {
-        self.fkt_phase_1 += 1;
+        if self.exceeds_limits() {
+            return false;
+        }
+        self.stats.phase_1_probes += 1;
+        self.stats.count_node(self.twists.len());
 
         // Check corner distance
-        if p1_depth + p2_depth < 10 {
-            self.fkt_corner_dst += 1;
+        if self.options.corner_threshold.is_some_and(|threshold| p1_depth + p2_depth < threshold) {
+            self.stats.corner_lookups += 1;
             let corner_distance = self.corners.distance(cube.corner_index());
             if corner_distance > p1_depth + p2_depth {
-                self.corner_cuts += 1;
+                self.stats.corner_cuts += 1;
                 return false;
             }
         }
 
         if p1_depth == 0 {
-            return self.search_phase_2(cube.subset_cube(), p2_depth);
+            let start = self.options.collect_timing.then(Instant::now);
+            let found = match self.legal_moves {
+                Some(legal_moves) => {
+                    self.stats.phase_2_probes += 1;
+                    self.search_phase_2_legal(cube, p2_depth, legal_moves)
+                }
+                None => self.search_phase_2(cube.subset_cube(), p2_depth),
+            };
+            if let Some(start) = start {
+                self.stats.phase_2_time += start.elapsed();
+            }
+            return found;
         }
 
-        let mut twists;
-        if let Some(&previous_twist) = self.twists.last() {
-            twists = unique_twists_after(previous_twist);
-        } else {
-            twists = TwistSet::FULL;
-        }
+        // Commuting twists of opposite faces are only tried in one order, which also rules out "L R L" after "L R".
+        let mut twists = match self.legal_moves {
+            Some(legal_moves) => legal_twists_after(legal_moves, &cube, self.twists.last().copied()),
+            None => self.twists.last().map_or(TwistSet::FULL, |&previous_twist| unique_twists_after(previous_twist)),
+        };
         if p1_depth == 1 {
             // H0 twists don't lead to a subset cube, so we omit them.
-            twists.remove(TwistSet::H0);
+            twists = twists - TwistSet::H0;
         }
 
         let coset_index = cube.coset_index();
-        self.fkt_phase_1_dst += 1;
+        self.stats.phase_1_lookups += 1;
         let subset_distance = self.phase_1.distance(coset_index);
         let slack = p1_depth - subset_distance;
 
         if subset_distance == 0 && p1_depth < 5 {
             // It takes at least 5 moves to reach a subset cube from an other subset cube, so we can prune this branch.
-            self.slack_cuts += 1;
+            self.stats.slack_cuts += 1;
             return false;
         }
 
-        if slack == 0 {
-            // Without slack, we need to take the shortest path.
-            twists.keep_only(self.phase_1.less_distance(coset_index));
+        if !self.costs.is_unit() {
+            return self.search_phase_1_costs(cube, twists, p1_depth, p2_depth);
         }
-        else if slack == 1 {
-            // With 1 move of slack, we cannot take any moves that increase the distance.
-            twists.remove(self.phase_1.more_distance(coset_index));
+
+        twists = self.within_slack(twists, coset_index, slack);
+
+        // The tail doesn't know which of its twists are legal.
+        if let Some(tail) = self.tail.filter(|_| self.legal_moves.is_none())
+            && tail.sequences()[0].len() == p1_depth as usize
+        {
+            return self.search_phase_1_tail(cube, tail, twists, p1_depth, p2_depth);
         }
-        
-        for twist in twists.iter() {
-            self.fkt_twist += 1;
+
+        for twist in twists {
+            self.stats.twists += 1;
             let next_cube = cube.twisted(twist);
             self.twists.push(twist);
             let found_solution = self.search_phase_1(next_cube, p1_depth - 1, p2_depth);
@@ -174,4 +641,237 @@ impl<'a> TwoPhaseSolver<'a> {
         }
         false
     }
+
+    /// The rest of `search_phase_1` for twists that cost more than 1, where the twists that reduce the distance
+    /// don't tell which ones fit into the remaining cost, so each cube is looked up before the search descends to it.
+    fn search_phase_1_costs(&mut self, cube: Cube, twists: TwistSet, p1_depth: u8, p2_depth: u8) -> bool {
+        for twist in twists {
+            let cost = self.costs.cost(twist);
+            // Phase 1 doesn't end with an H0 twist, since the cube was in the subset before it.
+            if cost > p1_depth || (cost == p1_depth && TwistSet::H0.contains(twist)) {
+                continue;
+            }
+            let next_cube = cube.twisted(twist);
+            self.stats.phase_1_lookups += 1;
+            if self.phase_1.distance(next_cube.coset_index()) > p1_depth - cost {
+                continue;
+            }
+            self.stats.twists += 1;
+            self.twists.push(twist);
+            if self.search_phase_1(next_cube, p1_depth - cost, p2_depth) {
+                return true;
+            }
+            self.twists.pop();
+        }
+        false
+    }
+
+    /// The twists that keep a cube with this slack on a shortest enough way into the subset.
+    fn within_slack(&self, twists: TwistSet, coset_index: usize, slack: u8) -> TwistSet {
+        match slack {
+            // Without slack, we need to take the shortest path.
+            0 => twists & self.phase_1.less_distance(coset_index),
+            // With 1 move of slack, we cannot take any moves that increase the distance.
+            1 => twists - self.phase_1.more_distance(coset_index),
+            _ => twists,
+        }
+    }
+
+    /// Tries the sequences of the tail, which are as long as phase 1 has left, with the same pruning as the search:
+    /// each twist of a sequence has to be within the slack of the cube before it. The sequences come in the order of `canonical_sequences`,
+    /// where neighbours share their prefixes, so the cube after each prefix is twisted and looked up once.
+    /// The cubes before the last twist are at hand, and one more twist is cheaper than the lookup of the whole sequence.
+    fn search_phase_1_tail(&mut self, cube: Cube, tail: &MultiTwister, first_twists: TwistSet, p1_depth: u8, p2_depth: u8) -> bool {
+        let subset_coset_index = Cube::solved().coset_index();
+        // The cube after each prefix of the current sequence that is within the slack, and the twists that may follow it.
+        let mut prefixes: Vec<(Cube, TwistSet)> = vec![(cube, first_twists)];
+        let mut previous: &[Twist] = &[];
+        for sequence in tail.sequences() {
+            // The prefixes are nodes of the search too.
+            if self.exceeds_limits() {
+                return false;
+            }
+            let shared = sequence.iter().zip(previous).take_while(|(a, b)| a == b).count();
+            prefixes.truncate(shared + 1);
+            previous = sequence;
+            while prefixes.len() < sequence.len() {
+                let (prefix_cube, twists) = *prefixes.last().unwrap();
+                let twist = sequence[prefixes.len() - 1];
+                if !twists.contains(twist) {
+                    break;
+                }
+                self.stats.twists += 1;
+                let next_cube = prefix_cube.twisted(twist);
+                let coset_index = next_cube.coset_index();
+                self.stats.phase_1_lookups += 1;
+                let subset_distance = self.phase_1.distance(coset_index);
+                let remaining = p1_depth - prefixes.len() as u8;
+                let next_twists = if subset_distance == 0 {
+                    // Like in the search, the subset is at least 5 twists away from itself.
+                    self.stats.slack_cuts += 1;
+                    TwistSet::EMPTY
+                } else {
+                    self.within_slack(TwistSet::FULL, coset_index, remaining - subset_distance)
+                };
+                prefixes.push((next_cube, next_twists));
+                self.stats.phase_1_probes += 1;
+                self.stats.count_node(self.twists.len() + prefixes.len() - 1);
+            }
+            let (prefix_cube, twists) = *prefixes.last().unwrap();
+            let last_twist = sequence[sequence.len() - 1];
+            if prefixes.len() < sequence.len() || !twists.contains(last_twist) {
+                continue;
+            }
+
+            self.stats.twists += 1;
+            let next_cube = prefix_cube.twisted(last_twist);
+            if next_cube.coset_index() != subset_coset_index {
+                continue;
+            }
+            self.twists.extend_from_slice(sequence);
+            if self.search_phase_1(next_cube, 0, p2_depth) {
+                return true;
+            }
+            self.twists.truncate(self.twists.len() - sequence.len());
+        }
+        false
+    }
+}
+
+impl Cube {
+    /// Twists that take `self` to `other`, e.g. to get back on track after deviating from a solution.
+    pub fn diff(&self, other: &Cube, solver: &mut TwoPhaseSolver, max_solution_length: u8) -> Result<Alg, String> {
+        solver.solve_twists(self.relative_to(other), max_solution_length).map(|twists| Alg::new(compressed(&twists)))
+    }
+}
+
+/// All canonical twist sequences of the given length that end in a twist outside of H0,
+/// since phase 1 never ends with an H0 twist.
+pub fn phase_1_tail(length: usize) -> MultiTwister {
+    assert!(length > 0);
+    let sequences = canonical_sequences(length).filter(|s| !TwistSet::H0.contains(*s.last().unwrap())).collect();
+    MultiTwister::new(sequences)
+}
+
+/// A solver on tables without twists, which only reach the solved cube, for tests of the solved cube
+/// or of limits that stop the search before it looks up another cube.
+#[cfg(test)]
+pub(crate) fn empty_table_solver() -> TwoPhaseSolver<'static> {
+    // The solved cube is in the coset of the last slice edge location, and the first cube of the subset and of the corners.
+    static TABLES: std::sync::LazyLock<(DirectionsTable, DistanceTable)> = std::sync::LazyLock::new(|| {
+        let phase_1 = DirectionsTable::create(&[], Cube::solved(), |c| c.coset_index(), Cube::from_coset_index, binomial(12, 4));
+        let table = DistanceTable::create(&[], Cube::solved(), |_| 0, |_| Cube::solved(), 1);
+        (phase_1, table)
+    });
+    TwoPhaseSolver::new(&TABLES.0, &TABLES.1, &TABLES.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phase_1_tail() {
+        let tail = phase_1_tail(2);
+        // Canonical pairs ending in one of the 8 twists outside of H0.
+        assert_eq!(tail.sequences().len(), 108);
+        assert!(tail.sequences().iter().all(|s| !TwistSet::H0.contains(s[1])));
+    }
+
+    #[test]
+    fn test_tuned_options() {
+        let corners = create_corners_table();
+        assert_eq!(SolveOptions::tuned(&corners, 0.01), SolveOptions::default());
+        assert_eq!(SolveOptions::tuned(&corners, 0.0).corner_threshold, Some(11));
+        assert_eq!(SolveOptions::tuned(&corners, 1.0).corner_threshold, None);
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let options = SolveOptions { max_time: Some(Duration::from_secs(1)), ..SolveOptions::default() };
+        assert!(options.deadline().is_some());
+        assert_eq!(SolveOptions { deterministic: true, ..options }.deadline(), None);
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let mut solver = empty_table_solver().with_options(SolveOptions { max_depth: 20, ..SolveOptions::default() });
+        let cube = Cube::solved().twisted(Twist::R1);
+        assert_eq!(solver.solve_twists(cube, 21), Err("The maximum solution length 21 exceeds the depth limit of 20".into()));
+    }
+
+    #[test]
+    fn test_node_limit() {
+        let mut solver = empty_table_solver();
+        let options = |max_nodes| SolveOptions { max_nodes: Some(max_nodes), ..SolveOptions::default() };
+        assert_eq!(solver.solve_with_options(Cube::solved(), &options(0)).unwrap_err(), "The search exceeded the node limit of 0");
+        // The solved cube takes one probe, since it is in the subset.
+        let solution = solver.solve_with_options(Cube::solved(), &options(1)).unwrap();
+        assert!(solution.alg.twists().is_empty());
+        assert_eq!(solution.stats.phase_1_probes, 1);
+    }
+
+    #[test]
+    fn test_costs() {
+        let table = |costs: &TwistCosts| DistanceTable::create_with_costs(&[], costs, Cube::solved(), |_| 0, |_| Cube::solved(), 1);
+        let phase_1 = |costs: &TwistCosts| DirectionsTable::create_with_costs(&[], costs, Cube::solved(), |_| 0, |_| Cube::solved(), 1);
+        let (qtm_phase_1, qtm) = (phase_1(&TwistCosts::QTM), table(&TwistCosts::QTM));
+        let solver = TwoPhaseSolver::new(&qtm_phase_1, &qtm, &qtm);
+        assert!(SEARCH_FRAMES.iter().all(|&frame| solver.searches_frame(frame)));
+
+        // The rotation about X turns F into another face, but the one about Y, the F-B axis, and the inverse keep it.
+        let robot = TwistCosts::QTM.with_face(Face::F, 3, 5);
+        let (robot_phase_1, robot_table) = (phase_1(&robot), table(&robot));
+        let solver = TwoPhaseSolver::new(&robot_phase_1, &robot_table, &robot_table);
+        let frames: Vec<_> = SEARCH_FRAMES.into_iter().filter(|&frame| solver.searches_frame(frame)).collect();
+        assert_eq!(frames, vec![(None, false), (Some(Axis::Y), false), (None, true), (Some(Axis::Y), true)]);
+
+        let mut solver = TwoPhaseSolver::new(&qtm_phase_1, &qtm, &robot_table);
+        assert_eq!(solver.solve_twists(Cube::solved(), 20), Err("The tables were created with different twist costs".into()));
+    }
+
+    #[test]
+    fn test_premoves() {
+        let scramble = parse_twist_sequence("R U2 F' L D B2 R'").unwrap();
+        let premoves = parse_twist_sequence("F R'").unwrap();
+        let cube = Cube::solved().twisted_by(&scramble);
+        assert_eq!(cube.premoved(&premoves), Cube::solved().twisted_by(&[premoves.as_slice(), &scramble].concat()));
+        // A solution of the premoved cube, followed by the premoves, solves the cube.
+        let solution = inverse(&[premoves.as_slice(), &scramble].concat());
+        assert_eq!(cube.twisted_by(&solution).twisted_by(&premoves), Cube::solved());
+    }
+
+    #[test]
+    fn test_unrotated() {
+        let twists = parse_twist_sequence("R U2 F' L D B2 R'").unwrap();
+        for rotation in rotations() {
+            // A cube that the twists solve after the rotation, is solved by the twists taken back.
+            let cube = Cube::solved().twisted_by(&inverse(&unrotated(&twists, &rotation)));
+            assert_eq!(cube.rotated(&rotation).twisted_by(&twists), Cube::solved());
+        }
+    }
+
+    #[test]
+    fn test_search_frames() {
+        let twists = parse_twist_sequence("R U2 F' L D B2 R'").unwrap();
+        for frame in SEARCH_FRAMES {
+            // A cube that the twists solve in the frame, is solved by the twists taken out of the frame.
+            let cube = Cube::solved().twisted_by(&inverse(&out_of_frame(&twists, frame)));
+            assert_eq!(into_frame(&cube, frame).twisted_by(&twists), Cube::solved());
+        }
+    }
+
+    #[test]
+    fn test_solve_stats_add_assign() {
+        let mut stats = SolveStats { nodes_per_depth: vec![0; 3], ..Default::default() };
+        stats.count_node(0);
+        stats.count_node(2);
+        let mut other = SolveStats { phase_1_probes: 3, nodes_per_depth: vec![0; 4], phase_2_time: Duration::from_millis(5), ..Default::default() };
+        other.count_node(0);
+        other.count_node(3);
+        stats += &other;
+        assert_eq!(stats.nodes_per_depth, vec![2, 0, 1, 1]);
+        assert_eq!(stats.phase_1_probes, 3);
+        assert_eq!(stats.phase_2_time, Duration::from_millis(5));
+    }
 }
@@ -0,0 +1,111 @@
+//! JavaScript bindings, e.g. for visualizations in the browser.
+//!
+//! Since there is no file system, the tables are passed as the bytes of their files, e.g. from `fetch`:
+//! ```js
+//! const bytes = async (url) => new Uint8Array(await (await fetch(url)).arrayBuffer());
+//! const solver = new Solver(await bytes("corners.dat"), await bytes("subset.dat"), await bytes("coset.dat"));
+//! const cube = Cube.fromScramble("R U R' U'");
+//! console.log(solver.solve(cube, 20));
+//! ```
+
+use crate::*;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(js_name = Cube)]
+pub struct WasmCube {
+    cube: Cube,
+}
+
+#[wasm_bindgen(js_class = Cube)]
+impl WasmCube {
+    /// The solved cube.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self { cube: Cube::solved() }
+    }
+
+    /// The cube after applying a scramble in WCA notation to the solved cube.
+    #[wasm_bindgen(js_name = fromScramble)]
+    pub fn from_scramble(scramble: &str) -> Result<WasmCube, JsError> {
+        let mut cube = Self::new();
+        cube.twist(scramble)?;
+        Ok(cube)
+    }
+
+    /// The cube of a facelet string in URFDLB order.
+    #[wasm_bindgen(js_name = fromFacelets)]
+    pub fn from_facelets(facelets: &str) -> Result<WasmCube, JsError> {
+        let facelets: Facelets = facelets.parse()?;
+        Ok(Self { cube: Cube::from_facelets(&facelets)? })
+    }
+
+    /// Applies twists in WCA notation.
+    pub fn twist(&mut self, twists: &str) -> Result<(), JsError> {
+        let twists = parse_twist_sequence(twists).map_err(|err| JsError::new(&err))?;
+        self.cube = self.cube.twisted_by(&twists);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = isSolved)]
+    pub fn is_solved(&self) -> bool {
+        self.cube == Cube::solved()
+    }
+
+    /// The facelet string in URFDLB order.
+    pub fn facelets(&self) -> String {
+        self.cube.facelets().to_string()
+    }
+
+    /// An SVG image of the cube, as a net or an isometric view.
+    #[wasm_bindgen(js_name = toSvg)]
+    pub fn to_svg(&self, isometric: bool) -> String {
+        let style = if isometric { SvgStyle::Isometric } else { SvgStyle::Net };
+        self.cube.to_svg(style, &ColorScheme::default())
+    }
+}
+
+impl Default for WasmCube {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen(js_name = Solver)]
+pub struct WasmSolver {
+    tables: TableSet,
+}
+
+#[wasm_bindgen(js_class = Solver)]
+impl WasmSolver {
+    /// Takes the bytes of the table files, see `TableSet::from_bytes`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(corners: Vec<u8>, subset: Vec<u8>, coset: Vec<u8>) -> Result<WasmSolver, JsError> {
+        Ok(Self { tables: TableSet::from_bytes(corners, subset, coset)? })
+    }
+
+    /// A solution in WCA notation with at most `max_length` twists.
+    pub fn solve(&self, cube: &WasmCube, max_length: u8) -> Result<String, JsError> {
+        let solution = TwoPhaseSolver::with_tables(&self.tables)
+            .solve_twists(cube.cube, max_length)
+            .map_err(|err| JsError::new(&err))?;
+        Ok(to_wca(&solution))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cube() {
+        let mut cube = WasmCube::from_scramble("R U").unwrap();
+        assert!(!cube.is_solved());
+        cube.twist("U' R'").unwrap();
+        assert!(cube.is_solved());
+        assert_eq!(cube.facelets(), Facelets::solved().to_string());
+        assert!(cube.to_svg(true).starts_with("<svg"));
+
+        let facelets = WasmCube::from_scramble("F2 D'").unwrap().facelets();
+        assert_eq!(WasmCube::from_facelets(&facelets).unwrap().cube, Cube::solved().twisted_by(&[Twist::F2, Twist::D3]));
+    }
+}